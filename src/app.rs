@@ -1,20 +1,28 @@
 // src/app.rs
 
-use crate::asset::{Asset, AssetLibrary, SamplerKitRef, SampleRef, SessionRef, SynthPresetRef};
+use crate::asset::{AssetLibrary, SampleRef};
 use crate::atmo::AtmoPreset;
 use crate::audio_device;
-use crate::audio_engine::{AudioCommand, AudioEngine};
+use crate::audio_engine::{self, AudioCommand, AudioEngine, LoopSnapshot, MidiMessage};
 use crate::audio_io;
 use crate::fx;
-use crate::looper::{SharedLooperState, NUM_LOOPERS};
+use crate::looper::{LooperState, SharedLooperState, NUM_LOOPERS};
 use crate::midi;
-use crate::mixer::MixerState;
+use crate::mixer::{MixerState, NUM_SEND_BUSES, NUM_SUB_BUSES};
 use crate::preset::{SynthEnginePreset, SynthPreset};
-use crate::sampler::{SamplerKit, SamplerPadFxSettings};
+use crate::sampler::{
+    self, KitSpriteMapping, PadGrooveSettings, SamplerKit, SamplerPadFxSettings,
+    SamplerPadSettings,
+};
 use crate::sampler_engine::{self, NUM_SAMPLE_SLOTS};
-use crate::settings::{self, AppSettings, ControllableParameter, FullMidiIdentifier, MidiControlMode};
+use crate::session_bundle::{self, BundleWriter};
+use crate::settings::{
+    self, AppSettings, ArmPolicy, ControllableParameter, FullMidiIdentifier, InputHeadroom,
+    InputHeadroomHold, LoopSyncSource, MeterSettings, MidiControlMode, RecordingFormat,
+};
 use crate::synth::{
-    EngineParamsUnion, EngineWithVolumeAndPeak, LfoRateMode, ModSource, SamplerParams,
+    AdsrSettings, EngineParamsUnion, EngineWithVolumeAndPeak, FilterMode, FilterSettings,
+    LfoRateMode, LfoSettings, LfoWaveform, ModDestination, ModRouting, ModSource, SamplerParams,
     WavetableParams, WAVETABLE_SIZE,
 };
 use crate::theme::Theme;
@@ -28,6 +36,7 @@ use egui::Color32;
 use hound;
 use midir::{MidiInputConnection, MidiInputPort};
 use rfd::FileDialog;
+use rand::Rng;
 use ringbuf::{HeapConsumer, HeapRb};
 use rodio::source::Source;
 use rodio::Decoder;
@@ -35,17 +44,19 @@ use rubato::{
     Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::fs;
 use std::fs::File;
 use std::io::BufReader;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{
-    AtomicBool, AtomicU16, AtomicI8, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering,
+    AtomicBool, AtomicU16, AtomicI16, AtomicI8, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering,
 };
 use std::sync::{mpsc, Arc, RwLock};
 use std::thread::{self, JoinHandle};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
 
 
@@ -59,9 +70,14 @@ pub enum LibraryView {
     EightyEightKeys,
 }
 
+/// Bumped whenever `SessionData` changes shape; `SessionData::migrate` upgrades older files.
+pub const SESSION_FORMAT_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(default)]
 pub struct SessionData {
+    /// `SESSION_FORMAT_VERSION` the file was written with; 0 for files that predate versioning.
+    pub version: u32,
     pub mixer_state: MixerState,
     pub synth_preset_path: Option<PathBuf>,
     pub sampler_kit_path: Option<PathBuf>,
@@ -78,18 +94,93 @@ pub struct SessionData {
     pub master_looper_index: usize,
 }
 
+impl SessionData {
+    /// Fixes up fields that `#[serde(default)]` alone would restore wrongly in sessions saved
+    /// by older versions, then marks the data as current.
+    fn migrate(&mut self) {
+        if self.version < 1 {
+            // Unversioned files may lack the tempo multiplier, which would default to 0x.
+            if self.tempo_multiplier == 0 {
+                self.tempo_multiplier = 1_000_000;
+            }
+        }
+        self.version = SESSION_FORMAT_VERSION;
+    }
+}
+
+/// Hidden directory under `Sessions/` that periodic autosaves overwrite.
+pub const AUTOSAVE_DIR_NAME: &str = ".autosave";
+
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum TheoryMode {
     Scales,
     Chords,
 }
 
+/// How the scale view reacts to held notes.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ScaleDetection {
+    /// A single held note becomes the root of the selected scale; chords are ignored.
+    SingleNote,
+    /// Two or more held notes pick the best matching scale from `theory::scales_containing`.
+    BestMatch,
+}
+
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum ChordDisplayMode {
     Spread,
     Stacked,
 }
 
+/// Which inversion the 88-keys view shows suggested chords in.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ChordInversion {
+    Root,
+    First,
+    Second,
+    Third,
+    /// Per chord, whichever inversion moves least from the held chord.
+    Auto,
+}
+
+impl ChordInversion {
+    pub const ALL: [ChordInversion; 5] = [
+        ChordInversion::Root,
+        ChordInversion::First,
+        ChordInversion::Second,
+        ChordInversion::Third,
+        ChordInversion::Auto,
+    ];
+
+    fn resolve(
+        self,
+        root: u8,
+        quality: theory::ChordQuality,
+        octave: u8,
+        held_notes: &BTreeSet<u8>,
+    ) -> usize {
+        match self {
+            ChordInversion::Root => 0,
+            ChordInversion::First => 1,
+            ChordInversion::Second => 2,
+            ChordInversion::Third => 3,
+            ChordInversion::Auto => theory::closest_inversion(root, quality, octave, held_notes),
+        }
+    }
+}
+
+impl std::fmt::Display for ChordInversion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChordInversion::Root => write!(f, "Root"),
+            ChordInversion::First => write!(f, "1st Inv"),
+            ChordInversion::Second => write!(f, "2nd Inv"),
+            ChordInversion::Third => write!(f, "3rd Inv"),
+            ChordInversion::Auto => write!(f, "Auto"),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SynthUISection {
     // Wavetable specific
@@ -136,15 +227,44 @@ impl EngineState {
     }
 }
 
+/// How the slicer finds slice boundaries.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SliceMode {
+    /// Slices are the audible regions between gaps of silence.
+    Silence,
+    /// Slices start at detected transients, e.g. the hits of a drum loop.
+    Transient,
+}
+
+/// Fewest samples the slicer's waveform view can be zoomed in to.
+pub const MIN_SLICER_VIEW_SAMPLES: usize = 64;
+
 pub struct SlicerState {
     pub source_audio: Option<SourceAudio>,
+    /// `slicer::block_peaks` of `source_audio`, for drawing zoomed-out views.
+    pub waveform_peaks: Vec<f32>,
     pub slice_regions: Vec<(usize, usize)>,
+    pub mode: SliceMode,
     pub threshold: f32,
     pub min_silence_ms: f32,
+    /// 0..1; higher finds softer transients in `SliceMode::Transient`.
+    pub transient_sensitivity: f32,
     pub tail_ms: f32,
     pub base_export_name: String,
     pub export_parent_path: PathBuf,
     pub export_new_folder_name: String,
+    /// Load the kit made by "Export to Kit" straight onto the pads.
+    pub load_exported_kit: bool,
+    /// Synth engine that "Export to Sampler" fills.
+    pub target_engine_index: usize,
+    /// Snap dragged and split boundaries to the nearest zero crossing.
+    pub snap_to_zero_crossing: bool,
+    /// Region index and whether its end (rather than start) is being dragged.
+    pub dragging_boundary: Option<(usize, bool)>,
+    /// Sample under the pointer when the waveform's context menu was opened.
+    pub context_menu_sample: Option<usize>,
+    /// Slice last clicked in the waveform, for "Zoom to Selection".
+    pub selected_slice: Option<usize>,
     pub view_start_sample: usize,
     pub view_end_sample: usize,
 }
@@ -153,17 +273,64 @@ impl SlicerState {
     pub fn new() -> Self {
         Self {
             source_audio: None,
+            waveform_peaks: Vec::new(),
             slice_regions: Vec::new(),
+            mode: SliceMode::Silence,
             threshold: 0.012,
             min_silence_ms: 1000.0,
+            transient_sensitivity: 0.5,
             tail_ms: 3000.0,
             base_export_name: "slice".to_string(),
             export_parent_path: PathBuf::new(),
             export_new_folder_name: "New Slices".to_string(),
+            load_exported_kit: true,
+            target_engine_index: 0,
+            snap_to_zero_crossing: true,
+            dragging_boundary: None,
+            context_menu_sample: None,
+            selected_slice: None,
             view_start_sample: 0,
             view_end_sample: 0,
         }
     }
+
+    pub fn set_source_audio(&mut self, source_audio: SourceAudio) {
+        self.waveform_peaks = crate::slicer::block_peaks(&source_audio.data);
+        self.source_audio = Some(source_audio);
+        self.selected_slice = None;
+        self.zoom_to_fit();
+    }
+
+    /// Shows `span` samples from `start`, kept inside the loaded audio and no narrower than
+    /// `MIN_SLICER_VIEW_SAMPLES`.
+    pub fn set_view(&mut self, start: f64, span: f64) {
+        let total = self.source_audio.as_ref().map_or(0, |sa| sa.data.len());
+        let span = span.clamp(MIN_SLICER_VIEW_SAMPLES.min(total) as f64, total as f64);
+        let start = start.clamp(0.0, total as f64 - span);
+        self.view_start_sample = start.round() as usize;
+        self.view_end_sample = ((start + span).round() as usize).min(total);
+    }
+
+    pub fn zoom_to_fit(&mut self) {
+        self.set_view(0.0, f64::MAX);
+    }
+
+    /// Frames the selected slice with a little room either side.
+    pub fn zoom_to_selection(&mut self) {
+        if let Some(&(start, end)) = self.selected_slice.and_then(|i| self.slice_regions.get(i)) {
+            let padding = (end - start) as f64 * 0.05;
+            self.set_view(start as f64 - padding, (end - start) as f64 + 2.0 * padding);
+        }
+    }
+
+    /// Samples of tail added past each slice's end. Transient slices already run up to the
+    /// next hit, so they get none.
+    pub fn tail_samples(&self, sample_rate: u32) -> usize {
+        match self.mode {
+            SliceMode::Silence => (self.tail_ms / 1000.0 * sample_rate as f32).round() as usize,
+            SliceMode::Transient => 0,
+        }
+    }
 }
 
 pub struct CypherApp {
@@ -177,17 +344,39 @@ pub struct CypherApp {
     pub about_window_open: bool,
     pub fx_editor_window_open: bool,
     pub atmo_window_open: bool,
+    pub chord_style_editor_window_open: bool,
     pub is_recording_output: bool,
     pub recording_notification: Option<(String, Instant)>,
+    pub is_recording_stems: bool,
+    pub stem_recording_limit_reached: Arc<AtomicBool>,
+    /// Set by the stem writer to the number of stems it saved, `NO_STEM_RESULT` otherwise.
+    pub stems_written: Arc<AtomicU32>,
+    /// Where the last stem recording is being saved, until the writer reports back.
+    pub stem_output_dir: Option<PathBuf>,
     pub library_path: Vec<String>,
+    /// Library filter; while non-empty the browser lists matches from every folder.
+    pub library_search: String,
+    /// The library sample last sent for preview, so a second click can stop it.
+    pub auditioned_sample: Option<PathBuf>,
+    pub audition_is_playing: Arc<AtomicBool>,
     pub settings: AppSettings,
     pub library_view: LibraryView,
     pub asset_library: AssetLibrary,
+    /// Bumped by each rescan so an older scan still running knows to stop.
+    asset_scan_generation: Arc<AtomicU64>,
+    asset_scan_receiver: Option<mpsc::Receiver<AssetLibrary>>,
     pub theme: Theme,
     pub available_themes: Vec<(String, PathBuf)>,
     pub active_synth_section: [SynthUISection; 2],
     pub bpm_rounding_setting_changed_unapplied: bool,
     pub current_session_path: Option<PathBuf>,
+    /// Autosave found at startup that is newer than any saved session, awaiting restore/discard.
+    pub recoverable_autosave: Option<PathBuf>,
+    last_autosave_check: Instant,
+    /// Hash of the session as last autosaved, so an unchanged session isn't written again.
+    last_autosave_fingerprint: Option<u64>,
+    /// Set while an autosave's loops are being copied and written.
+    autosave_is_writing: Arc<AtomicBool>,
 
     // --- Audio Engine Resources (managed) ---
     _input_stream: Option<Stream>,
@@ -203,6 +392,13 @@ pub struct CypherApp {
     pub looper_states: Vec<SharedLooperState>,
     pub master_looper_index: Arc<AtomicUsize>,
     pub tempo_multiplier: Arc<AtomicU32>,
+    pub tempo_locked: Arc<AtomicBool>,
+    pub exclusive_arm: Arc<AtomicBool>,
+    pub safe_mode: Arc<AtomicBool>,
+    pub loudness: audio_engine::LoudnessReadings,
+    pub master_spectrum: Arc<RwLock<Vec<f32>>>,
+    pub master_correlation: Arc<AtomicU32>,
+    pub loudness_meter_enabled: Arc<AtomicBool>,
     pub transport_playhead: Arc<AtomicUsize>,
     pub transport_len_samples: Arc<AtomicUsize>,
     pub transport_is_playing: Arc<AtomicBool>,
@@ -212,9 +408,11 @@ pub struct CypherApp {
     pub sampler_is_active: Arc<AtomicBool>,
     pub sampler_pad_info: [Option<SampleRef>; 16],
     pub sampler_pad_fx_settings: [SamplerPadFxSettings; 16],
+    pub pad_groove: PadGrooveSettings,
     pub playing_pads: Arc<AtomicU16>,
     pub cpu_load: Arc<AtomicU32>,
     pub xrun_count: Arc<AtomicUsize>,
+    pub performance_history: PerformanceHistory,
     pub live_midi_notes: Arc<RwLock<BTreeSet<u8>>>,
     pub should_toggle_record_from_midi: Arc<AtomicBool>,
     pub should_clear_all_from_midi: Arc<AtomicBool>,
@@ -224,24 +422,37 @@ pub struct CypherApp {
     pub track_mixer_state: Arc<RwLock<MixerState>>,
     pub peak_meters: Arc<[AtomicU32; NUM_LOOPERS]>,
     pub displayed_peak_levels: [f32; NUM_LOOPERS],
+    pub looper_peak_holds: [PeakHold; NUM_LOOPERS],
     pub input_peak_meter: Arc<AtomicU32>,
     pub displayed_input_peak_level: f32,
+    pub displayed_spectrum: Vec<f32>,
+    pub displayed_correlation: f32,
+    pub input_headroom: InputHeadroom,
+    input_headroom_hold: InputHeadroomHold,
     pub master_volume: Arc<AtomicU32>,
     pub limiter_is_active: Arc<AtomicBool>,
     pub limiter_threshold: Arc<AtomicU32>,
     pub limiter_release_mode: LfoRateMode,
     pub limiter_release_ms: Arc<AtomicU32>,
     pub limiter_release_sync_rate: Arc<AtomicU32>,
+    pub limiter_true_peak: bool,
+    pub limiter_lookahead_ms: f32,
     pub gain_reduction_db: Arc<AtomicU32>,
     pub displayed_gain_reduction: f32,
     pub master_peak_meter: Arc<AtomicU32>,
     pub displayed_master_peak_level: f32,
+    pub master_peak_hold: PeakHold,
 
     // --- Synth State ---
     pub engine_states: [EngineState; 2],
     pub synth_master_volume: Arc<AtomicU32>,
     pub synth_master_peak_meter: Arc<AtomicU32>,
     pub displayed_synth_master_peak_level: f32,
+    /// In-memory A/B snapshots of both engines. The active slot mirrors the live patch.
+    pub synth_ab_slots: [Option<SynthPreset>; 2],
+    pub synth_ab_active: usize,
+    /// Limits the synth editor's "Randomize" to LFOs and mod routings.
+    pub randomize_modulation_only: bool,
 
     // --- Sampler State ---
     pub sampler_volume: Arc<AtomicU32>,
@@ -260,11 +471,32 @@ pub struct CypherApp {
     // --- 88 Keys Theory State ---
     pub theory_mode: TheoryMode,
     pub chord_display_mode: ChordDisplayMode,
+    pub chord_inversion: ChordInversion,
+    pub scale_detection: ScaleDetection,
+    /// Root pitch class and scale picked by `ScaleDetection::BestMatch`, for display.
+    pub detected_scale: Option<(u8, theory::Scale)>,
     pub selected_scale: theory::Scale,
+    /// Index into `available_user_scales`; when set it replaces `selected_scale`.
+    pub selected_user_scale: Option<usize>,
+    pub available_user_scales: Vec<theory::UserScale>,
+    /// Pitch class of the key that chord suggestions stay inside (using `selected_scale`),
+    /// or `None` for unconstrained suggestions.
+    pub chord_key_root: Option<u8>,
     pub selected_chord_style: theory::ChordStyle,
+    /// The style being edited in the chord style editor; saved as a new file.
+    pub chord_style_draft: theory::ChordStyle,
     pub available_chord_styles: Vec<(String, PathBuf)>,
     pub displayed_theory_notes: Vec<(u8, usize)>,
     pub last_recognized_chord_notes: BTreeSet<u8>,
+    /// Notes of the chord suggestion currently being auditioned from the keyboard view.
+    pub auditioned_chord_notes: Vec<u8>,
+    /// MIDI channel the auditioned chord was sent on, or `None` if it went straight to the synth.
+    auditioned_chord_channel: Option<u8>,
+    /// Set while the auditioned chord is held by a mapped MIDI control rather than the pointer.
+    pub chord_held_from_midi: bool,
+    /// Chord pad mode: suggestions are played as MIDI notes on the audio note channel, so
+    /// they reach the synth (and sampler pads) exactly like notes from a keyboard.
+    pub chord_pad_sends_midi: bool,
 
     // --- Slicer State ---
     pub slicer_state: SlicerState,
@@ -282,6 +514,15 @@ pub struct CypherApp {
     pub midi_synth_editor_toggle_request: Arc<AtomicBool>,
     pub midi_sampler_editor_toggle_request: Arc<AtomicBool>,
     pub midi_fx_preset_change_request: Arc<AtomicI8>,
+    /// Chord suggestion group to play from a mapped MIDI control, or one of the
+    /// `midi::CHORD_PAD_*` values.
+    pub midi_chord_pad_request: Arc<AtomicI8>,
+    /// Scale mask the MIDI input snaps notes to; kept in sync with the scale settings.
+    pub midi_note_quantize_mask: Arc<AtomicU16>,
+    /// The last MIDI Program Change number received, or -1 when none is pending.
+    pub midi_program_change_request: Arc<AtomicI16>,
+    /// Set when the transport should follow incoming MIDI clock instead of the first loop.
+    pub follow_midi_clock: Arc<AtomicBool>,
     pub midi_mapping_inversions: Arc<RwLock<BTreeMap<FullMidiIdentifier, bool>>>,
 
     // --- FX State ---
@@ -315,13 +556,75 @@ pub struct CypherApp {
     pub audio_settings_status: Option<(String, Color32)>,
 }
 
+/// Peak-hold marker for a meter: the highest level it showed, held for
+/// `MeterSettings::peak_hold_ms` and then released like the meter itself.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PeakHold {
+    pub level: f32,
+    held_secs: f32,
+}
+
+impl PeakHold {
+    pub fn update(&mut self, displayed_level: f32, dt: f32, meters: &MeterSettings) {
+        if displayed_level >= self.level {
+            self.level = displayed_level;
+            self.held_secs = 0.0;
+        } else if self.held_secs * 1000.0 < meters.peak_hold_ms {
+            self.held_secs += dt;
+        } else {
+            self.level = meters.follow(self.level, displayed_level, dt);
+        }
+    }
+}
+
+/// CPU load readings kept for the options window graph, one per UI frame.
+pub const CPU_HISTORY_LEN: usize = 300;
+const MAX_XRUN_LOG_ENTRIES: usize = 50;
+
+/// Recent CPU load and a log of xruns, gathered on the UI thread from the engine's atomics.
+#[derive(Default)]
+pub struct PerformanceHistory {
+    /// CPU load in percent, oldest first.
+    pub cpu_load: VecDeque<f32>,
+    /// One line per xrun burst, oldest first.
+    pub xrun_log: VecDeque<String>,
+    last_xrun_count: usize,
+}
+
+impl PerformanceHistory {
+    pub fn record(&mut self, cpu_load_percent: f32, xrun_count: usize, buffer_size: u32, sample_rate: u32) {
+        if self.cpu_load.len() == CPU_HISTORY_LEN {
+            self.cpu_load.pop_front();
+        }
+        self.cpu_load.push_back(cpu_load_percent);
+
+        if xrun_count > self.last_xrun_count {
+            let entry = format!(
+                "{}  {} xrun(s) at {:.1}% CPU, {} samples @ {} Hz",
+                Local::now().format("%H:%M:%S"),
+                xrun_count - self.last_xrun_count,
+                cpu_load_percent,
+                buffer_size,
+                sample_rate
+            );
+            eprintln!("Xrun: {}", entry);
+            if self.xrun_log.len() == MAX_XRUN_LOG_ENTRIES {
+                self.xrun_log.pop_front();
+            }
+            self.xrun_log.push_back(entry);
+        }
+        self.last_xrun_count = xrun_count;
+    }
+}
+
 /// Helper struct to hold audio data and its original sample rate.
 pub struct SourceAudio {
     pub sample_rate: u32,
     pub data: Vec<f32>,
 }
 
-// Helper function to load and convert a WAV file to mono f32 samples, retaining its original SR.
+// Helper function to load and convert an audio file (any format in `SAMPLE_FILE_EXTENSIONS`)
+// to mono f32 samples, retaining its original SR.
 pub fn load_source_audio_file_with_sr(path: &Path) -> Result<SourceAudio> {
     let file = BufReader::new(File::open(path)?);
     let source = Decoder::new(file)?;
@@ -343,10 +646,101 @@ pub fn load_source_audio_file_with_sr(path: &Path) -> Result<SourceAudio> {
     Ok(SourceAudio { sample_rate, data })
 }
 
+/// The notes of one chord suggestion group, in display order.
+pub fn chord_group_notes(displayed_theory_notes: &[(u8, usize)], group: usize) -> Vec<u8> {
+    displayed_theory_notes
+        .iter()
+        .filter(|(_, g)| *g == group)
+        .map(|(note, _)| *note)
+        .collect()
+}
+
+/// The autosave directory, if its session is newer than every session saved by hand, i.e. it
+/// holds work that was never saved.
+fn find_recoverable_autosave() -> Option<PathBuf> {
+    let sessions_dir = settings::get_config_dir()?.join("Sessions");
+    let autosave_dir = sessions_dir.join(AUTOSAVE_DIR_NAME);
+    let saved_at = |dir: &Path| fs::metadata(dir.join("session.json")).and_then(|m| m.modified()).ok();
+    let autosaved_at = saved_at(&autosave_dir)?;
+    let newest_saved = fs::read_dir(&sessions_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            // Skips the autosave and its staging copies.
+            !path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        })
+        .filter_map(|path| saved_at(&path))
+        .max();
+    newest_saved
+        .is_none_or(|saved| autosaved_at > saved)
+        .then_some(autosave_dir)
+}
+
+// Helpers for the synth editor's "Randomize" button. Ranges stay inside what the UI can set.
+fn random_adsr(rng: &mut impl Rng) -> AdsrSettings {
+    AdsrSettings {
+        attack: rng.gen_range(0.001..0.5),
+        decay: rng.gen_range(0.05..1.5),
+        sustain: rng.gen_range(0.2..1.0),
+        release: rng.gen_range(0.05..2.0),
+    }
+}
+
+fn randomize_lfo(rng: &mut impl Rng, lfo: &mut LfoSettings, is_wavetable: bool) {
+    // The wavetable shapes read the engine's tables, which a sampler doesn't have.
+    let shapes: Vec<LfoWaveform> = LfoWaveform::ALL
+        .into_iter()
+        .filter(|shape| {
+            is_wavetable
+                || !matches!(
+                    shape,
+                    LfoWaveform::Wavetable1
+                        | LfoWaveform::Wavetable2
+                        | LfoWaveform::Wavetable3
+                        | LfoWaveform::Wavetable4
+                )
+        })
+        .collect();
+    lfo.waveform = shapes[rng.gen_range(0..shapes.len())];
+    lfo.hz_rate = 0.05 * (8.0f32 / 0.05).powf(rng.gen::<f32>());
+}
+
+fn random_mod_matrix(rng: &mut impl Rng, is_wavetable: bool) -> Vec<ModRouting> {
+    let sources = [ModSource::Lfo1, ModSource::Lfo2, ModSource::Env2];
+    let destinations: Vec<ModDestination> = ModDestination::ALL
+        .into_iter()
+        .filter(|dest| {
+            is_wavetable
+                || !matches!(
+                    dest,
+                    ModDestination::WavetablePosition
+                        | ModDestination::BellPosition
+                        | ModDestination::BellAmount
+                        | ModDestination::BellWidth
+                )
+        })
+        .collect();
+    (0..2)
+        .map(|_| {
+            let destination = destinations[rng.gen_range(0..destinations.len())];
+            // Pitch modulation gets loud fast; keep it to a gentle wobble.
+            let depth = if destination == ModDestination::Pitch { 0.05 } else { 0.5 };
+            ModRouting {
+                source: sources[rng.gen_range(0..sources.len())],
+                destination,
+                amount: rng.gen_range(-depth..depth),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
 impl CypherApp {
     pub fn new(_cc: &eframe::CreationContext) -> Result<Self> {
         let settings = settings::load_settings();
         let theme = Theme::default();
+        let follow_midi_clock = settings.loop_sync_source == LoopSyncSource::MidiClock;
 
         let available_hosts = cpal::available_hosts();
         let mut selected_host_index = available_hosts
@@ -417,6 +811,8 @@ impl CypherApp {
                 .map(fx::InsertionPoint::Looper)
                 .collect::<Vec<_>>(),
             (0..2).map(fx::InsertionPoint::Synth).collect::<Vec<_>>(),
+            (0..NUM_SUB_BUSES).map(fx::InsertionPoint::Bus).collect::<Vec<_>>(),
+            (0..NUM_SEND_BUSES).map(fx::InsertionPoint::Send).collect::<Vec<_>>(),
             vec![
                 fx::InsertionPoint::Sampler,
                 fx::InsertionPoint::Input,
@@ -438,18 +834,32 @@ impl CypherApp {
             slicer_window_open: false,
             midi_mapping_window_open: false,
             about_window_open: false,
+            chord_style_editor_window_open: false,
             fx_editor_window_open: false,
             atmo_window_open: false,
             is_recording_output: false,
             recording_notification: None,
+            is_recording_stems: false,
+            stem_recording_limit_reached: Arc::new(AtomicBool::new(false)),
+            stems_written: Arc::new(AtomicU32::new(audio_engine::NO_STEM_RESULT)),
+            stem_output_dir: None,
             library_path: Vec::new(),
+            library_search: String::new(),
+            auditioned_sample: None,
+            audition_is_playing: Arc::new(AtomicBool::new(false)),
             library_view: LibraryView::Samples,
             asset_library: AssetLibrary::default(),
+            asset_scan_generation: Arc::new(AtomicU64::new(0)),
+            asset_scan_receiver: None,
             theme,
             available_themes: Vec::new(),
             active_synth_section: [SynthUISection::Wavetable; 2],
             bpm_rounding_setting_changed_unapplied: false,
             current_session_path: None,
+            recoverable_autosave: None,
+            last_autosave_check: Instant::now(),
+            last_autosave_fingerprint: None,
+            autosave_is_writing: Arc::new(AtomicBool::new(false)),
             _input_stream: None,
             _output_stream: None,
             _midi_connections: Vec::new(),
@@ -461,6 +871,13 @@ impl CypherApp {
             looper_states: Vec::new(),
             master_looper_index: Arc::new(AtomicUsize::new(usize::MAX)),
             tempo_multiplier: Arc::new(AtomicU32::new(1_000_000)),
+            tempo_locked: Arc::new(AtomicBool::new(false)),
+            exclusive_arm: Arc::new(AtomicBool::new(true)),
+            safe_mode: Arc::new(AtomicBool::new(false)),
+            loudness: Default::default(),
+            master_spectrum: Arc::new(RwLock::new(vec![0.0; audio_engine::SPECTRUM_BANDS])),
+            master_correlation: Arc::new(AtomicU32::new(1_000_000)),
+            loudness_meter_enabled: Arc::new(AtomicBool::new(false)),
             transport_playhead: Arc::new(AtomicUsize::new(0)),
             transport_len_samples: Arc::new(AtomicUsize::new(0)),
             transport_is_playing: Arc::new(AtomicBool::new(true)),
@@ -470,9 +887,11 @@ impl CypherApp {
             sampler_is_active: Arc::new(AtomicBool::new(false)),
             sampler_pad_info: Default::default(),
             sampler_pad_fx_settings: Default::default(),
+            pad_groove: PadGrooveSettings::default(),
             playing_pads: Arc::new(AtomicU16::new(0)),
             cpu_load,
             xrun_count,
+            performance_history: PerformanceHistory::default(),
             live_midi_notes: Arc::new(RwLock::new(BTreeSet::new())),
             should_toggle_record_from_midi,
             should_clear_all_from_midi,
@@ -480,22 +899,33 @@ impl CypherApp {
             track_mixer_state,
             peak_meters,
             displayed_peak_levels: [0.0; NUM_LOOPERS],
+            looper_peak_holds: [PeakHold::default(); NUM_LOOPERS],
             input_peak_meter,
             displayed_input_peak_level: 0.0,
+            displayed_spectrum: vec![0.0; audio_engine::SPECTRUM_BANDS],
+            displayed_correlation: 0.0,
+            input_headroom: InputHeadroom::Ok,
+            input_headroom_hold: InputHeadroomHold::default(),
             master_volume,
             limiter_is_active,
             limiter_threshold,
             limiter_release_mode: LfoRateMode::Hz,
             limiter_release_ms,
             limiter_release_sync_rate,
+            limiter_true_peak: false,
+            limiter_lookahead_ms: 0.0,
             gain_reduction_db,
             displayed_gain_reduction: 0.0,
             master_peak_meter,
             displayed_master_peak_level: 0.0,
+            master_peak_hold: PeakHold::default(),
             engine_states: [EngineState::new_wavetable(), EngineState::new_wavetable()],
             synth_master_volume,
             synth_master_peak_meter: Arc::new(AtomicU32::new(0)),
             displayed_synth_master_peak_level: 0.0,
+            synth_ab_slots: [None, None],
+            synth_ab_active: 0,
+            randomize_modulation_only: false,
             sampler_volume,
             sampler_peak_meter: Arc::new(AtomicU32::new(0)),
             displayed_sampler_peak_level: 0.0,
@@ -508,11 +938,22 @@ impl CypherApp {
             available_atmo_presets: Vec::new(),
             theory_mode: TheoryMode::Scales,
             chord_display_mode: ChordDisplayMode::Stacked,
+            chord_inversion: ChordInversion::Root,
+            scale_detection: ScaleDetection::SingleNote,
+            detected_scale: None,
             selected_scale: Scale::Ionian,
+            selected_user_scale: None,
+            available_user_scales: Vec::new(),
+            chord_key_root: None,
             selected_chord_style: ChordStyle::default(),
+            chord_style_draft: ChordStyle::default(),
             available_chord_styles: Vec::new(),
             displayed_theory_notes: Vec::new(),
             last_recognized_chord_notes: BTreeSet::new(),
+            auditioned_chord_notes: Vec::new(),
+            auditioned_chord_channel: None,
+            chord_held_from_midi: false,
+            chord_pad_sends_midi: false,
             slicer_state: SlicerState::new(),
             midi_mappings,
             midi_mapping_modes,
@@ -526,6 +967,10 @@ impl CypherApp {
             midi_synth_editor_toggle_request: Arc::new(AtomicBool::new(false)),
             midi_sampler_editor_toggle_request: Arc::new(AtomicBool::new(false)),
             midi_fx_preset_change_request: Arc::new(AtomicI8::new(0)),
+            midi_chord_pad_request: Arc::new(AtomicI8::new(midi::CHORD_PAD_IDLE)),
+            midi_note_quantize_mask: Arc::new(AtomicU16::new(0)),
+            midi_program_change_request: Arc::new(AtomicI16::new(-1)),
+            follow_midi_clock: Arc::new(AtomicBool::new(follow_midi_clock)),
             active_fx_target: Arc::new(RwLock::new(None)),
             fx_presets: BTreeMap::new(),
             fx_wet_dry_mixes,
@@ -575,6 +1020,42 @@ impl CypherApp {
         }
     }
 
+    /// Builds the stop command for the live recording with the current format settings.
+    pub fn stop_output_recording_command(&self, output_path: PathBuf) -> AudioCommand {
+        AudioCommand::StopOutputRecording {
+            output_path,
+            format: self.settings.recording_format,
+            trim: self.settings.trim_silence,
+            normalize_to_db: self
+                .settings
+                .normalize_recordings
+                .then_some(self.settings.normalize_target_db),
+            dither: self.settings.dither_recordings,
+        }
+    }
+
+    pub fn toggle_stem_recording(&mut self) {
+        self.is_recording_stems = !self.is_recording_stems;
+        if self.is_recording_stems {
+            let stems = audio_engine::StemRecording::start(
+                NUM_LOOPERS,
+                self.active_sample_rate as f32,
+                self.stems_written.clone(),
+            );
+            self.send_command(AudioCommand::StartStemRecording(stems));
+        } else if let Some(config_dir) = settings::get_config_dir() {
+            let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+            let output_dir = config_dir
+                .join("LiveRecordings")
+                .join(format!("Stems_{}", timestamp));
+            self.send_command(AudioCommand::StopStemRecording {
+                output_dir: output_dir.clone(),
+                format: self.settings.recording_format,
+            });
+            self.stem_output_dir = Some(output_dir);
+        }
+    }
+
     pub fn handle_fx_button_click(&mut self, target: fx::InsertionPoint) {
         let mut active_target = self.active_fx_target.write().unwrap();
         if self.fx_editor_window_open && *active_target == Some(target) {
@@ -618,8 +1099,10 @@ impl CypherApp {
         app.rescan_asset_library();
         app.rescan_available_themes();
         app.rescan_chord_styles();
+        app.rescan_user_scales();
         app.rescan_fx_presets();
         app.rescan_atmo_presets();
+        app.recoverable_autosave = find_recoverable_autosave();
 
         // Load first available chord style if none is selected
         let style_to_load = app.available_chord_styles.first().map(|(_, path)| path.clone());
@@ -711,6 +1194,7 @@ impl CypherApp {
                         // Update UI state
                         sampler_state.sample_names[slot_index] = name.to_string();
                         sampler_state.sample_paths[slot_index] = Some(path.clone());
+                        sampler_state.round_robin_paths[slot_index].clear();
                         *sampler_state.sample_data_for_ui[slot_index].write().unwrap() =
                             audio_data.clone();
                         // Bust the visualizer cache
@@ -731,6 +1215,81 @@ impl CypherApp {
         }
     }
 
+    /// Loads exported slices into a sampler engine's slots in order. Each slot is triggered by
+    /// one octave of the keyboard, so its root note is set to the bottom key of that octave:
+    /// the slice plays at its original pitch there and chromatically up to the next slot.
+    pub fn load_slices_into_sampler_engine(&mut self, engine_index: usize, slice_paths: &[PathBuf]) {
+        if !matches!(self.engine_states.get(engine_index), Some(EngineState::Sampler(_))) {
+            self.recording_notification = Some((
+                format!("Engine {} is not a sampler; switch it to Sampler first.", engine_index + 1),
+                Instant::now(),
+            ));
+            return;
+        }
+
+        for (slot_index, path) in slice_paths.iter().take(NUM_SAMPLE_SLOTS).enumerate() {
+            self.load_sample_for_sampler_slot(engine_index, slot_index, path.clone());
+        }
+
+        if let EngineState::Sampler(state) = &mut self.engine_states[engine_index] {
+            for slot_index in 0..slice_paths.len().min(NUM_SAMPLE_SLOTS) {
+                state.root_notes[slot_index] = (12 * (slot_index + 1)) as u8;
+            }
+            let command = AudioCommand::SetSamplerSettings {
+                engine_index,
+                root_notes: state.root_notes,
+                global_fine_tune_cents: state.global_fine_tune_cents,
+                fade_out: state.fade_out,
+            };
+            self.send_command(command);
+        }
+
+        if slice_paths.len() > NUM_SAMPLE_SLOTS {
+            self.recording_notification = Some((
+                format!(
+                    "{} slices found; only the first {} fit the sampler's slots.",
+                    slice_paths.len(),
+                    NUM_SAMPLE_SLOTS
+                ),
+                Instant::now(),
+            ));
+        }
+    }
+
+    /// Adds another take to a sampler slot. Slots cycle through their takes on each note.
+    /// An empty slot simply receives the sample as its main take.
+    pub fn append_round_robin_sample(
+        &mut self,
+        engine_index: usize,
+        slot_index: usize,
+        path: PathBuf,
+    ) {
+        let slot_is_empty = match &self.engine_states[engine_index] {
+            EngineState::Sampler(state) => state.sample_paths[slot_index].is_none(),
+            _ => return,
+        };
+        if slot_is_empty {
+            self.load_sample_for_sampler_slot(engine_index, slot_index, path);
+            return;
+        }
+
+        match self.load_and_resample_wav_file(&path, self.active_sample_rate as f32) {
+            Ok(audio_data) => {
+                if let EngineState::Sampler(sampler_state) = &mut self.engine_states[engine_index] {
+                    sampler_state.round_robin_paths[slot_index].push(path);
+                }
+                self.send_command(AudioCommand::AppendSampleToSamplerSlot {
+                    engine_index,
+                    slot_index,
+                    audio_data: Arc::new(audio_data),
+                });
+            }
+            Err(e) => {
+                eprintln!("Error loading sample {}: {}", path.display(), e);
+            }
+        }
+    }
+
     pub fn reset_wavetable_slot_to_default(
         &mut self,
         engine_index: usize,
@@ -770,6 +1329,8 @@ impl CypherApp {
         if let EngineState::Sampler(sampler_state) = &mut self.engine_states[engine_index] {
             sampler_state.sample_names[slot_index] = "Empty".to_string();
             sampler_state.sample_paths[slot_index] = None;
+            sampler_state.round_robin_paths[slot_index].clear();
+            sampler_state.loop_regions[slot_index] = Default::default();
             *sampler_state.sample_data_for_ui[slot_index].write().unwrap() = Vec::new();
             sampler_state.force_redraw_generation += 1; // Bust the visualizer cache
         }
@@ -780,6 +1341,11 @@ impl CypherApp {
             slot_index,
             audio_data: Arc::new(vec![]),
         });
+        self.send_command(AudioCommand::SetSamplerLoopRegion {
+            engine_index,
+            slot_index,
+            region: Default::default(),
+        });
     }
 
     fn resolve_path(&self, path_to_resolve: &Path) -> Option<PathBuf> {
@@ -832,124 +1398,41 @@ impl CypherApp {
         self.selected_output_device_index = None;
     }
 
+    /// Starts scanning the library on a worker thread; `poll_asset_library_scan` swaps the
+    /// result in when it arrives. Starting a new scan cancels any scan still in progress.
     pub fn rescan_asset_library(&mut self) {
-        self.asset_library.clear();
-        if let Some(config_dir) = settings::get_config_dir() {
-            let samples_dir = config_dir.join("Samples");
-            let presets_dir = config_dir.join("SynthPresets");
-            let kits_dir = config_dir.join("Kits");
-            let sessions_dir = config_dir.join("Sessions");
-            let soundscapes_dir = samples_dir.join("Soundscapes");
-
-            // Ensure the dedicated Soundscapes directory exists
-            if !soundscapes_dir.exists() {
-                fs::create_dir_all(&soundscapes_dir).ok();
-            }
-
-            // --- Scan for normal samples, EXCLUDING the Soundscapes folder ---
-            for entry in WalkDir::new(&samples_dir)
-                .min_depth(1) // Start inside the Samples dir
-                .into_iter()
-                .filter_entry(|e| e.path() != soundscapes_dir) // Skip the Soundscapes folder itself
-                .filter_map(|e| e.ok())
-            {
-                if let Ok(relative_path) = entry.path().strip_prefix(&samples_dir) {
-                    let segments: Vec<String> = relative_path
-                        .iter()
-                        .map(|s| s.to_string_lossy().to_string())
-                        .collect();
-
-                    if entry.file_type().is_file()
-                        && entry.path().extension().map_or(false, |e| e == "wav")
-                    {
-                        if let Some(sample_ref) = SampleRef::new(entry.path().to_path_buf()) {
-                            self.asset_library
-                                .sample_root
-                                .insert_asset(&segments, Asset::Sample(sample_ref));
-                        }
-                    }
-                }
-            }
-
-            // --- Scan ONLY the Soundscapes folder for draggable folders ---
-            if soundscapes_dir.is_dir() {
-                for entry in WalkDir::new(&soundscapes_dir)
-                    .min_depth(1)
-                    .max_depth(1) // Do not recurse into subdirectories
-                    .into_iter()
-                    .filter_map(|e| e.ok())
-                {
-                    if entry.file_type().is_dir() {
-                        // We add these to the root of the sample library for the UI to find.
-                        let segments = vec![entry.file_name().to_string_lossy().to_string()];
-                        if let Some(folder_ref) = crate::asset::FolderRef::new(entry.path()) {
-                            self.asset_library
-                                .sample_root
-                                .insert_asset(&segments, Asset::Folder(folder_ref));
-                        }
-                    }
-                }
-            }
-
-            // Scan for synth presets
-            for entry in WalkDir::new(&presets_dir).into_iter().filter_map(|e| e.ok()) {
-                if entry.file_type().is_file()
-                    && entry.path().extension().map_or(false, |e| e == "json")
-                {
-                    if let Ok(relative_path) = entry.path().strip_prefix(&presets_dir) {
-                        let segments: Vec<String> = relative_path
-                            .iter()
-                            .map(|s| s.to_string_lossy().to_string())
-                            .collect();
-                        if let Some(preset_ref) = SynthPresetRef::new(entry.path().to_path_buf()) {
-                            self.asset_library
-                                .synth_root
-                                .insert_asset(&segments, Asset::SynthPreset(preset_ref));
-                        }
-                    }
-                }
-            }
-
-            // Scan for kits
-            for entry in WalkDir::new(&kits_dir).into_iter().filter_map(|e| e.ok()) {
-                if entry.file_type().is_file()
-                    && entry.path().extension().map_or(false, |e| e == "json")
-                {
-                    if let Ok(relative_path) = entry.path().strip_prefix(&kits_dir) {
-                        let segments: Vec<String> = relative_path
-                            .iter()
-                            .map(|s| s.to_string_lossy().to_string())
-                            .collect();
-                        if let Some(kit_ref) = SamplerKitRef::new(entry.path().to_path_buf()) {
-                            self.asset_library
-                                .kit_root
-                                .insert_asset(&segments, Asset::SamplerKit(kit_ref));
-                        }
-                    }
-                }
+        let Some(config_dir) = settings::get_config_dir() else {
+            return;
+        };
+        let generation = self.asset_scan_generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let latest_generation = self.asset_scan_generation.clone();
+        let (sender, receiver) = mpsc::channel();
+        self.asset_scan_receiver = Some(receiver);
+        thread::spawn(move || {
+            let is_superseded = || latest_generation.load(Ordering::Relaxed) != generation;
+            if let Some(library) = AssetLibrary::scan(&config_dir, is_superseded) {
+                sender.send(library).ok();
             }
+        });
+    }
 
-            // Scan for sessions
-            if sessions_dir.is_dir() {
-                for entry in WalkDir::new(&sessions_dir)
-                    .min_depth(1)
-                    .max_depth(1)
-                    .into_iter()
-                    .filter_map(|e| e.ok())
-                {
-                    if entry.file_type().is_dir() {
-                        let segments = vec![entry.file_name().to_string_lossy().to_string()];
-                        if let Some(session_ref) = SessionRef::new(entry.path().to_path_buf()) {
-                            self.asset_library
-                                .session_root
-                                .insert_asset(&segments, Asset::Session(session_ref));
-                        }
-                    }
+    fn poll_asset_library_scan(&mut self) {
+        if let Some(receiver) = &self.asset_scan_receiver {
+            match receiver.try_recv() {
+                Ok(library) => {
+                    self.asset_library = library;
+                    self.asset_scan_receiver = None;
                 }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.asset_scan_receiver = None,
             }
         }
     }
 
+    pub fn is_scanning_asset_library(&self) -> bool {
+        self.asset_scan_receiver.is_some()
+    }
+
     pub fn rescan_available_themes(&mut self) {
         self.available_themes.clear();
         if let Some(config_dir) = settings::get_config_dir() {
@@ -1021,6 +1504,71 @@ impl CypherApp {
         self.available_chord_styles.sort_by(|a, b| a.0.cmp(&b.0));
     }
 
+    pub fn rescan_user_scales(&mut self) {
+        // Keep the selection pointing at the same scale if it still exists.
+        let selected_name = self
+            .selected_user_scale
+            .and_then(|i| self.available_user_scales.get(i))
+            .map(|scale| scale.name.clone());
+        self.available_user_scales.clear();
+        if let Some(config_dir) = settings::get_config_dir() {
+            let scales_dir = config_dir.join("Scales");
+            if !scales_dir.exists() {
+                fs::create_dir_all(&scales_dir).ok();
+                // Create an example scale so the file format is discoverable.
+                let example_path = scales_dir.join("Hirajoshi.json");
+                if !example_path.exists() {
+                    let example = theory::UserScale {
+                        name: "Hirajoshi".to_string(),
+                        intervals: vec![0, 2, 3, 7, 8],
+                    };
+                    if let Ok(json) = serde_json::to_string_pretty(&example) {
+                        fs::write(example_path, json).ok();
+                    }
+                }
+            }
+
+            if scales_dir.is_dir() {
+                for entry in WalkDir::new(scales_dir).into_iter().filter_map(|e| e.ok()) {
+                    if entry.file_type().is_file()
+                        && entry.path().extension().is_some_and(|e| e == "json")
+                    {
+                        if let Ok(json_string) = fs::read_to_string(entry.path()) {
+                            if let Ok(scale) =
+                                serde_json::from_str::<theory::UserScale>(&json_string)
+                            {
+                                if !scale.name.is_empty() {
+                                    self.available_user_scales.push(scale.normalized());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        self.available_user_scales.sort_by(|a, b| a.name.cmp(&b.name));
+        self.selected_user_scale = selected_name.and_then(|name| {
+            self.available_user_scales.iter().position(|scale| scale.name == name)
+        });
+    }
+
+    /// Interval pattern of the scale picked in the 88-keys view, built-in or user.
+    pub fn selected_scale_intervals(&self) -> Vec<u8> {
+        self.selected_user_scale
+            .and_then(|i| self.available_user_scales.get(i))
+            .map_or_else(
+                || self.selected_scale.get_intervals().to_vec(),
+                |scale| scale.intervals.clone(),
+            )
+    }
+
+    /// Display name of the scale picked in the 88-keys view.
+    pub fn selected_scale_name(&self) -> String {
+        self.selected_user_scale
+            .and_then(|i| self.available_user_scales.get(i))
+            .map_or_else(|| self.selected_scale.to_string(), |scale| scale.name.clone())
+    }
+
     pub fn rescan_fx_presets(&mut self) {
         self.available_fx_presets.clear();
         if let Some(config_dir) = settings::get_config_dir() {
@@ -1078,6 +1626,42 @@ impl CypherApp {
         }
     }
 
+    /// Opens the chord style editor on a copy of the selected style.
+    pub fn open_chord_style_editor(&mut self) {
+        self.chord_style_draft = self.selected_chord_style.clone();
+        self.chord_style_editor_window_open = true;
+    }
+
+    /// Writes the draft style to `ChordStyles/<name>.json`, then selects it.
+    pub fn save_chord_style_draft(&mut self) -> Result<(), String> {
+        let style = self.chord_style_draft.clone();
+        let name = style.name.trim();
+        if name.is_empty() {
+            return Err("The style needs a name.".to_string());
+        }
+        let missing = style.missing_functions();
+        if !missing.is_empty() {
+            return Err(format!("Missing chord functions: {}", missing.join(", ")));
+        }
+        let config_dir =
+            settings::get_config_dir().ok_or("Could not find the config directory.")?;
+        let styles_dir = config_dir.join("ChordStyles");
+        fs::create_dir_all(&styles_dir).map_err(|e| e.to_string())?;
+
+        let file_name: String = name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        let path = styles_dir.join(format!("{}.json", file_name));
+        let json = serde_json::to_string_pretty(&style).map_err(|e| e.to_string())?;
+        fs::write(&path, json).map_err(|e| e.to_string())?;
+
+        self.rescan_chord_styles();
+        self.selected_chord_style = style;
+        self.last_recognized_chord_notes.clear();
+        Ok(())
+    }
+
     /// Stops all MIDI connections and their associated timer threads.
     fn stop_midi(&mut self) {
         self.midi_timer_should_exit.store(true, Ordering::Relaxed);
@@ -1181,6 +1765,21 @@ impl CypherApp {
         self.looper_states = looper_states;
         self.master_looper_index = engine.master_looper_index.clone();
         self.tempo_multiplier = engine.tempo_multiplier.clone();
+        self.tempo_locked = engine.tempo_locked.clone();
+        self.exclusive_arm = engine.exclusive_arm.clone();
+        self.exclusive_arm.store(
+            self.settings.arm_policy == ArmPolicy::Exclusive,
+            Ordering::Relaxed,
+        );
+        self.safe_mode = engine.safe_mode.clone();
+        self.stem_recording_limit_reached = engine.stem_recording_limit_reached.clone();
+        self.loudness = engine.loudness.clone();
+        self.master_spectrum = engine.master_spectrum.clone();
+        self.audition_is_playing = engine.audition_is_playing.clone();
+        self.master_correlation = engine.master_correlation.clone();
+        self.loudness_meter_enabled = engine.loudness_meter_enabled.clone();
+        self.loudness_meter_enabled
+            .store(self.settings.loudness_meter_enabled, Ordering::Relaxed);
         self.transport_playhead = engine.transport_playhead.clone();
         self.transport_len_samples = engine.transport_len_samples.clone();
         self.transport_is_playing = engine.transport_is_playing.clone();
@@ -1205,6 +1804,19 @@ impl CypherApp {
         self._input_stream = Some(input_stream);
         self._output_stream = Some(output_stream);
         self.command_sender = Some(mpsc_sender);
+        self.send_command(AudioCommand::SetTuningReference(self.settings.tuning_reference_hz));
+        self.send_command(AudioCommand::SetLoopCrossfade(self.settings.loop_crossfade_ms));
+        self.send_command(AudioCommand::SetMonitorWhileStopped(
+            self.settings.monitor_while_stopped,
+        ));
+        self.send_command(AudioCommand::SetLfoTransportSync(self.settings.lfo_transport_sync));
+        self.send_command(AudioCommand::SetSampleDeclick(self.settings.sample_declick_ms));
+        self.send_command(AudioCommand::SetEngineSwapFade(self.settings.engine_swap_fade_ms));
+        self.send_command(AudioCommand::SetOutputClipMode(self.settings.output_clip_mode));
+        self.send_command(AudioCommand::SetLimiterTruePeak(self.limiter_true_peak));
+        self.send_command(AudioCommand::SetLimiterLookahead(self.limiter_lookahead_ms));
+        self.load_send_racks();
+        self.load_default_fx_chains();
         self.active_sample_rate = active_sr;
         self.active_buffer_size = active_bs;
         self.active_input_device_name = input_device_name;
@@ -1236,6 +1848,7 @@ impl CypherApp {
                     state.saturation_mod_atomic.clone(),
                     state.final_wt_pos_atomic.clone(),
                     state.final_cutoff_atomic.clone(),
+                    state.unison_settings.clone(),
                 );
                 (
                     state.volume.clone(),
@@ -1355,7 +1968,12 @@ impl CypherApp {
                         self.midi_synth_editor_toggle_request.clone(),
                         self.midi_sampler_editor_toggle_request.clone(),
                         self.midi_fx_preset_change_request.clone(),
+                        self.midi_chord_pad_request.clone(),
+                        self.midi_note_quantize_mask.clone(),
                         self.midi_mapping_inversions.clone(),
+                        self.midi_program_change_request.clone(),
+                        self.follow_midi_clock.clone(),
+                        self.active_sample_rate,
                     ) {
                         Ok((conn, handle)) => {
                             self._midi_connections.push(conn);
@@ -1411,6 +2029,29 @@ impl CypherApp {
         }
     }
 
+    /// Previews a library sample through the master without assigning it anywhere. Clicking
+    /// the sample that is still playing stops it.
+    pub fn toggle_sample_audition(&mut self, path: &Path) {
+        if self.audition_is_playing.load(Ordering::Relaxed)
+            && self.auditioned_sample.as_deref() == Some(path)
+        {
+            self.send_command(AudioCommand::StopAudition);
+            self.auditioned_sample = None;
+            return;
+        }
+        if self.active_sample_rate == 0 {
+            return;
+        }
+        match self.load_and_resample_wav_file(path, self.active_sample_rate as f32) {
+            Ok(audio) => {
+                self.send_command(AudioCommand::AuditionSample(Arc::new(audio)));
+                self.audition_is_playing.store(true, Ordering::Relaxed);
+                self.auditioned_sample = Some(path.to_path_buf());
+            }
+            Err(e) => eprintln!("Failed to audition sample {}: {}", path.display(), e),
+        }
+    }
+
     pub fn load_and_resample_wav_file(&self, path: &Path, target_sr: f32) -> Result<Vec<f32>> {
         let file = BufReader::new(File::open(path)?);
         let source = Decoder::new(file)?;
@@ -1481,6 +2122,25 @@ impl CypherApp {
         }
     }
 
+    /// Loads the Nth synth preset or sampler kit in the library, depending on the
+    /// configured Program Change target. Out-of-range program numbers are ignored.
+    pub fn handle_program_change(&mut self, program: usize) {
+        match self.settings.program_change_target {
+            settings::ProgramChangeTarget::SynthPresets => {
+                let presets = self.asset_library.synth_root.flattened_paths();
+                if let Some(path) = presets.get(program) {
+                    self.load_preset_from_path(path);
+                }
+            }
+            settings::ProgramChangeTarget::SamplerKits => {
+                let kits = self.asset_library.kit_root.flattened_paths();
+                if let Some(path) = kits.get(program) {
+                    self.load_kit(path);
+                }
+            }
+        }
+    }
+
     pub fn load_kit(&mut self, path: &PathBuf) {
         let absolute_path = if path.is_absolute() {
             path.clone()
@@ -1517,6 +2177,9 @@ impl CypherApp {
                     });
                 }
 
+                self.pad_groove = kit.groove;
+                self.send_command(AudioCommand::SetPadGroove(kit.groove));
+
                 // Convert the kit path to be relative for portability before saving.
                 if let Some(config_dir) = settings::get_config_dir() {
                     if let Ok(relative_path) = absolute_path.strip_prefix(&config_dir) {
@@ -1533,6 +2196,102 @@ impl CypherApp {
         }
     }
 
+    /// Writes the current pads as a single WAV sprite plus a `.sprite.json` mapping
+    /// holding each pad's offset, length and FX settings.
+    pub fn export_kit_sprite(&self, wav_path: &Path) -> Result<()> {
+        let sample_rate = self.active_sample_rate;
+        let pad_buffers: [Option<Vec<f32>>; 16] = std::array::from_fn(|i| {
+            self.sampler_pad_info[i].as_ref().and_then(|sample_ref| {
+                self.load_and_resample_wav_file(&sample_ref.path, sample_rate as f32)
+                    .map_err(|e| eprintln!("Skipping pad {} in sprite export: {}", i + 1, e))
+                    .ok()
+            })
+        });
+
+        let (sprite, regions) = sampler::build_kit_sprite(&pad_buffers);
+        audio_engine::write_wav_file(
+            wav_path,
+            &sprite,
+            sample_rate as f32,
+            RecordingFormat::Int16,
+        )?;
+
+        let mapping = KitSpriteMapping {
+            sample_rate,
+            regions,
+            fx: self.sampler_pad_fx_settings,
+            groove: self.pad_groove,
+        };
+        fs::write(
+            wav_path.with_extension("sprite.json"),
+            serde_json::to_string_pretty(&mapping)?,
+        )?;
+        Ok(())
+    }
+
+    /// Unpacks a WAV sprite and its mapping into individual pad samples under
+    /// `Samples/Sprites/<name>`, saves a matching kit and loads it.
+    pub fn import_kit_sprite(&mut self, wav_path: &Path) -> Result<()> {
+        let config_dir = settings::get_config_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?;
+        let mapping: KitSpriteMapping =
+            serde_json::from_str(&fs::read_to_string(wav_path.with_extension("sprite.json"))?)?;
+
+        // Decode at the sprite's own rate so the stored offsets stay valid.
+        let sprite = self.load_and_resample_wav_file(wav_path, mapping.sample_rate as f32)?;
+        let pad_buffers = sampler::split_kit_sprite(&sprite, &mapping.regions);
+
+        let base_name = wav_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Imported Kit".to_string());
+        // Never overwrite an existing kit or the samples another import unpacked.
+        let kits_dir = config_dir.join("Kits");
+        let sprites_dir = config_dir.join("Samples").join("Sprites");
+        let mut kit_name = base_name.clone();
+        let mut copy = 2;
+        while kits_dir.join(format!("{}.json", kit_name)).exists()
+            || sprites_dir.join(&kit_name).exists()
+        {
+            kit_name = format!("{} {}", base_name, copy);
+            copy += 1;
+        }
+        let samples_dir = sprites_dir.join(&kit_name);
+        fs::create_dir_all(&samples_dir)?;
+        fs::create_dir_all(&kits_dir)?;
+
+        let mut pads: [SamplerPadSettings; 16] = Default::default();
+        for (i, buffer) in pad_buffers.iter().enumerate() {
+            if let Some(buffer) = buffer {
+                let pad_path = samples_dir.join(format!("Pad {:02}.wav", i + 1));
+                audio_engine::write_wav_file(
+                    &pad_path,
+                    buffer,
+                    mapping.sample_rate as f32,
+                    RecordingFormat::Int16,
+                )?;
+                pads[i].path = Some(
+                    pad_path
+                        .strip_prefix(&config_dir)
+                        .unwrap_or(&pad_path)
+                        .to_path_buf(),
+                );
+            }
+            pads[i].fx = mapping.fx[i];
+        }
+
+        let kit = SamplerKit {
+            pads,
+            groove: mapping.groove,
+        };
+        let kit_path = kits_dir.join(format!("{}.json", kit_name));
+        fs::write(&kit_path, serde_json::to_string_pretty(&kit)?)?;
+
+        self.rescan_asset_library();
+        self.load_kit(&kit_path);
+        Ok(())
+    }
+
     pub fn load_preset_from_path(&mut self, path: &Path) {
         // --- Step 1: Resolve the incoming path to an absolute one for reading ---
         let absolute_path = if path.is_absolute() {
@@ -1551,243 +2310,431 @@ impl CypherApp {
         // --- Step 2: Read the file and parse the preset ---
         if let Ok(json_string) = fs::read_to_string(&absolute_path) {
             if let Ok(preset) = serde_json::from_str::<SynthPreset>(&json_string) {
-                let mut commands_to_send = Vec::new();
-                let mut sampler_loads_to_perform = Vec::new();
-
-                // --- Three-pass loading to avoid borrow checker issues ---
-                // Pass 1: Load all raw audio data immutably.
-                let mut loaded_wavetables = Vec::new();
-                for i in 0..2 {
-                    if let SynthEnginePreset::Wavetable(engine_preset) = &preset.engine_presets[i]
-                    {
-                        for k in 0..4 {
-                            if let WavetableSource::File(p) = &engine_preset.wavetable_sources[k] {
-                                if let Some(resolved_path) = self.resolve_path(p) {
-                                    if let Ok(source_audio) =
-                                        load_source_audio_file_with_sr(&resolved_path)
-                                    {
-                                        loaded_wavetables
-                                            .push((i, k, resolved_path, source_audio));
-                                    }
-                                }
-                            }
-                        }
+                self.apply_synth_preset(&preset, &[0, 1]);
+
+                // --- Step 3: Store a relative path if possible ---
+                if let Some(config_dir) = settings::get_config_dir() {
+                    if let Ok(relative_path) = absolute_path.strip_prefix(&config_dir) {
+                        // Success: store the portable, relative path.
+                        self.settings.last_synth_preset = Some(relative_path.to_path_buf());
+                    } else {
+                        // Fallback: the preset is outside the portable folder, store its absolute path.
+                        self.settings.last_synth_preset = Some(absolute_path);
                     }
+                } else {
+                    // Fallback: can't get config dir, store absolute path.
+                    self.settings.last_synth_preset = Some(absolute_path);
                 }
+            }
+        }
+    }
 
-                // Pass 2: Mutably update all state.
-                for i in 0..2 {
-                    let needs_engine_change =
-                        match (&preset.engine_presets[i], &self.engine_states[i]) {
-                            (SynthEnginePreset::Wavetable(_), EngineState::Sampler(_)) => true,
-                            (SynthEnginePreset::Sampler(_), EngineState::Wavetable(_)) => true,
-                            _ => false,
-                        };
+    fn capture_synth_preset(&self) -> SynthPreset {
+        let config_dir = settings::get_config_dir().unwrap_or_default();
+        SynthPreset {
+            engine_presets: [
+                self.create_engine_preset(0, &config_dir),
+                self.create_engine_preset(1, &config_dir),
+            ],
+        }
+    }
 
-                    if needs_engine_change {
-                        let is_wavetable =
-                            matches!(preset.engine_presets[i], SynthEnginePreset::Wavetable(_));
-                        self.set_engine_type(i, is_wavetable);
-                    }
+    /// Stores the live patch in the active A/B slot and switches to `slot`. An empty slot
+    /// starts out as a copy of the patch being left.
+    pub fn select_synth_ab_slot(&mut self, slot: usize) {
+        if slot == self.synth_ab_active {
+            return;
+        }
+        let current = self.capture_synth_preset();
+        self.synth_ab_slots[self.synth_ab_active] = Some(current.clone());
+        self.synth_ab_active = slot;
+        if self.synth_ab_slots[slot].is_none() {
+            self.synth_ab_slots[slot] = Some(current);
+        } else {
+            self.recall_synth_ab_slot();
+        }
+    }
 
-                    match &preset.engine_presets[i] {
-                        SynthEnginePreset::Wavetable(engine_preset) => {
-                            if let EngineState::Wavetable(wt_state) = &mut self.engine_states[i] {
-                                // Apply non-wavetable settings
-                                wt_state.volume.store(
-                                    (engine_preset.volume * 1_000_000.0) as u32,
-                                    Ordering::Relaxed,
-                                );
-                                *wt_state.saturation_settings.write().unwrap() =
-                                    engine_preset.saturation_settings;
-                                wt_state.amp_adsr = engine_preset.amp_adsr;
-                                wt_state.filter_adsr = engine_preset.filter_adsr;
-                                *wt_state.filter_settings.write().unwrap() = engine_preset.filter;
-                                *wt_state.lfo_settings.write().unwrap() =
-                                    engine_preset.lfo_settings;
-                                *wt_state.lfo2_settings.write().unwrap() =
-                                    engine_preset.lfo2_settings;
-                                *wt_state.mod_matrix.write().unwrap() =
-                                    engine_preset.mod_matrix.clone();
-                                wt_state.is_polyphonic = engine_preset.is_polyphonic;
-                                wt_state.wavetable_position.store(
-                                    engine_preset.wavetable_position_m_u32,
-                                    Ordering::Relaxed,
-                                );
-                                *wt_state.wavetable_mixer_settings.write().unwrap() =
-                                    engine_preset.wavetable_mixer;
+    pub fn copy_synth_a_to_b(&mut self) {
+        if self.synth_ab_active == 0 {
+            self.synth_ab_slots[0] = Some(self.capture_synth_preset());
+        }
+        self.synth_ab_slots[1] = self.synth_ab_slots[0].clone();
+        if self.synth_ab_active == 1 {
+            self.recall_synth_ab_slot();
+        }
+    }
 
-                                // Queue commands for settings
-                                commands_to_send
-                                    .push(AudioCommand::SetAmpAdsr(i, engine_preset.amp_adsr));
-                                commands_to_send.push(AudioCommand::SetFilterAdsr(
-                                    i,
-                                    engine_preset.filter_adsr,
-                                ));
-                                commands_to_send.push(AudioCommand::SetSynthMode(
-                                    i,
-                                    engine_preset.is_polyphonic,
-                                ));
-
-                                // Store the pre-loaded raw audio data
-                                for (loaded_i, loaded_k, resolved_path, source_audio) in
-                                    &loaded_wavetables
-                                {
-                                    if *loaded_i == i {
-                                        wt_state.wavetable_names[*loaded_k] = resolved_path
-                                            .file_stem()
-                                            .unwrap()
-                                            .to_string_lossy()
-                                            .to_string();
-                                        wt_state.wavetable_sources[*loaded_k] =
-                                            WavetableSource::File(resolved_path.clone());
-                                        wt_state.window_positions[*loaded_k] =
-                                            engine_preset.window_positions[*loaded_k];
-                                        wt_state.original_sources[*loaded_k] =
-                                            Arc::new(source_audio.data.clone());
-                                        wt_state.source_sample_rates[*loaded_k] =
-                                            source_audio.sample_rate;
-                                    }
-                                }
+    pub fn swap_synth_ab_slots(&mut self) {
+        self.synth_ab_slots[self.synth_ab_active] = Some(self.capture_synth_preset());
+        self.synth_ab_slots.swap(0, 1);
+        self.recall_synth_ab_slot();
+    }
+
+    fn recall_synth_ab_slot(&mut self) {
+        let Some(preset) = self.synth_ab_slots[self.synth_ab_active].clone() else {
+            return;
+        };
+        self.apply_synth_preset(&preset, &[0, 1]);
+        for state in self.engine_states.iter_mut() {
+            match state {
+                EngineState::Wavetable(s) => s.force_redraw_generation += 1,
+                EngineState::Sampler(s) => s.force_redraw_generation += 1,
+            }
+        }
+    }
+
+    /// Rolls new LFOs and mod routings for an engine and, unless `modulation_only` is set, a new
+    /// filter, envelopes and saturation drive too.
+    pub fn randomize_engine(&mut self, engine_index: usize, modulation_only: bool) {
+        let mut rng = rand::thread_rng();
+        let is_wavetable = matches!(self.engine_states[engine_index], EngineState::Wavetable(_));
+        let mod_matrix = random_mod_matrix(&mut rng, is_wavetable);
+        let tone = (!modulation_only).then(|| {
+            let filter = FilterSettings {
+                mode: FilterMode::ALL[rng.gen_range(0..FilterMode::ALL.len())],
+                cutoff: rng.gen_range(0.3..1.0f32).powi(4),
+                resonance: rng.gen_range(0.0..0.7),
+            };
+            (filter, random_adsr(&mut rng), random_adsr(&mut rng), rng.gen_range(0.0..0.5))
+        });
+
+        match &mut self.engine_states[engine_index] {
+            EngineState::Wavetable(state) => {
+                randomize_lfo(&mut rng, &mut state.lfo_settings.write().unwrap(), is_wavetable);
+                randomize_lfo(&mut rng, &mut state.lfo2_settings.write().unwrap(), is_wavetable);
+                *state.mod_matrix.write().unwrap() = mod_matrix;
+                if let Some((filter, amp_adsr, filter_adsr, drive)) = tone {
+                    *state.filter_settings.write().unwrap() = filter;
+                    state.amp_adsr = amp_adsr;
+                    state.filter_adsr = filter_adsr;
+                    state.saturation_settings.write().unwrap().drive = drive;
+                }
+                state.force_redraw_generation += 1;
+            }
+            EngineState::Sampler(state) => {
+                randomize_lfo(&mut rng, &mut state.lfo_settings.write().unwrap(), is_wavetable);
+                randomize_lfo(&mut rng, &mut state.lfo2_settings.write().unwrap(), is_wavetable);
+                *state.mod_matrix.write().unwrap() = mod_matrix;
+                if let Some((filter, amp_adsr, filter_adsr, drive)) = tone {
+                    *state.filter_settings.write().unwrap() = filter;
+                    state.amp_adsr = amp_adsr;
+                    state.filter_adsr = filter_adsr;
+                    state.saturation_settings.write().unwrap().drive = drive;
+                }
+                state.force_redraw_generation += 1;
+            }
+        }
+
+        if let Some((_, amp_adsr, filter_adsr, _)) = tone {
+            self.send_command(AudioCommand::SetAmpAdsr(engine_index, amp_adsr));
+            self.send_command(AudioCommand::SetFilterAdsr(engine_index, filter_adsr));
+        }
+    }
+
+    /// Gives `to` an independent copy of every setting and wavetable/sample on `from`, switching
+    /// its engine type first if needed.
+    pub fn copy_engine_settings(&mut self, from: usize, to: usize) {
+        let config_dir = settings::get_config_dir().unwrap_or_default();
+        let mut preset = SynthPreset::default();
+        preset.engine_presets[to] = self.create_engine_preset(from, &config_dir);
+        self.apply_synth_preset(&preset, &[to]);
+        match &mut self.engine_states[to] {
+            EngineState::Wavetable(s) => s.force_redraw_generation += 1,
+            EngineState::Sampler(s) => s.force_redraw_generation += 1,
+        }
+    }
+
+    /// Applies the given engines of a synth preset, loading any referenced audio from disk.
+    fn apply_synth_preset(&mut self, preset: &SynthPreset, engine_indices: &[usize]) {
+        let mut commands_to_send = Vec::new();
+        let mut sampler_loads_to_perform = Vec::new();
+        let mut round_robin_loads_to_perform = Vec::new();
+
+        // --- Three-pass loading to avoid borrow checker issues ---
+        // Pass 1: Load all raw audio data immutably.
+        let mut loaded_wavetables = Vec::new();
+        for &i in engine_indices {
+            if let SynthEnginePreset::Wavetable(engine_preset) = &preset.engine_presets[i]
+            {
+                for k in 0..4 {
+                    if let WavetableSource::File(p) = &engine_preset.wavetable_sources[k] {
+                        if let Some(resolved_path) = self.resolve_path(p) {
+                            if let Ok(source_audio) =
+                                load_source_audio_file_with_sr(&resolved_path)
+                            {
+                                loaded_wavetables
+                                    .push((i, k, resolved_path, source_audio));
                             }
                         }
-                        SynthEnginePreset::Sampler(engine_preset) => {
-                            if let EngineState::Sampler(sampler_state) = &mut self.engine_states[i]
-                            {
-                                // Apply global settings
-                                sampler_state.volume.store(
-                                    (engine_preset.volume * 1_000_000.0) as u32,
-                                    Ordering::Relaxed,
-                                );
-                                *sampler_state.saturation_settings.write().unwrap() =
-                                    engine_preset.saturation_settings;
-                                sampler_state.amp_adsr = engine_preset.amp_adsr;
-                                sampler_state.filter_adsr = engine_preset.filter_adsr;
-                                *sampler_state.filter_settings.write().unwrap() =
-                                    engine_preset.filter;
-                                *sampler_state.lfo_settings.write().unwrap() =
-                                    engine_preset.lfo_settings;
-                                *sampler_state.lfo2_settings.write().unwrap() =
-                                    engine_preset.lfo2_settings;
-                                *sampler_state.mod_matrix.write().unwrap() =
-                                    engine_preset.mod_matrix.clone();
-                                sampler_state.is_polyphonic = engine_preset.is_polyphonic;
-
-                                // Sampler specifics
-                                sampler_state.root_notes = engine_preset.root_notes;
-                                sampler_state.global_fine_tune_cents =
-                                    engine_preset.global_fine_tune_cents;
-                                sampler_state.fade_out = engine_preset.fade_out;
-
-                                // Queue commands
-                                commands_to_send
-                                    .push(AudioCommand::SetAmpAdsr(i, engine_preset.amp_adsr));
-                                commands_to_send.push(AudioCommand::SetFilterAdsr(
-                                    i,
-                                    engine_preset.filter_adsr,
-                                ));
-                                commands_to_send.push(AudioCommand::SetSynthMode(
-                                    i,
-                                    engine_preset.is_polyphonic,
-                                ));
-                                commands_to_send.push(AudioCommand::SetSamplerSettings {
-                                    engine_index: i,
-                                    root_notes: engine_preset.root_notes,
-                                    global_fine_tune_cents: engine_preset.global_fine_tune_cents,
-                                    fade_out: engine_preset.fade_out,
-                                });
+                    }
+                }
+            }
+        }
 
-                                // Clear all slots before loading new ones
-                                for k in 0..NUM_SAMPLE_SLOTS {
-                                    sampler_state.sample_names[k] = "Empty".to_string();
-                                    sampler_state.sample_paths[k] = None;
-                                    sampler_state.sample_data_for_ui[k].write().unwrap().clear();
-                                }
+        // Pass 2: Mutably update all state.
+        for &i in engine_indices {
+            let needs_engine_change =
+                match (&preset.engine_presets[i], &self.engine_states[i]) {
+                    (SynthEnginePreset::Wavetable(_), EngineState::Sampler(_)) => true,
+                    (SynthEnginePreset::Sampler(_), EngineState::Wavetable(_)) => true,
+                    _ => false,
+                };
 
-                                // Defer the actual loading until after this loop
-                                for (k, path_opt) in engine_preset.sample_paths.iter().enumerate()
-                                {
-                                    if let Some(p) = path_opt {
-                                        if let Some(resolved_path) = self.resolve_path(p) {
-                                            sampler_loads_to_perform
-                                                .push((i, k, resolved_path.clone()));
-                                        } else {
-                                            eprintln!("Sample file not found: {:?}", p);
-                                        }
-                                    }
-                                }
+            if needs_engine_change {
+                let is_wavetable =
+                    matches!(preset.engine_presets[i], SynthEnginePreset::Wavetable(_));
+                self.set_engine_type(i, is_wavetable);
+            }
+
+            match &preset.engine_presets[i] {
+                SynthEnginePreset::Wavetable(engine_preset) => {
+                    if let EngineState::Wavetable(wt_state) = &mut self.engine_states[i] {
+                        // Apply non-wavetable settings
+                        wt_state.volume.store(
+                            (engine_preset.volume * 1_000_000.0) as u32,
+                            Ordering::Relaxed,
+                        );
+                        *wt_state.saturation_settings.write().unwrap() =
+                            engine_preset.saturation_settings;
+                        wt_state.amp_adsr = engine_preset.amp_adsr;
+                        wt_state.filter_adsr = engine_preset.filter_adsr;
+                        wt_state.velocity_sensitivity = engine_preset.velocity_sensitivity;
+                        *wt_state.filter_settings.write().unwrap() = engine_preset.filter;
+                        *wt_state.lfo_settings.write().unwrap() =
+                            engine_preset.lfo_settings;
+                        *wt_state.lfo2_settings.write().unwrap() =
+                            engine_preset.lfo2_settings;
+                        *wt_state.mod_matrix.write().unwrap() =
+                            engine_preset.mod_matrix.clone();
+                        wt_state.is_polyphonic = engine_preset.is_polyphonic;
+                        wt_state.glide_time_ms = engine_preset.glide_time_ms;
+                        wt_state.wavetable_position.store(
+                            engine_preset.wavetable_position_m_u32,
+                            Ordering::Relaxed,
+                        );
+                        *wt_state.wavetable_mixer_settings.write().unwrap() =
+                            engine_preset.wavetable_mixer;
+                        *wt_state.unison_settings.write().unwrap() = engine_preset.unison;
+
+                        // Queue commands for settings
+                        commands_to_send
+                            .push(AudioCommand::SetAmpAdsr(i, engine_preset.amp_adsr));
+                        commands_to_send.push(AudioCommand::SetFilterAdsr(
+                            i,
+                            engine_preset.filter_adsr,
+                        ));
+                        commands_to_send.push(AudioCommand::SetVelocitySensitivity(
+                            i,
+                            engine_preset.velocity_sensitivity,
+                        ));
+                        commands_to_send
+                            .push(AudioCommand::SetGlideTime(i, engine_preset.glide_time_ms));
+                        commands_to_send.push(AudioCommand::SetSynthMode(
+                            i,
+                            engine_preset.is_polyphonic,
+                        ));
+
+                        // Store the pre-loaded raw audio data
+                        for (loaded_i, loaded_k, resolved_path, source_audio) in
+                            &loaded_wavetables
+                        {
+                            if *loaded_i == i {
+                                wt_state.wavetable_names[*loaded_k] = resolved_path
+                                    .file_stem()
+                                    .unwrap()
+                                    .to_string_lossy()
+                                    .to_string();
+                                wt_state.wavetable_sources[*loaded_k] =
+                                    WavetableSource::File(resolved_path.clone());
+                                wt_state.window_positions[*loaded_k] =
+                                    engine_preset.window_positions[*loaded_k];
+                                wt_state.original_sources[*loaded_k] =
+                                    Arc::new(source_audio.data.clone());
+                                wt_state.source_sample_rates[*loaded_k] =
+                                    source_audio.sample_rate;
                             }
                         }
                     }
                 }
-
-                // Pass 3: Generate and send wavetables now that state is updated.
-                let default_tables = wavetable_engine::WavetableSet::new_basic();
-                for i in 0..2 {
-                    if let SynthEnginePreset::Wavetable(engine_preset) = &preset.engine_presets[i]
+                SynthEnginePreset::Sampler(engine_preset) => {
+                    if let EngineState::Sampler(sampler_state) = &mut self.engine_states[i]
                     {
-                        for k in 0..4 {
-                            match &engine_preset.wavetable_sources[k] {
-                                WavetableSource::File(_) => {
-                                    // We only need to generate for files that were actually found and loaded
-                                    if loaded_wavetables
-                                        .iter()
-                                        .any(|(li, lk, _, _)| *li == i && *lk == k)
-                                    {
-                                        self.generate_and_send_wavetable(
-                                            i,
-                                            k,
-                                            engine_preset.window_positions[k],
-                                        );
-                                    }
+                        // Apply global settings
+                        sampler_state.volume.store(
+                            (engine_preset.volume * 1_000_000.0) as u32,
+                            Ordering::Relaxed,
+                        );
+                        *sampler_state.saturation_settings.write().unwrap() =
+                            engine_preset.saturation_settings;
+                        sampler_state.amp_adsr = engine_preset.amp_adsr;
+                        sampler_state.filter_adsr = engine_preset.filter_adsr;
+                        sampler_state.velocity_sensitivity =
+                            engine_preset.velocity_sensitivity;
+                        *sampler_state.filter_settings.write().unwrap() =
+                            engine_preset.filter;
+                        *sampler_state.lfo_settings.write().unwrap() =
+                            engine_preset.lfo_settings;
+                        *sampler_state.lfo2_settings.write().unwrap() =
+                            engine_preset.lfo2_settings;
+                        *sampler_state.mod_matrix.write().unwrap() =
+                            engine_preset.mod_matrix.clone();
+                        sampler_state.is_polyphonic = engine_preset.is_polyphonic;
+                        sampler_state.glide_time_ms = engine_preset.glide_time_ms;
+
+                        // Sampler specifics
+                        sampler_state.root_notes = engine_preset.root_notes;
+                        sampler_state.global_fine_tune_cents =
+                            engine_preset.global_fine_tune_cents;
+                        sampler_state.fade_out = engine_preset.fade_out;
+                        for (k, region) in
+                            sampler_state.loop_regions.iter_mut().enumerate()
+                        {
+                            *region = engine_preset
+                                .loop_regions
+                                .get(k)
+                                .copied()
+                                .unwrap_or_default();
+                        }
+                        sampler_state.velocity_zones = engine_preset.velocity_zones.clone();
+
+                        // Queue commands
+                        commands_to_send
+                            .push(AudioCommand::SetAmpAdsr(i, engine_preset.amp_adsr));
+                        commands_to_send.push(AudioCommand::SetFilterAdsr(
+                            i,
+                            engine_preset.filter_adsr,
+                        ));
+                        commands_to_send.push(AudioCommand::SetVelocitySensitivity(
+                            i,
+                            engine_preset.velocity_sensitivity,
+                        ));
+                        commands_to_send
+                            .push(AudioCommand::SetGlideTime(i, engine_preset.glide_time_ms));
+                        commands_to_send.push(AudioCommand::SetSynthMode(
+                            i,
+                            engine_preset.is_polyphonic,
+                        ));
+                        commands_to_send.push(AudioCommand::SetSamplerSettings {
+                            engine_index: i,
+                            root_notes: engine_preset.root_notes,
+                            global_fine_tune_cents: engine_preset.global_fine_tune_cents,
+                            fade_out: engine_preset.fade_out,
+                        });
+                        for (k, region) in sampler_state.loop_regions.iter().enumerate() {
+                            commands_to_send.push(AudioCommand::SetSamplerLoopRegion {
+                                engine_index: i,
+                                slot_index: k,
+                                region: *region,
+                            });
+                        }
+                        commands_to_send.push(AudioCommand::SetSamplerVelocityZones {
+                            engine_index: i,
+                            zones: sampler_state.velocity_zones.clone(),
+                        });
+
+                        // Clear all slots before loading new ones
+                        for k in 0..NUM_SAMPLE_SLOTS {
+                            sampler_state.sample_names[k] = "Empty".to_string();
+                            sampler_state.sample_paths[k] = None;
+                            sampler_state.round_robin_paths[k].clear();
+                            sampler_state.sample_data_for_ui[k].write().unwrap().clear();
+                        }
+
+                        // Defer the actual loading until after this loop
+                        for (k, path_opt) in engine_preset.sample_paths.iter().enumerate()
+                        {
+                            if let Some(p) = path_opt {
+                                if let Some(resolved_path) = self.resolve_path(p) {
+                                    sampler_loads_to_perform
+                                        .push((i, k, resolved_path.clone()));
+                                } else {
+                                    eprintln!("Sample file not found: {:?}", p);
                                 }
-                                WavetableSource::Default(name) => {
-                                    let (default_name, default_audio) = default_tables
-                                        .tables
-                                        .iter()
-                                        .find(|t| &t.name == name)
-                                        .map(|t| (t.name.clone(), t.table.clone()))
-                                        .unwrap_or_else(|| {
-                                            (
-                                                "Sine".to_string(),
-                                                default_tables.tables[0].table.clone(),
-                                            )
-                                        });
-                                    commands_to_send.push(AudioCommand::SetWavetable {
-                                        engine_index: i,
-                                        slot_index: k,
-                                        audio_data: Arc::new(default_audio),
-                                        name: default_name,
-                                    });
+                            }
+                        }
+                        for (k, paths) in engine_preset
+                            .round_robin_paths
+                            .iter()
+                            .enumerate()
+                            .take(NUM_SAMPLE_SLOTS)
+                        {
+                            for p in paths {
+                                if let Some(resolved_path) = self.resolve_path(p) {
+                                    round_robin_loads_to_perform
+                                        .push((i, k, resolved_path));
+                                } else {
+                                    eprintln!("Sample file not found: {:?}", p);
                                 }
                             }
                         }
                     }
                 }
+            }
+        }
 
-                // Send all commands and perform sample loads
-                for cmd in commands_to_send {
-                    self.send_command(cmd);
-                }
-                for (engine_idx, slot_idx, p) in sampler_loads_to_perform {
-                    self.load_sample_for_sampler_slot(engine_idx, slot_idx, p);
-                }
-
-                // --- Step 3: Store a relative path if possible ---
-                if let Some(config_dir) = settings::get_config_dir() {
-                    if let Ok(relative_path) = absolute_path.strip_prefix(&config_dir) {
-                        // Success: store the portable, relative path.
-                        self.settings.last_synth_preset = Some(relative_path.to_path_buf());
-                    } else {
-                        // Fallback: the preset is outside the portable folder, store its absolute path.
-                        self.settings.last_synth_preset = Some(absolute_path);
+        // Pass 3: Generate and send wavetables now that state is updated.
+        let default_tables = wavetable_engine::WavetableSet::new_basic();
+        for &i in engine_indices {
+            if let SynthEnginePreset::Wavetable(engine_preset) = &preset.engine_presets[i]
+            {
+                for k in 0..4 {
+                    match &engine_preset.wavetable_sources[k] {
+                        WavetableSource::File(_) => {
+                            // We only need to generate for files that were actually found and loaded
+                            if loaded_wavetables
+                                .iter()
+                                .any(|(li, lk, _, _)| *li == i && *lk == k)
+                            {
+                                self.generate_and_send_wavetable(
+                                    i,
+                                    k,
+                                    engine_preset.window_positions[k],
+                                );
+                            }
+                        }
+                        WavetableSource::Default(name) => {
+                            let (default_name, default_audio) = default_tables
+                                .tables
+                                .iter()
+                                .find(|t| &t.name == name)
+                                .map(|t| (t.name.clone(), t.table.clone()))
+                                .unwrap_or_else(|| {
+                                    (
+                                        "Sine".to_string(),
+                                        default_tables.tables[0].table.clone(),
+                                    )
+                                });
+                            if let EngineState::Wavetable(wt_state) = &mut self.engine_states[i] {
+                                wt_state.wavetable_names[k] = default_name.clone();
+                                wt_state.wavetable_sources[k] =
+                                    WavetableSource::Default(default_name.clone());
+                                wt_state.window_positions[k] = engine_preset.window_positions[k];
+                                wt_state.original_sources[k] = Arc::new(default_audio.clone());
+                                wt_state.source_sample_rates[k] = self.active_sample_rate;
+                            }
+                            commands_to_send.push(AudioCommand::SetWavetable {
+                                engine_index: i,
+                                slot_index: k,
+                                audio_data: Arc::new(default_audio),
+                                name: default_name,
+                            });
+                        }
                     }
-                } else {
-                    // Fallback: can't get config dir, store absolute path.
-                    self.settings.last_synth_preset = Some(absolute_path);
                 }
             }
         }
+
+        // Send all commands and perform sample loads
+        for cmd in commands_to_send {
+            self.send_command(cmd);
+        }
+        for (engine_idx, slot_idx, p) in sampler_loads_to_perform {
+            self.load_sample_for_sampler_slot(engine_idx, slot_idx, p);
+        }
+        for (engine_idx, slot_idx, p) in round_robin_loads_to_perform {
+            self.append_round_robin_sample(engine_idx, slot_idx, p);
+        }
     }
 
     pub fn save_preset(&mut self) {
@@ -1832,13 +2779,16 @@ impl CypherApp {
                     volume: state.volume.load(Ordering::Relaxed) as f32 / 1_000_000.0,
                     amp_adsr: state.amp_adsr,
                     filter_adsr: state.filter_adsr,
+                    velocity_sensitivity: state.velocity_sensitivity,
                     filter: *state.filter_settings.read().unwrap(),
                     lfo_settings: *state.lfo_settings.read().unwrap(),
                     lfo2_settings: *state.lfo2_settings.read().unwrap(),
                     mod_matrix: state.mod_matrix.read().unwrap().clone(),
                     saturation_settings: *state.saturation_settings.read().unwrap(),
+                    unison: *state.unison_settings.read().unwrap(),
                     wavetable_position_m_u32: state.wavetable_position.load(Ordering::Relaxed),
                     is_polyphonic: state.is_polyphonic,
+                    glide_time_ms: state.glide_time_ms,
                     wavetable_sources: sources,
                     window_positions: state.window_positions,
                     wavetable_mixer: *state.wavetable_mixer_settings.read().unwrap(),
@@ -1853,19 +2803,34 @@ impl CypherApp {
                             Some(p.strip_prefix(config_dir).unwrap_or(p).to_path_buf());
                     }
                 }
+                let round_robin_paths = state
+                    .round_robin_paths
+                    .iter()
+                    .map(|paths| {
+                        paths
+                            .iter()
+                            .map(|p| p.strip_prefix(config_dir).unwrap_or(p).to_path_buf())
+                            .collect()
+                    })
+                    .collect();
 
                 let sampler_preset = sampler_engine::SamplerEnginePreset {
                     volume: state.volume.load(Ordering::Relaxed) as f32 / 1_000_000.0,
                     amp_adsr: state.amp_adsr,
                     filter_adsr: state.filter_adsr,
+                    velocity_sensitivity: state.velocity_sensitivity,
                     filter: *state.filter_settings.read().unwrap(),
                     lfo_settings: *state.lfo_settings.read().unwrap(),
                     lfo2_settings: *state.lfo2_settings.read().unwrap(),
                     mod_matrix: state.mod_matrix.read().unwrap().clone(),
                     saturation_settings: *state.saturation_settings.read().unwrap(),
                     is_polyphonic: state.is_polyphonic,
+                    glide_time_ms: state.glide_time_ms,
                     sample_paths: relative_paths,
+                    round_robin_paths,
                     root_notes: state.root_notes,
+                    loop_regions: state.loop_regions.clone(),
+                    velocity_zones: state.velocity_zones.clone(),
                     global_fine_tune_cents: state.global_fine_tune_cents,
                     fade_out: state.fade_out,
                 };
@@ -2007,6 +2972,7 @@ impl CypherApp {
             if let Some(mix) = self.fx_wet_dry_mixes.get(&point) {
                 mix.store(0, Ordering::Relaxed);
             }
+            self.load_default_fx_chain(point);
         }
         // --- END CORRECTION ---
 
@@ -2044,15 +3010,18 @@ impl CypherApp {
             let default_adsr = crate::synth::AdsrSettings::default();
             engine_state.amp_adsr = default_adsr;
             engine_state.filter_adsr = default_adsr;
+            engine_state.velocity_sensitivity = 1.0;
             *engine_state.filter_settings.write().unwrap() = Default::default();
             *engine_state.lfo_settings.write().unwrap() = Default::default();
             *engine_state.lfo2_settings.write().unwrap() = Default::default();
             engine_state.mod_matrix.write().unwrap().clear();
             *engine_state.wavetable_mixer_settings.write().unwrap() = Default::default();
+            *engine_state.unison_settings.write().unwrap() = Default::default();
             *engine_state.saturation_settings.write().unwrap() = Default::default();
             engine_state.wavetable_position.store(0, Ordering::Relaxed);
             engine_state.window_positions = [0.0; 4];
             engine_state.is_polyphonic = true;
+            engine_state.glide_time_ms = 0.0;
             engine_state.volume.store(1_000_000, Ordering::Relaxed);
 
             let default_tables = wavetable_engine::WavetableSet::new_basic();
@@ -2073,6 +3042,8 @@ impl CypherApp {
         let default_adsr = crate::synth::AdsrSettings::default();
         self.send_command(AudioCommand::SetAmpAdsr(engine_index, default_adsr));
         self.send_command(AudioCommand::SetFilterAdsr(engine_index, default_adsr));
+        self.send_command(AudioCommand::SetVelocitySensitivity(engine_index, 1.0));
+        self.send_command(AudioCommand::SetGlideTime(engine_index, 0.0));
         self.send_command(AudioCommand::ResetWavetables(engine_index));
         self.send_command(AudioCommand::SetSynthMode(engine_index, true));
     }
@@ -2084,12 +3055,14 @@ impl CypherApp {
             let default_adsr = crate::synth::AdsrSettings::default();
             engine_state.amp_adsr = default_adsr;
             engine_state.filter_adsr = default_adsr;
+            engine_state.velocity_sensitivity = 1.0;
             *engine_state.filter_settings.write().unwrap() = Default::default();
             *engine_state.lfo_settings.write().unwrap() = Default::default();
             *engine_state.lfo2_settings.write().unwrap() = Default::default();
             engine_state.mod_matrix.write().unwrap().clear();
             *engine_state.saturation_settings.write().unwrap() = Default::default();
             engine_state.is_polyphonic = true;
+            engine_state.glide_time_ms = 0.0;
             engine_state.volume.store(1_000_000, Ordering::Relaxed);
             engine_state.global_fine_tune_cents = 0.0;
             engine_state.fade_out = 0.01;
@@ -2097,6 +3070,8 @@ impl CypherApp {
 
             commands_to_send.push(AudioCommand::SetAmpAdsr(engine_index, default_adsr));
             commands_to_send.push(AudioCommand::SetFilterAdsr(engine_index, default_adsr));
+            commands_to_send.push(AudioCommand::SetVelocitySensitivity(engine_index, 1.0));
+            commands_to_send.push(AudioCommand::SetGlideTime(engine_index, 0.0));
             commands_to_send.push(AudioCommand::SetSamplerSettings {
                 engine_index,
                 root_notes: engine_state.root_notes,
@@ -2104,11 +3079,23 @@ impl CypherApp {
                 fade_out: engine_state.fade_out,
             });
             commands_to_send.push(AudioCommand::SetSynthMode(engine_index, true));
+            engine_state.velocity_zones.clear();
+            commands_to_send.push(AudioCommand::SetSamplerVelocityZones {
+                engine_index,
+                zones: Vec::new(),
+            });
 
             for i in 0..NUM_SAMPLE_SLOTS {
                 engine_state.sample_names[i] = "Empty".to_string();
                 engine_state.sample_paths[i] = None;
+                engine_state.round_robin_paths[i].clear();
+                engine_state.loop_regions[i] = Default::default();
                 engine_state.sample_data_for_ui[i].write().unwrap().clear();
+                commands_to_send.push(AudioCommand::SetSamplerLoopRegion {
+                    engine_index,
+                    slot_index: i,
+                    region: Default::default(),
+                });
                 commands_to_send.push(AudioCommand::LoadSampleForSamplerSlot {
                     engine_index,
                     slot_index: i,
@@ -2205,12 +3192,33 @@ impl CypherApp {
             TheoryMode::Scales => {
                 if notes.len() == 1 {
                     if let Some(&root_note) = notes.iter().next() {
+                        self.detected_scale = None;
                         self.displayed_theory_notes.clear();
-                        let scale_notes = theory::get_scale_notes(root_note, self.selected_scale);
+                        let scale_notes = theory::notes_from_intervals(
+                            root_note,
+                            &self.selected_scale_intervals(),
+                        );
                         for (i, &note) in scale_notes.iter().enumerate() {
                             self.displayed_theory_notes.push((note, i % NUM_LOOPERS));
                         }
                     }
+                } else if notes.len() >= 2 && self.scale_detection == ScaleDetection::BestMatch {
+                    let Some(&(root, scale)) = theory::scales_containing(&notes).first() else {
+                        return;
+                    };
+                    if self.detected_scale == Some((root, scale)) {
+                        return;
+                    }
+                    self.detected_scale = Some((root, scale));
+                    // Anchor the scale on the first `root` at or below the lowest held note.
+                    let lowest = notes.iter().next().copied().unwrap_or(0);
+                    let offset = (lowest % 12 + 12 - root) % 12;
+                    let root_note = lowest.checked_sub(offset).unwrap_or(lowest + 12 - offset);
+                    self.displayed_theory_notes.clear();
+                    let scale_notes = theory::get_scale_notes(root_note, scale);
+                    for (i, &note) in scale_notes.iter().enumerate() {
+                        self.displayed_theory_notes.push((note, i % NUM_LOOPERS));
+                    }
                 }
             }
             TheoryMode::Chords => {
@@ -2228,15 +3236,31 @@ impl CypherApp {
                     if let Some(chord) = theory::recognize_chord(&notes) {
                         self.last_recognized_chord_notes = notes.clone();
                         self.displayed_theory_notes.clear();
-                        let suggestions =
-                            theory::get_chord_suggestions(&chord, &self.selected_chord_style);
+                        let suggestions = match self.chord_key_root {
+                            Some(key_root) => theory::get_chord_suggestions_in_scale(
+                                &chord,
+                                &self.selected_chord_style,
+                                key_root,
+                                &self.selected_scale_intervals(),
+                            ),
+                            None => {
+                                theory::get_chord_suggestions(&chord, &self.selected_chord_style)
+                            }
+                        };
                         match self.chord_display_mode {
                             ChordDisplayMode::Spread => {
                                 let display_octaves = [1, 3, 5, 7];
                                 for (i, (quality, root)) in suggestions.iter().enumerate() {
                                     if let Some(&octave_to_use) = display_octaves.get(i) {
-                                        let chord_notes =
-                                            theory::build_chord_notes(*root, *quality, octave_to_use);
+                                        let inversion = self
+                                            .chord_inversion
+                                            .resolve(*root, *quality, octave_to_use, &notes);
+                                        let chord_notes = theory::build_chord_notes(
+                                            *root,
+                                            *quality,
+                                            octave_to_use,
+                                            inversion,
+                                        );
                                         for note in chord_notes {
                                             if note <= 127 {
                                                 self.displayed_theory_notes.push((note, i));
@@ -2248,8 +3272,15 @@ impl CypherApp {
                             ChordDisplayMode::Stacked => {
                                 const STACK_OCTAVE: u8 = 4;
                                 for (i, (quality, root)) in suggestions.iter().enumerate() {
-                                    let chord_notes =
-                                        theory::build_chord_notes(*root, *quality, STACK_OCTAVE);
+                                    let inversion = self
+                                        .chord_inversion
+                                        .resolve(*root, *quality, STACK_OCTAVE, &notes);
+                                    let chord_notes = theory::build_chord_notes(
+                                        *root,
+                                        *quality,
+                                        STACK_OCTAVE,
+                                        inversion,
+                                    );
                                     for note in chord_notes {
                                         if note <= 127 {
                                             self.displayed_theory_notes.push((note, i));
@@ -2264,6 +3295,43 @@ impl CypherApp {
         }
     }
 
+    /// Plays every note of a displayed chord suggestion group through the synth,
+    /// replacing any chord that is already being auditioned.
+    pub fn audition_chord_group(&mut self, group: usize) {
+        self.stop_chord_audition();
+        let notes = chord_group_notes(&self.displayed_theory_notes, group);
+        self.auditioned_chord_channel = self
+            .chord_pad_sends_midi
+            .then(|| self.audio_note_channel.load(Ordering::Relaxed));
+        for &note in &notes {
+            match self.auditioned_chord_channel {
+                Some(channel) => self.send_command(AudioCommand::MidiMessage(MidiMessage {
+                    status: 0x90 | channel,
+                    data1: note,
+                    data2: 100,
+                })),
+                None => self.send_command(AudioCommand::SynthNoteOn { note, velocity: 100 }),
+            }
+        }
+        self.auditioned_chord_notes = notes;
+    }
+
+    /// Releases the auditioned chord on the same path (and channel) it was started on, so
+    /// switching modes or channels mid-chord can't leave notes hanging.
+    pub fn stop_chord_audition(&mut self) {
+        self.chord_held_from_midi = false;
+        for note in std::mem::take(&mut self.auditioned_chord_notes) {
+            match self.auditioned_chord_channel {
+                Some(channel) => self.send_command(AudioCommand::MidiMessage(MidiMessage {
+                    status: 0x80 | channel,
+                    data1: note,
+                    data2: 0,
+                })),
+                None => self.send_command(AudioCommand::SynthNoteOff(note)),
+            }
+        }
+    }
+
     pub fn save_session(&mut self, path_override: Option<PathBuf>) {
         // 1. Get the configuration directory, which is essential for saving anything.
         let config_dir = match settings::get_config_dir() {
@@ -2298,22 +3366,49 @@ impl CypherApp {
             }
         };
 
-        // 3. Create the session directory. If this fails, we can't continue.
-        if let Err(e) = fs::create_dir_all(&session_dir) {
-            eprintln!(
-                "Failed to create session directory '{}': {}",
-                session_dir.display(),
-                e
-            );
+        // 3. Write the session, then update the application's state to reflect the successful save.
+        let session_data = self.session_data(&config_dir);
+        if self.write_session(&session_dir, &session_data) {
+            self.remember_recent_session(&session_dir);
+            self.current_session_path = Some(session_dir);
+            self.rescan_asset_library();
+        }
+    }
+
+    /// Moves `session_dir` to the front of the recent sessions list. The autosave isn't a
+    /// session the user chose, so it is never listed.
+    fn remember_recent_session(&mut self, session_dir: &Path) {
+        if session_dir.ends_with(AUTOSAVE_DIR_NAME) {
             return;
         }
+        let recent = &mut self.settings.recent_sessions;
+        recent.retain(|path| path != session_dir);
+        recent.insert(0, session_dir.to_path_buf());
+        recent.truncate(settings::MAX_RECENT_SESSIONS);
+    }
 
-        // 4. Gather all the data for the session file.
+    /// Recent sessions that still exist on disk, newest first and without duplicates.
+    pub fn recent_sessions(&self) -> Vec<PathBuf> {
+        let mut seen = BTreeSet::new();
+        self.settings
+            .recent_sessions
+            .iter()
+            .filter(|path| path.join("session.json").is_file() && seen.insert(path.as_path()))
+            .cloned()
+            .collect()
+    }
+
+    /// Gathers everything `session.json` stores, with preset and kit paths made relative to
+    /// `config_dir`.
+    fn session_data(&self, config_dir: &Path) -> SessionData {
         let mixer_state = {
             let live_mixer_state = self.track_mixer_state.read().unwrap();
             MixerState {
                 tracks: live_mixer_state.tracks,
                 metronome: live_mixer_state.metronome,
+                buses: live_mixer_state.buses,
+                send_returns: live_mixer_state.send_returns,
+                sidechain: live_mixer_state.sidechain,
                 master_volume_m_u32: self.master_volume.load(Ordering::Relaxed),
                 limiter_is_active: self.limiter_is_active.load(Ordering::Relaxed),
                 limiter_threshold_m_u32: self.limiter_threshold.load(Ordering::Relaxed),
@@ -2322,17 +3417,18 @@ impl CypherApp {
                 limiter_release_sync_rate_m_u32: self
                     .limiter_release_sync_rate
                     .load(Ordering::Relaxed),
+                limiter_true_peak: self.limiter_true_peak,
+                limiter_lookahead_ms: self.limiter_lookahead_ms,
             }
         }; // `live_mixer_state` is dropped here, releasing the lock.
 
-
         let synth_preset_path = self.settings.last_synth_preset.as_ref().and_then(|p| {
-            p.strip_prefix(&config_dir)
+            p.strip_prefix(config_dir)
                 .ok()
                 .map(|rp| rp.to_path_buf())
         });
         let sampler_kit_path = self.settings.last_sampler_kit.as_ref().and_then(|p| {
-            p.strip_prefix(&config_dir)
+            p.strip_prefix(config_dir)
                 .ok()
                 .map(|rp| rp.to_path_buf())
         });
@@ -2350,8 +3446,8 @@ impl CypherApp {
             self.looper_states[i].get_length_in_cycles()
         });
 
-
-        let session_data = SessionData {
+        SessionData {
+            version: SESSION_FORMAT_VERSION,
             mixer_state,
             synth_preset_path,
             sampler_kit_path,
@@ -2366,15 +3462,43 @@ impl CypherApp {
             looper_cycles,
             tempo_multiplier: self.tempo_multiplier.load(Ordering::Relaxed),
             master_looper_index: self.master_looper_index.load(Ordering::Relaxed),
-        };
+        }
+    }
+
+    /// Creates `session_dir`, writes `session.json` into it and has the audio thread write the
+    /// loops. Returns false if the directory or JSON couldn't be written.
+    fn write_session(&self, session_dir: &Path, session_data: &SessionData) -> bool {
+        if !Self::write_session_json(session_dir, session_data) {
+            return false;
+        }
 
-        // 5. Serialize the data and write the `session.json` file.
+        // Only after the JSON is saved successfully, tell the audio thread to save the loops.
+        self.send_command(AudioCommand::SaveSessionAudio {
+            session_path: session_dir.to_path_buf(),
+            format: self.settings.recording_format,
+        });
+        true
+    }
+
+    /// Creates `session_dir` and writes `session.json` into it.
+    fn write_session_json(session_dir: &Path, session_data: &SessionData) -> bool {
+        // Create the session directory. If this fails, we can't continue.
+        if let Err(e) = fs::create_dir_all(session_dir) {
+            eprintln!(
+                "Failed to create session directory '{}': {}",
+                session_dir.display(),
+                e
+            );
+            return false;
+        }
+
+        // Serialize the data and write the `session.json` file.
         let json_path = session_dir.join("session.json");
-        let json_string = match serde_json::to_string_pretty(&session_data) {
+        let json_string = match serde_json::to_string_pretty(session_data) {
             Ok(s) => s,
             Err(e) => {
                 eprintln!("Failed to serialize session data: {}", e);
-                return;
+                return false;
             }
         };
 
@@ -2384,19 +3508,326 @@ impl CypherApp {
                 json_path.display(),
                 e
             );
-            return;
+            return false;
         }
 
         println!("Successfully saved session data to {}", json_path.display());
+        true
+    }
 
-        // 6. Only after the JSON is saved successfully, tell the audio thread to save the loops.
-        self.send_command(AudioCommand::SaveSessionAudio {
-            session_path: session_dir.clone(),
-        });
+    /// Saves a copy of the session to `Sessions/.autosave` once every
+    /// `autosave_interval_secs`, as long as a looper holds audio and something has changed
+    /// since the last copy.
+    fn autosave_if_due(&mut self) {
+        let interval = self.settings.autosave_interval_secs;
+        if interval == 0 || self.last_autosave_check.elapsed() < Duration::from_secs(interval as u64) {
+            return;
+        }
+        self.last_autosave_check = Instant::now();
+        if self.autosave_is_writing.load(Ordering::Relaxed)
+            || self.looper_states.iter().all(|looper| looper.get() == LooperState::Empty)
+        {
+            return;
+        }
+        let Some(config_dir) = settings::get_config_dir() else {
+            return;
+        };
 
-        // 7. Update the application's state to reflect the successful save.
-        self.current_session_path = Some(session_dir);
-        self.rescan_asset_library();
+        let session_data = self.session_data(&config_dir);
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_string(&session_data).unwrap_or_default().hash(&mut hasher);
+        for looper in &self.looper_states {
+            (looper.get() as u8).hash(&mut hasher);
+            // Overdubs change the audio but not the loop length; the waveform shows them.
+            for peak in looper.get_waveform_summary().read().unwrap().iter() {
+                peak.to_bits().hash(&mut hasher);
+            }
+        }
+        let fingerprint = hasher.finish();
+        if self.last_autosave_fingerprint == Some(fingerprint) {
+            return;
+        }
+
+        // The copy is built in an empty staging directory, so loops cleared since the last
+        // autosave stay cleared, and replaces the previous autosave only once it's complete.
+        let autosave_dir = config_dir.join("Sessions").join(AUTOSAVE_DIR_NAME);
+        let staging_dir = autosave_dir.with_extension("tmp");
+        if staging_dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&staging_dir) {
+                eprintln!("Failed to clear {}: {}", staging_dir.display(), e);
+                return;
+            }
+        }
+        if !Self::write_session_json(&staging_dir, &session_data) {
+            return;
+        }
+        // The audio thread copies the loops a chunk at a time rather than cloning them.
+        let loop_lens: Vec<usize> =
+            self.looper_states.iter().map(|looper| looper.get_audio_len()).collect();
+        let snapshot = LoopSnapshot::start(
+            &loop_lens,
+            staging_dir,
+            autosave_dir,
+            self.active_sample_rate as f32,
+            self.settings.recording_format,
+            self.autosave_is_writing.clone(),
+        );
+        self.send_command(AudioCommand::SnapshotLoops(snapshot));
+        self.last_autosave_fingerprint = Some(fingerprint);
+    }
+
+    /// Loads the autosave offered at startup. It becomes an unsaved session, so "Save" asks
+    /// for a new location instead of writing back into the autosave directory.
+    pub fn restore_autosave(&mut self) {
+        if let Some(path) = self.recoverable_autosave.take() {
+            self.load_session(&path);
+            self.current_session_path = None;
+        }
+    }
+
+    pub fn discard_autosave(&mut self) {
+        if let Some(path) = self.recoverable_autosave.take() {
+            if let Err(e) = fs::remove_dir_all(&path) {
+                eprintln!("Failed to delete autosave at {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Packs the current session as last saved, with everything it references, into a zip
+    /// chosen by the user. See `session_bundle` for the layout.
+    pub fn export_session_bundle(&mut self) {
+        let Some(session_dir) = self.current_session_path.clone() else {
+            self.recording_notification =
+                Some(("Save the session before exporting a bundle.".to_string(), Instant::now()));
+            return;
+        };
+        let Some(config_dir) = settings::get_config_dir() else {
+            return;
+        };
+        let file_name = session_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let Some(bundle_path) = FileDialog::new()
+            .add_filter("zip", &["zip"])
+            .set_file_name(format!("{}.zip", file_name))
+            .save_file()
+        else {
+            return;
+        };
+
+        let message = match self.write_session_bundle(&session_dir, &config_dir, &bundle_path) {
+            Ok(()) => format!("Exported bundle {}", bundle_path.display()),
+            Err(e) => {
+                eprintln!("Failed to export session bundle {}: {}", bundle_path.display(), e);
+                format!("Bundle export failed: {}", e)
+            }
+        };
+        self.recording_notification = Some((message, Instant::now()));
+    }
+
+    fn write_session_bundle(&self, session_dir: &Path, config_dir: &Path, bundle_path: &Path) -> Result<()> {
+        let mut session_data: SessionData =
+            serde_json::from_str(&fs::read_to_string(session_dir.join("session.json"))?)?;
+        let mut writer = BundleWriter::create(bundle_path)?;
+
+        // Referenced files are packed under config-relative names, and every reference is
+        // rewritten to that name so it resolves inside whichever config dir imports it.
+        let pack = |writer: &mut BundleWriter, path: &mut PathBuf| -> Result<()> {
+            match self.resolve_path(path) {
+                Some(resolved) => *path = writer.add_file(config_dir, &resolved)?,
+                None => eprintln!("Bundle: missing file {}, leaving it out", path.display()),
+            }
+            Ok(())
+        };
+
+        if let Some(preset_ref) = session_data.synth_preset_path.as_mut() {
+            if let Some(preset_path) = self.resolve_path(preset_ref) {
+                let mut preset: SynthPreset = serde_json::from_str(&fs::read_to_string(&preset_path)?)?;
+                for path in preset.sample_paths_mut() {
+                    pack(&mut writer, path)?;
+                }
+                let name = writer.add_unique(
+                    &session_bundle::entry_name(config_dir, &preset_path),
+                    serde_json::to_string_pretty(&preset)?.as_bytes(),
+                )?;
+                *preset_ref = PathBuf::from(name);
+            }
+        }
+
+        if let Some(kit_ref) = session_data.sampler_kit_path.as_mut() {
+            if let Some(kit_path) = self.resolve_path(kit_ref) {
+                let mut kit: SamplerKit = serde_json::from_str(&fs::read_to_string(&kit_path)?)?;
+                for path in kit.pads.iter_mut().filter_map(|pad| pad.path.as_mut()) {
+                    pack(&mut writer, path)?;
+                }
+                let name = writer.add_unique(
+                    &session_bundle::entry_name(config_dir, &kit_path),
+                    serde_json::to_string_pretty(&kit)?.as_bytes(),
+                )?;
+                *kit_ref = PathBuf::from(name);
+            }
+        }
+
+        for layer in session_data.atmo_preset.scenes.iter_mut().flat_map(|scene| scene.layers.iter_mut()) {
+            let Some(folder) = layer.sample_folder_path.as_mut() else {
+                continue;
+            };
+            let Some(resolved) = self.resolve_path(folder).filter(|p| p.is_dir()) else {
+                continue;
+            };
+            *folder = writer.add_folder(config_dir, &resolved)?;
+        }
+
+        writer.add(
+            &format!("{}/session.json", session_bundle::SESSION_ENTRY_DIR),
+            serde_json::to_string_pretty(&session_data)?.as_bytes(),
+        )?;
+        for entry in fs::read_dir(session_dir)?.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "wav") {
+                let name = format!(
+                    "{}/{}",
+                    session_bundle::SESSION_ENTRY_DIR,
+                    entry.file_name().to_string_lossy()
+                );
+                writer.add(&name, &fs::read(&path)?)?;
+            }
+        }
+        writer.finish()
+    }
+
+    /// Unpacks a bundle made by `export_session_bundle` into the config dir and loads it. The
+    /// session gets a new folder under `Sessions/`; presets, kits and samples go to their
+    /// usual places. Local files are never overwritten: a bundled file that differs from the
+    /// one already at its path is unpacked under a numbered name and used from there.
+    pub fn import_session_bundle(&mut self) {
+        let Some(bundle_path) = FileDialog::new().add_filter("zip", &["zip"]).pick_file() else {
+            return;
+        };
+        let Some(config_dir) = settings::get_config_dir() else {
+            return;
+        };
+        match Self::unpack_session_bundle(&bundle_path, &config_dir) {
+            Ok((session_dir, renamed)) => {
+                self.rescan_asset_library();
+                self.rescan_atmo_presets();
+                self.load_session(&session_dir);
+                if renamed > 0 {
+                    self.recording_notification = Some((
+                        format!(
+                            "Imported bundle: {} file(s) differed from local files with the same name and were unpacked under new names",
+                            renamed
+                        ),
+                        Instant::now(),
+                    ));
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to import session bundle {}: {}", bundle_path.display(), e);
+                self.recording_notification =
+                    Some((format!("Bundle import failed: {}", e), Instant::now()));
+            }
+        }
+    }
+
+    /// Writes the bundle's files into `config_dir` and returns the new session folder, along
+    /// with how many files had to be renamed around differing local ones.
+    fn unpack_session_bundle(bundle_path: &Path, config_dir: &Path) -> Result<(PathBuf, usize)> {
+        let session_prefix = format!("{}/", session_bundle::SESSION_ENTRY_DIR);
+        let mut session_files = Vec::new();
+        let mut entries = BTreeMap::new();
+        for (name, data) in session_bundle::read_bundle(bundle_path)? {
+            if session_bundle::entry_destination(config_dir, &name).is_none() {
+                eprintln!("Bundle: skipping unsafe entry '{}'", name);
+            } else if let Some(session_file) = name.strip_prefix(&session_prefix) {
+                session_files.push((session_file.to_string(), data));
+            } else {
+                entries.insert(name, data);
+            }
+        }
+        let Some(session_json) = session_files.iter().position(|(name, _)| name == "session.json") else {
+            anyhow::bail!("no session.json in bundle");
+        };
+        let mut session_data: SessionData = serde_json::from_slice(&session_files.remove(session_json).1)?;
+
+        let sessions_dir = config_dir.join("Sessions");
+        let base_name = bundle_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let mut session_dir = sessions_dir.join(&base_name);
+        let mut suffix = 2;
+        while session_dir.exists() {
+            session_dir = sessions_dir.join(format!("{} ({})", base_name, suffix));
+            suffix += 1;
+        }
+
+        // References to bundled files are their entry names, so each one is repointed at
+        // wherever its entry ends up. Returns whether the reference changed.
+        let mut unpacker = session_bundle::Unpacker::new(config_dir);
+        let place = |unpacker: &mut session_bundle::Unpacker, path: &mut PathBuf| -> Result<bool> {
+            let name = path.to_string_lossy().to_string();
+            let Some(data) = entries.get(&name) else {
+                return Ok(false);
+            };
+            let placed = unpacker.place(&name, data)?;
+            *path = PathBuf::from(&placed);
+            Ok(placed != name)
+        };
+
+        if let Some(preset_ref) = session_data.synth_preset_path.as_mut() {
+            if let Some(data) = entries.get(&*preset_ref.to_string_lossy()) {
+                let mut preset: SynthPreset = serde_json::from_slice(data)?;
+                let mut changed = false;
+                for path in preset.sample_paths_mut() {
+                    changed |= place(&mut unpacker, path)?;
+                }
+                let data = if changed { serde_json::to_string_pretty(&preset)?.into_bytes() } else { data.clone() };
+                *preset_ref = PathBuf::from(unpacker.place(&preset_ref.to_string_lossy(), &data)?);
+            }
+        }
+
+        if let Some(kit_ref) = session_data.sampler_kit_path.as_mut() {
+            if let Some(data) = entries.get(&*kit_ref.to_string_lossy()) {
+                let mut kit: SamplerKit = serde_json::from_slice(data)?;
+                let mut changed = false;
+                for path in kit.pads.iter_mut().filter_map(|pad| pad.path.as_mut()) {
+                    changed |= place(&mut unpacker, path)?;
+                }
+                let data = if changed { serde_json::to_string_pretty(&kit)?.into_bytes() } else { data.clone() };
+                *kit_ref = PathBuf::from(unpacker.place(&kit_ref.to_string_lossy(), &data)?);
+            }
+        }
+
+        let mut folders = BTreeSet::new();
+        for layer in session_data.atmo_preset.scenes.iter_mut().flat_map(|scene| scene.layers.iter_mut()) {
+            let Some(folder) = layer.sample_folder_path.as_mut() else {
+                continue;
+            };
+            let prefix = format!("{}/", folder.to_string_lossy());
+            let files: Vec<(String, &[u8])> = entries
+                .iter()
+                .filter_map(|(name, data)| Some((name.strip_prefix(&prefix)?.to_string(), data.as_slice())))
+                .collect();
+            if !files.is_empty() {
+                *folder = PathBuf::from(unpacker.place_folder(&prefix[..prefix.len() - 1], &files)?);
+                folders.insert(prefix);
+            }
+        }
+
+        // Anything the session doesn't refer to still comes along, under its own name.
+        for (name, data) in &entries {
+            if !folders.iter().any(|prefix| name.starts_with(prefix.as_str())) {
+                unpacker.place(name, data)?;
+            }
+        }
+
+        fs::create_dir_all(&session_dir)?;
+        fs::write(session_dir.join("session.json"), serde_json::to_string_pretty(&session_data)?)?;
+        for (name, data) in session_files {
+            if let Some(destination) = session_bundle::entry_destination(&session_dir, &name) {
+                if let Some(parent) = destination.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&destination, data)?;
+            }
+        }
+        Ok((session_dir, unpacker.renamed()))
     }
 
     pub fn clear_all_fx_racks(&mut self) {
@@ -2406,6 +3837,7 @@ impl CypherApp {
                 .map(fx::InsertionPoint::Looper)
                 .collect::<Vec<_>>(),
             (0..2).map(fx::InsertionPoint::Synth).collect::<Vec<_>>(),
+            (0..NUM_SUB_BUSES).map(fx::InsertionPoint::Bus).collect::<Vec<_>>(),
             vec![
                 fx::InsertionPoint::Sampler,
                 fx::InsertionPoint::Input,
@@ -2422,6 +3854,54 @@ impl CypherApp {
                 mix.store(0, Ordering::Relaxed);
             }
         }
+        // Send returns are never left empty; they go back to their default chains.
+        self.load_send_racks();
+    }
+
+    /// Sends each send return's chain to the audio thread, creating the default reverb
+    /// or delay chain (fully wet) for any send that doesn't have one yet.
+    pub fn load_send_racks(&mut self) {
+        for send_index in 0..NUM_SEND_BUSES {
+            let point = fx::InsertionPoint::Send(send_index);
+            let preset = self.fx_presets.entry(point).or_insert_with(|| {
+                if let Some(mix) = self.fx_wet_dry_mixes.get(&point) {
+                    mix.store(1_000_000, Ordering::Relaxed);
+                }
+                fx::default_send_preset(send_index)
+            });
+            let preset = preset.clone();
+            self.send_command(AudioCommand::LoadFxRack(point, preset));
+        }
+    }
+
+    /// Loads the user's default chain into every insertion point that has one configured
+    /// and is currently empty, e.g. after a Clear All.
+    pub fn load_default_fx_chains(&mut self) {
+        let points: Vec<fx::InsertionPoint> =
+            self.settings.default_fx_presets.keys().copied().collect();
+        for point in points {
+            if !self.fx_presets.contains_key(&point) {
+                self.load_default_fx_chain(point);
+            }
+        }
+    }
+
+    /// Loads the saved preset set as the default for `point`, fully wet. Returns false
+    /// if no default is configured or its preset file can no longer be read.
+    pub fn load_default_fx_chain(&mut self, point: fx::InsertionPoint) -> bool {
+        let Some(preset) = fx::read_default_preset(
+            &self.settings.default_fx_presets,
+            &self.available_fx_presets,
+            point,
+        ) else {
+            return false;
+        };
+        if let Some(mix) = self.fx_wet_dry_mixes.get(&point) {
+            mix.store(1_000_000, Ordering::Relaxed);
+        }
+        self.fx_presets.insert(point, preset.clone());
+        self.send_command(AudioCommand::LoadFxRack(point, preset));
+        true
     }
 
     pub fn load_session(&mut self, path: &Path) {
@@ -2438,13 +3918,25 @@ impl CypherApp {
             }
         };
 
-        let session_data: SessionData = match serde_json::from_str(&json_string) {
+        let mut session_data: SessionData = match serde_json::from_str(&json_string) {
             Ok(d) => d,
             Err(e) => {
                 eprintln!("Failed to parse session file {}: {}", json_path.display(), e);
                 return;
             }
         };
+        println!(
+            "Loading session format version {} from {}",
+            session_data.version,
+            json_path.display()
+        );
+        if session_data.version > SESSION_FORMAT_VERSION {
+            eprintln!(
+                "Session was saved by a newer version (format {}); settings this version doesn't know are ignored.",
+                session_data.version
+            );
+        }
+        session_data.migrate();
 
         // --- Begin state restoration ---
         self.send_command(AudioCommand::ClearAll);
@@ -2452,7 +3944,7 @@ impl CypherApp {
 
         // Send the entire mixer state to the audio thread for atomic update
         let mixer_state = session_data.mixer_state.clone();
-        self.send_command(AudioCommand::SetMixerState(mixer_state));
+        self.send_command(AudioCommand::SetMixerState(Box::new(mixer_state)));
 
         // Also update the UI's direct view of the state
         *self.track_mixer_state.write().unwrap() = session_data.mixer_state.clone();
@@ -2475,6 +3967,10 @@ impl CypherApp {
             session_data.mixer_state.limiter_release_sync_rate_m_u32,
             Ordering::Relaxed,
         );
+        self.limiter_true_peak = session_data.mixer_state.limiter_true_peak;
+        self.send_command(AudioCommand::SetLimiterTruePeak(self.limiter_true_peak));
+        self.limiter_lookahead_ms = session_data.mixer_state.limiter_lookahead_ms;
+        self.send_command(AudioCommand::SetLimiterLookahead(self.limiter_lookahead_ms));
 
         if let Some(relative_path) = session_data.synth_preset_path {
             if let Some(config_dir) = settings::get_config_dir() {
@@ -2547,8 +4043,23 @@ impl CypherApp {
                 atomic_val.store((value * 1_000_000.0) as u32, Ordering::Relaxed);
             }
         }
+        // Sessions saved before send returns existed fall back to the default chains.
+        self.load_send_racks();
 
 
+        // The transport length is stored at the session's sample rate; the loops get resampled
+        // on load, so scale the length to match. It is sent first so each loop can be fitted
+        // to a whole number of transport cycles.
+        let transport_len = if session_data.original_sample_rate > 0 && self.active_sample_rate > 0
+        {
+            (session_data.transport_len_samples as f64 * self.active_sample_rate as f64
+                / session_data.original_sample_rate as f64)
+                .round() as usize
+        } else {
+            session_data.transport_len_samples
+        };
+        self.send_command(AudioCommand::SetTransportLen(transport_len));
+
         for i in 0..NUM_LOOPERS {
             let loop_filename = format!("loop_{}.wav", i);
             let loop_path = path.join(loop_filename);
@@ -2562,16 +4073,13 @@ impl CypherApp {
             }
         }
 
-        // Send the transport length and tempo state AFTER queuing the loops
-        self.send_command(AudioCommand::SetTransportLen(
-            session_data.transport_len_samples,
-        ));
         self.send_command(AudioCommand::SetTempoState {
             master_index: session_data.master_looper_index,
             multiplier: session_data.tempo_multiplier,
         });
 
         self.current_session_path = Some(path.to_path_buf());
+        self.remember_recent_session(path);
 
         self.reconnect_midi().ok();
     }
@@ -2755,6 +4263,15 @@ impl eframe::App for CypherApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // --- State Updates ---
                 self.update_theory_display();
+        let quantize_mask = if self.settings.scale_quantize_midi {
+            theory::scale_pitch_class_mask(
+                self.settings.scale_quantize_key,
+                &self.selected_scale_intervals(),
+            )
+        } else {
+            0
+        };
+        self.midi_note_quantize_mask.store(quantize_mask, Ordering::Relaxed);
         //ctx.set_debug_on_hover(true); // <-- Uncomment for visual debugging of panels
 
         // --- Handle MIDI FX Preset Change ---
@@ -2796,6 +4313,22 @@ impl eframe::App for CypherApp {
             }
         }
         
+        // --- Handle MIDI Chord Pad Triggers ---
+        match self.midi_chord_pad_request.swap(midi::CHORD_PAD_IDLE, Ordering::Relaxed) {
+            midi::CHORD_PAD_RELEASE if self.chord_held_from_midi => self.stop_chord_audition(),
+            group if group >= 0 && self.theory_mode == TheoryMode::Chords => {
+                self.audition_chord_group(group as usize);
+                self.chord_held_from_midi = !self.auditioned_chord_notes.is_empty();
+            }
+            _ => {}
+        }
+
+        // --- Handle MIDI Program Change ---
+        let program = self.midi_program_change_request.swap(-1, Ordering::Relaxed);
+        if program >= 0 {
+            self.handle_program_change(program as usize);
+        }
+
         // --- Handle MIDI FX Editor Toggle ---
         let point_to_toggle = self.midi_fx_editor_toggle_request.write().unwrap().take();
         
@@ -2825,6 +4358,7 @@ impl eframe::App for CypherApp {
         if self.should_clear_all_from_midi.swap(false, Ordering::Relaxed) {
             self.clear_all_fx_racks(); // Clear UI state
             self.send_command(AudioCommand::ClearAllAndPlay); // Command audio thread
+            self.load_default_fx_chains();
         }
 
         if self.should_toggle_record_from_midi.swap(false, Ordering::Relaxed) {
@@ -2837,15 +4371,31 @@ impl eframe::App for CypherApp {
                     let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
                     let filename = format!("LiveRec_{}.wav", timestamp);
                     let path = rec_dir.join(filename);
-                    self.send_command(AudioCommand::StopOutputRecording {
-                        output_path: path.clone(),
-                    });
+                    self.send_command(self.stop_output_recording_command(path.clone()));
                     self.recording_notification =
                         Some((format!("Saved to {}", path.display()), Instant::now()));
                 }
             }
         }
 
+        if self.stem_recording_limit_reached.swap(false, Ordering::Relaxed) {
+            self.recording_notification = Some((
+                "Stem recording reached its 10 minute limit and stopped capturing.".to_string(),
+                Instant::now(),
+            ));
+        }
+
+        let stems_written = self
+            .stems_written
+            .swap(audio_engine::NO_STEM_RESULT, Ordering::Relaxed);
+        if stems_written != audio_engine::NO_STEM_RESULT {
+            let message = match (stems_written, self.stem_output_dir.take()) {
+                (0, _) | (_, None) => "No stems saved: every source was silent.".to_string(),
+                (count, Some(dir)) => format!("{} stems saved to {}", count, dir.display()),
+            };
+            self.recording_notification = Some((message, Instant::now()));
+        }
+
         if let Some((_, time)) = self.recording_notification {
             if time.elapsed() > std::time::Duration::from_secs(5) {
                 self.recording_notification = None;
@@ -2893,52 +4443,80 @@ impl eframe::App for CypherApp {
 
         ctx.request_repaint_after(std::time::Duration::from_millis(10));
 
-        // --- Peak Meter Decay Logic ---
+        // --- Meter Ballistics ---
+        let meters = self.settings.meters;
+        let dt = ctx.input(|i| i.stable_dt).min(0.1);
+        let read_peak = |meter: &AtomicU32| meter.load(Ordering::Relaxed) as f32 / u32::MAX as f32;
         for i in 0..NUM_LOOPERS {
-            let new_peak = self.peak_meters[i].load(Ordering::Relaxed) as f32 / u32::MAX as f32;
-            self.displayed_peak_levels[i] = (self.displayed_peak_levels[i] * 0.95).max(new_peak);
+            let new_peak = read_peak(&self.peak_meters[i]);
+            self.displayed_peak_levels[i] = meters.follow(self.displayed_peak_levels[i], new_peak, dt);
+            self.looper_peak_holds[i].update(self.displayed_peak_levels[i], dt, &meters);
         }
-        let new_input_peak = self.input_peak_meter.load(Ordering::Relaxed) as f32 / u32::MAX as f32;
+        // The engine keeps the highest input peak until it's read, so no buffer is missed.
+        let new_input_peak =
+            self.input_peak_meter.swap(0, Ordering::Relaxed) as f32 / u32::MAX as f32;
         self.displayed_input_peak_level =
-            (self.displayed_input_peak_level * 0.95).max(new_input_peak);
+            meters.follow(self.displayed_input_peak_level, new_input_peak, dt);
+        // Judged on the raw peak, since the meter ballistics may smooth a clip away.
+        self.input_headroom = self.input_headroom_hold.update(
+            new_input_peak,
+            self.settings.input_headroom_warning_db,
+            dt,
+        );
+        if let Ok(spectrum) = self.master_spectrum.read() {
+            for (displayed, &new_level) in self.displayed_spectrum.iter_mut().zip(spectrum.iter()) {
+                *displayed = meters.follow(*displayed, new_level, dt);
+            }
+        }
+        // Smoothed rather than peak-held: the interesting readings are the negative ones.
+        let new_correlation =
+            audio_engine::stored_to_correlation(self.master_correlation.load(Ordering::Relaxed));
+        self.displayed_correlation = self.displayed_correlation * 0.8 + new_correlation * 0.2;
 
         for i in 0..2 {
             match &mut self.engine_states[i] {
                 EngineState::Wavetable(state) => {
-                    let new_peak =
-                        state.peak_meter.load(Ordering::Relaxed) as f32 / u32::MAX as f32;
-                    state.displayed_peak_level = (state.displayed_peak_level * 0.95).max(new_peak);
+                    let new_peak = read_peak(&state.peak_meter);
+                    state.displayed_peak_level = meters.follow(state.displayed_peak_level, new_peak, dt);
                 }
                 EngineState::Sampler(state) => {
-                    let new_peak =
-                        state.peak_meter.load(Ordering::Relaxed) as f32 / u32::MAX as f32;
-                    state.displayed_peak_level = (state.displayed_peak_level * 0.95).max(new_peak);
+                    let new_peak = read_peak(&state.peak_meter);
+                    state.displayed_peak_level = meters.follow(state.displayed_peak_level, new_peak, dt);
                 }
             }
         }
 
-        let new_synth_master_peak =
-            self.synth_master_peak_meter.load(Ordering::Relaxed) as f32 / u32::MAX as f32;
+        let new_synth_master_peak = read_peak(&self.synth_master_peak_meter);
         self.displayed_synth_master_peak_level =
-            (self.displayed_synth_master_peak_level * 0.95).max(new_synth_master_peak);
+            meters.follow(self.displayed_synth_master_peak_level, new_synth_master_peak, dt);
 
-        let new_sampler_peak =
-            self.sampler_peak_meter.load(Ordering::Relaxed) as f32 / u32::MAX as f32;
+        let new_sampler_peak = read_peak(&self.sampler_peak_meter);
         self.displayed_sampler_peak_level =
-            (self.displayed_sampler_peak_level * 0.95).max(new_sampler_peak);
+            meters.follow(self.displayed_sampler_peak_level, new_sampler_peak, dt);
 
-        let new_master_peak =
-            self.master_peak_meter.load(Ordering::Relaxed) as f32 / u32::MAX as f32;
+        let new_master_peak = read_peak(&self.master_peak_meter);
         self.displayed_master_peak_level =
-            (self.displayed_master_peak_level * 0.95).max(new_master_peak);
+            meters.follow(self.displayed_master_peak_level, new_master_peak, dt);
+        self.master_peak_hold.update(self.displayed_master_peak_level, dt, &meters);
 
-        let new_atmo_peak =
-            self.atmo_peak_meter.load(Ordering::Relaxed) as f32 / u32::MAX as f32;
+        let new_atmo_peak = read_peak(&self.atmo_peak_meter);
         self.displayed_atmo_peak_level =
-            (self.displayed_atmo_peak_level * 0.95).max(new_atmo_peak);
+            meters.follow(self.displayed_atmo_peak_level, new_atmo_peak, dt);
 
         let new_gr = self.gain_reduction_db.load(Ordering::Relaxed) as f32 / 24_000_000.0;
-        self.displayed_gain_reduction = (self.displayed_gain_reduction * 0.92).max(new_gr);
+        self.displayed_gain_reduction = meters.follow(self.displayed_gain_reduction, new_gr, dt);
+
+        self.performance_history.record(
+            self.cpu_load.load(Ordering::Relaxed) as f32 / 10.0,
+            self.xrun_count.load(Ordering::Relaxed),
+            self.active_buffer_size,
+            self.active_sample_rate,
+        );
+        self.autosave_if_due();
+        self.poll_asset_library_scan();
+        if self.is_scanning_asset_library() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
 
         // --- UI Drawing ---
         ui::draw_main_view(self, ctx);
@@ -2,8 +2,10 @@
 
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use walkdir::WalkDir;
 
 // A simple atomic counter to generate unique IDs for widgets.
 static NEXT_ID: AtomicU64 = AtomicU64::new(0);
@@ -12,6 +14,32 @@ fn new_id() -> egui::Id {
     egui::Id::new(NEXT_ID.fetch_add(1, AtomicOrdering::Relaxed))
 }
 
+/// Audio formats that can be imported as samples; rodio's decoder reads all of them.
+/// Everything the app writes is still WAV.
+pub const SAMPLE_FILE_EXTENSIONS: [&str; 4] = ["wav", "flac", "mp3", "ogg"];
+
+pub fn is_sample_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SAMPLE_FILE_EXTENSIONS.iter().any(|known| ext.eq_ignore_ascii_case(known)))
+}
+
+/// Takes the files dropped onto the window from the OS file manager this frame and returns
+/// the sample files among them, in drop order. The first drop target to call this consumes
+/// the drop, so targets drawn later in the frame don't load the same files again.
+pub fn take_dropped_sample_files(ctx: &egui::Context) -> Vec<PathBuf> {
+    ctx.input_mut(|i| std::mem::take(&mut i.raw.dropped_files))
+        .into_iter()
+        .filter_map(|file| file.path)
+        .filter(|path| is_sample_file(path))
+        .collect()
+}
+
+/// Whether files are being dragged over the window from the OS file manager.
+pub fn is_os_file_hovering(ctx: &egui::Context) -> bool {
+    ctx.input(|i| !i.raw.hovered_files.is_empty())
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Asset {
     Sample(SampleRef),
@@ -246,9 +274,40 @@ impl LibraryFolder {
         current_folder.assets.insert(asset);
     }
 
-    pub fn clear(&mut self) {
-        self.assets.clear();
-        self.subfolders.clear();
+    /// Every asset in this folder and its subfolders whose name contains `query`, ignoring
+    /// case, sorted by name.
+    pub fn search(&self, query: &str) -> Vec<&Asset> {
+        let query = query.to_lowercase();
+        let mut matches = Vec::new();
+        self.collect_matches(&query, &mut matches);
+        matches.sort_by_key(|asset| asset.name().to_lowercase());
+        matches
+    }
+
+    fn collect_matches<'a>(&'a self, lowercase_query: &str, matches: &mut Vec<&'a Asset>) {
+        matches.extend(
+            self.assets
+                .iter()
+                .filter(|asset| asset.name().to_lowercase().contains(lowercase_query)),
+        );
+        for subfolder in self.subfolders.values() {
+            subfolder.collect_matches(lowercase_query, matches);
+        }
+    }
+
+    /// Returns the paths of every non-folder asset in this folder and its subfolders, sorted.
+    pub fn flattened_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self
+            .assets
+            .iter()
+            .filter(|asset| !matches!(asset, Asset::Folder(_)))
+            .map(|asset| asset.path().clone())
+            .collect();
+        for subfolder in self.subfolders.values() {
+            paths.extend(subfolder.flattened_paths());
+        }
+        paths.sort();
+        paths
     }
 }
 
@@ -261,10 +320,131 @@ pub struct AssetLibrary {
 }
 
 impl AssetLibrary {
-    pub fn clear(&mut self) {
-        self.sample_root.clear();
-        self.synth_root.clear();
-        self.kit_root.clear();
-        self.session_root.clear();
+    /// Builds the library from the config directory's Samples, SynthPresets, Kits and
+    /// Sessions folders. Meant for a worker thread: gives up and returns `None` once
+    /// `is_superseded` reports that a newer scan has started.
+    pub fn scan(config_dir: &Path, is_superseded: impl Fn() -> bool) -> Option<Self> {
+        let mut library = Self::default();
+        let samples_dir = config_dir.join("Samples");
+        let presets_dir = config_dir.join("SynthPresets");
+        let kits_dir = config_dir.join("Kits");
+        let sessions_dir = config_dir.join("Sessions");
+        let soundscapes_dir = samples_dir.join("Soundscapes");
+
+        // Ensure the dedicated Soundscapes directory exists
+        if !soundscapes_dir.exists() {
+            fs::create_dir_all(&soundscapes_dir).ok();
+        }
+
+        // --- Scan for normal samples, EXCLUDING the Soundscapes folder ---
+        for entry in WalkDir::new(&samples_dir)
+            .min_depth(1) // Start inside the Samples dir
+            .into_iter()
+            .filter_entry(|e| e.path() != soundscapes_dir) // Skip the Soundscapes folder itself
+            .filter_map(|e| e.ok())
+        {
+            if is_superseded() {
+                return None;
+            }
+            if let Ok(relative_path) = entry.path().strip_prefix(&samples_dir) {
+                let segments: Vec<String> = relative_path
+                    .iter()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .collect();
+
+                if entry.file_type().is_file() && is_sample_file(entry.path()) {
+                    if let Some(sample_ref) = SampleRef::new(entry.path().to_path_buf()) {
+                        library
+                            .sample_root
+                            .insert_asset(&segments, Asset::Sample(sample_ref));
+                    }
+                }
+            }
+        }
+
+        // --- Scan ONLY the Soundscapes folder for draggable folders ---
+        if soundscapes_dir.is_dir() {
+            for entry in WalkDir::new(&soundscapes_dir)
+                .min_depth(1)
+                .max_depth(1) // Do not recurse into subdirectories
+                .into_iter()
+                .filter_map(|e| e.ok())
+            {
+                if entry.file_type().is_dir() {
+                    // We add these to the root of the sample library for the UI to find.
+                    let segments = vec![entry.file_name().to_string_lossy().to_string()];
+                    if let Some(folder_ref) = crate::asset::FolderRef::new(entry.path()) {
+                        library
+                            .sample_root
+                            .insert_asset(&segments, Asset::Folder(folder_ref));
+                    }
+                }
+            }
+        }
+
+        if is_superseded() {
+            return None;
+        }
+
+        // Scan for synth presets
+        for entry in WalkDir::new(&presets_dir).into_iter().filter_map(|e| e.ok()) {
+            if entry.file_type().is_file()
+                && entry.path().extension().is_some_and(|e| e == "json")
+            {
+                if let Ok(relative_path) = entry.path().strip_prefix(&presets_dir) {
+                    let segments: Vec<String> = relative_path
+                        .iter()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .collect();
+                    if let Some(preset_ref) = SynthPresetRef::new(entry.path().to_path_buf()) {
+                        library
+                            .synth_root
+                            .insert_asset(&segments, Asset::SynthPreset(preset_ref));
+                    }
+                }
+            }
+        }
+
+        // Scan for kits
+        for entry in WalkDir::new(&kits_dir).into_iter().filter_map(|e| e.ok()) {
+            if entry.file_type().is_file()
+                && entry.path().extension().is_some_and(|e| e == "json")
+            {
+                if let Ok(relative_path) = entry.path().strip_prefix(&kits_dir) {
+                    let segments: Vec<String> = relative_path
+                        .iter()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .collect();
+                    if let Some(kit_ref) = SamplerKitRef::new(entry.path().to_path_buf()) {
+                        library
+                            .kit_root
+                            .insert_asset(&segments, Asset::SamplerKit(kit_ref));
+                    }
+                }
+            }
+        }
+
+        // Scan for sessions
+        if sessions_dir.is_dir() {
+            for entry in WalkDir::new(&sessions_dir)
+                .min_depth(1)
+                .max_depth(1)
+                .into_iter()
+                .filter_map(|e| e.ok())
+            {
+                // Hidden directories (the autosave) aren't sessions to browse.
+                let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+                if entry.file_type().is_dir() && !is_hidden {
+                    let segments = vec![entry.file_name().to_string_lossy().to_string()];
+                    if let Some(session_ref) = SessionRef::new(entry.path().to_path_buf()) {
+                        library
+                            .session_root
+                            .insert_asset(&segments, Asset::Session(session_ref));
+                    }
+                }
+            }
+        }
+
+        Some(library)
     }
 }
\ No newline at end of file
@@ -2,11 +2,12 @@
 // ==================================
 
 use crate::atmo::AtmoScene;
+use crate::audio_engine::{LoopSnapshot, StemRecording};
 use crate::fx;
-use crate::mixer::MixerState;
-use crate::sampler::SamplerPadFxSettings;
-use crate::sampler_engine::NUM_SAMPLE_SLOTS;
-use crate::settings;
+use crate::mixer::{MeterPoint, MixerState, SidechainState};
+use crate::sampler::{PadGrooveSettings, SamplerPadFxSettings};
+use crate::sampler_engine::{SampleLoopRegion, VelocityZone, NUM_SAMPLE_SLOTS};
+use crate::settings::{self, OutputClipMode, RecordingFormat, TrimSilenceSettings};
 use crate::synth::{AdsrSettings, EngineParamsUnion, LfoRateMode};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU32};
@@ -28,11 +29,30 @@ pub enum AudioCommand {
     DoubleTempo,
     SetTempoState { master_index: usize, multiplier: u32 },
     MidiMessage(MidiMessage),
+    /// Plays a note on the synth directly, bypassing MIDI channel filtering and the sampler pads.
+    SynthNoteOn { note: u8, velocity: u8 },
+    SynthNoteOff(u8),
     ActivateSynth,
     DeactivateSynth,
     SetSynthMode(usize, bool),
     SetAmpAdsr(usize, AdsrSettings),
     SetFilterAdsr(usize, AdsrSettings),
+    SetVelocitySensitivity(usize, f32),
+    SetGlideTime(usize, f32),
+    /// Frequency of A4 in Hz, applied to both synth engines.
+    SetTuningReference(f32),
+    /// Crossfade length in milliseconds at the wrap point of newly recorded loops.
+    SetLoopCrossfade(f32),
+    /// Length in milliseconds of the anti-click fade at both ends of pad and sampler playback.
+    SetSampleDeclick(f32),
+    /// Length in milliseconds of the crossfade when an engine slot changes type.
+    SetEngineSwapFade(f32),
+    /// How the master output is held within full scale after the limiter.
+    SetOutputClipMode(OutputClipMode),
+    /// Whether tempo-synced synth LFOs restart on every transport downbeat.
+    SetLfoTransportSync(bool),
+    /// Whether the synth, sampler and monitored input stay audible while paused.
+    SetMonitorWhileStopped(bool),
     ResetWavetables(usize),
     SetWavetable {
         engine_index: usize,
@@ -45,12 +65,26 @@ pub enum AudioCommand {
         slot_index: usize,
         audio_data: Arc<Vec<f32>>,
     },
+    AppendSampleToSamplerSlot {
+        engine_index: usize,
+        slot_index: usize,
+        audio_data: Arc<Vec<f32>>,
+    },
     SetSamplerSettings {
         engine_index: usize,
         root_notes: [u8; NUM_SAMPLE_SLOTS],
         global_fine_tune_cents: f32,
         fade_out: f32,
     },
+    SetSamplerLoopRegion {
+        engine_index: usize,
+        slot_index: usize,
+        region: SampleLoopRegion,
+    },
+    SetSamplerVelocityZones {
+        engine_index: usize,
+        zones: Vec<VelocityZone>,
+    },
     ChangeEngineType {
         engine_index: usize,
         volume: Arc<AtomicU32>,
@@ -72,23 +106,47 @@ pub enum AudioCommand {
         pad_index: usize,
         settings: SamplerPadFxSettings,
     },
+    SetPadGroove(PadGrooveSettings),
+    /// Plays a library sample once through the master, replacing any preview already playing.
+    AuditionSample(Arc<Vec<f32>>),
+    StopAudition,
     SetMasterVolume(f32),
     SetLimiterThreshold(f32),
     ToggleLimiter,
     SetLimiterReleaseMode(LfoRateMode),
     SetLimiterReleaseMs(f32),
     SetLimiterReleaseSync(f32),
+    SetLimiterTruePeak(bool),
+    SetLimiterLookahead(f32),
     PlayTransport,
     StopTransport,
     ClearAllAndPlay,
     ClearAll,
+    /// Clears voices, FX tails and limiter state without touching loaded audio or presets.
+    SoftReset,
+    /// Discards the integrated loudness measured so far and starts a new one.
+    ResetLoudnessMeter,
     StartOutputRecording,
     StopOutputRecording {
         output_path: PathBuf,
+        format: RecordingFormat,
+        trim: TrimSilenceSettings,
+        /// Peak target in dBFS, applied after trimming; `None` leaves the level as recorded.
+        normalize_to_db: Option<f32>,
+        dither: bool,
+    },
+    /// Starts capturing stems into a recording set up (with its writer thread) by the UI.
+    StartStemRecording(StemRecording),
+    StopStemRecording {
+        output_dir: PathBuf,
+        format: RecordingFormat,
     },
     SaveSessionAudio {
         session_path: PathBuf,
+        format: RecordingFormat,
     },
+    /// Starts copying the loops for an autosave set up (with its writer thread) by the UI.
+    SnapshotLoops(LoopSnapshot),
     LoadLoopAudio {
         looper_index: usize,
         path: PathBuf,
@@ -96,7 +154,7 @@ pub enum AudioCommand {
         length_in_cycles: u32,
     },
     SetTransportLen(usize),
-    SetMixerState(MixerState),
+    SetMixerState(Box<MixerState>),
     SetMixerTrackVolume {
         track_index: usize,
         volume: f32,
@@ -105,6 +163,12 @@ pub enum AudioCommand {
     SetMetronomePitch(f32),
     SetMetronomeAccentPitch(f32),
     ToggleMetronomeMute,
+    /// Sets the click's on/off state, level and downbeat accent in one go.
+    SetMetronome {
+        enabled: bool,
+        volume: f32,
+        accent_first: bool,
+    },
     ToggleSynth,
     SetSynthMasterVolume(f32),
     ToggleSampler,
@@ -114,6 +178,42 @@ pub enum AudioCommand {
     ToggleRecord,
     ToggleMixerMute(usize),
     ToggleMixerSolo(usize),
+    ToggleMixerSoloSafe(usize),
+    SetMixerTrackBus {
+        track_index: usize,
+        bus: Option<usize>,
+    },
+    SetMixerBusVolume {
+        bus_index: usize,
+        volume: f32,
+    },
+    ToggleMixerBusMute(usize),
+    SetMixerTrackPan {
+        track_index: usize,
+        pan: f32,
+    },
+    SetMixerSidechain(SidechainState),
+    /// Shifts a looper's playback by a number of samples, wrapping around the loop.
+    SetLooperNudge {
+        looper_index: usize,
+        samples: i32,
+    },
+    /// Flips a looper between forward and backward playback. The loop audio is unchanged.
+    ToggleLooperReverse(usize),
+    SetMixerTrackSend {
+        track_index: usize,
+        send_index: usize,
+        level: f32,
+    },
+    SetMixerSendReturnVolume {
+        send_index: usize,
+        volume: f32,
+    },
+    ToggleMixerSendReturnMute(usize),
+    SetMixerTrackMeterPoint {
+        track_index: usize,
+        meter_point: MeterPoint,
+    },
 
     // --- FX Commands ---
     LoadFxRack(fx::InsertionPoint, fx::FxPreset),
@@ -14,9 +14,18 @@ pub struct FxRack {
     components: Vec<Box<dyn fx_components::DspComponent>>,
     mod_routings: Vec<fx::ModulationRoutingData>,
     wet_dry_mix: Arc<AtomicU32>, // Now an atomic for real-time control
+    kill_dry: bool,
+    /// Added to `wet_dry_mix` for the next buffer, e.g. by a synth engine's mod matrix.
+    mix_modulation: f32,
     mod_outputs: Vec<f32>,       // Buffer to store current mod outputs
     // NEW: Pre-allocated buffer for modulation values to avoid heap allocation in process loop.
     mod_values_buffer: BTreeMap<String, f32>,
+    // Shared params of each component, used to read the input/output trims.
+    component_params: Vec<fx_components::ComponentParams>,
+    // Stored (input, output) trim per component, `None` while bypassed, and the linear gains
+    // worked out from it. The gains are only recomputed when the stored values change.
+    trim_stored: Vec<Option<(u32, u32)>>,
+    trim_gains: Vec<(f32, f32)>,
 }
 
 impl FxRack {
@@ -24,6 +33,7 @@ impl FxRack {
     pub fn new(preset: &fx::FxPreset, wet_dry_mix: Arc<AtomicU32>, sample_rate: f32) -> Self {
         let mut components: Vec<Box<dyn fx_components::DspComponent>> = Vec::new();
         let mut mod_routings = Vec::new();
+        let mut component_params = Vec::new();
 
         for link in &preset.chain {
             let component: Box<dyn fx_components::DspComponent> = match &link.params {
@@ -46,7 +56,7 @@ impl FxRack {
                     Box::new(fx_components::Waveshaper::new(p.clone()))
                 }
                 fx_components::ComponentParams::Quantizer(p) => {
-                    Box::new(fx_components::Quantizer::new(p.clone()))
+                    Box::new(fx_components::Quantizer::new(sample_rate, p.clone()))
                 }
                 fx_components::ComponentParams::Reverb(p) => {
                     Box::new(fx_components::Reverb::new(sample_rate, p.clone()))
@@ -59,6 +69,7 @@ impl FxRack {
                 }
             };
             components.push(component);
+            component_params.push(link.params.clone());
         }
 
         // Collect all modulations from all links in the chain
@@ -71,21 +82,55 @@ impl FxRack {
             components,
             mod_routings,
             wet_dry_mix, // Use the persistent atomic passed in
+            kill_dry: preset.kill_dry,
+            mix_modulation: 0.0,
             // NEW: Initialize the buffer. This is a safe, one-time allocation.
             mod_values_buffer: BTreeMap::new(),
+            trim_stored: vec![None; component_params.len()],
+            trim_gains: vec![(1.0, 1.0); component_params.len()],
+            component_params,
         }
     }
 
+    /// Clears the internal state of every component in the chain (delay lines,
+    /// reverb tails, filter memory) while keeping the chain itself intact.
+    pub fn reset(&mut self) {
+        for component in self.components.iter_mut() {
+            component.reset();
+        }
+        self.mod_outputs.fill(0.0);
+    }
+
+    pub fn set_mix_modulation(&mut self, amount: f32) {
+        self.mix_modulation = amount;
+    }
+
     /// Processes an entire audio buffer using a two-pass system for modulation.
     pub fn process_buffer(&mut self, buffer: &mut [f32]) {
         let wet_dry_mix_u32 = self.wet_dry_mix.load(Ordering::Relaxed);
-        let wet_mix = wet_dry_mix_u32 as f32 / PARAM_SCALER;
+        let wet_mix = (wet_dry_mix_u32 as f32 / PARAM_SCALER + self.mix_modulation).clamp(0.0, 1.0);
 
-        if wet_mix < 1e-9 && self.components.is_empty() {
+        if wet_mix < 1e-9 && self.components.is_empty() && !self.kill_dry {
             return; // Optimization: If 100% dry and no components, do nothing.
         }
 
-        let dry_mix = 1.0 - wet_mix;
+        let dry_mix = if self.kill_dry { 0.0 } else { 1.0 - wet_mix };
+
+        // Bypassed components pass audio through untouched, trims included.
+        for ((stored, gains), params) in
+            self.trim_stored.iter_mut().zip(self.trim_gains.iter_mut()).zip(&self.component_params)
+        {
+            let current = (!params.bypassed().load(Ordering::Relaxed)).then(|| params.trim().stored());
+            if current != *stored {
+                *stored = current;
+                *gains = current.map_or((1.0, 1.0), |(input, output)| {
+                    (
+                        fx_components::GainTrim::stored_to_linear(input),
+                        fx_components::GainTrim::stored_to_linear(output),
+                    )
+                });
+            }
+        }
 
         for sample in buffer.iter_mut() {
             let dry_sample = *sample;
@@ -112,9 +157,93 @@ impl FxRack {
                     }
                 }
                 // MODIFIED: Pass the pre-allocated buffer.
-                wet_output = component.process_audio(wet_output, &self.mod_values_buffer);
+                let (input_gain, output_gain) = self.trim_gains[i];
+                wet_output = component
+                    .process_audio(wet_output * input_gain, &self.mod_values_buffer)
+                    * output_gain;
             }
             *sample = (dry_sample * dry_mix) + wet_output;
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fx_components::waveshaper::WaveshaperMode;
+    use crate::fx_components::{ComponentParams, GainTrim};
+
+    /// A fully wet rack holding one hard clipper with the given trims in dB.
+    fn hard_clip_rack(input_db: f32, output_db: f32) -> FxRack {
+        let preset = fx::FxPreset {
+            chain: vec![fx::FxChainLink::new(fx::FxComponentType::Waveshaper)],
+            ..Default::default()
+        };
+        let ComponentParams::Waveshaper(params) = &preset.chain[0].params else {
+            unreachable!();
+        };
+        params.mode.store(WaveshaperMode::HardClip as u32, Ordering::Relaxed);
+        params.trim.input_gain_db.store(GainTrim::db_to_stored(input_db), Ordering::Relaxed);
+        params.trim.output_gain_db.store(GainTrim::db_to_stored(output_db), Ordering::Relaxed);
+        FxRack::new(&preset, Arc::new(AtomicU32::new(PARAM_SCALER as u32)), 48_000.0)
+    }
+
+    fn process_constant(rack: &mut FxRack, level: f32) -> f32 {
+        let mut buffer = [level; 64];
+        rack.process_buffer(&mut buffer);
+        buffer[63]
+    }
+
+    #[test]
+    fn unity_trims_leave_the_effect_alone() {
+        assert!((process_constant(&mut hard_clip_rack(0.0, 0.0), 0.5) - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn input_trim_drives_the_effect_harder() {
+        // +12 dB takes 0.5 to about 2.0, which the clipper then holds at 1.0.
+        let out = process_constant(&mut hard_clip_rack(12.0, 0.0), 0.5);
+        assert!((out - 1.0).abs() < 1e-3, "got {out}");
+    }
+
+    #[test]
+    fn output_trim_scales_the_result() {
+        // The clipped 1.0 is brought down after the effect, so it is not clipped again.
+        let out = process_constant(&mut hard_clip_rack(12.0, -6.0206), 0.5);
+        assert!((out - 0.5).abs() < 1e-3, "got {out}");
+        let out = process_constant(&mut hard_clip_rack(0.0, 6.0206), 0.25);
+        assert!((out - 0.5).abs() < 1e-3, "got {out}");
+    }
+
+    /// A reverb rack at half wet, fed a single impulse; returns its first half second.
+    fn reverb_impulse_response(kill_dry: bool) -> Vec<f32> {
+        let preset = fx::FxPreset {
+            chain: vec![fx::FxChainLink::new(fx::FxComponentType::Reverb)],
+            kill_dry,
+            ..Default::default()
+        };
+        let half_wet = Arc::new(AtomicU32::new((PARAM_SCALER * 0.5) as u32));
+        let mut rack = FxRack::new(&preset, half_wet, 48_000.0);
+        let mut buffer = vec![0.0; 24_000];
+        buffer[0] = 1.0;
+        for block in buffer.chunks_mut(256) {
+            rack.process_buffer(block);
+        }
+        buffer
+    }
+
+    #[test]
+    fn kill_dry_outputs_only_the_wet_tail() {
+        let with_dry = reverb_impulse_response(false);
+        assert!((with_dry[0] - 0.5).abs() < 1e-3, "dry impulse, got {}", with_dry[0]);
+
+        let wet_only = reverb_impulse_response(true);
+        assert!(wet_only[0].abs() < 1e-3, "no dry impulse, got {}", wet_only[0]);
+        let tail_energy: f32 = wet_only.iter().map(|s| s * s).sum();
+        assert!(tail_energy > 1e-4, "the reverb tail should remain");
+        // Past the impulse, both racks carry the same wet signal.
+        for (a, b) in with_dry[1..].iter().zip(&wet_only[1..]) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+}
@@ -1,31 +1,229 @@
 // FILE: src\audio_engine\helpers.rs
 // =================================
 
+use crate::settings::RecordingFormat;
 use anyhow::Result;
 use hound;
-use std::path::Path;
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::Arc;
+use ringbuf::{HeapConsumer, HeapProducer, HeapRb};
+use std::collections::VecDeque;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+/// Upper bound of the master limiter's lookahead.
+pub const MAX_LIMITER_LOOKAHEAD_MS: f32 = 5.0;
+/// Longest loop seam crossfade the options allow.
+pub const MAX_LOOP_CROSSFADE_MS: f32 = 50.0;
+/// Length of the interpolation filter the true-peak detector runs per channel.
+const TRUE_PEAK_TAPS: usize = 8;
+/// How many samples true-peak detection delays the limiter's output by.
+pub const TRUE_PEAK_LATENCY_SAMPLES: usize = TRUE_PEAK_TAPS / 2;
+/// The detector estimates the signal at this many points per sample period.
+const TRUE_PEAK_OVERSAMPLING: usize = 4;
+
+/// Estimates inter-sample peaks by 4x oversampling with a windowed-sinc interpolator.
+///
+/// The interpolator needs samples on both sides of the point it estimates, so the frame
+/// that comes out is `TRUE_PEAK_TAPS / 2` samples behind the one that went in; its level
+/// covers that frame and the three interpolated points between it and the next one.
+struct TruePeakDetector {
+    phases: [[f32; TRUE_PEAK_TAPS]; TRUE_PEAK_OVERSAMPLING - 1],
+    history: [[f32; TRUE_PEAK_TAPS]; 2],
+}
+
+impl TruePeakDetector {
+    fn new() -> Self {
+        let center = TRUE_PEAK_TAPS / 2 - 1;
+        let half_span = (TRUE_PEAK_TAPS / 2) as f32;
+        let mut phases = [[0.0; TRUE_PEAK_TAPS]; TRUE_PEAK_OVERSAMPLING - 1];
+        for (p, phase) in phases.iter_mut().enumerate() {
+            let offset = (p + 1) as f32 / TRUE_PEAK_OVERSAMPLING as f32;
+            for (k, coeff) in phase.iter_mut().enumerate() {
+                let x = k as f32 - center as f32 - offset;
+                let sinc = (std::f32::consts::PI * x).sin() / (std::f32::consts::PI * x);
+                let window = 0.5 * (1.0 + (std::f32::consts::PI * x / half_span).cos());
+                *coeff = sinc * window;
+            }
+            // Unity gain at DC, so a constant signal reads the same between samples.
+            let sum: f32 = phase.iter().sum();
+            phase.iter_mut().for_each(|c| *c /= sum);
+        }
+        Self {
+            phases,
+            history: [[0.0; TRUE_PEAK_TAPS]; 2],
+        }
+    }
+
+    fn reset(&mut self) {
+        self.history = [[0.0; TRUE_PEAK_TAPS]; 2];
+    }
+
+    /// Pushes `frame` and returns the delayed frame together with its true-peak level.
+    fn process(&mut self, frame: [f32; 2]) -> ([f32; 2], f32) {
+        let center = TRUE_PEAK_TAPS / 2 - 1;
+        let mut delayed = [0.0; 2];
+        let mut level: f32 = 0.0;
+        for (ch, history) in self.history.iter_mut().enumerate() {
+            history.copy_within(1.., 0);
+            history[TRUE_PEAK_TAPS - 1] = frame[ch];
+            delayed[ch] = history[center];
+            level = level.max(history[center].abs());
+            for phase in &self.phases {
+                let interpolated: f32 = phase.iter().zip(history.iter()).map(|(c, s)| c * s).sum();
+                level = level.max(interpolated.abs());
+            }
+        }
+        (delayed, level)
+    }
+}
 
 pub struct Limiter {
     attack_coeffs: f32,
     envelope: f32,
+    true_peak: bool,
+    detector: TruePeakDetector,
+    sample_rate: f32,
+    /// Lookahead in samples; 0 applies the gain to the frame it was measured on.
+    lookahead: usize,
+    delay_line: Vec<[f32; 2]>,
+    delay_pos: usize,
+    /// (sample count, level) pairs with decreasing levels, so the front is the loudest level
+    /// of the last `lookahead + 1` samples.
+    level_window: VecDeque<(usize, f32)>,
+    sample_count: usize,
+    /// The last `lookahead` gains, averaged so reduction ramps in over the lookahead.
+    gain_window: Vec<f32>,
+    gain_pos: usize,
+    gain_sum: f64,
     pub gain_reduction_db: Arc<AtomicU32>,
 }
 
 impl Limiter {
     pub fn new(sample_rate: f32, gain_reduction_db: Arc<AtomicU32>) -> Self {
         let attack_ms = 0.01;
+        let max_lookahead = Self::lookahead_samples(MAX_LIMITER_LOOKAHEAD_MS, sample_rate);
         Self {
             attack_coeffs: (-(1.0 / (attack_ms * 0.001 * sample_rate))).exp(),
             envelope: 0.0,
+            true_peak: false,
+            detector: TruePeakDetector::new(),
+            sample_rate,
+            lookahead: 0,
+            delay_line: vec![[0.0; 2]; max_lookahead],
+            delay_pos: 0,
+            level_window: VecDeque::with_capacity(max_lookahead + 2),
+            sample_count: 0,
+            gain_window: vec![1.0; max_lookahead],
+            gain_pos: 0,
+            gain_sum: 0.0,
             gain_reduction_db,
         }
     }
 
-    pub fn process(&mut self, input: f32, threshold: f32, release_coeffs: f32) -> f32 {
-        let input_abs = input.abs();
+    fn lookahead_samples(lookahead_ms: f32, sample_rate: f32) -> usize {
+        (lookahead_ms.clamp(0.0, MAX_LIMITER_LOOKAHEAD_MS) * 0.001 * sample_rate).round() as usize
+    }
+
+    /// The most the limiter can delay the signal, for sizing buffers that compensate for it.
+    pub fn max_latency_samples(sample_rate: f32) -> usize {
+        Self::lookahead_samples(MAX_LIMITER_LOOKAHEAD_MS, sample_rate) + TRUE_PEAK_LATENCY_SAMPLES
+    }
+
+    /// How far behind its input the limiter's output currently is.
+    pub fn latency_samples(&self) -> usize {
+        self.lookahead + if self.true_peak { TRUE_PEAK_LATENCY_SAMPLES } else { 0 }
+    }
+
+    /// Sets how early gain reduction starts ahead of a peak. The audio is delayed by the same
+    /// amount; 0 turns lookahead off.
+    pub fn set_lookahead_ms(&mut self, lookahead_ms: f32) {
+        let lookahead = Self::lookahead_samples(lookahead_ms, self.sample_rate);
+        if lookahead != self.lookahead {
+            self.lookahead = lookahead;
+            self.reset_lookahead();
+        }
+    }
+
+    fn reset_lookahead(&mut self) {
+        self.delay_line.fill([0.0; 2]);
+        self.delay_pos = 0;
+        self.level_window.clear();
+        self.gain_window.fill(1.0);
+        self.gain_pos = 0;
+        self.gain_sum = self.lookahead as f64;
+    }
+
+    /// Switches between sample-peak and true-peak detection. True-peak costs about three
+    /// 8-tap filters per channel and delays the output by four samples.
+    pub fn set_true_peak(&mut self, enabled: bool) {
+        if self.true_peak != enabled {
+            self.true_peak = enabled;
+            self.detector.reset();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.detector.reset();
+        self.reset_lookahead();
+        self.gain_reduction_db.store(0, Ordering::Relaxed);
+    }
+
+    /// Linked stereo limiting: both channels get the gain derived from the louder one,
+    /// so the stereo image doesn't shift under gain reduction. With true-peak on, the level
+    /// also includes the peaks between samples that would otherwise clip on conversion.
+    /// With lookahead, the gain for each peak is fully applied by the time the delayed peak
+    /// comes out, instead of being chased by the envelope.
+    pub fn process(
+        &mut self,
+        frame: [f32; 2],
+        threshold: f32,
+        release_coeffs: f32,
+    ) -> [f32; 2] {
+        let (frame, level) = if self.true_peak {
+            self.detector.process(frame)
+        } else {
+            (frame, frame[0].abs().max(frame[1].abs()))
+        };
+        if self.lookahead == 0 {
+            let gain = self.gain_for(level, threshold, release_coeffs);
+            return [frame[0] * gain, frame[1] * gain];
+        }
+
+        let delayed = std::mem::replace(&mut self.delay_line[self.delay_pos], frame);
+        self.delay_pos = (self.delay_pos + 1) % self.lookahead;
 
+        // Hold the loudest level until its frame has come out of the delay line.
+        let now = self.sample_count;
+        self.sample_count = self.sample_count.wrapping_add(1);
+        while self.level_window.back().is_some_and(|&(_, l)| l <= level) {
+            self.level_window.pop_back();
+        }
+        self.level_window.push_back((now, level));
+        while self
+            .level_window
+            .front()
+            .is_some_and(|&(t, _)| now.wrapping_sub(t) > self.lookahead)
+        {
+            self.level_window.pop_front();
+        }
+        let held_level = self.level_window.front().map_or(level, |&(_, l)| l);
+        let target_gain = self.gain_for(held_level, threshold, release_coeffs);
+
+        self.gain_sum += (target_gain - self.gain_window[self.gain_pos]) as f64;
+        self.gain_window[self.gain_pos] = target_gain;
+        self.gain_pos = (self.gain_pos + 1) % self.lookahead;
+        if self.gain_pos == 0 {
+            // Resum once per lap so rounding in the running sum can't build up.
+            self.gain_sum = self.gain_window[..self.lookahead].iter().map(|&g| g as f64).sum();
+        }
+        let gain = (self.gain_sum / self.lookahead as f64) as f32;
+        [delayed[0] * gain, delayed[1] * gain]
+    }
+
+    fn gain_for(&mut self, input_abs: f32, threshold: f32, release_coeffs: f32) -> f32 {
         self.envelope = if input_abs > self.envelope {
             self.attack_coeffs * (self.envelope - input_abs) + input_abs
         } else {
@@ -44,7 +242,7 @@ impl Limiter {
         self.gain_reduction_db
             .store(reduction_scaled, Ordering::Relaxed);
 
-        input * gain
+        gain
     }
 }
 
@@ -86,28 +284,656 @@ impl Metronome {
     }
 }
 
-pub fn write_wav_file(path: &Path, audio_buffer: &[f32], sample_rate: f32) -> Result<()> {
-    let spec = hound::WavSpec {
-        channels: 2,
-        sample_rate: sample_rate as u32,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
-    };
+/// One-shot player for previewing library samples before they are assigned anywhere.
+pub struct AuditionPlayer {
+    audio: Option<Arc<Vec<f32>>>,
+    position: usize,
+    is_playing: Arc<AtomicBool>,
+}
+
+impl AuditionPlayer {
+    pub fn new(is_playing: Arc<AtomicBool>) -> Self {
+        Self {
+            audio: None,
+            position: 0,
+            is_playing,
+        }
+    }
+
+    pub fn play(&mut self, audio: Arc<Vec<f32>>) {
+        self.audio = Some(audio);
+        self.position = 0;
+        self.is_playing.store(true, Ordering::Relaxed);
+    }
+
+    pub fn stop(&mut self) {
+        self.audio = None;
+        self.is_playing.store(false, Ordering::Relaxed);
+    }
+
+    pub fn process(&mut self) -> f32 {
+        let Some(audio) = &self.audio else {
+            return 0.0;
+        };
+        match audio.get(self.position) {
+            Some(&sample) => {
+                self.position += 1;
+                sample
+            }
+            None => {
+                self.stop();
+                0.0
+            }
+        }
+    }
+}
+
+const I24_MAX: f32 = 8_388_607.0;
+
+pub fn write_wav_file(
+    path: &Path,
+    audio_buffer: &[f32],
+    sample_rate: f32,
+    format: RecordingFormat,
+) -> Result<()> {
+    let spec = format.wav_spec(2, sample_rate as u32);
     let mut writer = hound::WavWriter::create(path, spec)?;
     for &sample in audio_buffer {
-        let amplitude = i16::MAX as f32;
-        let sample_i16 = (sample * amplitude) as i16;
-        writer.write_sample(sample_i16)?; // Left channel
-        writer.write_sample(sample_i16)?; // Right channel
+        write_formatted_sample(&mut writer, sample, format)?; // Left channel
+        write_formatted_sample(&mut writer, sample, format)?; // Right channel
     }
     writer.finalize()?;
     Ok(())
 }
 
-pub fn trim_silence(audio_buffer: Vec<f32>) -> Vec<f32> {
-    const SILENCE_THRESHOLD: f32 = 0.005; // RMS threshold
+/// Writes interleaved left/right samples as a stereo WAV file.
+pub fn write_stereo_wav_file(
+    path: &Path,
+    interleaved: &[f32],
+    sample_rate: f32,
+    format: RecordingFormat,
+) -> Result<()> {
+    let spec = format.wav_spec(2, sample_rate as u32);
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for &sample in interleaved {
+        write_formatted_sample(&mut writer, sample, format)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Writes a mono WAV file, the layout session loops are saved in.
+pub fn write_mono_wav_file(
+    path: &Path,
+    audio_buffer: &[f32],
+    sample_rate: f32,
+    format: RecordingFormat,
+) -> Result<()> {
+    let spec = format.wav_spec(1, sample_rate as u32);
+    let mut writer = hound::WavWriter::create(path, spec)?;
+    for &sample in audio_buffer {
+        write_formatted_sample(&mut writer, sample, format)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Writes one sample in the writer's format. Float output is the raw value, unscaled.
+pub fn write_formatted_sample<W: Write + Seek>(
+    writer: &mut hound::WavWriter<W>,
+    sample: f32,
+    format: RecordingFormat,
+) -> Result<()> {
+    match format {
+        RecordingFormat::Int16 => writer.write_sample((sample * i16::MAX as f32) as i16)?,
+        RecordingFormat::Int24 => writer.write_sample((sample * I24_MAX) as i32)?,
+        RecordingFormat::Float32 => writer.write_sample(sample)?,
+    }
+    Ok(())
+}
+
+/// Reads every sample of a WAV as f32, whatever its integer width or float encoding.
+pub fn read_samples_as_f32<R: std::io::Read>(reader: hound::WavReader<R>) -> Vec<f32> {
+    let spec = reader.spec();
+    match spec.sample_format {
+        hound::SampleFormat::Float => reader.into_samples::<f32>().filter_map(Result::ok).collect(),
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32 - 1.0;
+            reader
+                .into_samples::<i32>()
+                .filter_map(Result::ok)
+                .map(|s| s as f32 / scale)
+                .collect()
+        }
+    }
+}
+
+/// Stems are capped at this length so a forgotten recording can't grow without bound.
+pub const MAX_STEM_RECORDING_SECONDS: f32 = 600.0;
+
+/// Frames per block the audio thread fills before handing it to the stem writer.
+const STEM_BLOCK_FRAMES: usize = 4096;
+/// Empty blocks kept ready for the audio thread, about 1.4 s at 48 kHz.
+const STEM_SPARE_BLOCKS: usize = 16;
+/// How long the stem writer sleeps when no block is waiting.
+const STEM_WRITER_POLL: Duration = Duration::from_millis(20);
+/// Value of the stems-written counter while no finished recording is waiting to be reported.
+pub const NO_STEM_RESULT: u32 = u32::MAX;
+
+/// Interleaved frames of every stem: each looper, then the synth, then the sampler.
+struct StemBlock {
+    /// Frames lost before this block because no empty block was ready in time. The writer
+    /// fills them with silence so the stems stay aligned with the mix.
+    frames_dropped_before: usize,
+    samples: Vec<f32>,
+}
+
+enum StemMessage {
+    Block(StemBlock),
+    Finish {
+        output_dir: PathBuf,
+        format: RecordingFormat,
+        /// Handed over so the spare blocks are freed by the writer, not the audio thread.
+        spare_blocks: HeapConsumer<StemBlock>,
+    },
+}
+
+/// Per-source capture of everything that reaches the master: one post-fader stem per
+/// looper plus the synth and sampler buses. All stems share one timeline.
+///
+/// This is the audio thread's end. It only fills preallocated blocks and passes them to a
+/// writer thread, which collects the stems and saves them when the recording finishes.
+pub struct StemRecording {
+    frame_len: usize,
+    current: Option<StemBlock>,
+    full_blocks: HeapProducer<StemMessage>,
+    spare_blocks: HeapConsumer<StemBlock>,
+    frames_dropped: usize,
+    frames_recorded: usize,
+    max_len: usize,
+    pub limit_reached: bool,
+    /// Dropped with the recording; lets the writer notice a recording that never finishes.
+    _alive: Arc<()>,
+}
+
+impl std::fmt::Debug for StemRecording {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StemRecording")
+            .field("frames_recorded", &self.frames_recorded)
+            .field("limit_reached", &self.limit_reached)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StemRecording {
+    /// Allocates the blocks and starts the writer thread. Called off the audio thread; the
+    /// writer stores how many stems it saved in `stems_written` once the recording finishes.
+    pub fn start(num_loopers: usize, sample_rate: f32, stems_written: Arc<AtomicU32>) -> Self {
+        let frame_len = num_loopers + 2;
+        let (full_producer, full_consumer) = HeapRb::new(STEM_SPARE_BLOCKS + 1).split();
+        let (mut spare_producer, spare_consumer) = HeapRb::new(STEM_SPARE_BLOCKS).split();
+        for _ in 0..STEM_SPARE_BLOCKS {
+            let block = StemBlock {
+                frames_dropped_before: 0,
+                samples: Vec::with_capacity(STEM_BLOCK_FRAMES * frame_len),
+            };
+            if spare_producer.push(block).is_err() {
+                unreachable!("the spare ring holds every block");
+            }
+        }
+        let alive = Arc::new(());
+        let writer_alive = Arc::downgrade(&alive);
+        std::thread::spawn(move || {
+            run_stem_writer(
+                frame_len,
+                full_consumer,
+                spare_producer,
+                writer_alive,
+                sample_rate,
+                stems_written,
+            );
+        });
+        Self {
+            frame_len,
+            current: None,
+            full_blocks: full_producer,
+            spare_blocks: spare_consumer,
+            frames_dropped: 0,
+            frames_recorded: 0,
+            max_len: (MAX_STEM_RECORDING_SECONDS * sample_rate) as usize,
+            limit_reached: false,
+            _alive: alive,
+        }
+    }
+
+    /// Appends one sample to every stem. Returns false once the length cap is hit.
+    pub fn push_frame(&mut self, looper_samples: &[f32], synth: f32, sampler: f32) -> bool {
+        if self.frames_recorded >= self.max_len {
+            self.limit_reached = true;
+            return false;
+        }
+        self.frames_recorded += 1;
+        if self.current.is_none() {
+            self.current = self.spare_blocks.pop().map(|mut block| {
+                block.frames_dropped_before = std::mem::take(&mut self.frames_dropped);
+                block
+            });
+        }
+        let Some(block) = &mut self.current else {
+            self.frames_dropped += 1;
+            return true;
+        };
+        block.samples.extend_from_slice(&looper_samples[..self.frame_len - 2]);
+        block.samples.push(synth);
+        block.samples.push(sampler);
+        if block.samples.len() >= STEM_BLOCK_FRAMES * self.frame_len {
+            if let Some(block) = self.current.take() {
+                send_stem_message(&mut self.full_blocks, StemMessage::Block(block));
+            }
+        }
+        true
+    }
+
+    /// Hands the last block to the writer and tells it to save the stems to `output_dir`.
+    pub fn finish(self, output_dir: PathBuf, format: RecordingFormat) {
+        let Self {
+            current,
+            mut full_blocks,
+            spare_blocks,
+            _alive,
+            ..
+        } = self;
+        if let Some(block) = current {
+            send_stem_message(&mut full_blocks, StemMessage::Block(block));
+        }
+        send_stem_message(
+            &mut full_blocks,
+            StemMessage::Finish {
+                output_dir,
+                format,
+                spare_blocks,
+            },
+        );
+    }
+}
+
+fn send_stem_message(full_blocks: &mut HeapProducer<StemMessage>, message: StemMessage) {
+    // The ring has room for every block in existence plus the finish message.
+    if full_blocks.push(message).is_err() {
+        unreachable!("the stem ring holds every block");
+    }
+}
+
+/// Collects the blocks of one stem recording and writes the stems that carry any signal.
+fn run_stem_writer(
+    frame_len: usize,
+    mut full_blocks: HeapConsumer<StemMessage>,
+    mut spare_blocks: HeapProducer<StemBlock>,
+    recording_alive: Weak<()>,
+    sample_rate: f32,
+    stems_written: Arc<AtomicU32>,
+) {
+    let mut stems: Vec<Vec<f32>> = vec![Vec::new(); frame_len];
+    loop {
+        let Some(message) = full_blocks.pop() else {
+            if recording_alive.strong_count() == 0 && full_blocks.is_empty() {
+                // The engine was dropped mid-recording; there is nowhere to save to.
+                return;
+            }
+            std::thread::sleep(STEM_WRITER_POLL);
+            continue;
+        };
+        match message {
+            StemMessage::Block(mut block) => {
+                let frames = block.frames_dropped_before + block.samples.len() / frame_len;
+                for (index, stem) in stems.iter_mut().enumerate() {
+                    stem.reserve(frames);
+                    stem.resize(stem.len() + block.frames_dropped_before, 0.0);
+                    stem.extend(block.samples.iter().skip(index).step_by(frame_len));
+                }
+                block.samples.clear();
+                block.frames_dropped_before = 0;
+                let _ = spare_blocks.push(block);
+            }
+            StemMessage::Finish {
+                output_dir,
+                format,
+                spare_blocks,
+            } => {
+                drop(spare_blocks);
+                let written = write_stems(stems, &output_dir, sample_rate, format);
+                stems_written.store(written, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+}
+
+/// Writes each stem that carries any signal to `output_dir` and returns how many were saved.
+fn write_stems(
+    stems: Vec<Vec<f32>>,
+    output_dir: &Path,
+    sample_rate: f32,
+    format: RecordingFormat,
+) -> u32 {
+    let num_loopers = stems.len() - 2;
+    let names = (0..num_loopers)
+        .map(|i| format!("stem_loop_{}.wav", i))
+        .chain(["stem_synth.wav".to_string(), "stem_sampler.wav".to_string()]);
+    let mut written = 0;
+    for (name, stem) in names.zip(stems) {
+        if stem.iter().all(|&s| s == 0.0) {
+            continue;
+        }
+        if let Err(e) = std::fs::create_dir_all(output_dir) {
+            eprintln!("Failed to create stem directory {}: {}", output_dir.display(), e);
+            return 0;
+        }
+        let path = output_dir.join(name);
+        match write_wav_file(&path, &stem, sample_rate, format) {
+            Ok(()) => written += 1,
+            Err(e) => eprintln!("Failed to save stem {}: {}", path.display(), e),
+        }
+    }
+    written
+}
+
+/// Loop samples the audio thread copies into a snapshot per callback, about 1.4 s at 48 kHz.
+const LOOP_SNAPSHOT_CHUNK: usize = 65_536;
+
+/// The loop copies of a snapshot, as handed to its writer.
+struct CopiedLoops {
+    loops: Vec<Vec<f32>>,
+    /// False if a loop changed length while it was being copied, which leaves nothing
+    /// consistent to save.
+    is_complete: bool,
+}
+
+/// A copy of every loop, saved as a session's `loop_<i>.wav` files without the audio thread
+/// allocating or copying a whole loop in one callback.
+///
+/// The UI allocates a buffer per loop and starts a writer thread. The audio thread copies
+/// `LOOP_SNAPSHOT_CHUNK` samples per callback and passes the full buffers to the writer,
+/// which saves them to a staging directory and then swaps it in for the session. An overdub
+/// running while the copy is taken may only be partly in it.
+pub struct LoopSnapshot {
+    loops: Vec<Vec<f32>>,
+    next_loop: usize,
+    finished: HeapProducer<CopiedLoops>,
+    /// Dropped with the snapshot; lets the writer notice a snapshot that never finishes.
+    _alive: Arc<()>,
+}
+
+impl std::fmt::Debug for LoopSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoopSnapshot")
+            .field("next_loop", &self.next_loop)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LoopSnapshot {
+    /// Allocates room for loops of `loop_lens` samples and starts the writer. `staging_dir`
+    /// must already hold the session's JSON; once the loops are written beside it, it
+    /// replaces `session_dir`. `is_writing` is set until the writer is done.
+    pub fn start(
+        loop_lens: &[usize],
+        staging_dir: PathBuf,
+        session_dir: PathBuf,
+        sample_rate: f32,
+        format: RecordingFormat,
+        is_writing: Arc<AtomicBool>,
+    ) -> Self {
+        let (finished, copied) = HeapRb::new(1).split();
+        let alive = Arc::new(());
+        let snapshot_alive = Arc::downgrade(&alive);
+        is_writing.store(true, Ordering::Relaxed);
+        std::thread::spawn(move || {
+            if let Some(copied) = wait_for_copied_loops(copied, snapshot_alive) {
+                save_loop_snapshot(copied, &staging_dir, &session_dir, sample_rate, format);
+            }
+            is_writing.store(false, Ordering::Relaxed);
+        });
+        Self {
+            loops: loop_lens.iter().map(|&len| Vec::with_capacity(len)).collect(),
+            next_loop: 0,
+            finished,
+            _alive: alive,
+        }
+    }
+
+    /// Copies up to `LOOP_SNAPSHOT_CHUNK` more samples from `loop_audio(i)`, the audio of
+    /// looper `i`. Returns true once the copies have been handed to the writer.
+    pub fn copy_chunk<'a>(&mut self, loop_audio: impl Fn(usize) -> &'a [f32]) -> bool {
+        let mut budget = LOOP_SNAPSHOT_CHUNK;
+        while let Some(copy) = self.loops.get_mut(self.next_loop) {
+            let source = loop_audio(self.next_loop);
+            if source.len() > copy.capacity() || source.len() < copy.len() {
+                self.finish(false);
+                return true;
+            }
+            let end = source.len().min(copy.len() + budget);
+            budget -= end - copy.len();
+            copy.extend_from_slice(&source[copy.len()..end]);
+            if copy.len() < source.len() {
+                return false;
+            }
+            self.next_loop += 1;
+        }
+        self.finish(true);
+        true
+    }
+
+    fn finish(&mut self, is_complete: bool) {
+        let copied = CopiedLoops {
+            loops: std::mem::take(&mut self.loops),
+            is_complete,
+        };
+        // A snapshot finishes once, into a ring with room for one.
+        if self.finished.push(copied).is_err() {
+            unreachable!("a loop snapshot finishes once");
+        }
+    }
+}
+
+fn wait_for_copied_loops(
+    mut copied: HeapConsumer<CopiedLoops>,
+    snapshot_alive: Weak<()>,
+) -> Option<CopiedLoops> {
+    loop {
+        if let Some(loops) = copied.pop() {
+            return Some(loops);
+        }
+        if snapshot_alive.strong_count() == 0 && copied.is_empty() {
+            // The snapshot was dropped before it was taken, e.g. by an engine restart.
+            return None;
+        }
+        std::thread::sleep(STEM_WRITER_POLL);
+    }
+}
+
+/// Writes the non-empty loops into `staging_dir` and swaps it in for `session_dir`.
+fn save_loop_snapshot(
+    copied: CopiedLoops,
+    staging_dir: &Path,
+    session_dir: &Path,
+    sample_rate: f32,
+    format: RecordingFormat,
+) {
+    if !copied.is_complete {
+        eprintln!("A loop changed while it was being copied; skipping this save.");
+        return;
+    }
+    for (i, audio) in copied.loops.iter().enumerate() {
+        if audio.is_empty() {
+            continue;
+        }
+        let path = staging_dir.join(format!("loop_{}.wav", i));
+        if let Err(e) = write_mono_wav_file(&path, audio, sample_rate, format) {
+            eprintln!("Failed to save loop {}: {}", path.display(), e);
+            return;
+        }
+    }
+    if let Err(e) = replace_dir(staging_dir, session_dir) {
+        eprintln!("Failed to replace {}: {}", session_dir.display(), e);
+    }
+}
+
+/// Moves `staging_dir` to `target_dir`. The old `target_dir` is moved aside first and only
+/// deleted once the new one is in place, so there is always a complete copy on disk.
+fn replace_dir(staging_dir: &Path, target_dir: &Path) -> std::io::Result<()> {
+    let old_dir = target_dir.with_extension("old");
+    if old_dir.exists() {
+        std::fs::remove_dir_all(&old_dir)?;
+    }
+    if target_dir.exists() {
+        std::fs::rename(target_dir, &old_dir)?;
+    }
+    std::fs::rename(staging_dir, target_dir)?;
+    if old_dir.exists() {
+        std::fs::remove_dir_all(&old_dir)?;
+    }
+    Ok(())
+}
+
+/// Scales the buffer so its loudest sample sits at `target_db` dBFS. Silent buffers are
+/// left untouched and return false.
+pub fn normalize_peak(audio_buffer: &mut [f32], target_db: f32) -> bool {
+    let peak = audio_buffer.iter().fold(0.0f32, |max, &s| max.max(s.abs()));
+    if peak <= 1e-9 {
+        return false;
+    }
+    let gain = 10.0_f32.powf(target_db / 20.0) / peak;
+    for sample in audio_buffer.iter_mut() {
+        *sample *= gain;
+    }
+    true
+}
+
+/// The un-faded audio at both ends of a loop, saved when its seam is crossfaded.
+///
+/// Overdubs landing on either end are added to the saved copy too, so once the pass is over
+/// the seam can be faded again from the original audio rather than on top of the old fade.
+/// Both halves are allocated up front for the longest fade, so nothing here allocates on
+/// the audio thread.
+#[derive(Debug, Clone)]
+pub struct LoopSeam {
+    head: Vec<f32>,
+    tail: Vec<f32>,
+    /// Length of the saved fade; 0 when no seam is saved.
+    fade_len: usize,
+    /// Length of the loop the seam was saved from.
+    loop_len: usize,
+    /// An overdub has changed the saved seam since it was last faded.
+    is_dirty: bool,
+}
+
+impl LoopSeam {
+    pub fn new(max_fade_len: usize) -> Self {
+        Self {
+            head: vec![0.0; max_fade_len],
+            tail: vec![0.0; max_fade_len],
+            fade_len: 0,
+            loop_len: 0,
+            is_dirty: false,
+        }
+    }
+
+    /// Equal-power crossfade across the wrap point of a loop so the jump from its last sample
+    /// back to its first doesn't click. The tail is blended into the head, and the tail in
+    /// turn fades into the audio just before it, so playback stays continuous through the
+    /// seam. The un-faded ends are saved first. Does nothing, and forgets any saved seam,
+    /// when `fade_len` is 0 or the loop is too short to hold three fades.
+    pub fn crossfade(&mut self, audio: &mut [f32], fade_len: usize) {
+        let len = audio.len();
+        let fade_len = fade_len.min(self.head.len());
+        if fade_len == 0 || len < fade_len * 3 {
+            self.forget();
+            return;
+        }
+        self.head[..fade_len].copy_from_slice(&audio[..fade_len]);
+        self.tail[..fade_len].copy_from_slice(&audio[len - fade_len..]);
+        self.fade_len = fade_len;
+        self.loop_len = len;
+        self.is_dirty = false;
+        self.apply(audio);
+    }
+
+    /// Mirrors an overdubbed sample written at `index` of the loop into the saved seam, with
+    /// the same clamp the loop applies.
+    pub fn overdub(&mut self, index: usize, input: f32) {
+        if self.fade_len == 0 || index >= self.loop_len {
+            return;
+        }
+        let saved = if index < self.fade_len {
+            &mut self.head[index]
+        } else if let Some(tail_index) = index.checked_sub(self.loop_len - self.fade_len) {
+            &mut self.tail[tail_index]
+        } else {
+            return;
+        };
+        *saved = (*saved + input).clamp(-1.0, 1.0);
+        self.is_dirty = true;
+    }
+
+    /// Fades the seam again from the saved copy if an overdub has touched it. Returns false,
+    /// leaving `audio` alone, when there is nothing to redo or the loop has changed length.
+    pub fn refade(&mut self, audio: &mut [f32]) -> bool {
+        if !self.is_dirty || audio.len() != self.loop_len {
+            return false;
+        }
+        self.is_dirty = false;
+        self.apply(audio);
+        true
+    }
+
+    /// True while an overdub has left the seam waiting to be faded again.
+    pub fn is_dirty(&self) -> bool {
+        self.is_dirty
+    }
+
+    /// Drops the saved seam, for when the loop's audio is replaced or cleared.
+    pub fn forget(&mut self) {
+        self.fade_len = 0;
+        self.loop_len = 0;
+        self.is_dirty = false;
+    }
+
+    fn apply(&self, audio: &mut [f32]) {
+        let fade_len = self.fade_len;
+        let tail_start = audio.len() - fade_len;
+        for i in 0..fade_len {
+            let angle = (i as f32 + 0.5) / fade_len as f32 * std::f32::consts::FRAC_PI_2;
+            let (rising, falling) = angle.sin_cos();
+            audio[i] = self.head[i] * rising + self.tail[i] * falling;
+            audio[tail_start + i] =
+                self.tail[i] * falling + audio[tail_start - fade_len + i] * rising;
+        }
+    }
+}
+
+/// Adds triangular (TPDF) dither of one LSB before 16-bit quantization. Other formats have
+/// enough resolution that dither is left off.
+pub fn apply_tpdf_dither(audio_buffer: &mut [f32], format: RecordingFormat) {
+    if format != RecordingFormat::Int16 {
+        return;
+    }
+    let lsb = 1.0 / i16::MAX as f32;
+    for sample in audio_buffer.iter_mut() {
+        let noise = rand::random::<f32>() - rand::random::<f32>();
+        *sample += noise * lsb;
+    }
+}
+
+/// Cuts leading and trailing silence. Sound starts once `required_blocks` consecutive
+/// 512-sample blocks exceed `rms_threshold`; an all-silent buffer comes back empty.
+pub fn trim_silence(
+    audio_buffer: Vec<f32>,
+    rms_threshold: f32,
+    required_blocks: usize,
+) -> Vec<f32> {
     const BLOCK_SIZE: usize = 512; // Analyze in chunks of 512 samples
-    const REQUIRED_BLOCKS: usize = 3; // Need 3 consecutive blocks of sound to confirm start
+    let required_blocks = required_blocks.max(1);
 
     let num_blocks = audio_buffer.len() / BLOCK_SIZE;
     let mut consecutive_sound_blocks = 0;
@@ -119,10 +945,10 @@ pub fn trim_silence(audio_buffer: Vec<f32>) -> Vec<f32> {
         let sum_sq: f32 = block.iter().map(|&s| s * s).sum();
         let rms = (sum_sq / BLOCK_SIZE as f32).sqrt();
 
-        if rms > SILENCE_THRESHOLD {
+        if rms > rms_threshold {
             consecutive_sound_blocks += 1;
-            if consecutive_sound_blocks >= REQUIRED_BLOCKS {
-                start_block = Some(i.saturating_sub(REQUIRED_BLOCKS - 1));
+            if consecutive_sound_blocks >= required_blocks {
+                start_block = Some(i.saturating_sub(required_blocks - 1));
                 break;
             }
         } else {
@@ -143,10 +969,11 @@ pub fn trim_silence(audio_buffer: Vec<f32>) -> Vec<f32> {
         let sum_sq: f32 = block.iter().map(|&s| s * s).sum();
         let rms = (sum_sq / BLOCK_SIZE as f32).sqrt();
 
-        if rms > SILENCE_THRESHOLD {
+        if rms > rms_threshold {
             consecutive_sound_blocks += 1;
-            if consecutive_sound_blocks >= REQUIRED_BLOCKS {
-                end_block = Some(i);
+            if consecutive_sound_blocks >= required_blocks {
+                // Scanning backwards, the run started `required_blocks - 1` blocks later.
+                end_block = Some(i + required_blocks - 1);
                 break;
             }
         } else {
@@ -164,4 +991,260 @@ pub fn trim_silence(audio_buffer: Vec<f32>) -> Vec<f32> {
     }
 
     audio_buffer[start_pos..end_pos].to_vec()
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("cypher-{}-{}", name, std::process::id()))
+    }
+
+    /// Finishes the recording and waits for the writer to report how many stems it saved.
+    fn finish_and_wait(stems: StemRecording, dir: &Path, written: &AtomicU32) -> u32 {
+        stems.finish(dir.to_path_buf(), RecordingFormat::Float32);
+        for _ in 0..500 {
+            let count = written.load(Ordering::Relaxed);
+            if count != NO_STEM_RESULT {
+                return count;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("the stem writer never finished");
+    }
+
+    fn read_stem(path: &Path) -> Vec<f32> {
+        let mut reader = hound::WavReader::open(path).unwrap();
+        reader.samples::<f32>().step_by(2).map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn stems_are_split_per_source_and_silent_ones_skipped() {
+        let written = Arc::new(AtomicU32::new(NO_STEM_RESULT));
+        let mut stems = StemRecording::start(3, SAMPLE_RATE, written.clone());
+        // Several blocks' worth, ending part way through a block.
+        let frames = STEM_BLOCK_FRAMES * 3 + 100;
+        for i in 0..frames {
+            let ramp = (i % 1000) as f32 / 1000.0;
+            assert!(stems.push_frame(&[ramp, 0.0, -ramp], 0.25, 0.0));
+        }
+        let dir = temp_dir("stems");
+        let count = finish_and_wait(stems, &dir, &written);
+
+        let loop_0 = read_stem(&dir.join("stem_loop_0.wav"));
+        let loop_2 = read_stem(&dir.join("stem_loop_2.wav"));
+        let synth = read_stem(&dir.join("stem_synth.wav"));
+        let skipped = ["stem_loop_1.wav", "stem_sampler.wav"].map(|name| dir.join(name).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(count, 3);
+        assert_eq!(skipped, [false, false]);
+        assert_eq!(loop_0.len(), frames);
+        assert_eq!(loop_0[frames - 1], ((frames - 1) % 1000) as f32 / 1000.0);
+        assert!(loop_0.iter().zip(&loop_2).all(|(a, b)| *a == -*b));
+        assert!(synth.iter().all(|&s| s == 0.25));
+    }
+
+    #[test]
+    fn silent_recording_saves_nothing() {
+        let written = Arc::new(AtomicU32::new(NO_STEM_RESULT));
+        let mut stems = StemRecording::start(2, SAMPLE_RATE, written.clone());
+        for _ in 0..1000 {
+            stems.push_frame(&[0.0, 0.0], 0.0, 0.0);
+        }
+        let dir = temp_dir("silent-stems");
+        assert_eq!(finish_and_wait(stems, &dir, &written), 0);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn stem_recording_stops_at_the_length_cap() {
+        let written = Arc::new(AtomicU32::new(NO_STEM_RESULT));
+        // At 1 Hz the ten minute cap is 600 frames.
+        let mut stems = StemRecording::start(1, 1.0, written.clone());
+        assert!((0..600).all(|_| stems.push_frame(&[0.5], 0.0, 0.0)));
+        assert!(!stems.push_frame(&[0.5], 0.0, 0.0));
+        assert!(stems.limit_reached);
+        let dir = temp_dir("capped-stems");
+        assert_eq!(finish_and_wait(stems, &dir, &written), 1);
+        let loop_0 = read_stem(&dir.join("stem_loop_0.wav"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loop_0.len(), 600);
+    }
+
+    fn wait_until_written(is_writing: &AtomicBool) {
+        for _ in 0..500 {
+            if !is_writing.load(Ordering::Relaxed) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("the loop snapshot writer never finished");
+    }
+
+    #[test]
+    fn loop_snapshot_is_copied_in_chunks_and_replaces_the_old_session() {
+        let loops = [
+            (0..LOOP_SNAPSHOT_CHUNK * 2 + 10).map(|i| (i % 100) as f32 / 100.0).collect(),
+            Vec::new(),
+            vec![-0.5; 300],
+        ];
+        let session_dir = temp_dir("loop-snapshot");
+        let staging_dir = session_dir.with_extension("tmp");
+        std::fs::create_dir_all(&session_dir).unwrap();
+        std::fs::write(session_dir.join("loop_1.wav"), "cleared since").unwrap();
+        std::fs::create_dir_all(&staging_dir).unwrap();
+
+        let is_writing = Arc::new(AtomicBool::new(false));
+        let lens = loops.each_ref().map(Vec::len);
+        let mut snapshot = LoopSnapshot::start(
+            &lens,
+            staging_dir.clone(),
+            session_dir.clone(),
+            SAMPLE_RATE,
+            RecordingFormat::Float32,
+            is_writing.clone(),
+        );
+        assert!(is_writing.load(Ordering::Relaxed));
+        let callbacks = (1..).find(|_| snapshot.copy_chunk(|i| &loops[i])).unwrap();
+        drop(snapshot);
+        wait_until_written(&is_writing);
+
+        let read_mono = |name: &str| -> Vec<f32> {
+            let mut reader = hound::WavReader::open(session_dir.join(name)).unwrap();
+            reader.samples::<f32>().map(Result::unwrap).collect()
+        };
+        let loop_0 = read_mono("loop_0.wav");
+        let loop_2 = read_mono("loop_2.wav");
+        let stale_loop_1 = session_dir.join("loop_1.wav").exists();
+        let leftovers = [staging_dir.exists(), session_dir.with_extension("old").exists()];
+        std::fs::remove_dir_all(&session_dir).unwrap();
+
+        assert_eq!(callbacks, 3);
+        assert_eq!(loop_0, loops[0]);
+        assert_eq!(loop_2, loops[2]);
+        assert!(!stale_loop_1);
+        assert_eq!(leftovers, [false, false]);
+    }
+
+    #[test]
+    fn loop_snapshot_of_a_loop_that_changed_length_saves_nothing() {
+        let session_dir = temp_dir("changed-loop-snapshot");
+        let staging_dir = session_dir.with_extension("tmp");
+        std::fs::create_dir_all(&staging_dir).unwrap();
+        let is_writing = Arc::new(AtomicBool::new(false));
+        let mut snapshot = LoopSnapshot::start(
+            &[100],
+            staging_dir.clone(),
+            session_dir.clone(),
+            SAMPLE_RATE,
+            RecordingFormat::Float32,
+            is_writing.clone(),
+        );
+        // Re-recorded longer between the request and the copy.
+        let longer = vec![0.1; 200];
+        assert!(snapshot.copy_chunk(|_| &longer));
+        drop(snapshot);
+        wait_until_written(&is_writing);
+        let replaced = session_dir.exists();
+        std::fs::remove_dir_all(&staging_dir).unwrap();
+        assert!(!replaced);
+    }
+
+    /// The highest peak of `output` between samples once the limiter has settled. For a
+    /// sine at a quarter of the sample rate that is the length of each pair of neighbours.
+    fn settled_quarter_rate_peak(output: &[[f32; 2]]) -> f32 {
+        output[output.len() / 2..]
+            .windows(2)
+            .map(|w| w[0][0].hypot(w[1][0]))
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn true_peak_limiting_keeps_inter_sample_peaks_under_the_threshold() {
+        use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+        let threshold = 0.8;
+        let release_coeffs = (-1.0 / (0.05 * SAMPLE_RATE)).exp();
+        // A full-scale sine at fs/4, sampled 45 degrees off its peaks: every sample reads
+        // 0.707 while the waveform between them reaches 1.0.
+        let input: Vec<[f32; 2]> = (0..4800)
+            .map(|n| {
+                let s = (FRAC_PI_2 * n as f32 + FRAC_PI_4).sin();
+                [s, s]
+            })
+            .collect();
+        let limit = |true_peak: bool| -> Vec<[f32; 2]> {
+            let mut limiter = Limiter::new(SAMPLE_RATE, Arc::new(AtomicU32::new(0)));
+            limiter.set_true_peak(true_peak);
+            input.iter().map(|&frame| limiter.process(frame, threshold, release_coeffs)).collect()
+        };
+
+        assert!(input.iter().all(|frame| frame[0].abs() < threshold));
+        assert!(settled_quarter_rate_peak(&limit(false)) > 0.99, "sample peaks pass untouched");
+        let true_peak = settled_quarter_rate_peak(&limit(true));
+        assert!(true_peak <= threshold * 1.01, "inter-sample peak of {} got through", true_peak);
+        assert!(true_peak > threshold * 0.95, "limited harder than needed: {}", true_peak);
+    }
+
+    /// A ramp from 0.5 down to -0.5, so the loop jumps by a full unit at its wrap point.
+    fn stepped_loop() -> Vec<f32> {
+        (0..1200)
+            .map(|i| 0.5 - i as f32 / 1200.0 + (i % 7) as f32 * 0.01)
+            .collect()
+    }
+
+    /// The largest jump between neighbouring samples, including the wrap back to the start.
+    fn largest_step(audio: &[f32]) -> f32 {
+        audio
+            .windows(2)
+            .chain(std::iter::once(&[audio[audio.len() - 1], audio[0]][..]))
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn seam_crossfade_smooths_the_wrap() {
+        let mut audio = stepped_loop();
+        assert!(largest_step(&audio) > 0.9);
+        let mut seam = LoopSeam::new(100);
+        seam.crossfade(&mut audio, 100);
+        assert!(largest_step(&audio) < 0.1);
+
+        let mut short = vec![0.5; 250];
+        seam.crossfade(&mut short, 100);
+        assert!(short.iter().all(|&s| s == 0.5), "too short for three fades");
+        assert!(!seam.refade(&mut short));
+    }
+
+    #[test]
+    fn seam_refade_after_overdub_matches_a_single_fade() {
+        let overdub = |i: usize| ((i as f32) * 0.05).sin() * 0.2;
+        let mut audio = stepped_loop();
+        let mut seam = LoopSeam::new(100);
+        seam.crossfade(&mut audio, 100);
+        assert!(!seam.refade(&mut audio), "nothing to redo without an overdub");
+
+        // Two passes over the whole loop, as an overdub held across a wrap writes them.
+        for _ in 0..2 {
+            for (i, sample) in audio.iter_mut().enumerate() {
+                *sample = (*sample + overdub(i)).clamp(-1.0, 1.0);
+                seam.overdub(i, overdub(i));
+            }
+        }
+        assert!(seam.is_dirty());
+        assert!(seam.refade(&mut audio));
+        assert!(!seam.refade(&mut audio));
+
+        let mut expected: Vec<f32> = stepped_loop()
+            .iter()
+            .enumerate()
+            .map(|(i, &s)| (s + overdub(i) * 2.0).clamp(-1.0, 1.0))
+            .collect();
+        LoopSeam::new(100).crossfade(&mut expected, 100);
+        for (i, (got, want)) in audio.iter().zip(&expected).enumerate() {
+            assert!((got - want).abs() < 1e-5, "sample {i}: {got} vs {want}");
+        }
+    }
+}
@@ -1,6 +1,7 @@
 // FILE: src\audio_engine\looper_track.rs
 // ======================================
 
+use super::helpers::LoopSeam;
 use crate::looper::SharedLooperState;
 use std::collections::BTreeSet;
 
@@ -17,10 +18,14 @@ pub struct Looper {
     pub peak_since_high_res_update: f32,
     pub samples_since_visual_update: usize,
     pub dirty_summary_chunks: BTreeSet<usize>,
+    /// Smoothed mute/solo gain, ramped towards 0 or 1 so muting doesn't click.
+    pub audible_gain: f32,
+    /// The loop's un-faded seam, so an overdub pass can be faded once when it ends.
+    pub seam: LoopSeam,
 }
 
 impl Looper {
-    pub fn new(shared_state: SharedLooperState) -> Self {
+    pub fn new(shared_state: SharedLooperState, max_seam_fade_len: usize) -> Self {
         Self {
             shared_state,
             audio: Vec::new(),
@@ -34,6 +39,8 @@ impl Looper {
             peak_since_high_res_update: 0.0,
             samples_since_visual_update: 0,
             dirty_summary_chunks: BTreeSet::new(),
+            audible_gain: 1.0,
+            seam: LoopSeam::new(max_seam_fade_len),
         }
     }
 }
\ No newline at end of file
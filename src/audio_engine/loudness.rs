@@ -0,0 +1,298 @@
+// FILE: src\audio_engine\loudness.rs
+// ==================================
+
+//! Loudness (LUFS) metering following ITU-R BS.1770 and EBU R 128: K-weighting,
+//! momentary (400 ms) and short-term (3 s) windows updated every 100 ms, and
+//! integrated loudness over 400 ms blocks with 75% overlap, an absolute gate at
+//! -70 LUFS and a relative gate 10 LU below the ungated level.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Stored loudness is `(lufs + LUFS_OFFSET) * LUFS_SCALER`; 0 means no reading yet.
+pub const LUFS_OFFSET: f32 = 100.0;
+pub const LUFS_SCALER: f32 = 1_000.0;
+
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+const HISTOGRAM_MAX_LUFS: f64 = 10.0;
+const HISTOGRAM_BINS_PER_LU: f64 = 10.0;
+const HISTOGRAM_BINS: usize =
+    ((HISTOGRAM_MAX_LUFS - ABSOLUTE_GATE_LUFS) * HISTOGRAM_BINS_PER_LU) as usize;
+/// A 400 ms gating block (and the momentary window) is made of four 100 ms hops.
+const SUB_BLOCKS_PER_BLOCK: usize = 4;
+/// The 3 s short-term window in 100 ms hops.
+const SUB_BLOCKS_PER_SHORT_TERM: usize = 30;
+
+/// Converts a stored loudness value back to LUFS, or `None` before the first reading.
+pub fn stored_to_lufs(stored: u32) -> Option<f32> {
+    (stored > 0).then(|| stored as f32 / LUFS_SCALER - LUFS_OFFSET)
+}
+
+fn lufs_to_stored(lufs: f64) -> u32 {
+    ((lufs as f32 + LUFS_OFFSET).max(0.0) * LUFS_SCALER) as u32
+}
+
+/// Block loudness from the summed mean square of the left and right channels, both
+/// weighted 1.
+fn block_loudness(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.max(1e-20).log10()
+}
+
+/// A direct form I biquad run in f64, as the K-weighting shelf has a lot of gain near Nyquist.
+#[derive(Default, Clone, Copy)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    x1: f64,
+    x2: f64,
+    y1: f64,
+    y2: f64,
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
+            - self.a1 * self.y1
+            - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+
+    fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.x2 = 0.0;
+        self.y1 = 0.0;
+        self.y2 = 0.0;
+    }
+
+    /// Stage 1 of the K-weighting: a high shelf modelling the acoustic effect of the head.
+    fn k_weighting_shelf(sample_rate: f64) -> Self {
+        let f0 = 1681.974450955533;
+        let gain_db = 3.999843853973347;
+        let q = 0.7071752369554196;
+        let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+        let vh = 10.0_f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        Self {
+            b0: (vh + vb * k / q + k * k) / a0,
+            b1: 2.0 * (k * k - vh) / a0,
+            b2: (vh - vb * k / q + k * k) / a0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+            ..Default::default()
+        }
+    }
+
+    /// Stage 2 of the K-weighting: the "RLB" high-pass.
+    fn k_weighting_highpass(sample_rate: f64) -> Self {
+        let f0 = 38.13547087602444;
+        let q = 0.5003270373238773;
+        let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        Self {
+            b0: 1.0,
+            b1: -2.0,
+            b2: 1.0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+            ..Default::default()
+        }
+    }
+}
+
+/// The published loudness values, each readable with `stored_to_lufs`.
+#[derive(Clone, Default)]
+pub struct LoudnessReadings {
+    pub momentary: Arc<AtomicU32>,
+    pub short_term: Arc<AtomicU32>,
+    pub integrated: Arc<AtomicU32>,
+}
+
+impl LoudnessReadings {
+    fn clear(&self) {
+        self.momentary.store(0, Ordering::Relaxed);
+        self.short_term.store(0, Ordering::Relaxed);
+        self.integrated.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Measures the loudness of the master output and publishes it to atomics.
+pub struct LoudnessMeter {
+    shelf: [Biquad; 2],
+    highpass: [Biquad; 2],
+    hop_len: usize,
+    hop_position: usize,
+    hop_energy: f64,
+    /// The most recent hop energies, enough for the short-term window.
+    sub_block_energies: [f64; SUB_BLOCKS_PER_SHORT_TERM],
+    sub_blocks_seen: usize,
+    // Gated block statistics, bucketed by loudness so memory stays fixed however long
+    // the measurement runs.
+    histogram_counts: Vec<u32>,
+    histogram_energies: Vec<f64>,
+    pub readings: LoudnessReadings,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: f32, readings: LoudnessReadings) -> Self {
+        let sample_rate = sample_rate as f64;
+        Self {
+            shelf: [Biquad::k_weighting_shelf(sample_rate); 2],
+            highpass: [Biquad::k_weighting_highpass(sample_rate); 2],
+            hop_len: ((sample_rate * 0.1).round() as usize).max(1),
+            hop_position: 0,
+            hop_energy: 0.0,
+            sub_block_energies: [0.0; SUB_BLOCKS_PER_SHORT_TERM],
+            sub_blocks_seen: 0,
+            histogram_counts: vec![0; HISTOGRAM_BINS],
+            histogram_energies: vec![0.0; HISTOGRAM_BINS],
+            readings,
+        }
+    }
+
+    /// Starts a fresh measurement.
+    pub fn reset(&mut self) {
+        self.shelf.iter_mut().for_each(Biquad::reset);
+        self.highpass.iter_mut().for_each(Biquad::reset);
+        self.hop_position = 0;
+        self.hop_energy = 0.0;
+        self.sub_block_energies = [0.0; SUB_BLOCKS_PER_SHORT_TERM];
+        self.sub_blocks_seen = 0;
+        self.histogram_counts.fill(0);
+        self.histogram_energies.fill(0.0);
+        self.readings.clear();
+    }
+
+    pub fn process(&mut self, frame: [f32; 2]) {
+        for (channel, &sample) in frame.iter().enumerate() {
+            let shelved = self.shelf[channel].process(sample as f64);
+            let weighted = self.highpass[channel].process(shelved);
+            self.hop_energy += weighted * weighted;
+        }
+        self.hop_position += 1;
+        if self.hop_position < self.hop_len {
+            return;
+        }
+
+        self.sub_block_energies[self.sub_blocks_seen % SUB_BLOCKS_PER_SHORT_TERM] =
+            self.hop_energy;
+        self.sub_blocks_seen += 1;
+        self.hop_position = 0;
+        self.hop_energy = 0.0;
+        if let Some(block_mean_square) = self.window_mean_square(SUB_BLOCKS_PER_BLOCK) {
+            self.readings
+                .momentary
+                .store(lufs_to_stored(block_loudness(block_mean_square)), Ordering::Relaxed);
+            self.add_block(block_mean_square);
+        }
+        if let Some(mean_square) = self.window_mean_square(SUB_BLOCKS_PER_SHORT_TERM) {
+            self.readings
+                .short_term
+                .store(lufs_to_stored(block_loudness(mean_square)), Ordering::Relaxed);
+        }
+    }
+
+    /// Mean square over the last `hops` hops, once that many have been measured.
+    fn window_mean_square(&self, hops: usize) -> Option<f64> {
+        if self.sub_blocks_seen < hops {
+            return None;
+        }
+        let energy: f64 = (1..=hops)
+            .map(|back| {
+                let index = (self.sub_blocks_seen - back) % SUB_BLOCKS_PER_SHORT_TERM;
+                self.sub_block_energies[index]
+            })
+            .sum();
+        Some(energy / (self.hop_len * hops) as f64)
+    }
+
+    fn add_block(&mut self, mean_square: f64) {
+        let loudness = block_loudness(mean_square);
+        if loudness <= ABSOLUTE_GATE_LUFS {
+            return;
+        }
+        let bin = (((loudness - ABSOLUTE_GATE_LUFS) * HISTOGRAM_BINS_PER_LU) as usize)
+            .min(HISTOGRAM_BINS - 1);
+        self.histogram_counts[bin] += 1;
+        self.histogram_energies[bin] += mean_square;
+
+        if let Some(integrated) = self.integrated() {
+            self.readings
+                .integrated
+                .store(lufs_to_stored(integrated), Ordering::Relaxed);
+        }
+    }
+
+    /// Integrated loudness over every block that passes both gates.
+    fn integrated(&self) -> Option<f64> {
+        let gated_mean = |first_bin: usize| {
+            let count: u32 = self.histogram_counts[first_bin..].iter().sum();
+            let energy: f64 = self.histogram_energies[first_bin..].iter().sum();
+            (count > 0).then(|| energy / count as f64)
+        };
+        let relative_gate = block_loudness(gated_mean(0)?) + RELATIVE_GATE_LU;
+        let first_bin = ((relative_gate - ABSOLUTE_GATE_LUFS) * HISTOGRAM_BINS_PER_LU)
+            .ceil()
+            .max(0.0) as usize;
+        gated_mean(first_bin.min(HISTOGRAM_BINS - 1)).map(block_loudness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    /// Measures four seconds of a full-scale 1 kHz sine with the given channel levels.
+    fn measure_sine(left: f32, right: f32) -> LoudnessReadings {
+        let readings = LoudnessReadings::default();
+        let mut meter = LoudnessMeter::new(SAMPLE_RATE, readings.clone());
+        for i in 0..(SAMPLE_RATE as usize * 4) {
+            let phase = 2.0 * std::f32::consts::PI * 1_000.0 * i as f32 / SAMPLE_RATE;
+            let s = phase.sin();
+            meter.process([s * left, s * right]);
+        }
+        readings
+    }
+
+    fn read(value: &AtomicU32) -> f32 {
+        stored_to_lufs(value.load(Ordering::Relaxed)).expect("no reading")
+    }
+
+    #[test]
+    fn full_scale_stereo_sine_reads_zero_lufs() {
+        let readings = measure_sine(1.0, 1.0);
+        for value in [&readings.momentary, &readings.short_term, &readings.integrated] {
+            let lufs = read(value);
+            assert!(lufs.abs() < 0.1, "got {lufs} LUFS");
+        }
+    }
+
+    #[test]
+    fn full_scale_sine_in_one_channel_reads_minus_three_lufs() {
+        let lufs = read(&measure_sine(1.0, 0.0).integrated);
+        assert!((lufs + 3.01).abs() < 0.1, "got {lufs} LUFS");
+    }
+
+    #[test]
+    fn reference_level_sine_reads_minus_twenty_three_lufs() {
+        let level = 10.0_f32.powf(-23.0 / 20.0);
+        let lufs = read(&measure_sine(level, level).integrated);
+        assert!((lufs + 23.0).abs() < 0.1, "got {lufs} LUFS");
+    }
+
+    #[test]
+    fn silence_is_gated_out() {
+        let readings = measure_sine(0.0, 0.0);
+        assert_eq!(stored_to_lufs(readings.integrated.load(Ordering::Relaxed)), None);
+    }
+}
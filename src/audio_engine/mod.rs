@@ -7,19 +7,38 @@ mod command;
 mod fx_rack;
 mod helpers;
 mod looper_track;
+mod loudness;
 mod sampler_pad;
+mod spectrum;
 
 // --- 2. Re-export public types to maintain the external API ---
 pub use command::{AudioCommand, MidiMessage};
+pub use helpers::{
+    write_wav_file, LoopSnapshot, StemRecording, MAX_LIMITER_LOOKAHEAD_MS, MAX_LOOP_CROSSFADE_MS,
+    NO_STEM_RESULT, TRUE_PEAK_LATENCY_SAMPLES,
+};
+pub use loudness::{stored_to_lufs, LoudnessReadings};
+pub use spectrum::SPECTRUM_BANDS;
 
 use crate::fx;
-use crate::looper::{LooperState, SharedLooperState, NUM_LOOPERS, WAVEFORM_DOWNSAMPLE_SIZE};
-use crate::mixer::MixerState;
-use crate::sampler::SamplerPadFxSettings;
+use crate::fx_components::{
+    envelope_follower, DspComponent, EnvelopeFollower, EnvelopeFollowerParams,
+};
+use crate::looper::{
+    next_looper_state, LooperEvent, LooperState, LooperTransition, SharedLooperState, NUM_LOOPERS,
+    WAVEFORM_DOWNSAMPLE_SIZE,
+};
+use crate::mixer::{pan_gains, MixerState, MAX_NUDGE_SAMPLES, NUM_SEND_BUSES, NUM_SUB_BUSES};
+use crate::sampler::{LaunchQuantize, PadGrooveSettings, SamplerPadFxSettings};
+use crate::settings::OutputClipMode;
 use crate::synth::{
     Engine, EngineWithVolumeAndPeak, LfoRateMode, Synth, SynthEngine,
 };
 use anyhow::Result;
+use rand::rngs::StdRng;
+use rodio::{Decoder, Source};
+use rand::SeedableRng;
+use rayon::prelude::*;
 use ringbuf::{HeapConsumer, HeapProducer};
 use rubato::{
     Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
@@ -38,15 +57,40 @@ use std::time::Instant;
 // --- 3. Import the private structs from our new sub-modules ---
 use self::atmo::AtmoEngine;
 use self::fx_rack::FxRack;
-use self::helpers::{trim_silence, write_wav_file, Limiter, Metronome};
+use self::helpers::{
+    apply_tpdf_dither, normalize_peak, read_samples_as_f32, trim_silence, write_mono_wav_file,
+    write_stereo_wav_file, AuditionPlayer, Limiter, Metronome,
+};
+use self::loudness::LoudnessMeter;
+use self::spectrum::SpectrumAnalyzer;
 use self::looper_track::Looper;
-use self::sampler_pad::SamplerPad;
+use self::sampler_pad::{groove_offset, SamplerPad};
 
 const LOOPER_ARM_THRESHOLD: f32 = 0.05;
 const HIGH_RES_CHUNK_SIZE: usize = 256;
+/// Upper bound of the input safety buffer set in the options.
+pub const MAX_INPUT_LATENCY_MS: f32 = 50.0;
+/// Length of the gain ramp when a track is muted, unmuted, soloed or unsoloed.
+const MUTE_FADE_MS: f32 = 10.0;
 const PARAM_SCALER: f32 = 1_000_000.0;
+
+/// Converts a stored master correlation back to -1..+1.
+pub fn stored_to_correlation(stored: u32) -> f32 {
+    stored as f32 / PARAM_SCALER - 1.0
+}
 // NEW: Define a safe maximum buffer size to pre-allocate memory.
 const MAX_BUFFER_SIZE: usize = 2048;
+/// Default length of the crossfade between the outgoing and incoming engine on a type change.
+pub const DEFAULT_ENGINE_SWAP_FADE_MS: f32 = 20.0;
+/// Upper bound of the engine swap crossfade set in the options.
+pub const MAX_ENGINE_SWAP_FADE_MS: f32 = 500.0;
+
+/// An engine waiting for the running swap crossfade on its slot to finish.
+struct PendingEngineSwap {
+    engine: SynthEngine,
+    volume: Arc<AtomicU32>,
+    peak_meter: Arc<AtomicU32>,
+}
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum TransportState {
@@ -62,8 +106,13 @@ pub struct AudioEngine {
     pub master_looper_index: Arc<AtomicUsize>,
     metronome: Metronome,
     metronome_playhead: usize,
+    audition: AuditionPlayer,
+    /// Whether a library sample preview is playing.
+    pub audition_is_playing: Arc<AtomicBool>,
     pub synth: Synth,
     sampler_pads: Vec<SamplerPad>,
+    pad_groove: PadGrooveSettings,
+    groove_rng: StdRng,
     atmo_engine: AtmoEngine,
     pub synth_is_active: Arc<AtomicBool>,
     pub audio_input_is_armed: Arc<AtomicBool>,
@@ -73,6 +122,13 @@ pub struct AudioEngine {
     pub transport_playhead: Arc<AtomicUsize>,
     pub transport_len_samples: Arc<AtomicUsize>,
     pub tempo_multiplier: Arc<AtomicU32>,
+    /// Mirrors `ArmPolicy::Exclusive`: arming a looper disarms any other armed looper.
+    pub exclusive_arm: Arc<AtomicBool>,
+    /// When set, an established transport length survives clearing loopers.
+    pub tempo_locked: Arc<AtomicBool>,
+    /// Troubleshooting mode: synth, sampler, atmosphere, metronome and every FX rack are
+    /// skipped so only the raw loopers and input reach the output.
+    pub safe_mode: Arc<AtomicBool>,
     pub transport_is_playing: Arc<AtomicBool>,
     transport_state: TransportState,
     sample_rate: f32,
@@ -91,6 +147,16 @@ pub struct AudioEngine {
     limiter_release_ms: Arc<AtomicU32>,
     limiter_release_sync_rate: Arc<AtomicU32>,
     limiter: Limiter,
+    loudness_meter: LoudnessMeter,
+    /// Momentary, short-term and integrated loudness of the output.
+    pub loudness: LoudnessReadings,
+    pub loudness_meter_enabled: Arc<AtomicBool>,
+    spectrum_analyzer: SpectrumAnalyzer,
+    /// L/R correlation of the master over the last buffer, readable with
+    /// `stored_to_correlation`. Silence reads as 0.
+    pub master_correlation: Arc<AtomicU32>,
+    /// Normalized 0..1 level of each master spectrum band, `SPECTRUM_BANDS` long.
+    pub master_spectrum: Arc<RwLock<Vec<f32>>>,
     master_peak_meter: Arc<AtomicU32>,
     synth_master_volume: Arc<AtomicU32>,
     synth_master_peak_meter: Arc<AtomicU32>,
@@ -99,7 +165,21 @@ pub struct AudioEngine {
     engine_volumes: [Arc<AtomicU32>; 2],
     engine_peak_meters: [Arc<AtomicU32>; 2],
     bpm_rounding: bool,
+    /// Length of the crossfade applied at a loop's wrap point when it is recorded or
+    /// overdubbed; 0 disables it.
+    loop_crossfade_samples: usize,
+    /// Keeps the synth, sampler and monitored input audible while the transport is paused.
+    /// Loopers are silent when paused either way.
+    monitor_while_stopped: bool,
+    /// Restart every tempo-synced synth LFO on the transport downbeat.
+    lfo_transport_sync: bool,
+    output_clip_mode: OutputClipMode,
     output_recording_buffer: Option<Vec<f32>>,
+    stem_recording: Option<StemRecording>,
+    /// Set when a stem recording hits its length cap and stops capturing.
+    pub stem_recording_limit_reached: Arc<AtomicBool>,
+    /// Autosave copy of the loops, filled a chunk per buffer.
+    loop_snapshot: Option<LoopSnapshot>,
     pub midi_cc_values: Arc<[[AtomicU32; 128]; 16]>,
     pub should_toggle_record: Arc<AtomicBool>,
     // MODIFIED: Pre-allocated buffers.
@@ -107,6 +187,41 @@ pub struct AudioEngine {
     engine_1_buffer: Vec<f32>,
     atmo_buffer: Vec<f32>,
     atmo_stereo_buffer: Vec<[f32; 2]>,
+    engine_fade_buffer: Vec<f32>,
+    /// The master output handed back by `process_buffer`.
+    output_buffer: Vec<[f32; 2]>,
+    // Per-sample results of the first pass of `process_buffer`, mixed in the second.
+    /// What each track read from its loop, run through its FX rack in place.
+    looper_buffers: Vec<Vec<f32>>,
+    /// Bit `id` is set where track `id` was playing.
+    looper_active_masks: Vec<u16>,
+    synth_mix_buffer: Vec<f32>,
+    sampler_mix_buffer: Vec<f32>,
+    /// Tracks panned straight to the master, as left/right frames.
+    looper_output_buffer: Vec<[f32; 2]>,
+    /// What each sub-bus and send return is fed, run through its rack once per buffer.
+    bus_buffers: Vec<Vec<f32>>,
+    send_buffers: Vec<Vec<f32>>,
+    metronome_buffer: Vec<f32>,
+    // The mic reaches the engine late by the input safety buffer. The synth and sampler are
+    // delayed by the same amount on their way into the loopers so one shift realigns both.
+    internal_record_delay: Vec<f32>,
+    internal_record_delay_pos: usize,
+    /// `input_latency_samples` as it was when the running takes started, kept until every
+    /// looper stops recording and overdubbing so a latency change can't shift a take.
+    latched_input_latency_samples: Option<usize>,
+    // --- Sidechain ducking: the attack/release come from the mixer's sidechain settings ---
+    sidechain_follower: EnvelopeFollower,
+    sidechain_follower_params: EnvelopeFollowerParams,
+    sidechain_envelope: f32,
+
+    // --- Engine Swap Crossfade ---
+    outgoing_engines: [Option<SynthEngine>; 2],
+    engine_swap_fade_len: usize,
+    engine_swap_fade_pos: [usize; 2],
+    /// A swap that arrived mid-crossfade. It starts once the running fade completes, so the
+    /// engine still fading out is never cut.
+    pending_engine_swaps: [Option<PendingEngineSwap>; 2],
 
     // --- FX Rack Storage ---
     fx_wet_dry_mixes: BTreeMap<fx::InsertionPoint, Arc<AtomicU32>>,
@@ -115,6 +230,8 @@ pub struct AudioEngine {
     sampler_fx_rack: Option<FxRack>,
     input_fx_rack: Option<FxRack>,
     master_fx_rack: Option<FxRack>,
+    bus_fx_racks: [Option<FxRack>; NUM_SUB_BUSES],
+    send_fx_racks: [Option<FxRack>; NUM_SEND_BUSES],
     atmo_fx_rack: Option<FxRack>,
 }
 
@@ -160,9 +277,10 @@ impl AudioEngine {
     ) -> (Self, Vec<SharedLooperState>) {
         let looper_states: Vec<SharedLooperState> =
             (0..NUM_LOOPERS).map(|_| SharedLooperState::new()).collect();
+        let max_seam_fade_len = (MAX_LOOP_CROSSFADE_MS * sample_rate / 1000.0).ceil() as usize;
         let loopers: Vec<Looper> = looper_states
             .iter()
-            .map(|s| Looper::new(s.clone()))
+            .map(|s| Looper::new(s.clone(), max_seam_fade_len))
             .collect();
 
         // Separate the Arcs from the parameters before moving engine_params
@@ -172,6 +290,10 @@ impl AudioEngine {
         let synth = Synth::new(sample_rate, engine_params);
         let sampler_pads = (0..16).map(|_| SamplerPad::new(sample_rate)).collect();
         let atmo_engine = AtmoEngine::new(sample_rate, atmo_xy_coords, atmo_layer_volumes);
+        let loudness = LoudnessReadings::default();
+        let master_spectrum = Arc::new(RwLock::new(vec![0.0; SPECTRUM_BANDS]));
+        let sidechain_follower_params = EnvelopeFollowerParams::default();
+        let audition_is_playing = Arc::new(AtomicBool::new(false));
 
         let engine = Self {
             command_consumer,
@@ -181,8 +303,12 @@ impl AudioEngine {
             master_looper_index: Arc::new(AtomicUsize::new(usize::MAX)),
             metronome: Metronome::new(sample_rate),
             metronome_playhead: 0,
+            audition: AuditionPlayer::new(audition_is_playing.clone()),
+            audition_is_playing,
             synth,
             sampler_pads,
+            pad_groove: PadGrooveSettings::default(),
+            groove_rng: StdRng::seed_from_u64(PadGrooveSettings::default().seed),
             atmo_engine,
             synth_is_active: Arc::new(AtomicBool::new(false)),
             audio_input_is_armed,
@@ -192,6 +318,9 @@ impl AudioEngine {
             transport_playhead: Arc::new(AtomicUsize::new(0)),
             transport_len_samples: Arc::new(AtomicUsize::new(0)),
             tempo_multiplier,
+            exclusive_arm: Arc::new(AtomicBool::new(true)),
+            tempo_locked: Arc::new(AtomicBool::new(false)),
+            safe_mode: Arc::new(AtomicBool::new(false)),
             transport_is_playing,
             transport_state: TransportState::Playing,
             sample_rate,
@@ -210,6 +339,12 @@ impl AudioEngine {
             limiter_release_ms,
             limiter_release_sync_rate,
             limiter: Limiter::new(sample_rate, gain_reduction_db),
+            loudness_meter: LoudnessMeter::new(sample_rate, loudness.clone()),
+            loudness,
+            loudness_meter_enabled: Arc::new(AtomicBool::new(false)),
+            master_correlation: Arc::new(AtomicU32::new(PARAM_SCALER as u32)),
+            spectrum_analyzer: SpectrumAnalyzer::new(sample_rate, master_spectrum.clone()),
+            master_spectrum,
             master_peak_meter,
             synth_master_volume,
             synth_master_peak_meter,
@@ -218,7 +353,14 @@ impl AudioEngine {
             engine_volumes,
             engine_peak_meters,
             bpm_rounding,
+            loop_crossfade_samples: 0,
+            monitor_while_stopped: true,
+            lfo_transport_sync: false,
+            output_clip_mode: OutputClipMode::default(),
             output_recording_buffer: None,
+            stem_recording: None,
+            stem_recording_limit_reached: Arc::new(AtomicBool::new(false)),
+            loop_snapshot: None,
             midi_cc_values,
             should_toggle_record,
             // MODIFIED: Initialize buffers to their maximum safe size.
@@ -226,12 +368,40 @@ impl AudioEngine {
             engine_1_buffer: vec![0.0; MAX_BUFFER_SIZE],
             atmo_buffer: vec![0.0; MAX_BUFFER_SIZE],
             atmo_stereo_buffer: vec![[0.0; 2]; MAX_BUFFER_SIZE],
+            output_buffer: vec![[0.0; 2]; MAX_BUFFER_SIZE],
+            looper_buffers: vec![vec![0.0; MAX_BUFFER_SIZE]; NUM_LOOPERS],
+            looper_active_masks: vec![0; MAX_BUFFER_SIZE],
+            synth_mix_buffer: vec![0.0; MAX_BUFFER_SIZE],
+            sampler_mix_buffer: vec![0.0; MAX_BUFFER_SIZE],
+            looper_output_buffer: vec![[0.0; 2]; MAX_BUFFER_SIZE],
+            bus_buffers: vec![vec![0.0; MAX_BUFFER_SIZE]; NUM_SUB_BUSES],
+            send_buffers: vec![vec![0.0; MAX_BUFFER_SIZE]; NUM_SEND_BUSES],
+            metronome_buffer: vec![0.0; MAX_BUFFER_SIZE],
+            engine_fade_buffer: vec![0.0; MAX_BUFFER_SIZE],
+            internal_record_delay: vec![
+                0.0;
+                (MAX_INPUT_LATENCY_MS / 1000.0 * sample_rate).ceil() as usize
+                    + Limiter::max_latency_samples(sample_rate)
+                    + 1
+            ],
+            internal_record_delay_pos: 0,
+            latched_input_latency_samples: None,
+            sidechain_follower: EnvelopeFollower::new(sample_rate, sidechain_follower_params.clone()),
+            sidechain_follower_params,
+            sidechain_envelope: 0.0,
+            outgoing_engines: [None, None],
+            engine_swap_fade_len: ((DEFAULT_ENGINE_SWAP_FADE_MS * 0.001 * sample_rate) as usize)
+                .max(1),
+            engine_swap_fade_pos: [0; 2],
+            pending_engine_swaps: [None, None],
             fx_wet_dry_mixes,
             looper_fx_racks: Default::default(),
             synth_fx_racks: Default::default(),
             sampler_fx_rack: None,
             input_fx_rack: None,
             master_fx_rack: None,
+            bus_fx_racks: Default::default(),
+            send_fx_racks: Default::default(),
             atmo_fx_rack: None,
         };
 
@@ -258,6 +428,17 @@ impl AudioEngine {
                         mixer_state.metronome.is_muted = !mixer_state.metronome.is_muted;
                     }
                 }
+                AudioCommand::SetMetronome {
+                    enabled,
+                    volume,
+                    accent_first,
+                } => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        mixer_state.metronome.is_muted = !enabled;
+                        mixer_state.metronome.volume = volume;
+                        mixer_state.metronome.accent_first = accent_first;
+                    }
+                }
                 AudioCommand::SetMetronomeVolume(vol) => {
                     if let Ok(mut mixer_state) = self.track_mixer_state.write() {
                         mixer_state.metronome.volume = vol;
@@ -299,6 +480,8 @@ impl AudioEngine {
                             fx::InsertionPoint::Input => self.input_fx_rack = Some(new_rack),
                             fx::InsertionPoint::Master => self.master_fx_rack = Some(new_rack),
                             fx::InsertionPoint::Atmo => self.atmo_fx_rack = Some(new_rack),
+                            fx::InsertionPoint::Bus(i) => self.bus_fx_racks[i] = Some(new_rack),
+                            fx::InsertionPoint::Send(i) => self.send_fx_racks[i] = Some(new_rack),
                         }
                     }
                 }
@@ -309,6 +492,8 @@ impl AudioEngine {
                     fx::InsertionPoint::Input => self.input_fx_rack = None,
                     fx::InsertionPoint::Master => self.master_fx_rack = None,
                     fx::InsertionPoint::Atmo => self.atmo_fx_rack = None,
+                    fx::InsertionPoint::Bus(i) => self.bus_fx_racks[i] = None,
+                    fx::InsertionPoint::Send(i) => self.send_fx_racks[i] = None,
                 },
 
                 AudioCommand::ClearAtmoLayer {
@@ -349,6 +534,13 @@ impl AudioEngine {
                         }
                     }
                 }
+                AudioCommand::ToggleMixerSoloSafe(track_index) => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        if let Some(track) = mixer_state.tracks.get_mut(track_index) {
+                            track.is_solo_safe = !track.is_solo_safe;
+                        }
+                    }
+                }
                 AudioCommand::ToggleSynth => {
                     let is_active = self.synth_is_active.load(Ordering::Relaxed);
                     self.synth_is_active.store(!is_active, Ordering::Relaxed);
@@ -401,20 +593,41 @@ impl AudioEngine {
                 }
                 AudioCommand::StartOutputRecording => {
                     self.output_recording_buffer = Some(Vec::new());
-                }
-                AudioCommand::StopOutputRecording { output_path } => {
+                    // Measure the loudness of the take itself.
+                    self.loudness_meter.reset();
+                }
+                AudioCommand::StopOutputRecording {
+                    output_path,
+                    format,
+                    trim,
+                    normalize_to_db,
+                    dither,
+                } => {
                     if let Some(buffer) = self.output_recording_buffer.take() {
                         let sample_rate = self.sample_rate;
                         thread::spawn(move || {
-                            let trimmed_buffer = trim_silence(buffer);
+                            let mut trimmed_buffer = if trim.enabled {
+                                trim_silence(buffer, trim.rms_threshold, trim.required_blocks)
+                            } else {
+                                buffer
+                            };
                             if trimmed_buffer.is_empty() {
                                 println!("Recording is empty after trimming silence. Not saved.");
                                 return;
                             }
+                            if let Some(target_db) = normalize_to_db {
+                                normalize_peak(&mut trimmed_buffer, target_db);
+                            }
+                            if dither {
+                                apply_tpdf_dither(&mut trimmed_buffer, format);
+                            }
 
-                            if let Err(e) =
-                                write_wav_file(&output_path, &trimmed_buffer, sample_rate)
-                            {
+                            if let Err(e) = write_stereo_wav_file(
+                                &output_path,
+                                &trimmed_buffer,
+                                sample_rate,
+                                format,
+                            ) {
                                 eprintln!("Failed to save recording: {}", e);
                             } else {
                                 println!("Recording saved to {}", output_path.display());
@@ -422,7 +635,21 @@ impl AudioEngine {
                         });
                     }
                 }
-                AudioCommand::SaveSessionAudio { session_path } => {
+                AudioCommand::StartStemRecording(stems) => {
+                    self.stem_recording = Some(stems);
+                }
+                AudioCommand::StopStemRecording { output_dir, format } => {
+                    if let Some(stems) = self.stem_recording.take() {
+                        stems.finish(output_dir, format);
+                    }
+                }
+                AudioCommand::SnapshotLoops(snapshot) => {
+                    self.loop_snapshot = Some(snapshot);
+                }
+                AudioCommand::SaveSessionAudio {
+                    session_path,
+                    format,
+                } => {
                     for (i, looper) in self.loopers.iter().enumerate() {
                         if !looper.audio.is_empty() {
                             let audio_data = looper.audio.clone();
@@ -430,22 +657,13 @@ impl AudioEngine {
                             let sample_rate = self.sample_rate;
                             thread::spawn(move || {
                                 // For session saving, we'll save as mono to preserve original data
-                                let spec = hound::WavSpec {
-                                    channels: 1,
-                                    sample_rate: sample_rate as u32,
-                                    bits_per_sample: 16,
-                                    sample_format: hound::SampleFormat::Int,
-                                };
-                                if let Ok(mut writer) = hound::WavWriter::create(&path, spec) {
-                                    for &sample in &audio_data {
-                                        let amplitude = i16::MAX as f32;
-                                        writer.write_sample((sample * amplitude) as i16).ok();
-                                    }
-                                    writer.finalize().ok();
-                                } else {
+                                if let Err(e) =
+                                    write_mono_wav_file(&path, &audio_data, sample_rate, format)
+                                {
                                     eprintln!(
-                                        "Failed to create session wav file at {}",
-                                        path.display()
+                                        "Failed to create session wav file at {}: {}",
+                                        path.display(),
+                                        e
                                     );
                                 }
                             });
@@ -464,10 +682,28 @@ impl AudioEngine {
                         original_sample_rate as f32,
                         target_sr,
                     ) {
-                        Ok(audio_data) => {
+                        Ok(mut audio_data) => {
+                            let transport_len = self.transport_len_samples.load(Ordering::Relaxed);
+                            // Sessions saved before cycle counts were stored report 0, so
+                            // fall back to the loop's length measured in transport cycles.
+                            let length_in_cycles = if length_in_cycles > 0 {
+                                length_in_cycles
+                            } else if transport_len > 0 {
+                                ((audio_data.len() as f32 / transport_len as f32).round() as u32)
+                                    .max(1)
+                            } else {
+                                1
+                            };
+                            if transport_len > 0 {
+                                // Resampling can leave the loop a few samples off; snap it to
+                                // its cycle count so it wraps exactly on the transport.
+                                audio_data.resize(transport_len * length_in_cycles as usize, 0.0);
+                            }
                             if let Some(looper) = self.loopers.get_mut(looper_index) {
                                 looper.audio = audio_data;
+                                looper.seam.forget();
                                 looper.playhead = 0;
+                                looper.cycles_recorded = length_in_cycles;
                                 looper.shared_state.set(LooperState::Playing);
                                 // USE THE VALUE FROM THE COMMAND
                                 looper.shared_state.set_length_in_cycles(length_in_cycles);
@@ -483,7 +719,91 @@ impl AudioEngine {
                 }
                 AudioCommand::SetMixerState(state) => {
                     if let Ok(mut mixer_state) = self.track_mixer_state.write() {
-                        *mixer_state = state;
+                        *mixer_state = *state;
+                    }
+                }
+                AudioCommand::SetMixerTrackBus { track_index, bus } => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        if let Some(track) = mixer_state.tracks.get_mut(track_index) {
+                            track.bus = bus.filter(|&b| b < NUM_SUB_BUSES);
+                        }
+                    }
+                }
+                AudioCommand::SetMixerTrackPan { track_index, pan } => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        if let Some(track) = mixer_state.tracks.get_mut(track_index) {
+                            track.pan = pan.clamp(-1.0, 1.0);
+                        }
+                    }
+                }
+                AudioCommand::SetMixerSidechain(sidechain) => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        mixer_state.sidechain = sidechain;
+                    }
+                    if sidechain.source.is_none() {
+                        self.sidechain_follower.reset();
+                        self.sidechain_envelope = 0.0;
+                    }
+                }
+                AudioCommand::SetLooperNudge { looper_index, samples } => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        if let Some(track) = mixer_state.tracks.get_mut(looper_index) {
+                            track.nudge_samples =
+                                samples.clamp(-MAX_NUDGE_SAMPLES, MAX_NUDGE_SAMPLES);
+                        }
+                    }
+                }
+                AudioCommand::ToggleLooperReverse(looper_index) => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        if let Some(track) = mixer_state.tracks.get_mut(looper_index) {
+                            track.is_reversed = !track.is_reversed;
+                        }
+                    }
+                }
+                AudioCommand::SetMixerTrackSend {
+                    track_index,
+                    send_index,
+                    level,
+                } => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        if let Some(track) = mixer_state.tracks.get_mut(track_index) {
+                            track.set_send_level(send_index, level);
+                        }
+                    }
+                }
+                AudioCommand::SetMixerSendReturnVolume { send_index, volume } => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        if let Some(send) = mixer_state.send_returns.get_mut(send_index) {
+                            send.volume = volume;
+                        }
+                    }
+                }
+                AudioCommand::ToggleMixerSendReturnMute(send_index) => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        if let Some(send) = mixer_state.send_returns.get_mut(send_index) {
+                            send.is_muted = !send.is_muted;
+                        }
+                    }
+                }
+                AudioCommand::SetMixerTrackMeterPoint { track_index, meter_point } => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        if let Some(track) = mixer_state.tracks.get_mut(track_index) {
+                            track.meter_point = meter_point;
+                        }
+                    }
+                }
+                AudioCommand::SetMixerBusVolume { bus_index, volume } => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        if let Some(bus) = mixer_state.buses.get_mut(bus_index) {
+                            bus.volume = volume;
+                        }
+                    }
+                }
+                AudioCommand::ToggleMixerBusMute(bus_index) => {
+                    if let Ok(mut mixer_state) = self.track_mixer_state.write() {
+                        if let Some(bus) = mixer_state.buses.get_mut(bus_index) {
+                            bus.is_muted = !bus.is_muted;
+                        }
                     }
                 }
                 AudioCommand::SetMixerTrackVolume { track_index, volume } => {
@@ -510,9 +830,11 @@ impl AudioEngine {
                     self.transport_state = TransportState::Playing;
                     self.transport_is_playing.store(true, Ordering::Relaxed);
                     self.transport_playhead.store(0, Ordering::Relaxed);
-                    self.transport_len_samples.store(0, Ordering::Relaxed);
                     self.master_looper_index.store(usize::MAX, Ordering::Relaxed);
-                    self.tempo_multiplier.store(1_000_000, Ordering::Relaxed);
+                    if !self.tempo_lock_engaged() {
+                        self.transport_len_samples.store(0, Ordering::Relaxed);
+                        self.tempo_multiplier.store(1_000_000, Ordering::Relaxed);
+                    }
                     for i in 0..NUM_LOOPERS {
                         self.clear_looper(i);
                     }
@@ -526,6 +848,10 @@ impl AudioEngine {
                     for rack in self.synth_fx_racks.iter_mut() {
                         *rack = None;
                     }
+                    for rack in self.bus_fx_racks.iter_mut() {
+                        *rack = None;
+                    }
+                    // Send returns are owned by the UI, which reloads their default chains.
                     self.sampler_fx_rack = None;
                     self.input_fx_rack = None;
                     self.master_fx_rack = None;
@@ -535,9 +861,11 @@ impl AudioEngine {
                     self.transport_state = TransportState::Paused;
                     self.transport_is_playing.store(false, Ordering::Relaxed);
                     self.transport_playhead.store(0, Ordering::Relaxed);
-                    self.transport_len_samples.store(0, Ordering::Relaxed);
                     self.master_looper_index.store(usize::MAX, Ordering::Relaxed);
-                    self.tempo_multiplier.store(1_000_000, Ordering::Relaxed);
+                    if !self.tempo_lock_engaged() {
+                        self.transport_len_samples.store(0, Ordering::Relaxed);
+                        self.tempo_multiplier.store(1_000_000, Ordering::Relaxed);
+                    }
                     for i in 0..NUM_LOOPERS {
                         self.clear_looper(i);
                     }
@@ -551,37 +879,45 @@ impl AudioEngine {
                     for rack in self.synth_fx_racks.iter_mut() {
                         *rack = None;
                     }
+                    for rack in self.bus_fx_racks.iter_mut() {
+                        *rack = None;
+                    }
+                    // Send returns are owned by the UI, which reloads their default chains.
                     self.sampler_fx_rack = None;
                     self.input_fx_rack = None;
                     self.master_fx_rack = None;
                     self.atmo_fx_rack = None;
                 }
+                AudioCommand::SoftReset => {
+                    self.soft_reset();
+                }
+                AudioCommand::ResetLoudnessMeter => {
+                    self.loudness_meter.reset();
+                }
                 AudioCommand::LooperPress(id) => {
-                    let is_playing = self.transport_is_playing.load(Ordering::Relaxed);
-                    let transport_has_started =
-                        self.transport_len_samples.load(Ordering::Relaxed) > 0;
-                    let state = self.loopers[id].shared_state.get();
-
-                    match state {
-                        LooperState::Empty => {
-                            if !transport_has_started {
-                                self.arm_looper(id);
-                                // Pre-allocate a generous buffer for the first recording to avoid many small reallocations
-                                let initial_capacity = (self.sample_rate * 5.0) as usize; // 5 seconds
-                                self.loopers[id].audio.reserve(initial_capacity);
-                            } else if is_playing {
-                                self.handle_toggle_looper(id);
-                            }
+                    let transition = next_looper_state(
+                        self.loopers[id].shared_state.get(),
+                        LooperEvent::Press,
+                        self.transport_is_playing.load(Ordering::Relaxed),
+                        self.transport_len_samples.load(Ordering::Relaxed),
+                    );
+                    match transition {
+                        LooperTransition::Ignore => {}
+                        LooperTransition::Set(state) => self.loopers[id].shared_state.set(state),
+                        LooperTransition::Arm => {
+                            self.arm_looper(id);
+                            // Pre-allocate a generous buffer for the first recording to avoid many small reallocations
+                            let initial_capacity = (self.sample_rate * 5.0) as usize; // 5 seconds
+                            self.loopers[id].audio.reserve(initial_capacity);
                         }
-                        LooperState::Armed => {
-                            self.clear_looper(id);
-                        }
-                        _ => {
-                            // Playing, Overdubbing, Stopped
-                            if is_playing {
-                                self.handle_toggle_looper(id);
+                        LooperTransition::Queue { show_armed } => {
+                            let looper = &mut self.loopers[id];
+                            looper.pending_command = true;
+                            if show_armed {
+                                looper.shared_state.set(LooperState::Armed);
                             }
                         }
+                        LooperTransition::Clear => self.clear_looper(id),
                     }
                 }
                 AudioCommand::ClearLooper(id) => self.clear_looper(id),
@@ -594,7 +930,10 @@ impl AudioEngine {
                 AudioCommand::ToggleLimiter => {
                     let is_active = self.limiter_is_active.load(Ordering::Relaxed);
                     self.limiter_is_active.store(!is_active, Ordering::Relaxed);
+                    self.limiter.reset();
                 }
+                AudioCommand::SetLimiterTruePeak(enabled) => self.limiter.set_true_peak(enabled),
+                AudioCommand::SetLimiterLookahead(ms) => self.limiter.set_lookahead_ms(ms),
                 AudioCommand::SetLimiterReleaseMode(mode) => self.limiter_release_mode = mode,
                 AudioCommand::SetLimiterReleaseMs(ms) => self
                     .limiter_release_ms
@@ -607,6 +946,37 @@ impl AudioEngine {
                         engine.set_amp_adsr(settings);
                     }
                 }
+                AudioCommand::SetVelocitySensitivity(idx, sensitivity) => {
+                    if let Some(engine) = self.synth.engines.get_mut(idx) {
+                        engine.set_velocity_sensitivity(sensitivity);
+                    }
+                }
+                AudioCommand::SetGlideTime(idx, glide_time_ms) => {
+                    if let Some(engine) = self.synth.engines.get_mut(idx) {
+                        engine.set_glide_time(glide_time_ms);
+                    }
+                }
+                AudioCommand::SetTuningReference(a4_hz) => self.synth.set_tuning_reference(a4_hz),
+                AudioCommand::SetSampleDeclick(ms) => {
+                    let declick_samples = ms.max(0.0) / 1000.0 * self.sample_rate;
+                    for pad in &mut self.sampler_pads {
+                        pad.declick_samples = declick_samples;
+                    }
+                    self.synth.set_declick_time(ms);
+                }
+                AudioCommand::SetOutputClipMode(mode) => self.output_clip_mode = mode,
+                AudioCommand::SetLfoTransportSync(enabled) => {
+                    self.lfo_transport_sync = enabled;
+                }
+                AudioCommand::SetMonitorWhileStopped(enabled) => {
+                    self.monitor_while_stopped = enabled;
+                }
+                AudioCommand::SetLoopCrossfade(fade_ms) => {
+                    self.loop_crossfade_samples = (fade_ms.clamp(0.0, MAX_LOOP_CROSSFADE_MS)
+                        * self.sample_rate
+                        / 1000.0)
+                        .round() as usize;
+                }
                 AudioCommand::SetFilterAdsr(idx, settings) => {
                     if let Some(engine) = self.synth.engines.get_mut(idx) {
                         engine.set_filter_adsr(settings);
@@ -642,6 +1012,16 @@ impl AudioEngine {
                         s.load_sample_for_slot(slot_index, audio_data);
                     }
                 }
+                AudioCommand::AppendSampleToSamplerSlot {
+                    engine_index,
+                    slot_index,
+                    audio_data,
+                } => {
+                    if let Some(SynthEngine::Sampler(s)) = self.synth.engines.get_mut(engine_index)
+                    {
+                        s.append_sample_to_slot(slot_index, audio_data);
+                    }
+                }
                 AudioCommand::SetSamplerSettings {
                     engine_index,
                     root_notes,
@@ -653,16 +1033,45 @@ impl AudioEngine {
                         s.set_sampler_settings(root_notes, global_fine_tune_cents, fade_out);
                     }
                 }
+                AudioCommand::SetSamplerLoopRegion {
+                    engine_index,
+                    slot_index,
+                    region,
+                } => {
+                    if let Some(SynthEngine::Sampler(s)) = self.synth.engines.get_mut(engine_index)
+                    {
+                        s.set_loop_region(slot_index, region);
+                    }
+                }
+                AudioCommand::SetSamplerVelocityZones { engine_index, zones } => {
+                    if let Some(SynthEngine::Sampler(s)) = self.synth.engines.get_mut(engine_index)
+                    {
+                        s.set_velocity_zones(zones);
+                    }
+                }
                 AudioCommand::ChangeEngineType {
                     engine_index,
                     volume,
                     peak_meter,
                     params,
                 } => {
-                    let new_engine = Synth::create_engine(self.sample_rate, params);
-                    self.synth.engines[engine_index] = new_engine;
-                    self.engine_volumes[engine_index] = volume;
-                    self.engine_peak_meters[engine_index] = peak_meter;
+                    let swap = PendingEngineSwap {
+                        engine: Synth::create_engine(self.sample_rate, params),
+                        volume,
+                        peak_meter,
+                    };
+                    if self.outgoing_engines[engine_index].is_some() {
+                        // A pending engine has not been heard yet, so replacing it is silent.
+                        self.pending_engine_swaps[engine_index] = Some(swap);
+                    } else {
+                        self.begin_engine_swap(engine_index, swap);
+                    }
+                }
+                AudioCommand::SetEngineSwapFade(ms) => {
+                    self.engine_swap_fade_len = ((ms.clamp(0.0, MAX_ENGINE_SWAP_FADE_MS)
+                        * 0.001
+                        * self.sample_rate) as usize)
+                        .max(1);
                 }
                 AudioCommand::MidiMessage(msg) => {
                     let channel = msg.status & 0x0F;
@@ -680,14 +1089,26 @@ impl AudioEngine {
                                     let pad_index = (note - 48) as usize;
                                     if let Some(pad) = self.sampler_pads.get_mut(pad_index) {
                                         if !pad.audio.is_empty() {
-                                            pad.volume = velocity as f32 / 127.0;
-                                            pad.playhead = 0.0;
-                                            pad.amp_adsr.note_on();
-                                            pad.gate_counter = (pad.fx.gate_close_time_ms / 1000.0
-                                                * self.sample_rate)
-                                                as usize;
-                                            pad.was_gate_open = true;
-                                            self.pad_event_producer.push(pad_index).ok();
+                                            // Live hits keep their timing; only velocity is humanized.
+                                            let (_, velocity_scale) = groove_offset(
+                                                &self.pad_groove,
+                                                &mut self.groove_rng,
+                                                0,
+                                                0,
+                                                self.sample_rate,
+                                            );
+                                            let volume =
+                                                (velocity as f32 / 127.0 * velocity_scale).clamp(0.0, 1.0);
+                                            pad.is_held = true;
+                                            if self.pad_groove.launch_quantize
+                                                == LaunchQuantize::Off
+                                            {
+                                                pad.trigger(volume, self.sample_rate);
+                                                self.pad_event_producer.push(pad_index).ok();
+                                            } else {
+                                                // Fired from process_buffer on the next grid step.
+                                                pad.pending_launch = Some(volume);
+                                            }
                                             note_consumed_by_sampler = true;
                                         }
                                     }
@@ -703,13 +1124,19 @@ impl AudioEngine {
                             if (48..=63).contains(&note) {
                                 let pad_index = (note - 48) as usize;
                                 if let Some(pad) = self.sampler_pads.get_mut(pad_index) {
-                                    pad.amp_adsr.note_off();
+                                    pad.note_off();
                                 }
                             }
                             self.synth.note_off(note);
                         }
                     }
                 }
+                AudioCommand::SynthNoteOn { note, velocity } => {
+                    if self.synth_is_active.load(Ordering::Relaxed) {
+                        self.synth.note_on(note, velocity);
+                    }
+                }
+                AudioCommand::SynthNoteOff(note) => self.synth.note_off(note),
                 AudioCommand::ActivateSynth => self.synth_is_active.store(true, Ordering::Relaxed),
                 AudioCommand::DeactivateSynth => {
                     self.synth_is_active.store(false, Ordering::Relaxed)
@@ -757,6 +1184,12 @@ impl AudioEngine {
                             .set_params(mapped_size, settings.reverb_decay, self.sample_rate);
                     }
                 }
+                AudioCommand::SetPadGroove(groove) => {
+                    self.pad_groove = groove;
+                    self.groove_rng = StdRng::seed_from_u64(groove.seed);
+                }
+                AudioCommand::AuditionSample(audio) => self.audition.play(audio),
+                AudioCommand::StopAudition => self.audition.stop(),
                 AudioCommand::AdjustParameterRelative { parameter, delta } => {
                     // This function will contain the logic to adjust the value
                     self.adjust_parameter(parameter, delta);
@@ -764,22 +1197,6 @@ impl AudioEngine {
             }
         }
     }
-    fn handle_toggle_looper(&mut self, id: usize) {
-        let looper = &mut self.loopers[id];
-        let current_state = looper.shared_state.get();
-
-        if current_state == LooperState::Playing {
-            looper.shared_state.set(LooperState::Overdubbing);
-        } else if current_state == LooperState::Overdubbing {
-            looper.shared_state.set(LooperState::Playing);
-        } else {
-            looper.pending_command = true;
-            if current_state == LooperState::Empty {
-                looper.shared_state.set(LooperState::Armed);
-            }
-        }
-    }
-
     /// Regenerates the high-resolution summary from the full audio buffer.
     /// This is used after loading, overdubbing, or finishing the first recording.
     fn regenerate_high_res_summary(&mut self, looper_id: usize) {
@@ -845,31 +1262,133 @@ impl AudioEngine {
             return;
         }
 
-        let mut visual_summary = Vec::with_capacity(WAVEFORM_DOWNSAMPLE_SIZE);
         let chunk_size = (summary.len() as f32 / WAVEFORM_DOWNSAMPLE_SIZE as f32).max(1.0) as usize;
 
-        for chunk in summary.chunks(chunk_size) {
-            let peak = chunk.iter().fold(0.0f32, |max, &v| max.max(v));
-            visual_summary.push(peak);
-        }
-
+        // Refilled in place so the audio thread reuses the summary's allocation.
         if let Ok(mut w) = looper.shared_state.get_waveform_summary().write() {
-            *w = visual_summary;
+            w.clear();
+            w.extend(
+                summary
+                    .chunks(chunk_size)
+                    .map(|chunk| chunk.iter().fold(0.0f32, |max, &v| max.max(v))),
+            );
         }
     }
 
+    /// Ends a free-running first recording as a one cycle loop of its current length.
+    fn close_free_running_loop(&mut self, id: usize, input_latency_samples: usize) {
+        let looper = &mut self.loopers[id];
+        let len = looper.audio.len();
+        looper.audio.rotate_left(input_latency_samples % len);
+        looper.shared_state.set(LooperState::Playing);
+        looper.playhead = 0;
+        looper.cycles_recorded = 1;
+        looper.shared_state.set_length_in_cycles(1);
+        looper.shared_state.set_playhead(0);
+        looper.pending_command = false;
+        looper.seam.crossfade(&mut looper.audio, self.loop_crossfade_samples);
+        self.regenerate_high_res_summary(id);
+        self.update_visual_summary(id);
+    }
+
     fn arm_looper(&mut self, id: usize) {
-        for (i, looper) in self.loopers.iter_mut().enumerate() {
-            if i != id && looper.shared_state.get() == LooperState::Armed {
-                looper.shared_state.set(LooperState::Empty);
+        if self.exclusive_arm.load(Ordering::Relaxed) {
+            for (i, looper) in self.loopers.iter_mut().enumerate() {
+                if i != id && looper.shared_state.get() == LooperState::Armed {
+                    looper.shared_state.set(LooperState::Empty);
+                }
             }
         }
         self.loopers[id].shared_state.set(LooperState::Armed);
     }
 
+    /// Puts `swap` in the engine slot and keeps the engine it replaces alive so it can be
+    /// faded out instead of cut.
+    fn begin_engine_swap(&mut self, engine_index: usize, swap: PendingEngineSwap) {
+        let old_engine = self.synth.replace_engine(engine_index, swap.engine);
+        self.outgoing_engines[engine_index] = Some(old_engine);
+        self.engine_swap_fade_pos[engine_index] = 0;
+        self.engine_volumes[engine_index] = swap.volume;
+        self.engine_peak_meters[engine_index] = swap.peak_meter;
+    }
+
+    /// Blends any engine that was just replaced by `ChangeEngineType` into the output
+    /// of its successor with an equal-gain ramp, then drops it once the fade completes.
+    /// A swap that queued up behind the fade starts as soon as it is done.
+    fn apply_engine_swap_crossfade(&mut self, num_samples: usize, musical_bar_len: usize) {
+        for engine_index in 0..2 {
+            let Some(old_engine) = &mut self.outgoing_engines[engine_index] else {
+                continue;
+            };
+            let fade_buffer = &mut self.engine_fade_buffer[..num_samples];
+            old_engine.process(fade_buffer, musical_bar_len, &self.midi_cc_values);
+
+            let new_buffer = if engine_index == 0 {
+                &mut self.engine_0_buffer[..num_samples]
+            } else {
+                &mut self.engine_1_buffer[..num_samples]
+            };
+            let fade_len = self.engine_swap_fade_len;
+            let mut fade_pos = self.engine_swap_fade_pos[engine_index];
+            for (new_sample, old_sample) in new_buffer.iter_mut().zip(fade_buffer.iter()) {
+                let t = (fade_pos as f32 / fade_len as f32).min(1.0);
+                *new_sample = *new_sample * t + *old_sample * (1.0 - t);
+                fade_pos += 1;
+            }
+            self.engine_swap_fade_pos[engine_index] = fade_pos;
+
+            if fade_pos >= fade_len {
+                self.outgoing_engines[engine_index] = None;
+                if let Some(swap) = self.pending_engine_swaps[engine_index].take() {
+                    self.begin_engine_swap(engine_index, swap);
+                }
+            }
+        }
+    }
+
+    /// Recovers from stuck notes or runaway feedback without rebuilding the engine.
+    /// Streams, loaded samples, loops and FX chains are all left in place.
+    fn soft_reset(&mut self) {
+        self.synth.reset_voices();
+        // Everything is silenced anyway, so queued swaps can take over without a fade.
+        for engine_index in 0..2 {
+            if let Some(swap) = self.pending_engine_swaps[engine_index].take() {
+                self.begin_engine_swap(engine_index, swap);
+            }
+        }
+        self.outgoing_engines = [None, None];
+        for pad in self.sampler_pads.iter_mut() {
+            pad.reset();
+        }
+        for rack in self
+            .looper_fx_racks
+            .iter_mut()
+            .chain(self.synth_fx_racks.iter_mut())
+            .chain(self.bus_fx_racks.iter_mut())
+            .chain(self.send_fx_racks.iter_mut())
+            .chain([
+                &mut self.sampler_fx_rack,
+                &mut self.input_fx_rack,
+                &mut self.master_fx_rack,
+                &mut self.atmo_fx_rack,
+            ])
+            .flatten()
+        {
+            rack.reset();
+        }
+        self.limiter.reset();
+        self.engine_0_buffer.fill(0.0);
+        self.engine_1_buffer.fill(0.0);
+        self.atmo_buffer.fill(0.0);
+        self.atmo_stereo_buffer.fill([0.0; 2]);
+        self.engine_fade_buffer.fill(0.0);
+        self.output_buffer.fill([0.0; 2]);
+    }
+
     fn clear_looper(&mut self, id: usize) {
         let looper = &mut self.loopers[id];
         looper.audio.clear();
+        looper.seam.forget();
         looper.playhead = 0;
         looper.pending_command = false;
         looper.stop_is_queued = false;
@@ -889,21 +1408,63 @@ impl AudioEngine {
         self.update_visual_summary(id);
 
         if self.loopers.iter().all(|l| l.audio.is_empty()) {
-            self.transport_len_samples.store(0, Ordering::Relaxed);
-            self.transport_playhead.store(0, Ordering::Relaxed);
             self.master_looper_index.store(usize::MAX, Ordering::Relaxed);
-            self.tempo_multiplier.store(1_000_000, Ordering::Relaxed);
+            // With the tempo locked, the next recording goes straight into the existing length.
+            if !self.tempo_lock_engaged() {
+                self.transport_len_samples.store(0, Ordering::Relaxed);
+                self.transport_playhead.store(0, Ordering::Relaxed);
+                self.tempo_multiplier.store(1_000_000, Ordering::Relaxed);
+            }
         }
     }
 
-    pub fn process_buffer(&mut self, mic_buffer: &mut [f32]) -> Vec<f32> {
+    /// The tempo lock only holds once a transport length has been established.
+    fn tempo_lock_engaged(&self) -> bool {
+        self.tempo_locked.load(Ordering::Relaxed)
+            && self.transport_len_samples.load(Ordering::Relaxed) > 0
+    }
+
+    /// The input safety buffer plus the master limiter's delay, in samples. Recorded audio
+    /// lags the transport by this much, since players follow what they hear.
+    fn input_latency_samples(&self) -> usize {
+        let latency_ms = self.input_latency_compensation_ms.load(Ordering::Relaxed) as f32 / 100.0;
+        let limiter_latency = if self.limiter_is_active.load(Ordering::Relaxed)
+            && !self.safe_mode.load(Ordering::Relaxed)
+        {
+            self.limiter.latency_samples()
+        } else {
+            0
+        };
+        ((latency_ms / 1000.0 * self.sample_rate).round() as usize + limiter_latency)
+            .min(self.internal_record_delay.len() - 1)
+    }
+
+    /// Runs every track's FX rack over the samples it read this buffer, one track per thread.
+    /// Racks only see the stretches where their track was playing, as before the split.
+    fn process_looper_fx_racks(&mut self, num_samples: usize) {
+        let active_masks = &self.looper_active_masks[..num_samples];
+        self.looper_fx_racks
+            .par_iter_mut()
+            .zip(self.looper_buffers.par_iter_mut())
+            .enumerate()
+            .for_each(|(id, (rack, buffer))| {
+                if let Some(rack) = rack {
+                    process_rack_where_active(rack, &mut buffer[..num_samples], active_masks, id);
+                }
+            });
+    }
+
+    /// Processes one block and returns the master output as left/right frames.
+    pub fn process_buffer(&mut self, mic_buffer: &mut [f32]) -> &[[f32; 2]] {
         let start_time = Instant::now();
         // NEW: Safety check. Cap the number of samples to process at our pre-allocated max size.
         let num_samples = mic_buffer.len().min(MAX_BUFFER_SIZE);
-        let mut output_buffer = vec![0.0; num_samples];
+        self.output_buffer[..num_samples].fill([0.0; 2]);
         let mut transport_len = self.transport_len_samples.load(Ordering::Relaxed);
         let mut transport_playhead = self.transport_playhead.load(Ordering::Relaxed);
         let transport_is_playing = self.transport_is_playing.load(Ordering::Relaxed);
+        let safe_mode = self.safe_mode.load(Ordering::Relaxed);
+        let loudness_meter_enabled = self.loudness_meter_enabled.load(Ordering::Relaxed);
         let mut playing_mask = 0u16;
 
         // REMOVED: The entire block that resized buffers has been deleted.
@@ -914,8 +1475,24 @@ impl AudioEngine {
         } else {
             transport_len
         };
-
-        if self.synth_is_active.load(Ordering::Relaxed) {
+        // Without a running transport there is no grid, so quantized pad hits fire at once.
+        let launch_interval = self
+            .pad_groove
+            .launch_quantize
+            .divisions_per_bar()
+            .filter(|_| transport_is_playing && musical_bar_len > 0)
+            .map(|divisions| (musical_bar_len / divisions).max(1));
+
+        if !safe_mode && self.synth_is_active.load(Ordering::Relaxed) {
+            // The synth renders the whole block up front, so synced LFOs restart at the start
+            // of the block that contains the downbeat.
+            let downbeat_in_block = transport_is_playing
+                && transport_len > 0
+                && (transport_playhead == 0
+                    || transport_playhead + num_samples > transport_len);
+            if self.lfo_transport_sync && downbeat_in_block {
+                self.synth.reset_synced_lfo_phases();
+            }
             // MODIFIED: Pass slices instead of the whole buffer.
             self.synth.process(
                 &mut self.engine_0_buffer[..num_samples],
@@ -923,6 +1500,7 @@ impl AudioEngine {
                 musical_bar_len,
                 &self.midi_cc_values,
             );
+            self.apply_engine_swap_crossfade(num_samples, musical_bar_len);
         } else {
             // MODIFIED: Use a slice.
             self.engine_0_buffer[..num_samples].fill(0.0);
@@ -930,25 +1508,31 @@ impl AudioEngine {
         }
 
         // --- Atmo Engine Processing ---
-        // MODIFIED: Use slices.
-        self.atmo_engine
-            .process(&mut self.atmo_stereo_buffer[..num_samples]);
-        for (i, frame) in self.atmo_stereo_buffer[..num_samples].iter().enumerate() {
-            self.atmo_buffer[i] = (frame[0] + frame[1]) * 0.5;
+        if safe_mode {
+            self.atmo_buffer[..num_samples].fill(0.0);
+        } else {
+            // MODIFIED: Use slices.
+            self.atmo_engine
+                .process(&mut self.atmo_stereo_buffer[..num_samples]);
+            for (i, frame) in self.atmo_stereo_buffer[..num_samples].iter().enumerate() {
+                self.atmo_buffer[i] = (frame[0] + frame[1]) * 0.5;
+            }
         }
 
-        if let Some(rack) = &mut self.atmo_fx_rack {
+        if let Some(rack) = self.atmo_fx_rack.as_mut().filter(|_| !safe_mode) {
             // MODIFIED: Use a slice.
             rack.process_buffer(&mut self.atmo_buffer[..num_samples]);
         }
         let mut atmo_peak_buffer = 0.0f32;
 
         // --- Apply Synth FX ---
-        if let Some(rack) = &mut self.synth_fx_racks[0] {
+        if let Some(rack) = self.synth_fx_racks[0].as_mut().filter(|_| !safe_mode) {
+            rack.set_mix_modulation(self.synth.engines[0].fx_mix_modulation());
             // MODIFIED: Use a slice.
             rack.process_buffer(&mut self.engine_0_buffer[..num_samples]);
         }
-        if let Some(rack) = &mut self.synth_fx_racks[1] {
+        if let Some(rack) = self.synth_fx_racks[1].as_mut().filter(|_| !safe_mode) {
+            rack.set_mix_modulation(self.synth.engines[1].fx_mix_modulation());
             // MODIFIED: Use a slice.
             rack.process_buffer(&mut self.engine_1_buffer[..num_samples]);
         }
@@ -957,6 +1541,8 @@ impl AudioEngine {
         let mut synth_master_peak_buffer = 0.0f32;
         let mut sampler_peak_buffer = 0.0f32;
         let mut master_peak_buffer = 0.0f32;
+        // Sums of L*R, L*L and R*R over the buffer for the correlation meter.
+        let mut correlation_sums = [0.0f32; 3];
 
         let release_coeffs = match self.limiter_release_mode {
             LfoRateMode::Hz => {
@@ -977,16 +1563,41 @@ impl AudioEngine {
         };
 
         // --- Apply Input FX ---
-        if let Some(rack) = &mut self.input_fx_rack {
+        if let Some(rack) = self.input_fx_rack.as_mut().filter(|_| !safe_mode) {
             rack.process_buffer(mic_buffer);
         }
 
         let input_peak = mic_buffer.iter().fold(0.0f32, |max, &val| max.max(val.abs()));
+        // Held at the highest peak until the UI reads and resets it.
         self.input_peak_meter
-            .store((input_peak * u32::MAX as f32) as u32, Ordering::Relaxed);
+            .fetch_max((input_peak * u32::MAX as f32) as u32, Ordering::Relaxed);
 
         let mixer_state = self.track_mixer_state.read().unwrap().clone();
         let is_any_soloed = mixer_state.tracks.iter().any(|t| t.is_soloed);
+        let mute_fade_step = 1000.0 / (MUTE_FADE_MS * self.sample_rate);
+        let is_taking = self.loopers.iter().any(|looper| {
+            matches!(
+                looper.shared_state.get(),
+                LooperState::Recording | LooperState::Overdubbing
+            )
+        });
+        let input_latency_samples = match self.latched_input_latency_samples {
+            Some(latched) if is_taking => latched,
+            _ => {
+                let latency = self.input_latency_samples();
+                self.latched_input_latency_samples = is_taking.then_some(latency);
+                latency
+            }
+        };
+        let sidechain = mixer_state.sidechain;
+        self.sidechain_follower_params.attack_ms.store(
+            (sidechain.attack_ms.max(0.0) * envelope_follower::PARAM_SCALER) as u32,
+            Ordering::Relaxed,
+        );
+        self.sidechain_follower_params.release_ms.store(
+            (sidechain.release_ms.max(0.0) * envelope_follower::PARAM_SCALER) as u32,
+            Ordering::Relaxed,
+        );
         let mut buffer_peaks = [0.0f32; NUM_LOOPERS];
 
         let synth_master_vol_f32 =
@@ -995,15 +1606,17 @@ impl AudioEngine {
         let atmo_master_vol_f32 =
             self.atmo_master_volume.load(Ordering::Relaxed) as f32 / 1_000_000.0;
 
-        for i in 0..num_samples {
+        for (i, &mic_input) in mic_buffer[..num_samples].iter().enumerate() {
             let just_wrapped = transport_len > 0 && transport_playhead == 0;
+            // Position within the current bar, captured before the metronome advances it.
+            let bar_position = self.metronome_playhead;
 
             // Metronome logic is now independent of wrapping
             if musical_bar_len > 0 && transport_is_playing {
                 let quarter_note_len = musical_bar_len / 4;
                 if quarter_note_len > 0 && self.metronome_playhead % quarter_note_len == 0 {
                     let beat_in_bar = (self.metronome_playhead / quarter_note_len) % 4;
-                    if beat_in_bar == 0 {
+                    if beat_in_bar == 0 && mixer_state.metronome.accent_first {
                         self.metronome
                             .trigger(mixer_state.metronome.accent_pitch_hz);
                     } else {
@@ -1014,6 +1627,13 @@ impl AudioEngine {
             } else {
                 self.metronome_playhead = 0;
             }
+            let metronome_state = &mixer_state.metronome;
+            self.metronome_buffer[i] =
+                if !safe_mode && !metronome_state.is_muted && metronome_state.volume > 0.0 {
+                    self.metronome.process() * metronome_state.volume
+                } else {
+                    0.0
+                };
 
             if just_wrapped {
                 for looper in self.loopers.iter_mut() {
@@ -1025,7 +1645,7 @@ impl AudioEngine {
                     }
                 }
 
-                let mut loopers_to_regenerate = Vec::new();
+                let mut loopers_to_regenerate = [false; NUM_LOOPERS];
                 for (id, looper) in self.loopers.iter_mut().enumerate() {
                     let was_overdubbing = looper.shared_state.get() == LooperState::Overdubbing;
                     if looper.pending_command {
@@ -1034,6 +1654,7 @@ impl AudioEngine {
                             LooperState::Recording => looper.stop_is_queued = true,
                             LooperState::Empty | LooperState::Armed => {
                                 looper.audio.clear();
+                                looper.seam.forget();
                                 looper.playhead = 0;
                                 looper.cycles_recorded = 0;
                                 looper.high_res_summary.clear();
@@ -1056,11 +1677,16 @@ impl AudioEngine {
                         }
                         looper.pending_command = false;
                     }
-                    if was_overdubbing {
-                        loopers_to_regenerate.push(id);
+                    // A finished overdub pass (switched off, or stopped at the loop's end) has
+                    // its seam faded again; the fade waits until then so it happens only once.
+                    let overdub_ended = looper.seam.is_dirty()
+                        && looper.shared_state.get() != LooperState::Overdubbing;
+                    if was_overdubbing || overdub_ended {
+                        loopers_to_regenerate[id] = true;
                     }
                 }
-                let mut loopers_to_clear = Vec::new();
+                let mut loopers_to_clear = [false; NUM_LOOPERS];
+                let mut loopers_to_fade = [false; NUM_LOOPERS];
                 for (id, looper) in self.loopers.iter_mut().enumerate() {
                     if looper.stop_is_queued && looper.shared_state.get() == LooperState::Recording
                     {
@@ -1075,24 +1701,34 @@ impl AudioEngine {
                         let final_len = transport_len * (looper.cycles_recorded as usize);
                         if final_len > 0 {
                             looper.audio.resize(final_len, 0.0);
+                            // Latency compensation: the first samples were heard before the
+                            // loop start, so they move round to the wrap.
+                            looper.audio.rotate_left(input_latency_samples % final_len);
                             looper.shared_state.set(LooperState::Playing);
                             looper.playhead = 0;
                             looper
                                 .shared_state
                                 .set_length_in_cycles(looper.cycles_recorded);
                             looper.shared_state.set_playhead(0);
-                            loopers_to_regenerate.push(id);
+                            loopers_to_regenerate[id] = true;
+                            loopers_to_fade[id] = true;
                         } else {
-                            loopers_to_clear.push(id);
+                            loopers_to_clear[id] = true;
                         }
                         looper.stop_is_queued = false;
                     }
                 }
-                for id in loopers_to_regenerate {
+                for id in (0..NUM_LOOPERS).filter(|&id| loopers_to_regenerate[id]) {
+                    let looper = &mut self.loopers[id];
+                    if loopers_to_fade[id] {
+                        looper.seam.crossfade(&mut looper.audio, self.loop_crossfade_samples);
+                    } else if looper.shared_state.get() != LooperState::Overdubbing {
+                        looper.seam.refade(&mut looper.audio);
+                    }
                     self.regenerate_high_res_summary(id);
                     self.update_visual_summary(id);
                 }
-                for id in loopers_to_clear {
+                for id in (0..NUM_LOOPERS).filter(|&id| loopers_to_clear[id]) {
                     self.clear_looper(id);
                 }
                 for looper in self.loopers.iter_mut() {
@@ -1108,7 +1744,9 @@ impl AudioEngine {
                 }
             }
 
-            if transport_len == 0 {
+            // Free-running first loop: its length becomes the transport length. Never taken
+            // while the tempo lock is engaged, since that requires an established length.
+            if transport_len == 0 && !self.tempo_lock_engaged() {
                 let mut looper_id_to_process = None;
                 for id in 0..self.loopers.len() {
                     if self.loopers[id].pending_command
@@ -1119,49 +1757,99 @@ impl AudioEngine {
                     }
                 }
                 if let Some(id) = looper_id_to_process {
-                    let looper = &mut self.loopers[id];
-                    let mut new_len = looper.audio.len();
+                    let recorded_len = self.loopers[id].audio.len();
+                    let mut new_len = recorded_len;
                     if new_len > 0 {
-                        if looper.samples_since_high_res_update > 0 {
-                            looper
-                                .high_res_summary
-                                .push(looper.peak_since_high_res_update);
-                            looper.peak_since_high_res_update = 0.0;
-                            looper.samples_since_high_res_update = 0;
-                        }
-
                         if self.bpm_rounding {
                             let bpm = (self.sample_rate * 60.0 * 4.0) / new_len as f32;
                             let rounded_bpm = bpm.round();
                             new_len = ((self.sample_rate * 60.0 * 4.0) / rounded_bpm) as usize;
-                            looper.audio.resize(new_len, 0.0);
+                            self.loopers[id].audio.resize(new_len, 0.0);
                         }
                         self.transport_len_samples.store(new_len, Ordering::Relaxed);
                         self.master_looper_index.store(id, Ordering::Relaxed);
                         transport_len = new_len;
-                        looper.shared_state.set(LooperState::Playing);
-                        looper.playhead = 0;
-                        looper.cycles_recorded = 1;
-                        looper.shared_state.set_length_in_cycles(1);
-                        looper.shared_state.set_playhead(0);
-                        looper.pending_command = false;
-                        self.regenerate_high_res_summary(id);
-                        self.update_visual_summary(id);
+                        self.close_free_running_loop(id, input_latency_samples);
+
+                        // Under multi-arm, other loopers can still be recording the same take.
+                        // Their last samples line up with this loop's, and they either close
+                        // now if they were pressed in the same block, or keep recording on the
+                        // new transport with their first cycle already complete.
+                        for other in 0..NUM_LOOPERS {
+                            let looper = &mut self.loopers[other];
+                            if other == id || looper.shared_state.get() != LooperState::Recording {
+                                continue;
+                            }
+                            let len = looper.audio.len();
+                            if len > recorded_len {
+                                looper.audio.drain(..len - recorded_len);
+                            } else {
+                                looper.audio.resize(recorded_len, 0.0);
+                                looper.audio.rotate_right(recorded_len - len);
+                            }
+                            looper.audio.resize(new_len, 0.0);
+                            if looper.pending_command {
+                                self.close_free_running_loop(other, input_latency_samples);
+                            } else {
+                                looper.cycles_recorded = 2;
+                                self.regenerate_high_res_summary(other);
+                            }
+                        }
                     }
                 }
             }
 
-            let sampler_is_active = self.sampler_is_active.load(Ordering::Relaxed);
+            let sampler_is_active = !safe_mode && self.sampler_is_active.load(Ordering::Relaxed);
             let audio_input_is_armed = self.audio_input_is_armed.load(Ordering::Relaxed);
-            let audio_input_is_monitored = self.audio_input_is_monitored.load(Ordering::Relaxed);
 
             let mut raw_sampler_output = 0.0;
             if sampler_is_active {
                 for (pad_idx, pad) in self.sampler_pads.iter_mut().enumerate() {
-                    if pad.amp_adsr.state != crate::synth::AdsrState::Idle {
+                    // --- Note Repeat: retrigger held pads on the transport grid ---
+                    if pad.is_held && transport_is_playing && musical_bar_len > 0 {
+                        if let Some(divisions) = pad.fx.note_repeat.divisions_per_bar() {
+                            let interval = (musical_bar_len / divisions).max(1);
+                            if bar_position.is_multiple_of(interval) {
+                                let (delay, velocity_scale) = groove_offset(
+                                    &self.pad_groove,
+                                    &mut self.groove_rng,
+                                    bar_position / interval,
+                                    interval,
+                                    self.sample_rate,
+                                );
+                                let volume = (pad.volume * velocity_scale).clamp(0.0, 1.0);
+                                pad.pending_trigger = Some((delay, volume));
+                            }
+                        }
+                    }
+                    // --- Launch quantize: fire held-back live hits on the grid ---
+                    if let Some(volume) = pad.pending_launch {
+                        let on_grid = launch_interval
+                            .is_none_or(|interval| bar_position.is_multiple_of(interval));
+                        if on_grid {
+                            pad.pending_launch = None;
+                            pad.trigger(volume, self.sample_rate);
+                            self.pad_event_producer.push(pad_idx).ok();
+                            // The note was already released while it waited.
+                            if !pad.is_held {
+                                pad.note_off();
+                            }
+                        }
+                    }
+                    if let Some((remaining, volume)) = pad.pending_trigger {
+                        if remaining == 0 {
+                            pad.pending_trigger = None;
+                            pad.trigger(volume, self.sample_rate);
+                            self.pad_event_producer.push(pad_idx).ok();
+                        } else {
+                            pad.pending_trigger = Some((remaining - 1, volume));
+                        }
+                    }
+
+                    if pad.is_sounding() {
                         playing_mask |= 1 << pad_idx;
 
-                        if pad.playhead as usize >= pad.audio.len() {
+                        if pad.is_finished() {
                             if pad.amp_adsr.state != crate::synth::AdsrState::Release {
                                 pad.amp_adsr.note_off();
                             }
@@ -1169,26 +1857,12 @@ impl AudioEngine {
 
                         let rate = 2.0_f32.powf(pad.fx.pitch_semitones / 12.0);
 
-                        let dry_sample = if (pad.playhead as usize) < pad.audio.len() {
-                            let p_floor = pad.playhead.floor();
-                            let p_fract = pad.playhead - p_floor;
-                            let index0 = p_floor as usize;
-                            let index1 = index0 + 1;
-
-                            let sample0 = pad.audio[index0];
-                            let sample1 = if index1 < pad.audio.len() {
-                                pad.audio[index1]
-                            } else {
-                                0.0
-                            };
-
-                            sample0 + p_fract * (sample1 - sample0)
-                        } else {
-                            0.0
-                        };
+                        let dry_sample = pad.sample_at_playhead() * pad.declick_gain();
 
                         let adsr_gain = pad.amp_adsr.process();
-                        let mut amp_sample = dry_sample * adsr_gain * pad.volume * pad.fx.volume;
+                        let tail_sample = pad.process_tails(rate);
+                        let mut amp_sample =
+                            (dry_sample * adsr_gain * pad.volume + tail_sample) * pad.fx.volume;
 
                         if pad.fx.distortion_amount > 0.0 {
                             let drive = 1.0 + pad.fx.distortion_amount * 20.0;
@@ -1219,9 +1893,7 @@ impl AudioEngine {
 
                         raw_sampler_output += amp_sample * (1.0 - pad.fx.reverb_mix) + wet_sample;
 
-                        if (pad.playhead as usize) < pad.audio.len() {
-                            pad.playhead += rate;
-                        }
+                        pad.advance(rate);
                     }
                 }
             }
@@ -1237,7 +1909,7 @@ impl AudioEngine {
             let final_synth_output = summed_engine_output * synth_master_vol_f32;
 
             let mut final_sampler_output = raw_sampler_output;
-            if let Some(rack) = &mut self.sampler_fx_rack {
+            if let Some(rack) = self.sampler_fx_rack.as_mut().filter(|_| !safe_mode) {
                 let mut buffer = [final_sampler_output];
                 rack.process_buffer(&mut buffer);
                 final_sampler_output = buffer[0];
@@ -1245,32 +1917,41 @@ impl AudioEngine {
             sampler_peak_buffer = sampler_peak_buffer.max(final_sampler_output.abs());
             final_sampler_output *= sampler_vol_f32;
 
-            let final_atmo_output = self.atmo_buffer[i] * atmo_master_vol_f32;
-            atmo_peak_buffer = atmo_peak_buffer.max(final_atmo_output.abs());
-
-            let mic_input = mic_buffer[i];
-
-            let record_input = {
-                let mut total = 0.0;
-                if audio_input_is_armed {
-                    total += mic_input;
-                }
-                total += final_synth_output;
-                if sampler_is_active {
-                    total += final_sampler_output;
-                }
-                total
+            let internal_record_input = if sampler_is_active {
+                final_synth_output + final_sampler_output
+            } else {
+                final_synth_output
+            };
+            let delay_len = self.internal_record_delay.len();
+            self.internal_record_delay[self.internal_record_delay_pos] = internal_record_input;
+            let delayed_internal_input = self.internal_record_delay
+                [(self.internal_record_delay_pos + delay_len - input_latency_samples) % delay_len];
+            self.internal_record_delay_pos = (self.internal_record_delay_pos + 1) % delay_len;
+
+            let record_input = if audio_input_is_armed {
+                mic_input + delayed_internal_input
+            } else {
+                delayed_internal_input
             };
 
-            let mut looper_output = 0.0;
+            // Tracks only read (and overdub) here; their FX racks and the mix run in a second
+            // pass so the racks can process the whole buffer in parallel.
+            let mut looper_active_mask = 0u16;
             for (id, looper) in self.loopers.iter_mut().enumerate() {
                 let state = looper.shared_state.get();
                 match state {
                     LooperState::Armed => {
-                        if transport_is_playing
-                            && transport_len == 0
-                            && record_input.abs() > LOOPER_ARM_THRESHOLD
-                        {
+                        let transition = if record_input.abs() > LOOPER_ARM_THRESHOLD {
+                            next_looper_state(
+                                state,
+                                LooperEvent::InputDetected,
+                                transport_is_playing,
+                                transport_len,
+                            )
+                        } else {
+                            LooperTransition::Ignore
+                        };
+                        if let LooperTransition::Set(LooperState::Recording) = transition {
                             looper.shared_state.set(LooperState::Recording);
                             looper.cycles_recorded = 1;
                         }
@@ -1294,28 +1975,37 @@ impl AudioEngine {
                     }
                     LooperState::Playing | LooperState::Overdubbing => {
                         if !looper.audio.is_empty() {
-                            let mut sample_to_play = looper.audio[looper.playhead];
-                            if let Some(rack) = &mut self.looper_fx_racks[id] {
-                                let mut buffer = [sample_to_play];
-                                rack.process_buffer(&mut buffer);
-                                sample_to_play = buffer[0];
-                            }
-
-                            buffer_peaks[id] = buffer_peaks[id].max(sample_to_play.abs());
                             let track_state = &mixer_state.tracks[id];
-                            let is_audible = if is_any_soloed {
-                                track_state.is_soloed
+                            let loop_len = looper.audio.len();
+                            // The nudge shifts where the loop is read (and overdubbed) so the
+                            // layer sits earlier or later against the transport.
+                            let nudged_index = (looper.playhead as i64
+                                - track_state.nudge_samples as i64)
+                                .rem_euclid(loop_len as i64)
+                                as usize;
+                            // Reverse mirrors the read while the playhead keeps running forward,
+                            // so the loop stays in sync and can be flipped at any moment.
+                            let read_index = if track_state.is_reversed {
+                                loop_len - 1 - nudged_index
                             } else {
-                                !track_state.is_muted
+                                nudged_index
                             };
-                            if transport_is_playing && is_audible {
-                                looper_output += sample_to_play * track_state.volume;
-                            }
+                            self.looper_buffers[id][i] = looper.audio[read_index];
+                            looper_active_mask |= 1 << id;
+
                             if state == LooperState::Overdubbing && transport_is_playing {
-                                looper.audio[looper.playhead] =
-                                    (looper.audio[looper.playhead] + record_input)
-                                        .clamp(-1.0, 1.0);
-                                let chunk_index = looper.playhead / HIGH_RES_CHUNK_SIZE;
+                                // Overdubs land where the loop was playing when the input
+                                // was actually played, one safety buffer ago.
+                                let latency = input_latency_samples % loop_len;
+                                let write_index = if track_state.is_reversed {
+                                    (read_index + latency) % loop_len
+                                } else {
+                                    (read_index + loop_len - latency) % loop_len
+                                };
+                                looper.audio[write_index] =
+                                    (looper.audio[write_index] + record_input).clamp(-1.0, 1.0);
+                                looper.seam.overdub(write_index, record_input);
+                                let chunk_index = write_index / HIGH_RES_CHUNK_SIZE;
                                 looper.dirty_summary_chunks.insert(chunk_index);
                                 looper.samples_since_visual_update += 1;
                             }
@@ -1334,59 +2024,209 @@ impl AudioEngine {
                     _ => {}
                 }
             }
-
-            let live_sampler_output = if sampler_is_active {
+            self.looper_active_masks[i] = looper_active_mask;
+            self.synth_mix_buffer[i] = final_synth_output;
+            self.sampler_mix_buffer[i] = if sampler_is_active {
                 final_sampler_output
             } else {
                 0.0
             };
-            let monitored_input = if audio_input_is_monitored {
+
+            if transport_len > 0 && transport_is_playing {
+                transport_playhead = (transport_playhead + 1) % transport_len;
+            }
+        }
+
+        if !safe_mode {
+            self.process_looper_fx_racks(num_samples);
+        }
+
+        // Tracks are mixed into the master, sub-buses and sends for the whole buffer before
+        // the bus and send racks run.
+        for i in 0..num_samples {
+            let mut looper_output = [0.0f32; 2];
+            let mut bus_inputs = [0.0f32; NUM_SUB_BUSES];
+            let mut send_inputs = [0.0f32; NUM_SEND_BUSES];
+            let mut looper_stem_samples = [0.0f32; NUM_LOOPERS];
+            // Targets are ducked by the envelope as of the previous sample, since the source
+            // may come later in the track order.
+            let duck_gain = sidechain.duck_gain(self.sidechain_envelope);
+            let mut sidechain_source_sample = 0.0;
+            let looper_active_mask = self.looper_active_masks[i];
+            for (id, looper) in self.loopers.iter_mut().enumerate() {
+                if looper_active_mask & (1 << id) == 0 {
+                    continue;
+                }
+                let track_state = &mixer_state.tracks[id];
+                let sample_to_play = self.looper_buffers[id][i];
+
+                let meter_level = track_state
+                    .meter_point
+                    .meter_level(sample_to_play, track_state.volume);
+                buffer_peaks[id] = buffer_peaks[id].max(meter_level);
+                let target_gain = if track_state.is_audible(is_any_soloed) {
+                    1.0
+                } else {
+                    0.0
+                };
+                looper.audible_gain = if looper.audible_gain < target_gain {
+                    (looper.audible_gain + mute_fade_step).min(target_gain)
+                } else {
+                    (looper.audible_gain - mute_fade_step).max(target_gain)
+                };
+                if transport_is_playing && looper.audible_gain > 0.0 {
+                    let mut contribution =
+                        sample_to_play * track_state.volume * looper.audible_gain;
+                    if sidechain.source == Some(id) {
+                        sidechain_source_sample = contribution;
+                    }
+                    if sidechain.is_target(id) {
+                        contribution *= duck_gain;
+                    }
+                    match track_state.bus.filter(|&b| b < NUM_SUB_BUSES) {
+                        Some(bus) => bus_inputs[bus] += contribution,
+                        None => {
+                            let (left, right) = pan_gains(track_state.pan);
+                            looper_output[0] += contribution * left;
+                            looper_output[1] += contribution * right;
+                        }
+                    }
+                    looper_stem_samples[id] = contribution;
+                    for (send, send_input) in send_inputs.iter_mut().enumerate() {
+                        *send_input += contribution * track_state.send_level(send);
+                    }
+                }
+            }
+
+            if sidechain.source.is_some() {
+                self.sidechain_envelope =
+                    self.sidechain_follower.get_mod_output(sidechain_source_sample);
+            }
+
+            if let Some(stems) = &mut self.stem_recording {
+                let (synth_stem, sampler_stem) = (self.synth_mix_buffer[i], self.sampler_mix_buffer[i]);
+                if !stems.limit_reached
+                    && !stems.push_frame(&looper_stem_samples, synth_stem, sampler_stem)
+                {
+                    self.stem_recording_limit_reached.store(true, Ordering::Relaxed);
+                }
+            }
+
+            self.looper_output_buffer[i] = looper_output;
+            for (buffer, input) in self.bus_buffers.iter_mut().zip(bus_inputs) {
+                buffer[i] = input;
+            }
+            for (buffer, input) in self.send_buffers.iter_mut().zip(send_inputs) {
+                buffer[i] = input;
+            }
+        }
+
+        // --- Sub-buses and send returns: one shared rack each, run over the whole buffer ---
+        if !safe_mode {
+            for (rack, buffer) in self.bus_fx_racks.iter_mut().zip(self.bus_buffers.iter_mut()) {
+                if let Some(rack) = rack {
+                    rack.process_buffer(&mut buffer[..num_samples]);
+                }
+            }
+            for (rack, buffer) in self.send_fx_racks.iter_mut().zip(self.send_buffers.iter_mut()) {
+                if let Some(rack) = rack {
+                    rack.process_buffer(&mut buffer[..num_samples]);
+                }
+            }
+        }
+
+        for (i, &mic_input) in mic_buffer[..num_samples].iter().enumerate() {
+            let final_synth_output = self.synth_mix_buffer[i];
+            let live_sampler_output = self.sampler_mix_buffer[i];
+            let final_atmo_output = self.atmo_buffer[i] * atmo_master_vol_f32;
+            atmo_peak_buffer = atmo_peak_buffer.max(final_atmo_output.abs());
+
+            let mut looper_output = self.looper_output_buffer[i];
+            for (bus_state, buffer) in mixer_state.buses.iter().zip(&self.bus_buffers) {
+                if !bus_state.is_muted {
+                    looper_output[0] += buffer[i] * bus_state.volume;
+                    looper_output[1] += buffer[i] * bus_state.volume;
+                }
+            }
+            // Sends only return through a rack; without one they are left out.
+            for ((return_state, buffer), rack) in
+                mixer_state.send_returns.iter().zip(&self.send_buffers).zip(&self.send_fx_racks)
+            {
+                if rack.is_some() && !safe_mode && !return_state.is_muted {
+                    looper_output[0] += buffer[i] * return_state.volume;
+                    looper_output[1] += buffer[i] * return_state.volume;
+                }
+            }
+
+            let monitored_input = if self.audio_input_is_monitored.load(Ordering::Relaxed) {
                 mic_input
             } else {
                 0.0
             };
 
-            let mut pre_master_mix = looper_output
-                + final_synth_output
-                + live_sampler_output
-                + monitored_input
-                + final_atmo_output;
+            // Live sources are never gated by the transport unless monitoring is turned off
+            // while stopped.
+            let live_output = if transport_is_playing || self.monitor_while_stopped {
+                final_synth_output + live_sampler_output + monitored_input
+            } else {
+                0.0
+            };
+
+            // Everything except the panned loopers is still mono and sits in the centre.
+            let centre_mix = live_output + final_atmo_output;
+            let mut pre_master_mix = [looper_output[0] + centre_mix, looper_output[1] + centre_mix];
 
-            if let Some(rack) = &mut self.master_fx_rack {
-                let mut buffer = [pre_master_mix];
+            // The master rack is mono, so it processes the mid signal and the side passes dry.
+            if let Some(rack) = self.master_fx_rack.as_mut().filter(|_| !safe_mode) {
+                let side = (pre_master_mix[0] - pre_master_mix[1]) * 0.5;
+                let mut buffer = [(pre_master_mix[0] + pre_master_mix[1]) * 0.5];
                 rack.process_buffer(&mut buffer);
-                pre_master_mix = buffer[0];
+                pre_master_mix = [buffer[0] + side, buffer[0] - side];
             }
 
-            let metronome_state = &mixer_state.metronome;
-            let mut metronome_sample = 0.0;
-            if !metronome_state.is_muted && metronome_state.volume > 0.0 {
-                metronome_sample = self.metronome.process() * metronome_state.volume;
-            }
+            let metronome_sample = self.metronome_buffer[i];
+            let audition_sample = self.audition.process();
 
-            pre_master_mix += metronome_sample;
+            pre_master_mix[0] += metronome_sample + audition_sample;
+            pre_master_mix[1] += metronome_sample + audition_sample;
 
-            master_peak_buffer = master_peak_buffer.max(pre_master_mix.abs());
+            master_peak_buffer = master_peak_buffer
+                .max(pre_master_mix[0].abs())
+                .max(pre_master_mix[1].abs());
             let master_vol = self.master_volume.load(Ordering::Relaxed) as f32 / 1_000_000.0;
-            let final_mix = pre_master_mix * master_vol;
+            let final_mix = pre_master_mix.map(|s| s * master_vol);
 
-            if self.limiter_is_active.load(Ordering::Relaxed) {
+            if !safe_mode && self.limiter_is_active.load(Ordering::Relaxed) {
                 let threshold =
                     self.limiter_threshold.load(Ordering::Relaxed) as f32 / 1_000_000.0;
-                output_buffer[i] = self.limiter.process(final_mix, threshold, release_coeffs);
+                let limited = self.limiter.process(final_mix, threshold, release_coeffs);
+                self.output_buffer[i] = limited.map(|s| self.output_clip_mode.apply(s));
             } else {
                 self.limiter
                     .gain_reduction_db
                     .store(0, Ordering::Relaxed);
-                output_buffer[i] = final_mix.clamp(-1.0, 1.0);
+                self.output_buffer[i] = final_mix.map(|s| self.output_clip_mode.apply(s));
             }
 
-            if transport_len > 0 && transport_is_playing {
-                transport_playhead = (transport_playhead + 1) % transport_len;
+            if loudness_meter_enabled {
+                self.loudness_meter.process(self.output_buffer[i]);
+            }
+            self.spectrum_analyzer.process(self.output_buffer[i]);
+            let [left, right] = self.output_buffer[i];
+            correlation_sums[0] += left * right;
+            correlation_sums[1] += left * left;
+            correlation_sums[2] += right * right;
+        }
+
+        if let Some(snapshot) = &mut self.loop_snapshot {
+            let loopers = &self.loopers;
+            if snapshot.copy_chunk(|i| &loopers[i].audio) {
+                self.loop_snapshot = None;
             }
         }
 
         for id in 0..self.loopers.len() {
+            self.loopers[id].shared_state.set_audio_len(self.loopers[id].audio.len());
             if self.loopers[id].samples_since_visual_update >= 256 {
                 let state = self.loopers[id].shared_state.get();
                 if state == LooperState::Overdubbing {
@@ -1398,7 +2238,7 @@ impl AudioEngine {
         }
 
         if let Some(rec_buffer) = &mut self.output_recording_buffer {
-            rec_buffer.extend_from_slice(&output_buffer);
+            rec_buffer.extend(self.output_buffer[..num_samples].iter().flatten());
         }
         for i in 0..2 {
             self.engine_peak_meters[i].store(
@@ -1425,6 +2265,16 @@ impl AudioEngine {
             (master_peak_buffer * u32::MAX as f32) as u32,
             Ordering::Relaxed,
         );
+        let energy = correlation_sums[1] * correlation_sums[2];
+        let correlation = if energy > 1e-12 {
+            (correlation_sums[0] / energy.sqrt()).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        self.master_correlation.store(
+            ((correlation + 1.0) * PARAM_SCALER) as u32,
+            Ordering::Relaxed,
+        );
         for i in 0..NUM_LOOPERS {
             self.peak_meters[i].store(
                 (buffer_peaks[i].clamp(0.0, 1.0) * u32::MAX as f32) as u32,
@@ -1439,26 +2289,51 @@ impl AudioEngine {
                 .store((load_ratio * 1000.0) as u32, Ordering::Relaxed);
         }
 
-        output_buffer
+        &self.output_buffer[..num_samples]
     }
 
+    /// Reads a session loop as mono at `target_sr`. Loops edited in another program may come
+    /// back with more channels, another bit depth or another rate, so none of those are
+    /// assumed: hound reads any WAV it understands losslessly, rodio's decoder handles the
+    /// rest, and multichannel audio is averaged down to mono.
     fn load_and_resample_wav_for_session(
         path: &Path,
-        source_sr: f32,
+        session_sr: f32,
         target_sr: f32,
     ) -> Result<Vec<f32>> {
-        let file = BufReader::new(File::open(path)?);
-        let reader = hound::WavReader::new(file)?;
-        let spec = reader.spec();
-        if spec.channels != 1 {
-            return Err(anyhow::anyhow!("Expected mono WAV file for session loop"));
-        }
+        let (samples, channels, file_sr) = match hound::WavReader::open(path) {
+            Ok(reader) => {
+                let spec = reader.spec();
+                (read_samples_as_f32(reader), spec.channels, spec.sample_rate)
+            }
+            Err(wav_error) => {
+                let decoder = Decoder::new(BufReader::new(File::open(path)?))
+                    .map_err(|e| anyhow::anyhow!("{} (as WAV: {})", e, wav_error))?;
+                let (channels, sample_rate) = (decoder.channels(), decoder.sample_rate());
+                let samples = decoder.map(|s| s as f32 / i16::MAX as f32).collect();
+                (samples, channels, sample_rate)
+            }
+        };
+        let channels = channels.max(1) as usize;
+        let mono_samples: Vec<f32> = if channels > 1 {
+            samples
+                .chunks_exact(channels)
+                .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+                .collect()
+        } else {
+            samples
+        };
 
-        let mono_samples: Vec<f32> = reader
-            .into_samples::<i16>()
-            .filter_map(Result::ok)
-            .map(|s| s as f32 / i16::MAX as f32)
-            .collect();
+        // The file's own rate wins if the loop was re-saved at a different one.
+        let source_sr = file_sr as f32;
+        if (source_sr - session_sr).abs() > 1e-3 {
+            println!(
+                "Session loop {} is at {} Hz, not the session's {} Hz",
+                path.display(),
+                source_sr,
+                session_sr
+            );
+        }
 
         if (source_sr - target_sr).abs() > 1e-3 {
             println!(
@@ -1545,4 +2420,971 @@ impl AudioEngine {
             _ => {}
         }
     }
-}
\ No newline at end of file
+}
+
+/// Runs `rack` over the stretches of `buffer` where track `id` was playing, as marked in
+/// `active_masks`.
+fn process_rack_where_active(rack: &mut FxRack, buffer: &mut [f32], active_masks: &[u16], id: usize) {
+    let is_active = |i: usize| active_masks[i] & (1 << id) != 0;
+    let mut start = 0;
+    while start < buffer.len() {
+        if !is_active(start) {
+            start += 1;
+            continue;
+        }
+        let mut end = start + 1;
+        while end < buffer.len() && is_active(end) {
+            end += 1;
+        }
+        rack.process_buffer(&mut buffer[start..end]);
+        start = end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mixer::MeterPoint;
+    use crate::synth::{EngineParamsUnion, LfoWaveform, SamplerParams, WavetableParams};
+    use crate::wavetable_engine::WavetableSet;
+    use ringbuf::HeapRb;
+    use std::time::Duration;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    fn sampler_params() -> EngineParamsUnion {
+        EngineParamsUnion::Sampler(SamplerParams(
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        ))
+    }
+
+    fn sampler_engine_params() -> EngineWithVolumeAndPeak {
+        (Arc::new(AtomicU32::new(1_000_000)), Arc::default(), sampler_params())
+    }
+
+    fn wavetable_params() -> EngineParamsUnion {
+        EngineParamsUnion::Wavetable(WavetableParams(
+            Arc::new(RwLock::new(WavetableSet::new_basic())),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        ))
+    }
+
+    /// An engine on fresh shared state, with the producer its commands are read from.
+    fn test_engine() -> (AudioEngine, HeapProducer<AudioCommand>) {
+        let (engine, commands, _pad_events) = test_engine_with_pad_events();
+        (engine, commands)
+    }
+
+    /// Like `test_engine`, also returning the consumer of pad trigger events.
+    fn test_engine_with_pad_events(
+    ) -> (AudioEngine, HeapProducer<AudioCommand>, HeapConsumer<usize>) {
+        let (command_producer, command_consumer) = HeapRb::<AudioCommand>::new(64).split();
+        let (_input_producer, input_consumer) = HeapRb::<f32>::new(16).split();
+        let (pad_event_producer, pad_event_consumer) = HeapRb::<usize>::new(32).split();
+        let midi_cc_values = Arc::new(std::array::from_fn(|_| {
+            std::array::from_fn(|_| AtomicU32::new(0))
+        }));
+        let (engine, _looper_states) = AudioEngine::new(
+            command_consumer,
+            input_consumer,
+            pad_event_producer,
+            SAMPLE_RATE,
+            Arc::default(),
+            Arc::default(),
+            Arc::default(),
+            Arc::default(),
+            Arc::default(),
+            Arc::default(),
+            Arc::default(),
+            Arc::default(),
+            Arc::default(),
+            Arc::new(AtomicU32::new(1_000_000)),
+            Arc::default(),
+            Arc::new(AtomicU32::new(1_000_000)),
+            Arc::new(AtomicBool::new(true)),
+            Arc::new(AtomicU32::new(1_000_000)),
+            Arc::new(AtomicU32::new(80_000)),
+            Arc::new(AtomicU32::new(1_000_000)),
+            Arc::default(),
+            Arc::default(),
+            Arc::new(AtomicU32::new(1_000_000)),
+            Arc::default(),
+            [sampler_engine_params(), sampler_engine_params()],
+            false,
+            Arc::new(AtomicU32::new(1_000_000)),
+            Arc::new(AtomicBool::new(true)),
+            Arc::default(),
+            Arc::default(),
+            midi_cc_values,
+            BTreeMap::new(),
+            Arc::new(AtomicU32::new(1_000_000)),
+            Default::default(),
+            Arc::default(),
+            Arc::default(),
+        );
+        (engine, command_producer, pad_event_consumer)
+    }
+
+    fn reverb_rack() -> FxRack {
+        let preset = fx::FxPreset {
+            chain: vec![fx::FxChainLink::new(fx::FxComponentType::Reverb)],
+            ..Default::default()
+        };
+        FxRack::new(&preset, Arc::new(AtomicU32::new(1_000_000)), SAMPLE_RATE)
+    }
+
+    #[test]
+    fn soft_reset_clears_feedback_state_but_keeps_loaded_audio() {
+        let (mut engine, _commands) = test_engine();
+        let mut rack = reverb_rack();
+        let mut impulse = vec![0.0; 4096];
+        impulse[0] = 1.0;
+        rack.process_buffer(&mut impulse);
+        let mut tail = vec![0.0; 64];
+        rack.process_buffer(&mut tail);
+        assert!(tail.iter().any(|&s| s != 0.0));
+        engine.master_fx_rack = Some(rack);
+        engine.limiter.process([1.0, 1.0], 0.5, 0.999);
+        assert!(engine.limiter.gain_reduction_db.load(Ordering::Relaxed) > 0);
+
+        let loop_audio = vec![0.25; 1024];
+        engine.loopers[0].audio = loop_audio.clone();
+        let pad_audio = Arc::new(vec![0.5; 512]);
+        engine.sampler_pads[0].audio = pad_audio.clone();
+
+        engine.soft_reset();
+
+        let mut silence = vec![0.0; 4096];
+        engine.master_fx_rack.as_mut().unwrap().process_buffer(&mut silence);
+        assert!(silence.iter().all(|&s| s == 0.0), "reverb tail survived the reset");
+        assert_eq!(engine.limiter.gain_reduction_db.load(Ordering::Relaxed), 0);
+        assert_eq!(engine.limiter.process([0.1, 0.1], 0.5, 0.999), [0.1, 0.1]);
+        assert_eq!(engine.loopers[0].audio, loop_audio);
+        assert_eq!(engine.sampler_pads[0].audio, pad_audio);
+    }
+
+    fn change_engine_type(commands: &mut HeapProducer<AudioCommand>, params: EngineParamsUnion) {
+        let sent = commands.push(AudioCommand::ChangeEngineType {
+            engine_index: 0,
+            volume: Arc::new(AtomicU32::new(1_000_000)),
+            peak_meter: Arc::default(),
+            params,
+        });
+        assert!(sent.is_ok());
+    }
+
+    /// An engine with a low wavetable note sounding in slot 0.
+    fn engine_with_sounding_wavetable() -> (AudioEngine, HeapProducer<AudioCommand>) {
+        let (mut engine, commands) = test_engine();
+        engine.synth_is_active.store(true, Ordering::Relaxed);
+        let wavetable = Synth::create_engine(SAMPLE_RATE, wavetable_params());
+        engine.synth.replace_engine(0, wavetable);
+        engine.synth.note_on(33, 127);
+        (engine, commands)
+    }
+
+    /// Runs `blocks` blocks and appends what engine slot 0 produced to `output`.
+    fn render_engine_0(engine: &mut AudioEngine, blocks: usize, output: &mut Vec<f32>) {
+        const BLOCK: usize = 256;
+        for _ in 0..blocks {
+            engine.handle_commands();
+            engine.process_buffer(&mut [0.0; BLOCK]);
+            output.extend_from_slice(&engine.engine_0_buffer[..BLOCK]);
+        }
+    }
+
+    fn largest_step(samples: &[f32]) -> f32 {
+        samples
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn engine_swap_is_ramped_not_cut() {
+        let (mut engine, mut commands) = engine_with_sounding_wavetable();
+        let mut before = Vec::new();
+        render_engine_0(&mut engine, 40, &mut before);
+        let level = before.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(level > 0.1, "the wavetable note should be sounding");
+
+        change_engine_type(&mut commands, sampler_params());
+        let mut around = before[before.len() - 1..].to_vec();
+        render_engine_0(&mut engine, 20, &mut around);
+
+        assert!(largest_step(&around) <= largest_step(&before) * 1.5 + 1e-3);
+        assert!(around[around.len() - 256..].iter().all(|&s| s.abs() < 1e-6));
+    }
+
+    #[test]
+    fn engine_swap_without_fade_is_a_cut() {
+        // Control for the test above: the same swap with the fade turned off jumps.
+        let (mut engine, mut commands) = engine_with_sounding_wavetable();
+        assert!(commands.push(AudioCommand::SetEngineSwapFade(0.0)).is_ok());
+        let mut before = Vec::new();
+        render_engine_0(&mut engine, 40, &mut before);
+
+        change_engine_type(&mut commands, sampler_params());
+        let mut around = before[before.len() - 1..].to_vec();
+        render_engine_0(&mut engine, 20, &mut around);
+
+        assert!(largest_step(&around) > largest_step(&before) * 1.5 + 1e-3);
+    }
+
+    #[test]
+    fn second_swap_waits_for_the_running_fade() {
+        let (mut engine, mut commands) = engine_with_sounding_wavetable();
+        let mut before = Vec::new();
+        render_engine_0(&mut engine, 40, &mut before);
+
+        change_engine_type(&mut commands, sampler_params());
+        let mut around = before[before.len() - 1..].to_vec();
+        render_engine_0(&mut engine, 1, &mut around);
+        assert!(engine.outgoing_engines[0].is_some(), "the first fade should still be running");
+        change_engine_type(&mut commands, wavetable_params());
+        render_engine_0(&mut engine, 20, &mut around);
+
+        assert!(largest_step(&around) <= largest_step(&before) * 1.5 + 1e-3);
+        assert!(matches!(engine.synth.engines[0], SynthEngine::Wavetable(_)));
+        assert!(engine.pending_engine_swaps[0].is_none());
+    }
+
+    /// A single cycle of sine, optionally inverted, `len` samples long.
+    fn sine_cycle(len: usize, inverted: bool) -> Vec<f32> {
+        let sign = if inverted { -1.0 } else { 1.0 };
+        (0..len)
+            .map(|i| sign * (i as f32 / len as f32 * std::f32::consts::TAU).sin())
+            .collect()
+    }
+
+    #[test]
+    fn wavetable_swap_under_a_sounding_voice_is_ramped() {
+        let (mut engine, mut commands) = engine_with_sounding_wavetable();
+        let mut before = Vec::new();
+        render_engine_0(&mut engine, 40, &mut before);
+
+        let inverted = sine_cycle(crate::synth::WAVETABLE_SIZE, true);
+        let swap = AudioCommand::SetWavetable {
+            engine_index: 0,
+            slot_index: 0,
+            audio_data: Arc::new(inverted),
+            name: String::new(),
+        };
+        assert!(commands.push(swap).is_ok());
+        let mut around = before[before.len() - 1..].to_vec();
+        render_engine_0(&mut engine, 20, &mut around);
+
+        assert!(largest_step(&around) <= largest_step(&before) * 1.5 + 1e-3);
+    }
+
+    #[test]
+    fn sampler_slot_swap_under_a_sounding_voice_is_ramped() {
+        let (mut engine, mut commands) = test_engine();
+        engine.synth_is_active.store(true, Ordering::Relaxed);
+        // A 440 Hz tone, built from repeated cycles so it has no edges of its own.
+        let tone: Vec<f32> = sine_cycle(100, false).repeat(480);
+        let load = |audio: Vec<f32>| AudioCommand::LoadSampleForSamplerSlot {
+            engine_index: 0,
+            slot_index: 0,
+            audio_data: Arc::new(audio),
+        };
+        assert!(commands.push(load(tone.clone())).is_ok());
+        engine.handle_commands();
+        engine.synth.note_on(24, 127);
+        let mut before = Vec::new();
+        render_engine_0(&mut engine, 40, &mut before);
+        let level = before.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(level > 0.1, "the sampler note should be sounding");
+
+        let inverted: Vec<f32> = tone.iter().map(|s| -s).collect();
+        assert!(commands.push(load(inverted)).is_ok());
+        let mut around = before[before.len() - 1..].to_vec();
+        render_engine_0(&mut engine, 20, &mut around);
+
+        assert!(largest_step(&around) <= largest_step(&before) * 1.5 + 1e-3);
+    }
+
+    /// Plays `num_samples` one sample at a time and returns the sample indices at which
+    /// a pad was triggered.
+    fn pad_trigger_times(
+        engine: &mut AudioEngine,
+        pad_events: &mut HeapConsumer<usize>,
+        num_samples: usize,
+    ) -> Vec<usize> {
+        let mut times = Vec::new();
+        for i in 0..num_samples {
+            engine.process_buffer(&mut [0.0]);
+            while pad_events.pop().is_some() {
+                times.push(i);
+            }
+        }
+        times
+    }
+
+    #[test]
+    fn note_repeat_retriggers_every_sixteenth() {
+        let (mut engine, _commands, mut pad_events) = test_engine_with_pad_events();
+        let bar_len = 4800;
+        engine.transport_len_samples.store(bar_len, Ordering::Relaxed);
+        engine.sampler_is_active.store(true, Ordering::Relaxed);
+        let pad = &mut engine.sampler_pads[0];
+        pad.audio = Arc::new(vec![0.5; 48_000]);
+        pad.fx.note_repeat = crate::sampler::NoteRepeatRate::Sixteenth;
+        pad.is_held = true;
+
+        let times = pad_trigger_times(&mut engine, &mut pad_events, bar_len);
+
+        let interval = bar_len / 16;
+        assert_eq!(times, (0..16).map(|step| step * interval).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn one_shot_pad_is_declicked_at_both_ends() {
+        let (mut engine, mut commands) = test_engine();
+        engine.sampler_is_active.store(true, Ordering::Relaxed);
+        let pad = &mut engine.sampler_pads[0];
+        pad.audio = Arc::new(vec![0.8; 2400]);
+        pad.fx.one_shot = true;
+        let declick_len = pad.declick_samples as usize;
+        let channel = engine.selected_midi_channel.load(Ordering::Relaxed);
+        let hit = MidiMessage {
+            status: 0x90 | channel,
+            data1: 48,
+            data2: 127,
+        };
+        assert!(commands.push(AudioCommand::MidiMessage(hit)).is_ok());
+        engine.handle_commands();
+        let mut output = Vec::new();
+        for _ in 0..3 {
+            engine.process_buffer(&mut [0.0; 1000]);
+            output.extend_from_slice(&engine.sampler_mix_buffer[..1000]);
+        }
+
+        let full = output[1200];
+        assert!(full > 0.1);
+        assert!(output[0].abs() < full * 0.05, "start not ramped: {}", output[0]);
+        assert!(output[2399].abs() < full * 0.05, "end not ramped: {}", output[2399]);
+        // Halfway through each fade the gain is about a half.
+        assert!((output[declick_len / 2] / full - 0.5).abs() < 0.1);
+        assert!((output[2399 - declick_len / 2] / full - 0.5).abs() < 0.1);
+        assert!(output[declick_len..2399 - declick_len]
+            .iter()
+            .all(|&s| (s - full).abs() < 1e-4));
+        assert!(output[2400..].iter().all(|&s| s == 0.0));
+    }
+
+    /// Times of the pad triggers after a live hit on pad 0 arrives 350 samples into the bar.
+    fn live_pad_hit_times(launch_quantize: LaunchQuantize) -> Vec<usize> {
+        let (mut engine, mut commands, mut pad_events) = test_engine_with_pad_events();
+        let bar_len = 4800;
+        engine.transport_len_samples.store(bar_len, Ordering::Relaxed);
+        engine.transport_is_playing.store(true, Ordering::Relaxed);
+        engine.sampler_is_active.store(true, Ordering::Relaxed);
+        engine.sampler_pads[0].audio = Arc::new(vec![0.5; 48_000]);
+        engine.pad_groove.launch_quantize = launch_quantize;
+        assert!(pad_trigger_times(&mut engine, &mut pad_events, 350).is_empty());
+
+        let channel = engine.selected_midi_channel.load(Ordering::Relaxed);
+        let hit = MidiMessage {
+            status: 0x90 | channel,
+            data1: 48,
+            data2: 100,
+        };
+        assert!(commands.push(AudioCommand::MidiMessage(hit)).is_ok());
+        engine.handle_commands();
+        let mut times: Vec<usize> = std::iter::from_fn(|| pad_events.pop()).map(|_| 0).collect();
+        times.extend(pad_trigger_times(&mut engine, &mut pad_events, bar_len));
+        times
+    }
+
+    #[test]
+    fn quantized_pad_hit_fires_on_the_next_grid_step() {
+        assert_eq!(live_pad_hit_times(LaunchQuantize::Off), vec![0]);
+        // The next quarter is at 1200 and the next sixteenth at 600.
+        assert_eq!(live_pad_hit_times(LaunchQuantize::Quarter), vec![850]);
+        assert_eq!(live_pad_hit_times(LaunchQuantize::Sixteenth), vec![250]);
+    }
+
+    /// Handles pending commands, then plays `num_samples` of constant mic input.
+    fn run_samples(engine: &mut AudioEngine, mic_level: f32, num_samples: usize) {
+        const BLOCK: usize = 256;
+        engine.handle_commands();
+        let mut remaining = num_samples;
+        while remaining > 0 {
+            let len = remaining.min(BLOCK);
+            engine.process_buffer(&mut [mic_level; BLOCK][..len]);
+            remaining -= len;
+        }
+    }
+
+    fn press_looper(commands: &mut HeapProducer<AudioCommand>, id: usize) {
+        assert!(commands.push(AudioCommand::LooperPress(id)).is_ok());
+    }
+
+    #[test]
+    fn overdub_held_across_wraps_fades_the_seam_once() {
+        let (mut engine, mut commands) = test_engine();
+        let loop_len = 4800;
+        engine.transport_len_samples.store(loop_len, Ordering::Relaxed);
+        engine.transport_is_playing.store(true, Ordering::Relaxed);
+        engine.audio_input_is_armed.store(true, Ordering::Relaxed);
+        assert!(commands.push(AudioCommand::SetLoopCrossfade(5.0)).is_ok());
+        engine.handle_commands();
+        let looper = &mut engine.loopers[0];
+        looper.audio = (0..loop_len).map(|i| if i < loop_len / 2 { 0.5 } else { -0.5 }).collect();
+        looper.seam.crossfade(&mut looper.audio, engine.loop_crossfade_samples);
+        looper.shared_state.set(LooperState::Playing);
+        let faded = looper.audio.clone();
+
+        press_looper(&mut commands, 0);
+        run_samples(&mut engine, 0.0, loop_len * 4);
+        assert_eq!(engine.loopers[0].shared_state.get(), LooperState::Overdubbing);
+        press_looper(&mut commands, 0);
+        run_samples(&mut engine, 0.0, loop_len);
+
+        assert_eq!(engine.loopers[0].shared_state.get(), LooperState::Playing);
+        assert!(!engine.loopers[0].seam.is_dirty());
+        assert_eq!(engine.loopers[0].audio, faded);
+    }
+
+    /// Records one bar into looper 0 with an impulse 1000 samples into the take, and returns
+    /// where the impulse landed. The compensation is `compensation_ms` when the take starts
+    /// and is changed to `mid_take_ms` before the impulse.
+    fn recorded_impulse_position(compensation_ms: f32, mid_take_ms: f32) -> usize {
+        let (mut engine, mut commands) = test_engine();
+        let bar_len = 4800;
+        engine.transport_len_samples.store(bar_len, Ordering::Relaxed);
+        engine.transport_is_playing.store(true, Ordering::Relaxed);
+        engine.audio_input_is_armed.store(true, Ordering::Relaxed);
+        engine.limiter_is_active.store(false, Ordering::Relaxed);
+        engine
+            .input_latency_compensation_ms
+            .store((compensation_ms * 100.0) as u32, Ordering::Relaxed);
+        press_looper(&mut commands, 0);
+        engine.handle_commands();
+        let mut waited = 0;
+        while engine.loopers[0].shared_state.get() != LooperState::Recording {
+            engine.process_buffer(&mut [0.0]);
+            waited += 1;
+            assert!(waited <= bar_len, "recording never started");
+        }
+        run_samples(&mut engine, 0.0, 500);
+        engine
+            .input_latency_compensation_ms
+            .store((mid_take_ms * 100.0) as u32, Ordering::Relaxed);
+        run_samples(&mut engine, 0.0, 500);
+        engine.process_buffer(&mut [1.0]);
+        press_looper(&mut commands, 0);
+        run_samples(&mut engine, 0.0, bar_len * 2);
+
+        let looper = &engine.loopers[0];
+        assert_eq!(looper.shared_state.get(), LooperState::Playing);
+        assert_eq!(looper.audio.len(), bar_len);
+        let peak = looper.audio.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        looper.audio.iter().position(|s| s.abs() == peak).unwrap()
+    }
+
+    #[test]
+    fn input_latency_compensation_moves_recorded_audio_earlier() {
+        // The sample that started the recording is its first, so the impulse is the 1002nd.
+        assert_eq!(recorded_impulse_position(0.0, 0.0), 1001);
+        // 10 ms at 48 kHz.
+        assert_eq!(recorded_impulse_position(10.0, 10.0), 1001 - 480);
+    }
+
+    #[test]
+    fn latency_changes_mid_take_keep_the_compensation_the_take_started_with() {
+        assert_eq!(recorded_impulse_position(0.0, 10.0), 1001);
+        assert_eq!(recorded_impulse_position(10.0, 0.0), 1001 - 480);
+    }
+
+    /// Records a two cycle loop into looper 0 against an established transport, after
+    /// clearing everything with the tempo lock set to `locked`.
+    fn record_after_clear_all(locked: bool) -> AudioEngine {
+        let (mut engine, mut commands) = test_engine();
+        let bar_len = 4800;
+        engine.transport_len_samples.store(bar_len, Ordering::Relaxed);
+        engine.tempo_locked.store(locked, Ordering::Relaxed);
+        engine.audio_input_is_armed.store(true, Ordering::Relaxed);
+        assert!(commands.push(AudioCommand::ClearAllAndPlay).is_ok());
+        press_looper(&mut commands, 0);
+        run_samples(&mut engine, 0.5, bar_len * 2);
+        press_looper(&mut commands, 0);
+        run_samples(&mut engine, 0.5, bar_len * 2);
+        engine
+    }
+
+    #[test]
+    fn tempo_lock_keeps_the_transport_length_when_recording() {
+        let engine = record_after_clear_all(true);
+        assert_eq!(engine.transport_len_samples.load(Ordering::Relaxed), 4800);
+        assert_eq!(engine.loopers[0].shared_state.get(), LooperState::Playing);
+        assert_eq!(engine.loopers[0].shared_state.get_length_in_cycles(), 2);
+        assert_eq!(engine.loopers[0].audio.len(), 4800 * 2);
+    }
+
+    #[test]
+    fn without_tempo_lock_the_recording_sets_a_new_length() {
+        let engine = record_after_clear_all(false);
+        let transport_len = engine.transport_len_samples.load(Ordering::Relaxed);
+        assert_ne!(transport_len, 4800);
+        assert_eq!(engine.loopers[0].audio.len(), transport_len);
+    }
+
+    /// Two playing loops at 0.2 and 0.1, with a reverb on the first track and on the
+    /// master, and the limiter pulling everything down to 0.1.
+    fn engine_with_fx_on_loops(safe_mode: bool) -> AudioEngine {
+        let (mut engine, _commands) = test_engine();
+        let loop_len = 4800;
+        engine.transport_len_samples.store(loop_len, Ordering::Relaxed);
+        engine.transport_is_playing.store(true, Ordering::Relaxed);
+        engine.master_volume.store(1_000_000, Ordering::Relaxed);
+        engine.limiter_is_active.store(true, Ordering::Relaxed);
+        engine.limiter_threshold.store(100_000, Ordering::Relaxed);
+        engine.safe_mode.store(safe_mode, Ordering::Relaxed);
+        for (id, level) in [(0, 0.2), (1, 0.1)] {
+            engine.loopers[id].audio = vec![level; loop_len];
+            engine.loopers[id].shared_state.set(LooperState::Playing);
+        }
+        engine.looper_fx_racks[0] = Some(reverb_rack());
+        engine.master_fx_rack = Some(reverb_rack());
+        engine
+    }
+
+    #[test]
+    fn safe_mode_outputs_the_plain_looper_sum() {
+        let mut engine = engine_with_fx_on_loops(true);
+        for _ in 0..4 {
+            let output = engine.process_buffer(&mut [0.0; 256]);
+            for frame in output {
+                assert!((frame[0] - 0.3).abs() < 1e-6, "got {frame:?}");
+                assert!((frame[1] - 0.3).abs() < 1e-6, "got {frame:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn without_safe_mode_the_fx_and_limiter_apply() {
+        // Control for the test above: the same setup is processed when safe mode is off.
+        let mut engine = engine_with_fx_on_loops(false);
+        let mut differs = false;
+        for _ in 0..4 {
+            let output = engine.process_buffer(&mut [0.0; 256]);
+            differs |= output.iter().any(|frame| (frame[0] - 0.3).abs() > 1e-3);
+        }
+        assert!(differs);
+    }
+
+    /// A playing engine with no transport yet and loopers 0 and 1 armed under the given
+    /// policy, looper 1 armed `late_by` samples after looper 0 with input already flowing.
+    fn engine_with_two_armed_loopers(
+        exclusive: bool,
+        late_by: usize,
+    ) -> (AudioEngine, HeapProducer<AudioCommand>) {
+        let (mut engine, mut commands) = test_engine();
+        engine.exclusive_arm.store(exclusive, Ordering::Relaxed);
+        engine.audio_input_is_armed.store(true, Ordering::Relaxed);
+        assert!(commands.push(AudioCommand::ClearAllAndPlay).is_ok());
+        press_looper(&mut commands, 0);
+        run_samples(&mut engine, 0.5, late_by);
+        press_looper(&mut commands, 1);
+        engine.handle_commands();
+        (engine, commands)
+    }
+
+    #[test]
+    fn exclusive_arm_disarms_the_other_looper() {
+        let (engine, _commands) = engine_with_two_armed_loopers(true, 0);
+        assert_eq!(engine.loopers[0].shared_state.get(), LooperState::Empty);
+        assert_eq!(engine.loopers[1].shared_state.get(), LooperState::Armed);
+    }
+
+    #[test]
+    fn multi_arm_loopers_record_the_same_take() {
+        let (mut engine, mut commands) = engine_with_two_armed_loopers(false, 0);
+        run_samples(&mut engine, 0.5, 1000);
+        for id in [0, 1] {
+            assert_eq!(engine.loopers[id].shared_state.get(), LooperState::Recording);
+        }
+
+        // Closing looper 0 sets the transport; looper 1 carries on into a second cycle.
+        press_looper(&mut commands, 0);
+        run_samples(&mut engine, 0.5, 1);
+        // The sample that crossed the arm threshold starts the take but isn't recorded.
+        let bar_len = engine.transport_len_samples.load(Ordering::Relaxed);
+        assert_eq!(bar_len, 999);
+        assert_eq!(engine.loopers[0].shared_state.get(), LooperState::Playing);
+        assert_eq!(engine.loopers[1].shared_state.get(), LooperState::Recording);
+
+        run_samples(&mut engine, 0.5, 500);
+        press_looper(&mut commands, 1);
+        run_samples(&mut engine, 0.5, bar_len);
+        let looper = &engine.loopers[1];
+        assert_eq!(looper.shared_state.get(), LooperState::Playing);
+        assert_eq!(looper.shared_state.get_length_in_cycles(), 2);
+        assert_eq!(looper.audio.len(), bar_len * 2);
+        assert!(looper.audio.iter().all(|&s| s == 0.5));
+    }
+
+    #[test]
+    fn multi_arm_loopers_closed_together_share_the_length() {
+        let (mut engine, mut commands) = engine_with_two_armed_loopers(false, 0);
+        run_samples(&mut engine, 0.5, 1000);
+        press_looper(&mut commands, 1);
+        press_looper(&mut commands, 0);
+        run_samples(&mut engine, 0.5, 1);
+        for id in [0, 1] {
+            assert_eq!(engine.loopers[id].shared_state.get(), LooperState::Playing);
+            assert_eq!(engine.loopers[id].audio.len(), 999);
+        }
+    }
+
+    #[test]
+    fn late_multi_arm_looper_lines_up_with_the_first() {
+        let (mut engine, mut commands) = engine_with_two_armed_loopers(false, 200);
+        run_samples(&mut engine, 0.5, 800);
+        press_looper(&mut commands, 0);
+        run_samples(&mut engine, 0.5, 1);
+        // Looper 1 keeps recording, so its first cycle is what lines up with looper 0.
+        let first_len = engine.loopers[0].audio.len();
+        let late = &engine.loopers[1].audio[..first_len];
+        // It missed the start of the take, which stays silent.
+        let missed = late.iter().take_while(|&&s| s == 0.0).count();
+        assert_eq!(missed, 200);
+        assert!(late[missed..].iter().all(|&s| s == 0.5));
+    }
+
+    /// Output of two playing loops at 0.2 and 0.1 once the mixer is set up by `setup`.
+    fn two_loop_output(setup: impl FnOnce(&mut MixerState)) -> [f32; 2] {
+        let (mut engine, _commands) = test_engine();
+        let loop_len = 4800;
+        engine.transport_len_samples.store(loop_len, Ordering::Relaxed);
+        engine.transport_is_playing.store(true, Ordering::Relaxed);
+        engine.master_volume.store(1_000_000, Ordering::Relaxed);
+        for (id, level) in [(0, 0.2), (1, 0.1)] {
+            engine.loopers[id].audio = vec![level; loop_len];
+            engine.loopers[id].shared_state.set(LooperState::Playing);
+        }
+        setup(&mut engine.track_mixer_state.write().unwrap());
+        engine.process_buffer(&mut [0.0; 256])[255]
+    }
+
+    #[test]
+    fn sub_bus_fader_only_affects_its_loopers() {
+        let unrouted = two_loop_output(|mixer| mixer.buses[0].volume = 0.5);
+        assert!((unrouted[0] - 0.3).abs() < 1e-6, "got {unrouted:?}");
+
+        let routed = two_loop_output(|mixer| {
+            mixer.tracks[0].bus = Some(0);
+            mixer.buses[0].volume = 0.5;
+        });
+        assert!((routed[0] - 0.2).abs() < 1e-6, "got {routed:?}");
+        assert!((routed[1] - 0.2).abs() < 1e-6, "got {routed:?}");
+
+        let muted = two_loop_output(|mixer| {
+            mixer.tracks[0].bus = Some(0);
+            mixer.buses[0].is_muted = true;
+        });
+        assert!((muted[0] - 0.1).abs() < 1e-6, "got {muted:?}");
+    }
+
+    #[test]
+    fn auditioned_chord_notes_sound_on_the_synth() {
+        let chord_peak = |activate: bool| {
+            let (mut engine, mut commands) = test_engine();
+            let tone = AudioCommand::LoadSampleForSamplerSlot {
+                engine_index: 0,
+                slot_index: 0,
+                audio_data: Arc::new(sine_cycle(100, false).repeat(480)),
+            };
+            assert!(commands.push(tone).is_ok());
+            if activate {
+                assert!(commands.push(AudioCommand::ActivateSynth).is_ok());
+            }
+            // Notes within the first sampler slot's key range.
+            for note in [24, 28, 31] {
+                assert!(commands.push(AudioCommand::SynthNoteOn { note, velocity: 100 }).is_ok());
+            }
+            let mut output = Vec::new();
+            render_engine_0(&mut engine, 8, &mut output);
+            output.iter().fold(0.0f32, |m, s| m.max(s.abs()))
+        };
+        assert!(chord_peak(true) > 0.01);
+        assert_eq!(chord_peak(false), 0.0);
+    }
+
+    /// Phase of a bar-synced saw LFO after the first block of a bar, having free-run for
+    /// `free_run_blocks` blocks while the transport was stopped.
+    fn synced_lfo_phase_after_downbeat(free_run_blocks: usize, transport_sync: bool) -> f32 {
+        let (mut engine, mut commands) = test_engine();
+        engine.transport_len_samples.store(4800, Ordering::Relaxed);
+        engine.transport_is_playing.store(false, Ordering::Relaxed);
+        assert!(commands.push(AudioCommand::ActivateSynth).is_ok());
+        assert!(commands.push(AudioCommand::SetLfoTransportSync(transport_sync)).is_ok());
+        engine.handle_commands();
+        let SynthEngine::Sampler(sampler) = &engine.synth.engines[0] else {
+            unreachable!();
+        };
+        let lfo_value = sampler.lfo_value_atomic.clone();
+        if let Ok(mut lfo) = sampler.lfo_settings.write() {
+            lfo.mode = LfoRateMode::Sync;
+            lfo.sync_rate = 1.0;
+            lfo.waveform = LfoWaveform::Saw;
+        }
+        for _ in 0..free_run_blocks {
+            engine.process_buffer(&mut [0.0; 256]);
+        }
+        engine.transport_is_playing.store(true, Ordering::Relaxed);
+        engine.process_buffer(&mut [0.0; 256]);
+        // The value meter maps the saw's -1..1 to 0..1, which is its phase.
+        lfo_value.load(Ordering::Relaxed) as f32 / 1_000_000.0
+    }
+
+    #[test]
+    fn transport_sync_restarts_synced_lfos_on_the_downbeat() {
+        // One cycle per 4800 sample bar, restarted at the start of the downbeat block.
+        let expected = 256.0 / 4800.0;
+        for free_run_blocks in [1, 5, 11] {
+            let phase = synced_lfo_phase_after_downbeat(free_run_blocks, true);
+            assert!((phase - expected).abs() < 1e-3, "{free_run_blocks} blocks: {phase}");
+        }
+        let unsynced = synced_lfo_phase_after_downbeat(5, false);
+        assert!((unsynced - expected).abs() > 0.1, "got {unsynced}");
+    }
+
+    /// Peak of the master output over a few blocks with the transport stopped, with a looper
+    /// loaded and/or a synth note held.
+    fn stopped_transport_peak(with_loop: bool, with_synth: bool, monitor: bool) -> f32 {
+        let (mut engine, mut commands) = test_engine();
+        engine.master_volume.store(1_000_000, Ordering::Relaxed);
+        engine.synth_master_volume.store(1_000_000, Ordering::Relaxed);
+        engine.transport_len_samples.store(4800, Ordering::Relaxed);
+        engine.transport_is_playing.store(false, Ordering::Relaxed);
+        assert!(commands.push(AudioCommand::SetMonitorWhileStopped(monitor)).is_ok());
+        if with_loop {
+            engine.loopers[0].audio = vec![0.3; 4800];
+            engine.loopers[0].shared_state.set(LooperState::Playing);
+        }
+        if with_synth {
+            let tone = AudioCommand::LoadSampleForSamplerSlot {
+                engine_index: 0,
+                slot_index: 0,
+                audio_data: Arc::new(sine_cycle(100, false).repeat(480)),
+            };
+            assert!(commands.push(tone).is_ok());
+            assert!(commands.push(AudioCommand::ActivateSynth).is_ok());
+            assert!(commands.push(AudioCommand::SynthNoteOn { note: 24, velocity: 127 }).is_ok());
+        }
+        engine.handle_commands();
+        let mut peak = 0.0f32;
+        for _ in 0..8 {
+            let output = engine.process_buffer(&mut [0.0; 256]);
+            peak = output.iter().flatten().fold(peak, |p, s| p.max(s.abs()));
+        }
+        peak
+    }
+
+    #[test]
+    fn stopped_transport_keeps_live_sources_and_silences_loopers() {
+        assert_eq!(stopped_transport_peak(true, false, true), 0.0);
+        let synth_only = stopped_transport_peak(false, true, true);
+        assert!(synth_only > 0.05, "synth should be monitored, got {synth_only}");
+        assert_eq!(stopped_transport_peak(true, true, true), synth_only);
+        assert_eq!(stopped_transport_peak(false, true, false), 0.0);
+    }
+
+    #[test]
+    fn reversed_playback_reads_backwards_without_touching_the_loop() {
+        let (mut engine, _commands) = test_engine();
+        let loop_len = 1000;
+        engine.transport_len_samples.store(loop_len, Ordering::Relaxed);
+        engine.transport_is_playing.store(true, Ordering::Relaxed);
+        let ramp: Vec<f32> = (0..loop_len).map(|i| i as f32 / loop_len as f32).collect();
+        engine.loopers[0].audio = ramp.clone();
+        engine.loopers[0].shared_state.set(LooperState::Playing);
+        let read_block = |engine: &mut AudioEngine, reversed: bool| {
+            engine.track_mixer_state.write().unwrap().tracks[0].is_reversed = reversed;
+            engine.process_buffer(&mut [0.0; 100]);
+            engine.looper_buffers[0][..100].to_vec()
+        };
+
+        let forward = read_block(&mut engine, false);
+        assert_eq!(forward, ramp[..100]);
+        let backward = read_block(&mut engine, true);
+        assert!(backward.windows(2).all(|w| w[1] < w[0]), "got {backward:?}");
+        // The playhead kept running forward, so the read mirrors samples 100..200.
+        assert_eq!(backward[0], ramp[loop_len - 1 - 100]);
+        assert_eq!(engine.loopers[0].audio, ramp);
+
+        let forward_again = read_block(&mut engine, false);
+        assert_eq!(forward_again, ramp[200..300]);
+        assert_eq!(engine.loopers[0].audio, ramp);
+    }
+
+    /// The first looper's meter reading with its fader at `volume`, metered at `meter_point`.
+    fn track_meter_reading(meter_point: MeterPoint, volume: f32) -> f32 {
+        let (mut engine, _commands) = test_engine();
+        engine.transport_len_samples.store(4800, Ordering::Relaxed);
+        engine.transport_is_playing.store(true, Ordering::Relaxed);
+        engine.loopers[0].audio = vec![0.4; 4800];
+        engine.loopers[0].shared_state.set(LooperState::Playing);
+        if let Ok(mut mixer) = engine.track_mixer_state.write() {
+            mixer.tracks[0].meter_point = meter_point;
+            mixer.tracks[0].volume = volume;
+        }
+        engine.process_buffer(&mut [0.0; 256]);
+        engine.peak_meters[0].load(Ordering::Relaxed) as f32 / u32::MAX as f32
+    }
+
+    #[test]
+    fn pre_fader_meter_ignores_the_track_volume() {
+        for volume in [1.0, 0.5, 0.0] {
+            let reading = track_meter_reading(MeterPoint::PreFader, volume);
+            assert!((reading - 0.4).abs() < 1e-4, "volume {volume}: got {reading}");
+        }
+    }
+
+    #[test]
+    fn post_fader_meter_scales_with_the_track_volume() {
+        for volume in [1.0, 0.5, 0.0] {
+            let reading = track_meter_reading(MeterPoint::PostFader, volume);
+            assert!((reading - 0.4 * volume).abs() < 1e-4, "volume {volume}: got {reading}");
+        }
+    }
+
+    #[test]
+    fn two_cycle_session_loop_reloads_and_wraps_over_two_cycles() {
+        let (mut engine, mut commands) = test_engine();
+        let bar_len = 4800;
+        let dir = std::env::temp_dir().join(format!("cypher-session-loop-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("loop_0.wav");
+        // A few samples short, as resampling can leave it.
+        let audio: Vec<f32> = (0..bar_len * 2 - 3).map(|i| (i % 100) as f32 / 200.0).collect();
+        write_wav_file(&path, &audio, SAMPLE_RATE, crate::settings::RecordingFormat::Float32)
+            .unwrap();
+
+        assert!(commands.push(AudioCommand::SetTransportLen(bar_len)).is_ok());
+        let load = AudioCommand::LoadLoopAudio {
+            looper_index: 0,
+            path,
+            original_sample_rate: SAMPLE_RATE as u32,
+            length_in_cycles: 2,
+        };
+        assert!(commands.push(load).is_ok());
+        engine.transport_is_playing.store(true, Ordering::Relaxed);
+        engine.handle_commands();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let looper = &engine.loopers[0];
+        assert_eq!(looper.shared_state.get(), LooperState::Playing);
+        assert_eq!(looper.shared_state.get_length_in_cycles(), 2);
+        assert_eq!(looper.audio.len(), bar_len * 2);
+
+        run_samples(&mut engine, 0.0, bar_len);
+        assert_eq!(engine.transport_playhead.load(Ordering::Relaxed), 0);
+        assert_eq!(engine.loopers[0].playhead, bar_len);
+        run_samples(&mut engine, 0.0, bar_len);
+        assert_eq!(engine.loopers[0].playhead, 0);
+    }
+
+    /// Times 16 tracks' FX racks run one after another against running them in parallel the
+    /// way `process_looper_fx_racks` does. Run it with
+    /// `cargo test --release looper_fx_racks -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn looper_fx_racks_serial_vs_parallel_timing() {
+        const TRACKS: usize = 16;
+        const BLOCK_LEN: usize = 256;
+        const BLOCKS: usize = 2000;
+        let preset = fx::FxPreset {
+            chain: [
+                fx::FxComponentType::Filter,
+                fx::FxComponentType::Delay,
+                fx::FxComponentType::Reverb,
+            ]
+            .into_iter()
+            .map(fx::FxChainLink::new)
+            .collect(),
+            ..Default::default()
+        };
+        let new_racks = || -> Vec<FxRack> {
+            (0..TRACKS)
+                .map(|_| {
+                    let mix = Arc::new(AtomicU32::new((PARAM_SCALER * 0.5) as u32));
+                    FxRack::new(&preset, mix, SAMPLE_RATE)
+                })
+                .collect()
+        };
+        let active_masks = vec![u16::MAX; BLOCK_LEN];
+        let input: Vec<f32> = (0..BLOCK_LEN).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+
+        let mut serial_racks = new_racks();
+        let mut serial_buffers = vec![vec![0.0; BLOCK_LEN]; TRACKS];
+        let start = Instant::now();
+        for _ in 0..BLOCKS {
+            for (id, (rack, buffer)) in serial_racks.iter_mut().zip(&mut serial_buffers).enumerate() {
+                buffer.copy_from_slice(&input);
+                process_rack_where_active(rack, buffer, &active_masks, id);
+            }
+        }
+        let serial = start.elapsed();
+
+        let mut parallel_racks = new_racks();
+        let mut parallel_buffers = vec![vec![0.0; BLOCK_LEN]; TRACKS];
+        let start = Instant::now();
+        for _ in 0..BLOCKS {
+            parallel_racks
+                .par_iter_mut()
+                .zip(parallel_buffers.par_iter_mut())
+                .enumerate()
+                .for_each(|(id, (rack, buffer))| {
+                    buffer.copy_from_slice(&input);
+                    process_rack_where_active(rack, buffer, &active_masks, id);
+                });
+        }
+        let parallel = start.elapsed();
+
+        let block_duration = Duration::from_secs_f32(BLOCK_LEN as f32 / SAMPLE_RATE);
+        let per_block = |total: Duration| total / BLOCKS as u32;
+        println!(
+            "{} tracks, {} sample blocks ({:?} of audio each): serial {:?}/block, parallel {:?}/block ({:.2}x)",
+            TRACKS,
+            BLOCK_LEN,
+            block_duration,
+            per_block(serial),
+            per_block(parallel),
+            serial.as_secs_f64() / parallel.as_secs_f64(),
+        );
+        assert_eq!(serial_buffers, parallel_buffers);
+    }
+}
@@ -1,10 +1,47 @@
 // FILE: src\audio_engine\sampler_pad.rs
 // =====================================
 
-use crate::sampler::SamplerPadFxSettings;
-use crate::synth::{Adsr};
+use crate::fx_components::flush_denormal;
+use crate::sampler::{
+    declick_gain, PadGrooveSettings, PadRetriggerMode, SamplerPadFxSettings, DEFAULT_DECLICK_MS,
+};
+use crate::synth::{Adsr, AdsrState};
+use rand::rngs::StdRng;
+use rand::Rng;
 use std::sync::Arc;
 
+/// Computes the swing/humanize adjustment for a single grid-aligned trigger.
+/// Returns the delay in samples (always >= 0) and a multiplier for the trigger velocity.
+pub fn groove_offset(
+    groove: &PadGrooveSettings,
+    rng: &mut StdRng,
+    step_index: usize,
+    step_len: usize,
+    sample_rate: f32,
+) -> (usize, f32) {
+    let swing_delay = if step_index % 2 == 1 {
+        (groove.swing.clamp(0.0, 1.0) * 0.5 * step_len as f32) as usize
+    } else {
+        0
+    };
+
+    let max_jitter = (groove.humanize_timing_ms.max(0.0) / 1000.0 * sample_rate) as usize;
+    let jitter = if max_jitter > 0 {
+        rng.gen_range(0..=max_jitter)
+    } else {
+        0
+    };
+
+    let velocity_range = groove.humanize_velocity.clamp(0.0, 1.0);
+    let velocity_scale = if velocity_range > 0.0 {
+        1.0 + rng.gen_range(-velocity_range..=velocity_range)
+    } else {
+        1.0
+    };
+
+    (swing_delay + jitter, velocity_scale)
+}
+
 /// A delay line with feedback, a core part of a reverb's sound.
 #[derive(Clone)]
 pub struct CombFilter {
@@ -25,7 +62,7 @@ impl CombFilter {
     fn process(&mut self, input: f32) -> f32 {
         let read_index = (self.index + self.buffer.len() - self.delay_length) % self.buffer.len();
         let output = self.buffer[read_index];
-        self.buffer[self.index] = input + output * self.feedback;
+        self.buffer[self.index] = flush_denormal(input + output * self.feedback);
         self.index = (self.index + 1) % self.buffer.len();
         output
     }
@@ -50,7 +87,7 @@ impl AllPassFilter {
         let read_index = (self.index + self.buffer.len() - self.delay_length) % self.buffer.len();
         let delayed = self.buffer[read_index];
         let output = -input + delayed;
-        self.buffer[self.index] = input + delayed * 0.5; // G = 0.5
+        self.buffer[self.index] = flush_denormal(input + delayed * 0.5); // G = 0.5
         self.index = (self.index + 1) % self.buffer.len();
         output
     }
@@ -121,17 +158,46 @@ impl SamplerPadReverb {
     }
 }
 
+/// Most earlier hits kept sounding under the newest one in `PadRetriggerMode::Stack`.
+const MAX_PAD_TAILS: usize = 4;
+/// Fade length for the previous hit in `PadRetriggerMode::FadeRestart`.
+const RETRIGGER_FADE_MS: f32 = 5.0;
+
+/// An earlier hit of the pad that keeps playing under the newest one.
+#[derive(Clone)]
+pub struct PadTail {
+    playhead: f32,
+    start_playhead: f32,
+    volume: f32,
+    amp_adsr: Adsr,
+    fade_gain: f32,
+    /// Per-sample decrement of `fade_gain`; zero for stacked hits, which ring out naturally.
+    fade_step: f32,
+}
+
 /// The audio-thread state for a single sampler pad.
 #[derive(Clone)]
 pub struct SamplerPad {
     pub audio: Arc<Vec<f32>>,
     pub playhead: f32,
+    /// Where the current hit started, for the declick fade-in.
+    start_playhead: f32,
+    /// Length of the declick fade at both ends of playback, in samples.
+    pub declick_samples: f32,
     pub volume: f32,
     pub fx: SamplerPadFxSettings,
     pub amp_adsr: Adsr,
     pub reverb: SamplerPadReverb,
     pub gate_counter: usize,
     pub was_gate_open: bool,
+    /// True while the triggering note is held down; drives note repeat.
+    pub is_held: bool,
+    /// A trigger delayed by swing/humanize: (samples remaining, volume).
+    pub pending_trigger: Option<(usize, f32)>,
+    /// A live hit held back by launch quantize until the next grid step: the volume.
+    pub pending_launch: Option<f32>,
+    /// Earlier hits still sounding, per the pad's retrigger mode.
+    pub tails: Vec<PadTail>,
 }
 
 impl SamplerPad {
@@ -140,12 +206,303 @@ impl SamplerPad {
         Self {
             audio: Arc::new(vec![]),
             playhead: 0.0,
+            start_playhead: 0.0,
+            declick_samples: DEFAULT_DECLICK_MS / 1000.0 * sample_rate,
             volume: 1.0,
             fx,
             amp_adsr: Adsr::new(fx.adsr, sample_rate),
             reverb: SamplerPadReverb::new(sample_rate),
             gate_counter: 0,
             was_gate_open: false,
+            is_held: false,
+            pending_trigger: None,
+            pending_launch: None,
+            tails: Vec::with_capacity(MAX_PAD_TAILS),
+        }
+    }
+
+    /// Restarts the pad from its start offset (counted from the end, when reversed) and
+    /// reopens the reverb gate.
+    pub fn trigger(&mut self, volume: f32, sample_rate: f32) {
+        self.keep_previous_hit(sample_rate);
+        self.volume = volume;
+        let offset = (self.fx.start_offset.clamp(0.0, 1.0) * self.audio.len() as f32).floor();
+        let offset = offset.min(self.audio.len().saturating_sub(1) as f32);
+        self.playhead = if self.fx.is_reversed {
+            self.audio.len() as f32 - 1.0 - offset
+        } else {
+            offset
+        };
+        self.start_playhead = self.playhead;
+        self.amp_adsr.note_on();
+        self.gate_counter = (self.fx.gate_close_time_ms / 1000.0 * sample_rate) as usize;
+        self.was_gate_open = true;
+    }
+
+    /// Hands the note that is about to be cut over to `tails`, as the retrigger mode asks.
+    fn keep_previous_hit(&mut self, sample_rate: f32) {
+        let fade_step = match self.fx.retrigger_mode {
+            PadRetriggerMode::Retrigger => {
+                self.tails.clear();
+                return;
+            }
+            PadRetriggerMode::Stack => 0.0,
+            PadRetriggerMode::FadeRestart => {
+                1.0 / (RETRIGGER_FADE_MS / 1000.0 * sample_rate).max(1.0)
+            }
+        };
+        if self.amp_adsr.state == AdsrState::Idle || self.is_finished() {
+            return;
+        }
+        if self.tails.len() >= MAX_PAD_TAILS {
+            self.tails.remove(0);
+        }
+        self.tails.push(PadTail {
+            playhead: self.playhead,
+            start_playhead: self.start_playhead,
+            volume: self.volume,
+            amp_adsr: self.amp_adsr,
+            fade_gain: 1.0,
+            fade_step,
+        });
+    }
+
+    /// True while the current hit or any kept earlier hit is producing sound.
+    pub fn is_sounding(&self) -> bool {
+        self.amp_adsr.state != AdsrState::Idle || !self.tails.is_empty()
+    }
+
+    /// Renders and advances every earlier hit, dropping those that have finished.
+    pub fn process_tails(&mut self, rate: f32) -> f32 {
+        let mut tails = std::mem::take(&mut self.tails);
+        let mut output = 0.0;
+        tails.retain_mut(|tail| {
+            if self.is_finished_at(tail.playhead)
+                || tail.amp_adsr.state == AdsrState::Idle
+                || tail.fade_gain <= 0.0
+            {
+                return false;
+            }
+            let gain = tail.amp_adsr.process()
+                * tail.volume
+                * tail.fade_gain
+                * self.declick_gain_at(tail.playhead, tail.start_playhead);
+            output += self.sample_at(tail.playhead) * gain;
+            tail.playhead += if self.fx.is_reversed { -rate } else { rate };
+            tail.fade_gain -= tail.fade_step;
+            true
+        });
+        self.tails = tails;
+        output
+    }
+
+    /// Silences the pad and clears its reverb tail. The loaded audio is kept.
+    pub fn reset(&mut self) {
+        self.amp_adsr.reset();
+        self.reverb.clear();
+        self.playhead = if self.fx.is_reversed {
+            -1.0
+        } else {
+            self.audio.len() as f32
+        };
+        self.gate_counter = 0;
+        self.was_gate_open = false;
+        self.is_held = false;
+        self.pending_trigger = None;
+        self.pending_launch = None;
+        self.tails.clear();
+    }
+
+    /// Releases the pad unless it is set to one-shot, in which case it plays on to the end.
+    pub fn note_off(&mut self) {
+        self.is_held = false;
+        if !self.fx.one_shot {
+            self.amp_adsr.note_off();
+            for tail in &mut self.tails {
+                tail.amp_adsr.note_off();
+            }
+        }
+    }
+
+    /// True once the playhead has run off the end of the sample, or off the start when reversed.
+    pub fn is_finished(&self) -> bool {
+        self.is_finished_at(self.playhead)
+    }
+
+    fn is_finished_at(&self, playhead: f32) -> bool {
+        if self.fx.is_reversed {
+            playhead < 0.0
+        } else {
+            playhead as usize >= self.audio.len()
+        }
+    }
+
+    /// Reads the sample at the playhead, interpolating towards the next frame in the
+    /// direction of travel.
+    pub fn sample_at_playhead(&self) -> f32 {
+        self.sample_at(self.playhead)
+    }
+
+    /// Short fade applied where the current hit starts and where the sample runs out, so
+    /// audio that doesn't begin or end at zero doesn't click.
+    pub fn declick_gain(&self) -> f32 {
+        self.declick_gain_at(self.playhead, self.start_playhead)
+    }
+
+    fn declick_gain_at(&self, playhead: f32, start_playhead: f32) -> f32 {
+        let to_end = if self.fx.is_reversed {
+            playhead
+        } else {
+            self.audio.len() as f32 - 1.0 - playhead
+        };
+        declick_gain((playhead - start_playhead).abs(), to_end, self.declick_samples)
+    }
+
+    fn sample_at(&self, playhead: f32) -> f32 {
+        if self.is_finished_at(playhead) {
+            return 0.0;
+        }
+        let len = self.audio.len();
+        if self.fx.is_reversed {
+            let p_ceil = playhead.ceil();
+            let p_fract = p_ceil - playhead;
+            let index0 = (p_ceil as usize).min(len - 1);
+            let sample0 = self.audio[index0];
+            let sample1 = if index0 > 0 {
+                self.audio[index0 - 1]
+            } else {
+                0.0
+            };
+            sample0 + p_fract * (sample1 - sample0)
+        } else {
+            let p_floor = playhead.floor();
+            let p_fract = playhead - p_floor;
+            let index0 = p_floor as usize;
+            let index1 = index0 + 1;
+
+            let sample0 = self.audio[index0];
+            let sample1 = if index1 < len { self.audio[index1] } else { 0.0 };
+
+            sample0 + p_fract * (sample1 - sample0)
+        }
+    }
+
+    /// Moves the playhead by `rate` samples in the playback direction.
+    pub fn advance(&mut self, rate: f32) {
+        if self.is_finished() {
+            return;
+        }
+        if self.fx.is_reversed {
+            self.playhead -= rate;
+        } else {
+            self.playhead += rate;
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    fn humanized() -> PadGrooveSettings {
+        PadGrooveSettings {
+            humanize_timing_ms: 10.0,
+            humanize_velocity: 0.2,
+            seed: 7,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn humanize_stays_within_bounds_and_varies_velocity() {
+        let groove = humanized();
+        let mut rng = StdRng::seed_from_u64(groove.seed);
+        let max_delay = (groove.humanize_timing_ms / 1000.0 * SAMPLE_RATE) as usize;
+
+        let offsets: Vec<(usize, f32)> = (0..64)
+            .map(|step| groove_offset(&groove, &mut rng, step * 2, 1200, SAMPLE_RATE))
+            .collect();
+
+        assert!(offsets.iter().all(|&(delay, _)| delay <= max_delay));
+        assert!(offsets.iter().any(|&(delay, _)| delay > 0));
+        assert!(offsets.iter().all(|&(_, scale)| (0.8..=1.2).contains(&scale)));
+        assert!(offsets.iter().any(|&(_, scale)| scale != offsets[0].1));
+    }
+
+    #[test]
+    fn humanize_is_deterministic_for_a_seed() {
+        let groove = humanized();
+        let run = || {
+            let mut rng = StdRng::seed_from_u64(groove.seed);
+            (0..16)
+                .map(|step| groove_offset(&groove, &mut rng, step, 1200, SAMPLE_RATE))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn swing_delays_only_off_beat_steps() {
+        let groove = PadGrooveSettings {
+            swing: 0.5,
+            ..Default::default()
+        };
+        let mut rng = StdRng::seed_from_u64(groove.seed);
+        assert_eq!(groove_offset(&groove, &mut rng, 0, 1200, SAMPLE_RATE), (0, 1.0));
+        assert_eq!(groove_offset(&groove, &mut rng, 1, 1200, SAMPLE_RATE), (300, 1.0));
+    }
+
+    /// A pad holding one second of DC, hit twice 10 ms apart in the given mode.
+    fn pad_hit_twice(mode: PadRetriggerMode) -> SamplerPad {
+        let mut pad = SamplerPad::new(SAMPLE_RATE);
+        pad.audio = Arc::new(vec![0.5; SAMPLE_RATE as usize]);
+        pad.fx.retrigger_mode = mode;
+        pad.trigger(1.0, SAMPLE_RATE);
+        for _ in 0..480 {
+            pad.amp_adsr.process();
+            pad.advance(1.0);
+        }
+        pad.trigger(1.0, SAMPLE_RATE);
+        pad
+    }
+
+    #[test]
+    fn stacked_retriggers_overlap() {
+        let mut pad = pad_hit_twice(PadRetriggerMode::Stack);
+        assert_eq!(pad.tails.len(), 1);
+        let tail: f32 = (0..480).map(|_| pad.process_tails(1.0)).sum();
+        assert!(tail > 0.4 * 480.0, "stacked hit should keep ringing, got {tail}");
+        assert!(pad.is_sounding());
+
+        for _ in 0..8 {
+            pad.trigger(1.0, SAMPLE_RATE);
         }
+        assert_eq!(pad.tails.len(), MAX_PAD_TAILS);
+    }
+
+    #[test]
+    fn retrigger_mode_only_plays_the_latest_hit() {
+        let mut pad = pad_hit_twice(PadRetriggerMode::Retrigger);
+        assert!(pad.tails.is_empty());
+        assert_eq!(pad.process_tails(1.0), 0.0);
+        assert_eq!(pad.playhead, 0.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn fade_restart_fades_the_previous_hit_within_the_fade_time() {
+        let mut pad = pad_hit_twice(PadRetriggerMode::FadeRestart);
+        let fade_samples = (RETRIGGER_FADE_MS / 1000.0 * SAMPLE_RATE) as usize;
+        let first = pad.process_tails(1.0);
+        assert!(first > 0.4);
+        let mut previous = first;
+        for _ in 0..fade_samples {
+            let sample = pad.process_tails(1.0);
+            assert!(sample <= previous);
+            previous = sample;
+        }
+        assert_eq!(pad.process_tails(1.0), 0.0);
+        assert!(pad.tails.is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+// FILE: src\audio_engine\spectrum.rs
+// ==================================
+
+//! Magnitude spectrum of the master output for the mixer's analyzer display. A Hann-windowed
+//! FFT runs over the most recent samples a few dozen times a second, so the audio thread only
+//! pays for one transform every couple of buffers.
+
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use std::f32::consts::PI;
+use std::sync::{Arc, RwLock};
+
+/// Number of log-spaced bands published to the UI.
+pub const SPECTRUM_BANDS: usize = 48;
+const FFT_SIZE: usize = 2048;
+const MIN_FREQ_HZ: f32 = 30.0;
+const ANALYSES_PER_SECOND: f32 = 30.0;
+/// Band levels are published as 0..1 over this range of dBFS.
+const FLOOR_DB: f32 = -90.0;
+
+pub struct SpectrumAnalyzer {
+    history: Vec<f32>,
+    write_pos: usize,
+    samples_until_analysis: usize,
+    analysis_interval: usize,
+    window: Vec<f32>,
+    fft: Arc<dyn Fft<f32>>,
+    spectrum: Vec<Complex<f32>>,
+    /// Working space for the planned FFT, allocated once so analysis never allocates.
+    scratch: Vec<Complex<f32>>,
+    /// FFT bin at the lower edge of each band, plus one past the last band.
+    band_edges: Vec<usize>,
+    levels: [f32; SPECTRUM_BANDS],
+    pub bands: Arc<RwLock<Vec<f32>>>,
+}
+
+impl SpectrumAnalyzer {
+    pub fn new(sample_rate: f32, bands: Arc<RwLock<Vec<f32>>>) -> Self {
+        let window = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+        let fft = FftPlanner::new().plan_fft_forward(FFT_SIZE);
+        let scratch = vec![Complex::default(); fft.get_inplace_scratch_len()];
+
+        let nyquist = sample_rate / 2.0;
+        let bin_hz = sample_rate / FFT_SIZE as f32;
+        let mut band_edges = Vec::with_capacity(SPECTRUM_BANDS + 1);
+        for band in 0..=SPECTRUM_BANDS {
+            let position = band as f32 / SPECTRUM_BANDS as f32;
+            let freq = MIN_FREQ_HZ * (nyquist / MIN_FREQ_HZ).powf(position);
+            let bin = ((freq / bin_hz).round() as usize).clamp(1, FFT_SIZE / 2);
+            // Low bands are narrower than a bin; give each at least one of its own.
+            let min_bin = band_edges.last().map_or(1, |&last: &usize| last + 1);
+            band_edges.push(bin.max(min_bin).min(FFT_SIZE / 2));
+        }
+
+        let analysis_interval = ((sample_rate / ANALYSES_PER_SECOND) as usize).max(1);
+        Self {
+            history: vec![0.0; FFT_SIZE],
+            write_pos: 0,
+            samples_until_analysis: analysis_interval,
+            analysis_interval,
+            window,
+            fft,
+            spectrum: vec![Complex::default(); FFT_SIZE],
+            scratch,
+            band_edges,
+            levels: [0.0; SPECTRUM_BANDS],
+            bands,
+        }
+    }
+
+    pub fn process(&mut self, frame: [f32; 2]) {
+        self.history[self.write_pos] = (frame[0] + frame[1]) * 0.5;
+        self.write_pos = (self.write_pos + 1) % FFT_SIZE;
+        self.samples_until_analysis -= 1;
+        if self.samples_until_analysis == 0 {
+            self.samples_until_analysis = self.analysis_interval;
+            self.analyse();
+        }
+    }
+
+    fn analyse(&mut self) {
+        // Oldest sample first, so the window is centred on the most recent audio.
+        for i in 0..FFT_SIZE {
+            let sample = self.history[(self.write_pos + i) % FFT_SIZE];
+            self.spectrum[i] = Complex::new(sample * self.window[i], 0.0);
+        }
+        self.fft.process_with_scratch(&mut self.spectrum, &mut self.scratch);
+
+        // A full-scale sine peaks at FFT_SIZE / 4 after the Hann window.
+        let full_scale = FFT_SIZE as f32 / 4.0;
+        for (band, level) in self.levels.iter_mut().enumerate() {
+            let (start, end) = (self.band_edges[band], self.band_edges[band + 1]);
+            let peak = (start..end.max(start + 1).min(FFT_SIZE / 2))
+                .map(|bin| self.spectrum[bin].norm())
+                .fold(0.0f32, f32::max);
+            let db = 20.0 * (peak / full_scale).max(1e-9).log10();
+            *level = ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0);
+        }
+
+        // Never block the audio thread on the UI; a skipped update is invisible.
+        if let Ok(mut bands) = self.bands.try_write() {
+            bands.clear();
+            bands.extend_from_slice(&self.levels);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f32 = 48_000.0;
+
+    #[test]
+    fn full_scale_sine_lights_only_its_band() {
+        let bands = Arc::new(RwLock::new(Vec::new()));
+        let mut analyzer = SpectrumAnalyzer::new(SAMPLE_RATE, bands.clone());
+        let freq = 1_000.0;
+        for i in 0..FFT_SIZE * 2 {
+            let sample = (2.0 * PI * freq * i as f32 / SAMPLE_RATE).sin();
+            analyzer.process([sample, sample]);
+        }
+
+        let bin = (freq / (SAMPLE_RATE / FFT_SIZE as f32)).round() as usize;
+        let band = analyzer.band_edges.iter().rposition(|&edge| edge <= bin).unwrap();
+        let levels = bands.read().unwrap().clone();
+        assert_eq!(levels.len(), SPECTRUM_BANDS);
+        assert!(levels[band] > 0.97, "got {}", levels[band]);
+        assert!(levels[0] < 0.3 && levels[SPECTRUM_BANDS - 1] < 0.3, "got {levels:?}");
+    }
+}
@@ -191,9 +191,15 @@ where
             // **THE FIX IS HERE**: Pass the buffer as mutable
             let output_buffer = engine.process_buffer(&mut input_buffer);
             for (i, frame) in data.chunks_mut(channels).enumerate() {
-                let sample_value = output_buffer.get(i).copied().unwrap_or(0.0);
-                for sample in frame.iter_mut() {
-                    *sample = T::from_sample(sample_value);
+                let [left, right] = output_buffer.get(i).copied().unwrap_or([0.0; 2]);
+                if channels == 1 {
+                    frame[0] = T::from_sample((left + right) * 0.5);
+                    continue;
+                }
+                // Devices with more than two outputs get the stereo pair repeated.
+                for (c, sample) in frame.iter_mut().enumerate() {
+                    let value = if c % 2 == 0 { left } else { right };
+                    *sample = T::from_sample(value);
                 }
             }
         },
@@ -5,8 +5,11 @@
 //! representing a user-created effect chain "recipe".
 
 use crate::fx_components;
+use crate::mixer::{DELAY_SEND, REVERB_SEND};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::fmt;
+use std::path::PathBuf;
 use std::sync::atomic::Ordering;
 
 /// Uniquely identifies a location in the audio pipeline where an FX Rack can be inserted.
@@ -19,6 +22,9 @@ pub enum InsertionPoint {
     Input,
     Master,
     Atmo,
+    Bus(usize),
+    /// Shared aux return fed by the per-track send levels.
+    Send(usize),
 }
 
 // Custom implementation to convert the enum to a string for JSON map keys.
@@ -34,6 +40,8 @@ impl Serialize for InsertionPoint {
             InsertionPoint::Input => "Input".to_string(),
             InsertionPoint::Master => "Master".to_string(),
             InsertionPoint::Atmo => "Atmo".to_string(),
+            InsertionPoint::Bus(i) => format!("Bus_{}", i),
+            InsertionPoint::Send(i) => format!("Send_{}", i),
         };
         serializer.serialize_str(&s)
     }
@@ -51,6 +59,8 @@ impl<'de> Deserialize<'de> for InsertionPoint {
             match prefix {
                 "Looper" => Ok(InsertionPoint::Looper(index)),
                 "Synth" => Ok(InsertionPoint::Synth(index)),
+                "Bus" => Ok(InsertionPoint::Bus(index)),
+                "Send" => Ok(InsertionPoint::Send(index)),
                 _ => Err(de::Error::custom(format!(
                     "Unknown insertion point prefix: {}",
                     prefix
@@ -78,12 +88,43 @@ impl fmt::Display for InsertionPoint {
             InsertionPoint::Input => write!(f, "Audio Input"),
             InsertionPoint::Master => write!(f, "Master Output"),
             InsertionPoint::Atmo => write!(f, "Atmosphere"),
+            InsertionPoint::Bus(i) => write!(f, "Bus {}", i + 1),
+            InsertionPoint::Send(i) => write!(f, "{} Send", send_name(*i)),
         }
     }
 }
 
+/// Short name of a send return, e.g. "Reverb".
+pub fn send_name(send_index: usize) -> String {
+    match send_index {
+        REVERB_SEND => "Reverb".to_string(),
+        DELAY_SEND => "Delay".to_string(),
+        i => format!("Aux {}", i + 1),
+    }
+}
+
+/// The chain a send return starts with: a reverb on the reverb send and a repeating
+/// delay on the delay send.
+pub fn default_send_preset(send_index: usize) -> FxPreset {
+    let component_type = match send_index {
+        DELAY_SEND => FxComponentType::Delay,
+        _ => FxComponentType::Reverb,
+    };
+    let link = FxChainLink::new(component_type);
+    if let fx_components::ComponentParams::Delay(p) = &link.params {
+        p.time_ms.store((375.0 * fx_components::delay::PARAM_SCALER) as u32, Ordering::Relaxed);
+        p.feedback.store((0.4 * fx_components::delay::PARAM_SCALER) as u32, Ordering::Relaxed);
+    }
+    FxPreset {
+        name: format!("{} Send", send_name(send_index)),
+        chain: vec![link],
+        kill_dry: true,
+        ..Default::default()
+    }
+}
+
 /// The different types of core DSP components a user can add to a chain.
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum FxComponentType {
     Gain,
     Delay,
@@ -143,6 +184,23 @@ impl FxChainLink {
             params: fx_components::ComponentParams::new(component_type),
         }
     }
+
+    /// Creates a link that starts from the user's saved default for this component
+    /// type, if there is one, instead of the factory defaults. Only for components the
+    /// user adds; loading a preset keeps factory values for anything it doesn't store.
+    pub fn with_user_defaults(
+        component_type: FxComponentType,
+        user_defaults: &BTreeMap<FxComponentType, serde_json::Value>,
+    ) -> Self {
+        let link = Self::new(component_type);
+        if let Some(p_map) = user_defaults
+            .get(&component_type)
+            .and_then(|v| v.as_object())
+        {
+            apply_component_params_json(&link.params, p_map);
+        }
+        link
+    }
 }
 
 /// A serializable version of `FxChainLink` for saving/loading presets.
@@ -169,6 +227,9 @@ pub struct FxPreset {
         deserialize_with = "deserialize_chain"
     )]
     pub chain: Vec<FxChainLink>,
+    /// Drops the dry signal entirely so the rack outputs only the processed chain, whatever
+    /// the dry/wet knob says. Meant for send returns, which would otherwise double the dry.
+    pub kill_dry: bool,
 }
 
 impl Default for FxPreset {
@@ -177,17 +238,298 @@ impl Default for FxPreset {
             name: "New Preset".to_string(),
             author: "".to_string(),
             chain: Vec::new(),
+            kill_dry: false,
+        }
+    }
+}
+
+/// Reads the saved preset configured as `point`'s default chain, looked up by name among
+/// `available_presets`. `None` if no default is set or its file is missing or unreadable.
+pub fn read_default_preset(
+    default_presets: &BTreeMap<InsertionPoint, String>,
+    available_presets: &[(String, PathBuf)],
+    point: InsertionPoint,
+) -> Option<FxPreset> {
+    let name = default_presets.get(&point)?;
+    let Some((_, path)) = available_presets.iter().find(|(n, _)| n == name) else {
+        eprintln!("Default FX preset '{}' for {} not found.", name, point);
+        return None;
+    };
+    match std::fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<FxPreset>(&json).ok())
+    {
+        Some(mut preset) => {
+            preset.name = name.clone();
+            Some(preset)
+        }
+        None => {
+            eprintln!("Failed to load default FX preset {}", path.display());
+            None
         }
     }
 }
 
 // --- Custom Serialization and Deserialization Logic ---
 
+/// Captures a component's current parameter values as a plain JSON map.
+pub fn component_params_to_json(params: &fx_components::ComponentParams) -> serde_json::Value {
+    use fx_components::*;
+
+    let mut value = match params {
+        ComponentParams::Gain(p) => {
+            let gain_db =
+                (p.gain_db.load(Ordering::Relaxed) as f32 / gain::DB_SCALER) - gain::DB_OFFSET;
+            serde_json::json!({ "gain_db": gain_db })
+        }
+        ComponentParams::Delay(p) => {
+            let time_ms = p.time_ms.load(Ordering::Relaxed) as f32 / delay::PARAM_SCALER;
+            let feedback = p.feedback.load(Ordering::Relaxed) as f32 / delay::PARAM_SCALER;
+            let damping = p.damping.load(Ordering::Relaxed) as f32 / delay::PARAM_SCALER;
+            serde_json::json!({ "time_ms": time_ms, "feedback": feedback, "damping": damping })
+        }
+        ComponentParams::Filter(p) => {
+            let mode = p.mode.load(Ordering::Relaxed);
+            let frequency_hz =
+                p.frequency_hz.load(Ordering::Relaxed) as f32 / filter::PARAM_SCALER;
+            let resonance =
+                p.resonance.load(Ordering::Relaxed) as f32 / filter::PARAM_SCALER;
+            serde_json::json!({ "mode": mode, "frequency_hz": frequency_hz, "resonance": resonance })
+        }
+        ComponentParams::Lfo(p) => {
+            let waveform = p.waveform.load(Ordering::Relaxed);
+            let frequency_hz =
+                p.frequency_hz.load(Ordering::Relaxed) as f32 / lfo::PARAM_SCALER;
+            serde_json::json!({ "waveform": waveform, "frequency_hz": frequency_hz })
+        }
+        ComponentParams::EnvelopeFollower(p) => {
+            let attack_ms = p.attack_ms.load(Ordering::Relaxed) as f32
+                / envelope_follower::PARAM_SCALER;
+            let release_ms = p.release_ms.load(Ordering::Relaxed) as f32
+                / envelope_follower::PARAM_SCALER;
+            let sensitivity = p.sensitivity.load(Ordering::Relaxed) as f32
+                / envelope_follower::PARAM_SCALER;
+            serde_json::json!({ "attack_ms": attack_ms, "release_ms": release_ms, "sensitivity": sensitivity })
+        }
+        ComponentParams::Waveshaper(p) => {
+            let mode = p.mode.load(Ordering::Relaxed);
+            let drive_db =
+                p.drive_db.load(Ordering::Relaxed) as f32 / waveshaper::DB_SCALER;
+            let oversample = p.oversample.load(Ordering::Relaxed);
+            serde_json::json!({ "mode": mode, "drive_db": drive_db, "oversample": oversample })
+        }
+        ComponentParams::Quantizer(p) => {
+            let bit_depth =
+                p.bit_depth.load(Ordering::Relaxed) as f32 / quantizer::PARAM_SCALER;
+            let downsample =
+                p.downsample.load(Ordering::Relaxed) as f32 / quantizer::PARAM_SCALER;
+            let slew_time =
+                p.slew_time.load(Ordering::Relaxed) as f32 / quantizer::PARAM_SCALER;
+            let probability =
+                p.probability.load(Ordering::Relaxed) as f32 / quantizer::PARAM_SCALER;
+            serde_json::json!({
+                "bit_depth": bit_depth,
+                "downsample": downsample,
+                "slew_time": slew_time,
+                "probability": probability
+            })
+        }
+        ComponentParams::Reverb(p) => {
+            let size = p.size.load(Ordering::Relaxed) as f32 / reverb::PARAM_SCALER;
+            let decay = p.decay.load(Ordering::Relaxed) as f32 / reverb::PARAM_SCALER;
+            let damping = p.damping.load(Ordering::Relaxed) as f32 / reverb::PARAM_SCALER;
+            serde_json::json!({ "size": size, "decay": decay, "damping": damping })
+        }
+        ComponentParams::Flanger(p) => {
+            let rate_hz = p.rate_hz.load(Ordering::Relaxed) as f32 / flanger::PARAM_SCALER;
+            let depth_ms =
+                p.depth_ms.load(Ordering::Relaxed) as f32 / flanger::PARAM_SCALER;
+            let feedback = (p.feedback.load(Ordering::Relaxed) as f32
+                / flanger::PARAM_SCALER)
+                - flanger::FEEDBACK_OFFSET;
+            serde_json::json!({ "rate_hz": rate_hz, "depth_ms": depth_ms, "feedback": feedback })
+        }
+        ComponentParams::Formant(p) => {
+            let character = (p.character.load(Ordering::Relaxed) as f32 / formant::PARAM_SCALER) - formant::CHARACTER_OFFSET;
+            let resonance = p.resonance.load(Ordering::Relaxed) as f32 / formant::PARAM_SCALER;
+            let vowel = p.vowel.load(Ordering::Relaxed) as f32 / formant::PARAM_SCALER;
+            let vowel_morph = p.vowel_morph.load(Ordering::Relaxed);
+            serde_json::json!({
+                "character": character,
+                "resonance": resonance,
+                "vowel": vowel,
+                "vowel_morph": vowel_morph
+            })
+        }
+    };
+    if let Some(map) = value.as_object_mut() {
+        map.insert("input_gain_db".into(), params.trim().input_db().into());
+        map.insert("output_gain_db".into(), params.trim().output_db().into());
+    }
+    value
+}
+
+/// Writes parameter values from a JSON map (as produced by `component_params_to_json`)
+/// into a component's atomics. Missing keys fall back to the factory defaults.
+pub fn apply_component_params_json(
+    params: &fx_components::ComponentParams,
+    p_map: &serde_json::Map<String, serde_json::Value>,
+) {
+    use fx_components::*;
+
+    let trim = params.trim();
+    let input_gain_db = p_map.get("input_gain_db").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+    let output_gain_db = p_map.get("output_gain_db").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+    trim.input_gain_db.store(GainTrim::db_to_stored(input_gain_db), Ordering::Relaxed);
+    trim.output_gain_db.store(GainTrim::db_to_stored(output_gain_db), Ordering::Relaxed);
+
+    match params {
+        ComponentParams::Gain(p) => {
+            let gain_db = p_map.get("gain_db").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            p.gain_db.store(
+                ((gain_db + gain::DB_OFFSET) * gain::DB_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+        }
+        ComponentParams::Delay(p) => {
+            let time_ms =
+                p_map.get("time_ms").and_then(|v| v.as_f64()).unwrap_or(250.0) as f32;
+            let feedback =
+                p_map.get("feedback").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            let damping = p_map.get("damping").and_then(|v| v.as_f64()).unwrap_or(0.5) as f32;
+            p.time_ms
+                .store((time_ms * delay::PARAM_SCALER) as u32, Ordering::Relaxed);
+            p.feedback
+                .store((feedback * delay::PARAM_SCALER) as u32, Ordering::Relaxed);
+            p.damping
+                .store((damping * delay::PARAM_SCALER) as u32, Ordering::Relaxed);
+        }
+        ComponentParams::Filter(p) => {
+            let mode = p_map.get("mode").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+            let frequency_hz =
+                p_map.get("frequency_hz").and_then(|v| v.as_f64()).unwrap_or(1000.0) as f32;
+            let resonance =
+                p_map.get("resonance").and_then(|v| v.as_f64()).unwrap_or(0.1) as f32;
+            p.mode.store(mode, Ordering::Relaxed);
+            p.frequency_hz.store(
+                (frequency_hz * filter::PARAM_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+            p.resonance.store(
+                (resonance * filter::PARAM_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+        }
+        ComponentParams::Lfo(p) => {
+            let waveform = p_map.get("waveform").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+            let frequency_hz =
+                p_map.get("frequency_hz").and_then(|v| v.as_f64()).unwrap_or(1.0) as f32;
+            p.waveform.store(waveform, Ordering::Relaxed);
+            p.frequency_hz.store(
+                (frequency_hz * lfo::PARAM_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+        }
+        ComponentParams::EnvelopeFollower(p) => {
+            let attack_ms =
+                p_map.get("attack_ms").and_then(|v| v.as_f64()).unwrap_or(10.0) as f32;
+            let release_ms =
+                p_map.get("release_ms").and_then(|v| v.as_f64()).unwrap_or(150.0) as f32;
+            let sensitivity =
+                p_map.get("sensitivity").and_then(|v| v.as_f64()).unwrap_or(1.0) as f32;
+            p.attack_ms.store(
+                (attack_ms * envelope_follower::PARAM_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+            p.release_ms.store(
+                (release_ms * envelope_follower::PARAM_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+            p.sensitivity.store(
+                (sensitivity * envelope_follower::PARAM_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+        }
+        ComponentParams::Waveshaper(p) => {
+            let mode = p_map.get("mode").and_then(|v| v.as_u64()).unwrap_or(1) as u32; // Default to Saturation
+            let drive_db = p_map.get("drive_db").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            let oversample = p_map.get("oversample").and_then(|v| v.as_u64()).unwrap_or(1) as u32;
+            p.mode.store(mode, Ordering::Relaxed);
+            p.drive_db.store(
+                (drive_db * waveshaper::DB_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+            p.oversample.store(oversample, Ordering::Relaxed);
+        }
+        ComponentParams::Quantizer(p) => {
+            let bit_depth =
+                p_map.get("bit_depth").and_then(|v| v.as_f64()).unwrap_or(16.0) as f32;
+            let downsample =
+                p_map.get("downsample").and_then(|v| v.as_f64()).unwrap_or(1.0) as f32;
+            let slew_time =
+                p_map.get("slew_time").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            let probability =
+                p_map.get("probability").and_then(|v| v.as_f64()).unwrap_or(1.0) as f32;
+            p.bit_depth.store(
+                (bit_depth * quantizer::PARAM_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+            p.downsample.store(
+                (downsample * quantizer::PARAM_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+            p.slew_time.store(
+                (slew_time * quantizer::PARAM_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+            p.probability.store(
+                (probability * quantizer::PARAM_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+        }
+        ComponentParams::Reverb(p) => {
+            let size = p_map.get("size").and_then(|v| v.as_f64()).unwrap_or(0.7) as f32;
+            let decay = p_map.get("decay").and_then(|v| v.as_f64()).unwrap_or(0.8) as f32;
+            let damping = p_map.get("damping").and_then(|v| v.as_f64()).unwrap_or(0.5) as f32;
+            p.size
+                .store((size * reverb::PARAM_SCALER) as u32, Ordering::Relaxed);
+            p.decay
+                .store((decay * reverb::PARAM_SCALER) as u32, Ordering::Relaxed);
+            p.damping
+                .store((damping * reverb::PARAM_SCALER) as u32, Ordering::Relaxed);
+        }
+        ComponentParams::Flanger(p) => {
+            let rate_hz = p_map.get("rate_hz").and_then(|v| v.as_f64()).unwrap_or(0.2) as f32;
+            let depth_ms =
+                p_map.get("depth_ms").and_then(|v| v.as_f64()).unwrap_or(5.0) as f32;
+            let feedback =
+                p_map.get("feedback").and_then(|v| v.as_f64()).unwrap_or(0.85) as f32;
+            p.rate_hz
+                .store((rate_hz * flanger::PARAM_SCALER) as u32, Ordering::Relaxed);
+            p.depth_ms
+                .store((depth_ms * flanger::PARAM_SCALER) as u32, Ordering::Relaxed);
+            p.feedback.store(
+                ((feedback + flanger::FEEDBACK_OFFSET) * flanger::PARAM_SCALER) as u32,
+                Ordering::Relaxed,
+            );
+        }
+        ComponentParams::Formant(p) => {
+            let character = p_map.get("character").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            let resonance = p_map.get("resonance").and_then(|v| v.as_f64()).unwrap_or(0.7) as f32;
+            p.character.store(((character + formant::CHARACTER_OFFSET) * formant::PARAM_SCALER) as u32, Ordering::Relaxed);
+            let vowel = p_map.get("vowel").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            let vowel_morph = p_map.get("vowel_morph").and_then(|v| v.as_bool()).unwrap_or(false);
+            p.resonance.store((resonance * formant::PARAM_SCALER) as u32, Ordering::Relaxed);
+            p.vowel.store((vowel * formant::PARAM_SCALER) as u32, Ordering::Relaxed);
+            p.vowel_morph.store(vowel_morph, Ordering::Relaxed);
+        }
+    }
+}
+
 fn serialize_chain<S>(chain: &[FxChainLink], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
 {
-    use fx_components::*;
     use serde::ser::SerializeSeq;
 
     let mut seq = serializer.serialize_seq(Some(chain.len()))?;
@@ -197,75 +539,7 @@ where
             component_type: link.component_type,
             modulations: link.modulations.clone(),
             bypassed: link.params.bypassed().load(Ordering::Relaxed),
-            parameters: match &link.params {
-                ComponentParams::Gain(p) => {
-                    let gain_db =
-                        (p.gain_db.load(Ordering::Relaxed) as f32 / gain::DB_SCALER) - gain::DB_OFFSET;
-                    serde_json::json!({ "gain_db": gain_db })
-                }
-                ComponentParams::Delay(p) => {
-                    let time_ms = p.time_ms.load(Ordering::Relaxed) as f32 / delay::PARAM_SCALER;
-                    let feedback = p.feedback.load(Ordering::Relaxed) as f32 / delay::PARAM_SCALER;
-                    let damping = p.damping.load(Ordering::Relaxed) as f32 / delay::PARAM_SCALER;
-                    serde_json::json!({ "time_ms": time_ms, "feedback": feedback, "damping": damping })
-                }
-                ComponentParams::Filter(p) => {
-                    let mode = p.mode.load(Ordering::Relaxed);
-                    let frequency_hz =
-                        p.frequency_hz.load(Ordering::Relaxed) as f32 / filter::PARAM_SCALER;
-                    let resonance =
-                        p.resonance.load(Ordering::Relaxed) as f32 / filter::PARAM_SCALER;
-                    serde_json::json!({ "mode": mode, "frequency_hz": frequency_hz, "resonance": resonance })
-                }
-                ComponentParams::Lfo(p) => {
-                    let waveform = p.waveform.load(Ordering::Relaxed);
-                    let frequency_hz =
-                        p.frequency_hz.load(Ordering::Relaxed) as f32 / lfo::PARAM_SCALER;
-                    serde_json::json!({ "waveform": waveform, "frequency_hz": frequency_hz })
-                }
-                ComponentParams::EnvelopeFollower(p) => {
-                    let attack_ms = p.attack_ms.load(Ordering::Relaxed) as f32
-                        / envelope_follower::PARAM_SCALER;
-                    let release_ms = p.release_ms.load(Ordering::Relaxed) as f32
-                        / envelope_follower::PARAM_SCALER;
-                    let sensitivity = p.sensitivity.load(Ordering::Relaxed) as f32
-                        / envelope_follower::PARAM_SCALER;
-                    serde_json::json!({ "attack_ms": attack_ms, "release_ms": release_ms, "sensitivity": sensitivity })
-                }
-                ComponentParams::Waveshaper(p) => {
-                    let mode = p.mode.load(Ordering::Relaxed);
-                    let drive_db =
-                        p.drive_db.load(Ordering::Relaxed) as f32 / waveshaper::DB_SCALER;
-                    serde_json::json!({ "mode": mode, "drive_db": drive_db })
-                }
-                ComponentParams::Quantizer(p) => {
-                    let bit_depth =
-                        p.bit_depth.load(Ordering::Relaxed) as f32 / quantizer::PARAM_SCALER;
-                    let downsample =
-                        p.downsample.load(Ordering::Relaxed) as f32 / quantizer::PARAM_SCALER;
-                    serde_json::json!({ "bit_depth": bit_depth, "downsample": downsample })
-                }
-                ComponentParams::Reverb(p) => {
-                    let size = p.size.load(Ordering::Relaxed) as f32 / reverb::PARAM_SCALER;
-                    let decay = p.decay.load(Ordering::Relaxed) as f32 / reverb::PARAM_SCALER;
-                    let damping = p.damping.load(Ordering::Relaxed) as f32 / reverb::PARAM_SCALER;
-                    serde_json::json!({ "size": size, "decay": decay, "damping": damping })
-                }
-                ComponentParams::Flanger(p) => {
-                    let rate_hz = p.rate_hz.load(Ordering::Relaxed) as f32 / flanger::PARAM_SCALER;
-                    let depth_ms =
-                        p.depth_ms.load(Ordering::Relaxed) as f32 / flanger::PARAM_SCALER;
-                    let feedback = (p.feedback.load(Ordering::Relaxed) as f32
-                        / flanger::PARAM_SCALER)
-                        - flanger::FEEDBACK_OFFSET;
-                    serde_json::json!({ "rate_hz": rate_hz, "depth_ms": depth_ms, "feedback": feedback })
-                }
-                ComponentParams::Formant(p) => {
-                    let character = (p.character.load(Ordering::Relaxed) as f32 / formant::PARAM_SCALER) - formant::CHARACTER_OFFSET;
-                    let resonance = p.resonance.load(Ordering::Relaxed) as f32 / formant::PARAM_SCALER;
-                    serde_json::json!({ "character": character, "resonance": resonance })
-                }
-            },
+            parameters: component_params_to_json(&link.params),
         };
         seq.serialize_element(&serializable_link)?;
     }
@@ -291,129 +565,7 @@ where
             .as_object()
             .ok_or_else(|| D::Error::custom("Parameters must be a map"))?;
 
-        match &params {
-            ComponentParams::Gain(p) => {
-                let gain_db = p_map.get("gain_db").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-                p.gain_db.store(
-                    ((gain_db + gain::DB_OFFSET) * gain::DB_SCALER) as u32,
-                    Ordering::Relaxed,
-                );
-            }
-            ComponentParams::Delay(p) => {
-                let time_ms =
-                    p_map.get("time_ms").and_then(|v| v.as_f64()).unwrap_or(250.0) as f32;
-                let feedback =
-                    p_map.get("feedback").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-                let damping = p_map.get("damping").and_then(|v| v.as_f64()).unwrap_or(0.5) as f32;
-                p.time_ms
-                    .store((time_ms * delay::PARAM_SCALER) as u32, Ordering::Relaxed);
-                p.feedback
-                    .store((feedback * delay::PARAM_SCALER) as u32, Ordering::Relaxed);
-                p.damping
-                    .store((damping * delay::PARAM_SCALER) as u32, Ordering::Relaxed);
-            }
-            ComponentParams::Filter(p) => {
-                let mode = p_map.get("mode").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
-                let frequency_hz =
-                    p_map.get("frequency_hz").and_then(|v| v.as_f64()).unwrap_or(1000.0) as f32;
-                let resonance =
-                    p_map.get("resonance").and_then(|v| v.as_f64()).unwrap_or(0.1) as f32;
-                p.mode.store(mode, Ordering::Relaxed);
-                p.frequency_hz.store(
-                    (frequency_hz * filter::PARAM_SCALER) as u32,
-                    Ordering::Relaxed,
-                );
-                p.resonance.store(
-                    (resonance * filter::PARAM_SCALER) as u32,
-                    Ordering::Relaxed,
-                );
-            }
-            ComponentParams::Lfo(p) => {
-                let waveform = p_map.get("waveform").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
-                let frequency_hz =
-                    p_map.get("frequency_hz").and_then(|v| v.as_f64()).unwrap_or(1.0) as f32;
-                p.waveform.store(waveform, Ordering::Relaxed);
-                p.frequency_hz.store(
-                    (frequency_hz * lfo::PARAM_SCALER) as u32,
-                    Ordering::Relaxed,
-                );
-            }
-            ComponentParams::EnvelopeFollower(p) => {
-                let attack_ms =
-                    p_map.get("attack_ms").and_then(|v| v.as_f64()).unwrap_or(10.0) as f32;
-                let release_ms =
-                    p_map.get("release_ms").and_then(|v| v.as_f64()).unwrap_or(150.0) as f32;
-                let sensitivity =
-                    p_map.get("sensitivity").and_then(|v| v.as_f64()).unwrap_or(1.0) as f32;
-                p.attack_ms.store(
-                    (attack_ms * envelope_follower::PARAM_SCALER) as u32,
-                    Ordering::Relaxed,
-                );
-                p.release_ms.store(
-                    (release_ms * envelope_follower::PARAM_SCALER) as u32,
-                    Ordering::Relaxed,
-                );
-                p.sensitivity.store(
-                    (sensitivity * envelope_follower::PARAM_SCALER) as u32,
-                    Ordering::Relaxed,
-                );
-            }
-            ComponentParams::Waveshaper(p) => {
-                let mode = p_map.get("mode").and_then(|v| v.as_u64()).unwrap_or(1) as u32; // Default to Saturation
-                let drive_db = p_map.get("drive_db").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-                p.mode.store(mode, Ordering::Relaxed);
-                p.drive_db.store(
-                    (drive_db * waveshaper::DB_SCALER) as u32,
-                    Ordering::Relaxed,
-                );
-            }
-            ComponentParams::Quantizer(p) => {
-                let bit_depth =
-                    p_map.get("bit_depth").and_then(|v| v.as_f64()).unwrap_or(16.0) as f32;
-                let downsample =
-                    p_map.get("downsample").and_then(|v| v.as_f64()).unwrap_or(1.0) as f32;
-                p.bit_depth.store(
-                    (bit_depth * quantizer::PARAM_SCALER) as u32,
-                    Ordering::Relaxed,
-                );
-                p.downsample.store(
-                    (downsample * quantizer::PARAM_SCALER) as u32,
-                    Ordering::Relaxed,
-                );
-            }
-            ComponentParams::Reverb(p) => {
-                let size = p_map.get("size").and_then(|v| v.as_f64()).unwrap_or(0.7) as f32;
-                let decay = p_map.get("decay").and_then(|v| v.as_f64()).unwrap_or(0.8) as f32;
-                let damping = p_map.get("damping").and_then(|v| v.as_f64()).unwrap_or(0.5) as f32;
-                p.size
-                    .store((size * reverb::PARAM_SCALER) as u32, Ordering::Relaxed);
-                p.decay
-                    .store((decay * reverb::PARAM_SCALER) as u32, Ordering::Relaxed);
-                p.damping
-                    .store((damping * reverb::PARAM_SCALER) as u32, Ordering::Relaxed);
-            }
-            ComponentParams::Flanger(p) => {
-                let rate_hz = p_map.get("rate_hz").and_then(|v| v.as_f64()).unwrap_or(0.2) as f32;
-                let depth_ms =
-                    p_map.get("depth_ms").and_then(|v| v.as_f64()).unwrap_or(5.0) as f32;
-                let feedback =
-                    p_map.get("feedback").and_then(|v| v.as_f64()).unwrap_or(0.85) as f32;
-                p.rate_hz
-                    .store((rate_hz * flanger::PARAM_SCALER) as u32, Ordering::Relaxed);
-                p.depth_ms
-                    .store((depth_ms * flanger::PARAM_SCALER) as u32, Ordering::Relaxed);
-                p.feedback.store(
-                    ((feedback + flanger::FEEDBACK_OFFSET) * flanger::PARAM_SCALER) as u32,
-                    Ordering::Relaxed,
-                );
-            }
-            ComponentParams::Formant(p) => {
-                let character = p_map.get("character").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-                let resonance = p_map.get("resonance").and_then(|v| v.as_f64()).unwrap_or(0.7) as f32;
-                p.character.store(((character + formant::CHARACTER_OFFSET) * formant::PARAM_SCALER) as u32, Ordering::Relaxed);
-                p.resonance.store((resonance * formant::PARAM_SCALER) as u32, Ordering::Relaxed);
-            }
-        }
+        apply_component_params_json(&params, p_map);
 
         chain.push(FxChainLink {
             component_type: s_link.component_type,
@@ -423,4 +575,78 @@ where
     }
 
     Ok(chain)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn added_components_start_from_the_saved_default_but_presets_do_not() {
+        let mut defaults = BTreeMap::new();
+        defaults.insert(
+            FxComponentType::Reverb,
+            serde_json::json!({ "size": 0.25, "decay": 0.4, "damping": 0.9 }),
+        );
+        let added = FxChainLink::with_user_defaults(FxComponentType::Reverb, &defaults);
+        let fx_components::ComponentParams::Reverb(p) = added.params else {
+            panic!("expected reverb params");
+        };
+        let scaler = fx_components::reverb::PARAM_SCALER;
+        assert_eq!(p.size.load(Ordering::Relaxed), (0.25 * scaler) as u32);
+        assert_eq!(p.decay.load(Ordering::Relaxed), (0.4 * scaler) as u32);
+        assert_eq!(p.damping.load(Ordering::Relaxed), (0.9 * scaler) as u32);
+
+        // A preset that predates a parameter loads it at the factory value.
+        let json = serde_json::json!([{
+            "component_type": "Reverb",
+            "modulations": [],
+            "bypassed": false,
+            "parameters": { "size": 0.5 },
+        }]);
+        let loaded = deserialize_chain(json).unwrap();
+        let factory = fx_components::ComponentParams::new(FxComponentType::Reverb);
+        let (
+            fx_components::ComponentParams::Reverb(loaded),
+            fx_components::ComponentParams::Reverb(factory),
+        ) = (&loaded[0].params, factory)
+        else {
+            panic!("expected reverb params");
+        };
+        assert_eq!(loaded.size.load(Ordering::Relaxed), (0.5 * scaler) as u32);
+        assert_eq!(loaded.decay.load(Ordering::Relaxed), factory.decay.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn configured_default_chain_is_read_for_its_insertion_point() {
+        let dir = std::env::temp_dir().join(format!("cypher-default-fx-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Master Glue.json");
+        let saved = FxPreset {
+            name: "Master Glue".to_string(),
+            chain: vec![
+                FxChainLink::new(FxComponentType::Filter),
+                FxChainLink::new(FxComponentType::Gain),
+            ],
+            ..Default::default()
+        };
+        std::fs::write(&path, serde_json::to_string(&saved).unwrap()).unwrap();
+        let available = vec![("Master Glue".to_string(), path)];
+        let mut defaults = BTreeMap::new();
+        defaults.insert(InsertionPoint::Master, "Master Glue".to_string());
+        defaults.insert(InsertionPoint::Input, "Deleted Gate".to_string());
+
+        let master = read_default_preset(&defaults, &available, InsertionPoint::Master).unwrap();
+        assert_eq!(master.name, "Master Glue");
+        let types: Vec<_> = master
+            .chain
+            .iter()
+            .map(|link| link.component_type)
+            .collect();
+        assert_eq!(types, vec![FxComponentType::Filter, FxComponentType::Gain]);
+
+        assert!(read_default_preset(&defaults, &available, InsertionPoint::Input).is_none());
+        assert!(read_default_preset(&defaults, &available, InsertionPoint::Sampler).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
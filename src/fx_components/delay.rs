@@ -1,7 +1,7 @@
 // src/fx_components/delay.rs
 
 //! A fractional delay line using a circular buffer and linear interpolation.
-use crate::fx_components::DspComponent;
+use crate::fx_components::{flush_denormal, DspComponent, GainTrim};
 use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
@@ -19,6 +19,7 @@ pub struct Params {
     /// High-frequency damping (0.0 to 1.0). Stored as `damping * PARAM_SCALER`.
     pub damping: Arc<AtomicU32>,
     pub bypassed: Arc<AtomicBool>,
+    pub trim: GainTrim,
 }
 
 impl Default for Params {
@@ -28,6 +29,7 @@ impl Default for Params {
             feedback: Arc::new(AtomicU32::new(0)),
             damping: Arc::new(AtomicU32::new((0.5 * PARAM_SCALER) as u32)),
             bypassed: Arc::new(AtomicBool::new(false)),
+            trim: GainTrim::default(),
         }
     }
 }
@@ -52,7 +54,7 @@ struct DampingFilter {
 impl DampingFilter {
     #[inline(always)]
     fn process(&mut self, input: f32, coeff: f32) -> f32 {
-        let output = input * (1.0 - coeff) + self.z1 * coeff;
+        let output = flush_denormal(input * (1.0 - coeff) + self.z1 * coeff);
         self.z1 = output;
         output
     }
@@ -163,9 +165,15 @@ impl DspComponent for DelayLine {
         let damped_sample = self.damping_filter.process(delayed_sample, self.smoothed_damping);
         let write_sample = input + damped_sample * self.smoothed_feedback;
 
-        self.write(write_sample.clamp(-1.0, 1.0));
+        self.write(flush_denormal(write_sample.clamp(-1.0, 1.0)));
 
         // Return the wet signal for the FxRack to mix
         delayed_sample
     }
-}
\ No newline at end of file
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.write_pos = 0;
+        self.damping_filter.z1 = 0.0;
+    }
+}
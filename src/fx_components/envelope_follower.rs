@@ -1,7 +1,7 @@
 // src/fx_components/envelope_follower.rs
 
 //! Tracks the amplitude envelope of an audio signal.
-use crate::fx_components::DspComponent;
+use crate::fx_components::{DspComponent, GainTrim};
 use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
@@ -19,6 +19,7 @@ pub struct Params {
     /// Pre-gain to boost the input signal, making the follower more or less sensitive.
     pub sensitivity: Arc<AtomicU32>,
     pub bypassed: Arc<AtomicBool>,
+    pub trim: GainTrim,
 }
 
 impl Default for Params {
@@ -29,6 +30,7 @@ impl Default for Params {
             // Default sensitivity of 1.0 (no boost).
             sensitivity: Arc::new(AtomicU32::new((1.0 * PARAM_SCALER) as u32)),
             bypassed: Arc::new(AtomicBool::new(false)),
+            trim: GainTrim::default(),
         }
     }
 }
@@ -111,4 +113,8 @@ impl DspComponent for EnvelopeFollower {
         // The envelope follower is a modulation source; it doesn't affect the audio path.
         input
     }
-}
\ No newline at end of file
+
+    fn reset(&mut self) {
+        self.envelope = 0.0;
+    }
+}
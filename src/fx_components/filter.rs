@@ -3,7 +3,7 @@
 //! A State Variable Filter implementation.
 //! Provides low-pass, high-pass, and band-pass outputs.
 
-use crate::fx_components::DspComponent;
+use crate::fx_components::{DspComponent, GainTrim};
 use std::collections::BTreeMap;
 use std::f32::consts::PI;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
@@ -40,6 +40,7 @@ pub struct Params {
     /// Resonance (0.0 to 1.0). Stored as `resonance * PARAM_SCALER`.
     pub resonance: Arc<AtomicU32>,
     pub bypassed: Arc<AtomicBool>,
+    pub trim: GainTrim,
 }
 
 impl Default for Params {
@@ -49,6 +50,7 @@ impl Default for Params {
             frequency_hz: Arc::new(AtomicU32::new((1000.0 * PARAM_SCALER) as u32)),
             resonance: Arc::new(AtomicU32::new((0.1 * PARAM_SCALER) as u32)),
             bypassed: Arc::new(AtomicBool::new(false)),
+            trim: GainTrim::default(),
         }
     }
 }
@@ -132,4 +134,9 @@ impl DspComponent for Filter {
             FilterMode::BandPass => v1,
         }
     }
-}
\ No newline at end of file
+
+    fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+}
@@ -5,7 +5,7 @@
 //! This component encapsulates the specific signal path required for a flanger effect,
 //! including a modulated delay line, a feedback path, and a final wet/dry mix stage.
 
-use crate::fx_components::{delay::DelayLine, lfo::Lfo, DspComponent};
+use crate::fx_components::{delay::DelayLine, lfo::Lfo, DspComponent, GainTrim};
 use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
@@ -25,6 +25,7 @@ pub struct Params {
     /// Feedback amount (-1.0 to 1.0). Stored as `(feedback + 1.0) * PARAM_SCALER`.
     pub feedback: Arc<AtomicU32>,
     pub bypassed: Arc<AtomicBool>,
+    pub trim: GainTrim,
 }
 
 impl Default for Params {
@@ -36,6 +37,7 @@ impl Default for Params {
                 ((0.85 + FEEDBACK_OFFSET) * PARAM_SCALER) as u32
             )),
             bypassed: Arc::new(AtomicBool::new(false)),
+            trim: GainTrim::default(),
         }
     }
 }
@@ -120,4 +122,9 @@ impl DspComponent for Flanger {
         // Return the 100% wet signal. The FxRack handles the final mix.
         wet_sample
     }
-}
\ No newline at end of file
+
+    fn reset(&mut self) {
+        self.delay_line.reset();
+        self.lfo.reset();
+    }
+}
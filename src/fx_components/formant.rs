@@ -1,5 +1,8 @@
 //! A formant filter effect that simulates changes in the vocal tract.
-use crate::fx_components::DspComponent;
+//!
+//! With vowel morphing engaged, the filter bank follows the tenor formant table from the Csound
+//! manual's "Formant Values" appendix, sweeping A -> E -> I -> O -> U as `vowel` goes 0 -> 1.
+use crate::fx_components::{DspComponent, GainTrim};
 use std::collections::BTreeMap;
 use std::f32::consts::PI;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
@@ -17,7 +20,12 @@ pub struct Params {
     pub character: Arc<AtomicU32>,
     /// Resonance/Q of the formant peaks (0.0 to 1.0).
     pub resonance: Arc<AtomicU32>,
+    /// Position in the vowel table (0.0 to 1.0), used while `vowel_morph` is on.
+    pub vowel: Arc<AtomicU32>,
+    /// Replaces the fixed "ah" formants with the vowel table. `character` still shifts them.
+    pub vowel_morph: Arc<AtomicBool>,
     pub bypassed: Arc<AtomicBool>,
+    pub trim: GainTrim,
 }
 
 impl Default for Params {
@@ -29,7 +37,10 @@ impl Default for Params {
             )),
             // Default to medium resonance
             resonance: Arc::new(AtomicU32::new((0.7 * PARAM_SCALER) as u32)),
+            vowel: Arc::new(AtomicU32::new(0)),
+            vowel_morph: Arc::new(AtomicBool::new(false)),
             bypassed: Arc::new(AtomicBool::new(false)),
+            trim: GainTrim::default(),
         }
     }
 }
@@ -41,11 +52,30 @@ impl Params {
         match name {
             "character" => Some(self.character.clone()),
             "resonance" => Some(self.resonance.clone()),
+            "vowel" => Some(self.vowel.clone()),
             _ => None,
         }
     }
 }
 
+/// Tenor formant frequencies in Hz for A, E, I, O and U (Csound manual, "Formant Values").
+const VOWEL_FORMANTS: [[f32; 5]; 5] = [
+    [650.0, 1080.0, 2650.0, 2900.0, 3250.0],
+    [400.0, 1700.0, 2600.0, 3200.0, 3580.0],
+    [290.0, 1870.0, 2800.0, 3250.0, 3540.0],
+    [400.0, 800.0, 2600.0, 2800.0, 3000.0],
+    [350.0, 600.0, 2700.0, 2900.0, 3300.0],
+];
+
+/// Formant set at `position` (0.0 to 1.0) along the vowel table, interpolating neighbours.
+fn vowel_formants(position: f32) -> [f32; 5] {
+    let scaled = position.clamp(0.0, 1.0) * (VOWEL_FORMANTS.len() - 1) as f32;
+    let index = (scaled as usize).min(VOWEL_FORMANTS.len() - 2);
+    let frac = scaled - index as f32;
+    let (from, to) = (&VOWEL_FORMANTS[index], &VOWEL_FORMANTS[index + 1]);
+    std::array::from_fn(|i| from[i] + (to[i] - from[i]) * frac)
+}
+
 /// A simple state-variable filter, configured for band-pass output.
 #[derive(Debug, Clone, Copy, Default)]
 struct BandPassFilter {
@@ -100,16 +130,30 @@ impl DspComponent for Formant {
     }
 
     #[inline]
-    fn process_audio(&mut self, input: f32, _mods: &BTreeMap<String, f32>) -> f32 {
+    fn process_audio(&mut self, input: f32, mods: &BTreeMap<String, f32>) -> f32 {
         if self.params.bypassed.load(Ordering::Relaxed) {
             return input;
         }
 
-        // --- 1. Get Target Values from Atomics ---
-        // Note: Modulation is not yet implemented for this component's internal params.
-        let character =
-            (self.params.character.load(Ordering::Relaxed) as f32 / PARAM_SCALER) - CHARACTER_OFFSET;
-        let resonance = self.params.resonance.load(Ordering::Relaxed) as f32 / PARAM_SCALER;
+        // --- 1. Get Target Values (Atomics + Modulation) ---
+        let character = {
+            let base = (self.params.character.load(Ordering::Relaxed) as f32 / PARAM_SCALER)
+                - CHARACTER_OFFSET;
+            let mod_val = mods.get("character").copied().unwrap_or(0.0);
+            (base + mod_val).clamp(-1.0, 1.0)
+        };
+        let resonance = {
+            let base = self.params.resonance.load(Ordering::Relaxed) as f32 / PARAM_SCALER;
+            let mod_val = mods.get("resonance").copied().unwrap_or(0.0);
+            (base + mod_val).clamp(0.0, 1.0)
+        };
+        let formants = if self.params.vowel_morph.load(Ordering::Relaxed) {
+            let base = self.params.vowel.load(Ordering::Relaxed) as f32 / PARAM_SCALER;
+            let mod_val = mods.get("vowel").copied().unwrap_or(0.0);
+            vowel_formants(base + mod_val)
+        } else {
+            self.base_formants
+        };
 
         // --- 2. Calculate final parameters ---
         // Map character (-1 to 1) to a frequency shift ratio (e.g., 0.7 to 1.3)
@@ -120,7 +164,7 @@ impl DspComponent for Formant {
         // --- 3. Process the signal through the parallel filter bank ---
         let mut output = 0.0;
         for (i, filter) in self.filters.iter_mut().enumerate() {
-            let formant_freq = (self.base_formants[i] * shift_ratio)
+            let formant_freq = (formants[i] * shift_ratio)
                 .clamp(20.0, self.sample_rate / 2.0 - 20.0);
             output += filter.process(input, formant_freq, q, self.sample_rate);
         }
@@ -129,4 +173,8 @@ impl DspComponent for Formant {
         // The FxRack will handle the final wet/dry mix, so we return the 100% wet signal.
         (output * 0.2).clamp(-1.0, 1.0)
     }
-}
\ No newline at end of file
+
+    fn reset(&mut self) {
+        self.filters = [BandPassFilter::default(); 5];
+    }
+}
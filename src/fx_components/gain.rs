@@ -3,7 +3,7 @@
 //! A simple audio gain component.
 //!
 //! Multiplies the incoming audio signal by a given factor.
-use crate::fx_components::DspComponent;
+use crate::fx_components::{DspComponent, GainTrim};
 use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
@@ -19,6 +19,7 @@ pub struct Params {
     /// Gain in dB, stored as a scaled u32: `(value_db + 60.0) * 100_000.0`
     pub gain_db: Arc<AtomicU32>,
     pub bypassed: Arc<AtomicBool>,
+    pub trim: GainTrim,
 }
 
 impl Default for Params {
@@ -29,6 +30,7 @@ impl Default for Params {
                 ((0.0 + DB_OFFSET) * DB_SCALER) as u32,
             )),
             bypassed: Arc::new(AtomicBool::new(false)),
+            trim: GainTrim::default(),
         }
     }
 }
@@ -1,7 +1,7 @@
 // src/fx_components/lfo.rs
 
 //! A Low-Frequency Oscillator for generating modulation signals.
-use crate::fx_components::DspComponent;
+use crate::fx_components::{DspComponent, GainTrim};
 use std::collections::BTreeMap;
 use std::f32::consts::TAU;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
@@ -19,6 +19,34 @@ pub enum LfoWaveform {
     InvSaw = 3, // Inverted Saw (Ramp)
     Square = 4,
     Random = 5, // Sample & Hold
+    SampleHold = 6,
+    SmoothRandom = 7,
+}
+
+impl LfoWaveform {
+    pub const ALL: [LfoWaveform; 8] = [
+        LfoWaveform::Sine,
+        LfoWaveform::Triangle,
+        LfoWaveform::Saw,
+        LfoWaveform::InvSaw,
+        LfoWaveform::Square,
+        LfoWaveform::Random,
+        LfoWaveform::SampleHold,
+        LfoWaveform::SmoothRandom,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LfoWaveform::Sine => "Sine",
+            LfoWaveform::Triangle => "Triangle",
+            LfoWaveform::Saw => "Saw",
+            LfoWaveform::InvSaw => "InvSaw",
+            LfoWaveform::Square => "Square",
+            LfoWaveform::Random => "Random",
+            LfoWaveform::SampleHold => "S&H",
+            LfoWaveform::SmoothRandom => "Smooth Random",
+        }
+    }
 }
 
 impl From<u32> for LfoWaveform {
@@ -29,6 +57,8 @@ impl From<u32> for LfoWaveform {
             3 => LfoWaveform::InvSaw,
             4 => LfoWaveform::Square,
             5 => LfoWaveform::Random,
+            6 => LfoWaveform::SampleHold,
+            7 => LfoWaveform::SmoothRandom,
             _ => LfoWaveform::Sine,
         }
     }
@@ -37,11 +67,12 @@ impl From<u32> for LfoWaveform {
 /// Shared, automatable parameters for the LFO component.
 #[derive(Debug, Clone)]
 pub struct Params {
-    /// LFO waveform shape. Stored as a u32 (0-5).
+    /// LFO waveform shape. Stored as a u32 (0-7).
     pub waveform: Arc<AtomicU32>,
     /// LFO rate in Hz. Stored as `freq * PARAM_SCALER`.
     pub frequency_hz: Arc<AtomicU32>,
     pub bypassed: Arc<AtomicBool>,
+    pub trim: GainTrim,
 }
 
 impl Default for Params {
@@ -50,6 +81,7 @@ impl Default for Params {
             waveform: Arc::new(AtomicU32::new(LfoWaveform::Sine as u32)),
             frequency_hz: Arc::new(AtomicU32::new((1.0 * PARAM_SCALER) as u32)),
             bypassed: Arc::new(AtomicBool::new(false)),
+            trim: GainTrim::default(),
         }
     }
 }
@@ -65,6 +97,59 @@ impl Params {
     }
 }
 
+/// Seeded random steps behind the S&H and smooth random shapes. Unlike `Random`, the sequence
+/// restarts from the seed on `reseed`, so a retriggered LFO repeats the same pattern.
+#[derive(Debug, Clone)]
+pub struct SteppedRandom {
+    seed: u32,
+    state: u32,
+    previous: f32,
+    current: f32,
+}
+
+impl SteppedRandom {
+    pub fn new(seed: u32) -> Self {
+        let mut random = Self {
+            // Xorshift gets stuck at zero.
+            seed: seed.max(1),
+            state: 0,
+            previous: 0.0,
+            current: 0.0,
+        };
+        random.reseed();
+        random
+    }
+
+    pub fn reseed(&mut self) {
+        self.state = self.seed;
+        self.previous = self.next_value();
+        self.current = self.next_value();
+    }
+
+    fn next_value(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+
+    /// Moves on to the next random target. Call once per LFO cycle.
+    pub fn advance(&mut self) {
+        self.previous = self.current;
+        self.current = self.next_value();
+    }
+
+    pub fn held(&self) -> f32 {
+        self.current
+    }
+
+    /// Eases from the previous target to the current one over a cycle (`phase` 0..1).
+    pub fn smooth(&self, phase: f32) -> f32 {
+        let t = phase * phase * (3.0 - 2.0 * phase);
+        self.previous + (self.current - self.previous) * t
+    }
+}
+
 /// The audio-thread state for the LFO component.
 #[derive(Debug)]
 pub struct Lfo {
@@ -72,6 +157,7 @@ pub struct Lfo {
     phase: f32,
     sample_rate: f32,
     last_output: f32,
+    random: SteppedRandom,
 }
 
 impl Lfo {
@@ -81,6 +167,7 @@ impl Lfo {
             phase: 0.0,
             sample_rate,
             last_output: 0.0,
+            random: SteppedRandom::new(rand::random()),
         }
     }
 
@@ -103,6 +190,18 @@ impl Lfo {
                 }
                 self.last_output
             }
+            LfoWaveform::SampleHold => {
+                if self.phase < phase_inc {
+                    self.random.advance();
+                }
+                self.random.held()
+            }
+            LfoWaveform::SmoothRandom => {
+                if self.phase < phase_inc {
+                    self.random.advance();
+                }
+                self.random.smooth(self.phase)
+            }
         }
     }
 }
@@ -127,4 +226,10 @@ impl DspComponent for Lfo {
         // LFO is a modulator, so it just passes audio through.
         input
     }
-}
\ No newline at end of file
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+        self.last_output = 0.0;
+        self.random.reseed();
+    }
+}
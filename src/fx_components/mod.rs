@@ -27,9 +27,76 @@ pub use waveshaper::{Waveshaper, Params as WaveshaperParams};
 use crate::fx::FxComponentType;
 use std::any::Any;
 use std::collections::BTreeMap;
-use std::sync::atomic::{AtomicBool, AtomicU32};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 
+/// Scaler for trim storage: `(value_db + TRIM_DB_OFFSET) * TRIM_DB_SCALER`.
+pub const TRIM_DB_SCALER: f32 = 100_000.0;
+/// Keeps the stored trim positive. The UI range is -24 to +24 dB.
+pub const TRIM_DB_OFFSET: f32 = 60.0;
+/// Magnitude below which `flush_denormal` snaps to zero, about -300 dBFS.
+const DENORMAL_THRESHOLD: f32 = 1e-15;
+
+/// Snaps values far below audibility to exactly zero. Applied to state that is fed back into
+/// itself, so a tail decaying into silence never reaches denormal floats, which are very slow
+/// on some CPUs.
+#[inline(always)]
+pub fn flush_denormal(x: f32) -> f32 {
+    if x.abs() < DENORMAL_THRESHOLD {
+        0.0
+    } else {
+        x
+    }
+}
+
+/// Input and output level trims the `FxRack` applies around a component's `process_audio`,
+/// so gain-dependent effects can be driven harder and then brought back to level.
+#[derive(Debug, Clone)]
+pub struct GainTrim {
+    pub input_gain_db: Arc<AtomicU32>,
+    pub output_gain_db: Arc<AtomicU32>,
+}
+
+impl Default for GainTrim {
+    fn default() -> Self {
+        Self {
+            input_gain_db: Arc::new(AtomicU32::new(Self::db_to_stored(0.0))),
+            output_gain_db: Arc::new(AtomicU32::new(Self::db_to_stored(0.0))),
+        }
+    }
+}
+
+impl GainTrim {
+    pub fn db_to_stored(db: f32) -> u32 {
+        ((db + TRIM_DB_OFFSET) * TRIM_DB_SCALER) as u32
+    }
+
+    pub fn stored_to_db(stored: u32) -> f32 {
+        stored as f32 / TRIM_DB_SCALER - TRIM_DB_OFFSET
+    }
+
+    pub fn input_db(&self) -> f32 {
+        Self::stored_to_db(self.input_gain_db.load(Ordering::Relaxed))
+    }
+
+    pub fn output_db(&self) -> f32 {
+        Self::stored_to_db(self.output_gain_db.load(Ordering::Relaxed))
+    }
+
+    /// The raw (input, output) values as stored, cheap to compare for changes.
+    pub fn stored(&self) -> (u32, u32) {
+        (
+            self.input_gain_db.load(Ordering::Relaxed),
+            self.output_gain_db.load(Ordering::Relaxed),
+        )
+    }
+
+    /// A stored trim value as a linear gain factor.
+    pub fn stored_to_linear(stored: u32) -> f32 {
+        10.0_f32.powf(Self::stored_to_db(stored) / 20.0)
+    }
+}
+
 /// A generic, clonable container for the shared atomic parameters of any DSP component.
 /// This is held by the `FxChainLink` on the UI thread and cloned for the `FxRack` on the audio thread.
 #[derive(Debug, Clone)]
@@ -83,9 +150,30 @@ impl ComponentParams {
         }
     }
 
+    /// Returns the input/output trims for this component.
+    pub fn trim(&self) -> &GainTrim {
+        match self {
+            ComponentParams::Gain(p) => &p.trim,
+            ComponentParams::Delay(p) => &p.trim,
+            ComponentParams::Filter(p) => &p.trim,
+            ComponentParams::Lfo(p) => &p.trim,
+            ComponentParams::EnvelopeFollower(p) => &p.trim,
+            ComponentParams::Waveshaper(p) => &p.trim,
+            ComponentParams::Quantizer(p) => &p.trim,
+            ComponentParams::Reverb(p) => &p.trim,
+            ComponentParams::Flanger(p) => &p.trim,
+            ComponentParams::Formant(p) => &p.trim,
+        }
+    }
+
     /// Retrieves a specific parameter's atomic value by its string name.
-    /// This is used for MIDI mapping.
+    /// This is used for MIDI mapping. The trims are shared by every component type.
     pub fn get_param(&self, name: &str) -> Option<Arc<AtomicU32>> {
+        match name {
+            "input_gain_db" => return Some(self.trim().input_gain_db.clone()),
+            "output_gain_db" => return Some(self.trim().output_gain_db.clone()),
+            _ => {}
+        }
         match self {
             ComponentParams::Gain(p) => p.get_param(name),
             ComponentParams::Delay(p) => p.get_param(name),
@@ -112,4 +200,8 @@ pub trait DspComponent: Send + Sync + Any {
     /// Processes a single audio sample.
     /// Modulation from other components is passed in via the `mods` BTreeMap.
     fn process_audio(&mut self, input: f32, mods: &BTreeMap<String, f32>) -> f32;
+
+    /// Clears any internal state (delay lines, filter memory, envelopes) without
+    /// touching the shared parameters. Stateless components can rely on the default.
+    fn reset(&mut self) {}
 }
\ No newline at end of file
@@ -1,7 +1,9 @@
 // src/fx_components/quantizer.rs
 
-//! A "lo-fi" effect that reduces the bit depth and/or sample rate of a signal.
-use crate::fx_components::DspComponent;
+//! A "lo-fi" effect that reduces the bit depth and/or sample rate of a signal. The held value
+//! can also glide between steps and skip updates at random, and is exposed as a mod output so
+//! the quantized signal can drive other components.
+use crate::fx_components::{DspComponent, GainTrim};
 use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
@@ -16,7 +18,12 @@ pub struct Params {
     pub bit_depth: Arc<AtomicU32>,
     /// Downsample factor (1 to 50). Stored as `downsample * PARAM_SCALER`.
     pub downsample: Arc<AtomicU32>,
+    /// Time in ms to ramp between quantized steps (0.0 to 1000.0). Stored as `ms * PARAM_SCALER`.
+    pub slew_time: Arc<AtomicU32>,
+    /// Chance (0.0 to 1.0) that each hold point takes a new sample. Stored as `p * PARAM_SCALER`.
+    pub probability: Arc<AtomicU32>,
     pub bypassed: Arc<AtomicBool>,
+    pub trim: GainTrim,
 }
 
 impl Default for Params {
@@ -24,7 +31,10 @@ impl Default for Params {
         Self {
             bit_depth: Arc::new(AtomicU32::new((16.0 * PARAM_SCALER) as u32)),
             downsample: Arc::new(AtomicU32::new((1.0 * PARAM_SCALER) as u32)),
+            slew_time: Arc::new(AtomicU32::new(0)),
+            probability: Arc::new(AtomicU32::new((1.0 * PARAM_SCALER) as u32)),
             bypassed: Arc::new(AtomicBool::new(false)),
+            trim: GainTrim::default(),
         }
     }
 }
@@ -35,6 +45,8 @@ impl Params {
         match name {
             "bit_depth" => Some(self.bit_depth.clone()),
             "downsample" => Some(self.downsample.clone()),
+            "slew_time" => Some(self.slew_time.clone()),
+            "probability" => Some(self.probability.clone()),
             _ => None,
         }
     }
@@ -46,21 +58,34 @@ pub struct Quantizer {
     params: Params,
     sample_counter: u32,
     last_sample: f32,
+    /// The (possibly slewing) output, also reported as the mod output.
+    output: f32,
+    slew_target: f32,
+    slew_step: f32,
+    sample_rate: f32,
 }
 
 impl Quantizer {
-    pub fn new(params: Params) -> Self {
+    pub fn new(sample_rate: f32, params: Params) -> Self {
         Self {
             params,
             sample_counter: 0,
             last_sample: 0.0,
+            output: 0.0,
+            slew_target: 0.0,
+            slew_step: 0.0,
+            sample_rate,
         }
     }
 }
 
 impl DspComponent for Quantizer {
     fn get_mod_output(&mut self, _input_sample: f32) -> f32 {
-        0.0 // Quantizer is an audio effect, not a modulator
+        if self.params.bypassed.load(Ordering::Relaxed) {
+            return 0.0;
+        }
+        // Reports the previous sample's output; the audio pass runs after the mod pass.
+        self.output
     }
 
     #[inline]
@@ -83,11 +108,23 @@ impl DspComponent for Quantizer {
             (base + mod_val).round().max(1.0) as u32
         };
 
+        let slew_ms = {
+            let base = self.params.slew_time.load(Ordering::Relaxed) as f32 / PARAM_SCALER;
+            let mod_val = mods.get("slew_time").copied().unwrap_or(0.0);
+            (base + mod_val).clamp(0.0, 1000.0)
+        };
+
+        let probability = {
+            let base = self.params.probability.load(Ordering::Relaxed) as f32 / PARAM_SCALER;
+            let mod_val = mods.get("probability").copied().unwrap_or(0.0);
+            (base + mod_val).clamp(0.0, 1.0)
+        };
+
         // ====================================================================
         // === BUG FIX: Replaced the unstable counter logic with a robust modulo-based approach. ===
         // This ensures the sample-and-hold timing is always stable, even when `target_downsample`
         // is modulated rapidly. This will eliminate the "porcupine" sound.
-        if self.sample_counter == 0 {
+        if self.sample_counter == 0 && (probability >= 1.0 || rand::random::<f32>() < probability) {
             // It's time to take a new sample.
             self.last_sample = input;
         }
@@ -106,7 +143,28 @@ impl DspComponent for Quantizer {
 
         let scaled_sample = (downsampled_input * 0.5 + 0.5) * num_steps;
         let quantized_sample_scaled = scaled_sample.round();
+        let quantized = (quantized_sample_scaled * inv_num_steps) * 2.0 - 1.0;
+
+        // --- 4. Slew towards the new step at a rate that covers each jump in `slew_ms` ---
+        let slew_samples = slew_ms * 0.001 * self.sample_rate;
+        if slew_samples < 1.0 {
+            self.output = quantized;
+        } else {
+            if quantized != self.slew_target {
+                self.slew_step = (quantized - self.output).abs() / slew_samples;
+            }
+            let delta = quantized - self.output;
+            self.output += delta.clamp(-self.slew_step, self.slew_step);
+        }
+        self.slew_target = quantized;
+        self.output
+    }
 
-        (quantized_sample_scaled * inv_num_steps) * 2.0 - 1.0
+    fn reset(&mut self) {
+        self.sample_counter = 0;
+        self.last_sample = 0.0;
+        self.output = 0.0;
+        self.slew_target = 0.0;
+        self.slew_step = 0.0;
     }
-}
\ No newline at end of file
+}
@@ -6,7 +6,7 @@
 //! delay lines (comb filters) and phase diffusers (all-pass filters) to create a
 //! reverberant sound.
 
-use crate::fx_components::DspComponent;
+use crate::fx_components::{flush_denormal, DspComponent, GainTrim};
 use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
@@ -25,6 +25,7 @@ pub struct Params {
     /// High-frequency damping (0.0 to 1.0). Stored as `damping * PARAM_SCALER`.
     pub damping: Arc<AtomicU32>,
     pub bypassed: Arc<AtomicBool>,
+    pub trim: GainTrim,
 }
 
 impl Default for Params {
@@ -34,6 +35,7 @@ impl Default for Params {
             decay: Arc::new(AtomicU32::new((0.8 * PARAM_SCALER) as u32)),
             damping: Arc::new(AtomicU32::new((0.5 * PARAM_SCALER) as u32)),
             bypassed: Arc::new(AtomicBool::new(false)),
+            trim: GainTrim::default(),
         }
     }
 }
@@ -60,7 +62,7 @@ struct DampingFilter {
 impl DampingFilter {
     #[inline(always)]
     fn process(&mut self, input: f32, coeff: f32) -> f32 {
-        let output = input * (1.0 - coeff) + self.z1 * coeff;
+        let output = flush_denormal(input * (1.0 - coeff) + self.z1 * coeff);
         self.z1 = output;
         output
     }
@@ -89,7 +91,7 @@ impl CombFilter {
             % self.buffer.len();
         let output = self.buffer[read_index];
         let damped_output = self.damping_filter.process(output, damping);
-        self.buffer[self.write_pos] = input + damped_output * feedback;
+        self.buffer[self.write_pos] = flush_denormal(input + damped_output * feedback);
         self.write_pos = (self.write_pos + 1) % self.buffer.len();
         output
     }
@@ -116,7 +118,7 @@ impl AllPassFilter {
             % self.buffer.len();
         let delayed = self.buffer[read_index];
         let output = -input + delayed;
-        self.buffer[self.write_pos] = input + delayed * 0.5; // G = 0.5 (fixed)
+        self.buffer[self.write_pos] = flush_denormal(input + delayed * 0.5); // G = 0.5 (fixed)
         self.write_pos = (self.write_pos + 1) % self.buffer.len();
         output
     }
@@ -220,4 +222,14 @@ impl DspComponent for Reverb {
         // Return the 100% wet signal. The FxRack is responsible for the final mix.
         wet_signal
     }
-}
\ No newline at end of file
+
+    fn reset(&mut self) {
+        for f in &mut self.comb_filters {
+            f.buffer.fill(0.0);
+            f.damping_filter.z1 = 0.0;
+        }
+        for f in &mut self.all_pass_filters {
+            f.buffer.fill(0.0);
+        }
+    }
+}
@@ -6,7 +6,7 @@ pub const NUM_LOOPERS: usize = 12;
 pub const WAVEFORM_DOWNSAMPLE_SIZE: usize = 512;
 
 #[repr(u8)]
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum LooperState {
     Empty,
     Armed,
@@ -36,6 +36,8 @@ pub struct SharedLooperState {
     state: Arc<AtomicU8>,
     length_in_cycles: Arc<AtomicU32>,
     playhead: Arc<AtomicUsize>,
+    /// Length of the loop's audio in samples, published every buffer.
+    audio_len: Arc<AtomicUsize>,
     waveform_summary: Arc<RwLock<Vec<f32>>>,
 }
 
@@ -45,6 +47,7 @@ impl SharedLooperState {
             state: Arc::new(AtomicU8::new(LooperState::Empty as u8)),
             length_in_cycles: Arc::new(AtomicU32::new(0)),
             playhead: Arc::new(AtomicUsize::new(0)),
+            audio_len: Arc::new(AtomicUsize::new(0)),
             waveform_summary: Arc::new(RwLock::new(Vec::new())),
         }
     }
@@ -74,7 +77,178 @@ impl SharedLooperState {
         self.playhead.store(playhead, Ordering::Relaxed);
     }
 
+    pub fn get_audio_len(&self) -> usize {
+        self.audio_len.load(Ordering::Relaxed)
+    }
+
+    pub fn set_audio_len(&self, len: usize) {
+        self.audio_len.store(len, Ordering::Relaxed);
+    }
+
     pub fn get_waveform_summary(&self) -> Arc<RwLock<Vec<f32>>> {
         self.waveform_summary.clone()
     }
+}
+/// Inputs to the per-looper arm/record state machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LooperEvent {
+    /// The looper's button (or its MIDI mapping) was pressed.
+    Press,
+    /// The record input crossed the arm threshold.
+    InputDetected,
+}
+
+/// What the engine should do in response to a `LooperEvent`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LooperTransition {
+    /// The event has no effect in this state.
+    Ignore,
+    /// Switch state immediately.
+    Set(LooperState),
+    /// Arm for a free-running first recording; disarms the others under `ArmPolicy::Exclusive`.
+    Arm,
+    /// Defer the change to the next cycle boundary, optionally showing `Armed` meanwhile.
+    Queue { show_armed: bool },
+    /// Discard the looper's audio and return to `Empty`.
+    Clear,
+}
+
+/// Decides the next step for a looper. With no transport (`transport_len == 0`) the first
+/// recording is free-running: pressing arms, input starts it and the next press closes the
+/// loop. Once a transport exists every record/play/stop change waits for the cycle boundary,
+/// except the overdub toggle which is immediate. A stopped transport ignores presses on
+/// loopers that would need a boundary to act.
+pub fn next_looper_state(
+    current: LooperState,
+    event: LooperEvent,
+    transport_is_playing: bool,
+    transport_len: usize,
+) -> LooperTransition {
+    match (event, current) {
+        (LooperEvent::InputDetected, LooperState::Armed)
+            if transport_is_playing && transport_len == 0 =>
+        {
+            LooperTransition::Set(LooperState::Recording)
+        }
+        (LooperEvent::InputDetected, _) => LooperTransition::Ignore,
+
+        (LooperEvent::Press, LooperState::Empty) if transport_len == 0 => LooperTransition::Arm,
+        (LooperEvent::Press, LooperState::Armed) => LooperTransition::Clear,
+        (LooperEvent::Press, _) if !transport_is_playing => LooperTransition::Ignore,
+        (LooperEvent::Press, LooperState::Empty) => LooperTransition::Queue { show_armed: true },
+        (LooperEvent::Press, LooperState::Playing) => {
+            LooperTransition::Set(LooperState::Overdubbing)
+        }
+        (LooperEvent::Press, LooperState::Overdubbing) => {
+            LooperTransition::Set(LooperState::Playing)
+        }
+        (LooperEvent::Press, LooperState::Recording | LooperState::Stopped) => {
+            LooperTransition::Queue { show_armed: false }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::LooperEvent::{InputDetected, Press};
+    use super::LooperState::*;
+    use super::*;
+
+    const STOPPED: bool = false;
+    const PLAYING: bool = true;
+    const NO_LEN: usize = 0;
+    const LEN: usize = 48_000;
+
+    const QUEUE_ARMED: LooperTransition = LooperTransition::Queue { show_armed: true };
+    const QUEUE: LooperTransition = LooperTransition::Queue { show_armed: false };
+    const IGNORE: LooperTransition = LooperTransition::Ignore;
+    const ARM: LooperTransition = LooperTransition::Arm;
+    const CLEAR: LooperTransition = LooperTransition::Clear;
+
+    /// Every state, event, transport state and transport length, with the expected step.
+    const TRANSITIONS: [(LooperState, LooperEvent, bool, usize, LooperTransition); 48] = [
+        (Empty, Press, STOPPED, NO_LEN, ARM),
+        (Empty, Press, STOPPED, LEN, IGNORE),
+        (Empty, Press, PLAYING, NO_LEN, ARM),
+        (Empty, Press, PLAYING, LEN, QUEUE_ARMED),
+        (Armed, Press, STOPPED, NO_LEN, CLEAR),
+        (Armed, Press, STOPPED, LEN, CLEAR),
+        (Armed, Press, PLAYING, NO_LEN, CLEAR),
+        (Armed, Press, PLAYING, LEN, CLEAR),
+        (Recording, Press, STOPPED, NO_LEN, IGNORE),
+        (Recording, Press, STOPPED, LEN, IGNORE),
+        (Recording, Press, PLAYING, NO_LEN, QUEUE),
+        (Recording, Press, PLAYING, LEN, QUEUE),
+        (Playing, Press, STOPPED, NO_LEN, IGNORE),
+        (Playing, Press, STOPPED, LEN, IGNORE),
+        (Playing, Press, PLAYING, NO_LEN, LooperTransition::Set(Overdubbing)),
+        (Playing, Press, PLAYING, LEN, LooperTransition::Set(Overdubbing)),
+        (Overdubbing, Press, STOPPED, NO_LEN, IGNORE),
+        (Overdubbing, Press, STOPPED, LEN, IGNORE),
+        (Overdubbing, Press, PLAYING, NO_LEN, LooperTransition::Set(Playing)),
+        (Overdubbing, Press, PLAYING, LEN, LooperTransition::Set(Playing)),
+        (Stopped, Press, STOPPED, NO_LEN, IGNORE),
+        (Stopped, Press, STOPPED, LEN, IGNORE),
+        (Stopped, Press, PLAYING, NO_LEN, QUEUE),
+        (Stopped, Press, PLAYING, LEN, QUEUE),
+        (Empty, InputDetected, STOPPED, NO_LEN, IGNORE),
+        (Empty, InputDetected, STOPPED, LEN, IGNORE),
+        (Empty, InputDetected, PLAYING, NO_LEN, IGNORE),
+        (Empty, InputDetected, PLAYING, LEN, IGNORE),
+        (Armed, InputDetected, STOPPED, NO_LEN, IGNORE),
+        (Armed, InputDetected, STOPPED, LEN, IGNORE),
+        (Armed, InputDetected, PLAYING, NO_LEN, LooperTransition::Set(Recording)),
+        (Armed, InputDetected, PLAYING, LEN, IGNORE),
+        (Recording, InputDetected, STOPPED, NO_LEN, IGNORE),
+        (Recording, InputDetected, STOPPED, LEN, IGNORE),
+        (Recording, InputDetected, PLAYING, NO_LEN, IGNORE),
+        (Recording, InputDetected, PLAYING, LEN, IGNORE),
+        (Playing, InputDetected, STOPPED, NO_LEN, IGNORE),
+        (Playing, InputDetected, STOPPED, LEN, IGNORE),
+        (Playing, InputDetected, PLAYING, NO_LEN, IGNORE),
+        (Playing, InputDetected, PLAYING, LEN, IGNORE),
+        (Overdubbing, InputDetected, STOPPED, NO_LEN, IGNORE),
+        (Overdubbing, InputDetected, STOPPED, LEN, IGNORE),
+        (Overdubbing, InputDetected, PLAYING, NO_LEN, IGNORE),
+        (Overdubbing, InputDetected, PLAYING, LEN, IGNORE),
+        (Stopped, InputDetected, STOPPED, NO_LEN, IGNORE),
+        (Stopped, InputDetected, STOPPED, LEN, IGNORE),
+        (Stopped, InputDetected, PLAYING, NO_LEN, IGNORE),
+        (Stopped, InputDetected, PLAYING, LEN, IGNORE),
+    ];
+
+    #[test]
+    fn every_transition_matches_the_table() {
+        for (state, event, is_playing, len, expected) in TRANSITIONS {
+            assert_eq!(
+                next_looper_state(state, event, is_playing, len),
+                expected,
+                "{:?} + {:?}, playing: {}, transport_len: {}",
+                state,
+                event,
+                is_playing,
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn table_covers_every_combination() {
+        let states = [Empty, Armed, Recording, Playing, Overdubbing, Stopped];
+        for state in states {
+            for event in [Press, InputDetected] {
+                for is_playing in [STOPPED, PLAYING] {
+                    for len in [NO_LEN, LEN] {
+                        let key = (state, event, is_playing, len);
+                        let rows = TRANSITIONS
+                            .iter()
+                            .filter(|row| (row.0, row.1, row.2, row.3) == key)
+                            .count();
+                        assert_eq!(rows, 1, "{:?} + {:?}, {}, {}", state, event, is_playing, len);
+                    }
+                }
+            }
+        }
+    }
 }
\ No newline at end of file
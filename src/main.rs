@@ -22,6 +22,7 @@ mod sampler_engine;
 mod theory;
 mod slicer;
 mod atmo;
+mod session_bundle;
 
 use crate::app::CypherApp;
 
@@ -7,8 +7,10 @@ use crate::settings::{
 };
 use anyhow::Result;
 use midir::{Ignore, MidiInput, MidiInputConnection, MidiInputPort};
-use std::collections::{BTreeMap, BTreeSet};
-use std::sync::atomic::{AtomicBool, AtomicI8, AtomicU32, AtomicU64, Ordering};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::sync::atomic::{
+    AtomicBool, AtomicI16, AtomicI8, AtomicU16, AtomicU32, AtomicU64, Ordering,
+};
 use std::sync::{mpsc::Sender, Arc, RwLock};
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
@@ -20,6 +22,128 @@ const LONG_PRESS_DURATION: Duration = Duration::from_millis(500);
 const HOLD_CHECK_INTERVAL: Duration = Duration::from_millis(50);
 const RELATIVE_SENSITIVITY: f32 = 0.005;
 
+/// Values of the chord pad request besides a suggestion group index.
+pub const CHORD_PAD_IDLE: i8 = -1;
+pub const CHORD_PAD_RELEASE: i8 = -2;
+
+const MIDI_CLOCK_TICK: u8 = 0xF8;
+const MIDI_CLOCK_START: u8 = 0xFA;
+const MIDI_CLOCK_CONTINUE: u8 = 0xFB;
+const MIDI_CLOCK_STOP: u8 = 0xFC;
+const MIDI_CLOCK_PPQN: usize = 24;
+/// Relative change in the derived bar length needed before the transport is updated,
+/// so clock jitter doesn't keep nudging the loop length.
+const MIDI_CLOCK_LEN_TOLERANCE: f64 = 0.002;
+
+/// Derives tempo from incoming 24 PPQN MIDI clock ticks, averaged over one beat.
+#[derive(Debug, Default)]
+pub struct MidiClockTracker {
+    last_tick_us: Option<u64>,
+    intervals_us: VecDeque<u64>,
+    last_sent_len: usize,
+}
+
+impl MidiClockTracker {
+    /// Records a clock tick at the given timestamp in microseconds.
+    pub fn tick(&mut self, stamp_us: u64) {
+        if let Some(last) = self.last_tick_us {
+            if stamp_us > last {
+                if self.intervals_us.len() == MIDI_CLOCK_PPQN {
+                    self.intervals_us.pop_front();
+                }
+                self.intervals_us.push_back(stamp_us - last);
+            }
+        }
+        self.last_tick_us = Some(stamp_us);
+    }
+
+    /// Forgets the tick history, e.g. when the external sequencer is restarted.
+    pub fn reset(&mut self) {
+        self.last_tick_us = None;
+        self.intervals_us.clear();
+    }
+
+    /// The measured tempo, once a full beat of ticks has been seen.
+    pub fn bpm(&self) -> Option<f64> {
+        if self.intervals_us.len() < MIDI_CLOCK_PPQN {
+            return None;
+        }
+        let avg_us = self.intervals_us.iter().sum::<u64>() as f64 / self.intervals_us.len() as f64;
+        (avg_us > 0.0).then(|| 60_000_000.0 / (avg_us * MIDI_CLOCK_PPQN as f64))
+    }
+
+    /// Returns a new transport length when the measured tempo has moved far enough
+    /// from the last one reported.
+    pub fn transport_len_update(&mut self, sample_rate: u32) -> Option<usize> {
+        let new_len = bar_len_samples_from_bpm(self.bpm()?, sample_rate);
+        let last = self.last_sent_len as f64;
+        if new_len == 0
+            || (last > 0.0 && (new_len as f64 - last).abs() / last < MIDI_CLOCK_LEN_TOLERANCE)
+        {
+            return None;
+        }
+        self.last_sent_len = new_len;
+        Some(new_len)
+    }
+}
+
+/// Remembers which pitch each incoming note was snapped to, so its note off releases the
+/// same pitch even if the scale changed while it was held. A pitch reached from two held
+/// keys (e.g. C and C# both snapping to C) is only released when both are let go.
+#[derive(Debug)]
+pub struct NoteQuantizeState {
+    snapped_to: [u8; 128],
+    key_held: [bool; 128],
+    pitch_holders: [u8; 128],
+}
+
+impl Default for NoteQuantizeState {
+    fn default() -> Self {
+        Self {
+            snapped_to: std::array::from_fn(|note| note as u8),
+            key_held: [false; 128],
+            pitch_holders: [0; 128],
+        }
+    }
+}
+
+impl NoteQuantizeState {
+    /// The pitch to play for an incoming note on, given the scale mask (0 = no snapping).
+    pub fn note_on(&mut self, note: u8, scale_mask: u16) -> u8 {
+        let key = note as usize & 0x7F;
+        if std::mem::replace(&mut self.key_held[key], true) {
+            // Repeated note on without a note off; drop the old hold first.
+            let old = self.snapped_to[key] as usize;
+            self.pitch_holders[old] = self.pitch_holders[old].saturating_sub(1);
+        }
+        let pitch = crate::theory::quantize_to_scale(note, scale_mask);
+        self.snapped_to[key] = pitch;
+        self.pitch_holders[pitch as usize] += 1;
+        pitch
+    }
+
+    /// The pitch to release for an incoming note off, or `None` while another held key
+    /// still sounds that pitch.
+    pub fn note_off(&mut self, note: u8) -> Option<u8> {
+        let key = note as usize & 0x7F;
+        if !std::mem::replace(&mut self.key_held[key], false) {
+            return Some(note);
+        }
+        let pitch = self.snapped_to[key];
+        let holders = &mut self.pitch_holders[pitch as usize];
+        *holders = holders.saturating_sub(1);
+        (*holders == 0).then_some(pitch)
+    }
+}
+
+/// Length in samples of one 4/4 bar at the given tempo, matching how the top bar derives BPM.
+pub fn bar_len_samples_from_bpm(bpm: f64, sample_rate: u32) -> usize {
+    if bpm <= 0.0 {
+        return 0;
+    }
+    (sample_rate as f64 * 60.0 * 4.0 / bpm).round() as usize
+}
+
 pub fn get_midi_ports() -> Result<Vec<(String, MidiInputPort)>> {
     let midi_in = MidiInput::new(APP_NAME)?;
     let ports = midi_in.ports();
@@ -60,7 +184,12 @@ pub fn connect_midi(
     midi_synth_editor_toggle_request: Arc<AtomicBool>,
     midi_sampler_editor_toggle_request: Arc<AtomicBool>,
     midi_fx_preset_change_request: Arc<AtomicI8>, // New
+    midi_chord_pad_request: Arc<AtomicI8>,
+    note_quantize_mask: Arc<AtomicU16>,
     midi_mapping_inversions: Arc<RwLock<BTreeMap<FullMidiIdentifier, bool>>>,
+    midi_program_change_request: Arc<AtomicI16>,
+    follow_midi_clock: Arc<AtomicBool>,
+    sample_rate: u32,
 ) -> Result<(MidiInputConnection<()>, JoinHandle<()>)> {
     let mut midi_in = MidiInput::new(APP_NAME)?;
     midi_in.ignore(Ignore::None);
@@ -103,12 +232,50 @@ pub fn connect_midi(
     });
 
     let mut last_press_times: BTreeMap<FullMidiIdentifier, Instant> = BTreeMap::new();
+    let mut clock_tracker = MidiClockTracker::default();
+    let mut note_quantize = NoteQuantizeState::default();
     let port_name_clone = port_name.clone();
 
     let conn_out = match midi_in.connect(
         &port,
         &format!("cypher-midi-in-{}", port_name),
-        move |_stamp, message, _| {
+        move |stamp, message, _| {
+            // System real-time messages are a single byte; clock and transport are only
+            // acted on when the loop sync source is set to MIDI clock.
+            if message.len() == 1 {
+                if follow_midi_clock.load(Ordering::Relaxed) {
+                    match message[0] {
+                        MIDI_CLOCK_TICK => {
+                            clock_tracker.tick(stamp);
+                            if let Some(len) = clock_tracker.transport_len_update(sample_rate) {
+                                command_sender.send(AudioCommand::SetTransportLen(len)).ok();
+                            }
+                        }
+                        MIDI_CLOCK_START => {
+                            clock_tracker.reset();
+                            command_sender.send(AudioCommand::StopTransport).ok();
+                            command_sender.send(AudioCommand::PlayTransport).ok();
+                        }
+                        MIDI_CLOCK_CONTINUE => {
+                            command_sender.send(AudioCommand::PlayTransport).ok();
+                        }
+                        MIDI_CLOCK_STOP => {
+                            command_sender.send(AudioCommand::StopTransport).ok();
+                        }
+                        _ => {}
+                    }
+                }
+                return;
+            }
+            // Program Change is a two-byte message, so handle it before the length check below.
+            // The preset/kit lookup touches the filesystem, so it is handed off to the UI thread.
+            if message.len() >= 2
+                && message[0] & 0xF0 == 0xC0
+                && message[0] & 0x0F == audio_note_channel
+            {
+                midi_program_change_request.store(message[1] as i16, Ordering::Relaxed);
+                return;
+            }
             if message.len() < 3 {
                 return;
             }
@@ -122,6 +289,15 @@ pub fn connect_midi(
                     let is_note_on = status == 0x90 && velocity > 0;
 
                     if channel == audio_note_channel {
+                        // Velocity passes through; only the pitch is snapped.
+                        let note = if is_note_on {
+                            note_quantize.note_on(note, note_quantize_mask.load(Ordering::Relaxed))
+                        } else {
+                            match note_quantize.note_off(note) {
+                                Some(pitch) => pitch,
+                                None => return,
+                            }
+                        };
                         let msg = MidiMessage {
                             status: message[0],
                             data1: note,
@@ -159,7 +335,13 @@ pub fn connect_midi(
                             }
 
                             if let Ok(mappings) = midi_mappings.read() {
-                                if let Some(&param) = mappings.get(&identifier) {
+                                if let Some(&ControllableParameter::ChordSuggestion(group)) =
+                                    mappings.get(&identifier)
+                                {
+                                    // Held like a key: note off releases the chord.
+                                    let request = if is_note_on { group as i8 } else { CHORD_PAD_RELEASE };
+                                    midi_chord_pad_request.store(request, Ordering::Relaxed);
+                                } else if let Some(&param) = mappings.get(&identifier) {
                                     if is_note_on {
                                         let now = Instant::now();
                                         let last_press = last_press_times.entry(identifier.clone()).or_insert_with(|| now.checked_sub(DEBOUNCE_DURATION * 2).unwrap_or(now));
@@ -248,7 +430,10 @@ pub fn connect_midi(
                         match mode {
                             MidiControlMode::Absolute => {
                                 // Logic to handle button-like actions for both non-continuous params and special cases
-                                if !param.is_continuous() || param == ControllableParameter::FxFocusedPresetChange {
+                                if let ControllableParameter::ChordSuggestion(group) = param {
+                                    let request = if value > 64 { group as i8 } else { CHORD_PAD_RELEASE };
+                                    midi_chord_pad_request.store(request, Ordering::Relaxed);
+                                } else if !param.is_continuous() || param == ControllableParameter::FxFocusedPresetChange {
                                     if value > 64 {
                                         let now = Instant::now();
                                         let last_press = last_press_times.entry(identifier.clone()).or_insert_with(|| now.checked_sub(DEBOUNCE_DURATION * 2).unwrap_or(now));
@@ -537,7 +722,11 @@ fn scale_midi_to_param(param_name: FxParamName, midi_value: u8) -> u32 {
             let depth = 0.1 + val_norm * (10.0 - 0.1);
             (depth * flanger::PARAM_SCALER) as u32
         }
-        FxParamName::Mode | FxParamName::Waveform => (val_norm * 5.0).round() as u32,
+        FxParamName::InputGainDb | FxParamName::OutputGainDb => {
+            GainTrim::db_to_stored(-24.0 + val_norm * 48.0)
+        }
+        FxParamName::Mode => (val_norm * 5.0).round() as u32,
+        FxParamName::Waveform => (val_norm * 7.0).round() as u32,
         FxParamName::AttackMs => {
             ((1.0 + val_norm * 199.0) * envelope_follower::PARAM_SCALER) as u32
         }
@@ -547,4 +736,63 @@ fn scale_midi_to_param(param_name: FxParamName, midi_value: u8) -> u32 {
         FxParamName::WetDry => (val_norm * delay::PARAM_SCALER) as u32,
         FxParamName::Bypass => (val_norm > 0.5) as u32,
     }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds `ticks` clock ticks spaced `interval_us` apart, starting at `start_us`.
+    fn feed(tracker: &mut MidiClockTracker, start_us: u64, interval_us: u64, ticks: u64) {
+        for i in 0..ticks {
+            tracker.tick(start_us + i * interval_us);
+        }
+    }
+
+    #[test]
+    fn clock_ticks_give_bpm_after_one_beat() {
+        let mut tracker = MidiClockTracker::default();
+        // 125 BPM is 480 ms per beat, so 20 ms per tick.
+        feed(&mut tracker, 1_000, 20_000, MIDI_CLOCK_PPQN as u64);
+        assert_eq!(tracker.bpm(), None);
+        tracker.tick(1_000 + MIDI_CLOCK_PPQN as u64 * 20_000);
+        assert!((tracker.bpm().unwrap() - 125.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn clock_ticks_give_one_bar_transport_length() {
+        let mut tracker = MidiClockTracker::default();
+        feed(&mut tracker, 0, 20_000, MIDI_CLOCK_PPQN as u64 + 1);
+        assert_eq!(tracker.transport_len_update(48_000), Some(92_160));
+        assert_eq!(bar_len_samples_from_bpm(125.0, 48_000), 92_160);
+    }
+
+    #[test]
+    fn clock_jitter_does_not_resend_the_length() {
+        let mut tracker = MidiClockTracker::default();
+        feed(&mut tracker, 0, 20_000, MIDI_CLOCK_PPQN as u64 + 1);
+        assert!(tracker.transport_len_update(48_000).is_some());
+        tracker.tick(MIDI_CLOCK_PPQN as u64 * 20_000 + 20_010);
+        assert_eq!(tracker.transport_len_update(48_000), None);
+    }
+
+    #[test]
+    fn tempo_change_updates_the_length() {
+        let mut tracker = MidiClockTracker::default();
+        feed(&mut tracker, 0, 20_000, MIDI_CLOCK_PPQN as u64 + 1);
+        assert!(tracker.transport_len_update(48_000).is_some());
+        // 100 BPM is 600 ms per beat, so 25 ms per tick.
+        feed(&mut tracker, 505_000, 25_000, MIDI_CLOCK_PPQN as u64);
+        assert!((tracker.bpm().unwrap() - 100.0).abs() < 1e-9);
+        assert_eq!(tracker.transport_len_update(48_000), Some(115_200));
+    }
+
+    #[test]
+    fn reset_forgets_the_tick_history() {
+        let mut tracker = MidiClockTracker::default();
+        feed(&mut tracker, 0, 20_000, MIDI_CLOCK_PPQN as u64 + 1);
+        tracker.reset();
+        assert_eq!(tracker.bpm(), None);
+    }
 }
\ No newline at end of file
@@ -4,11 +4,139 @@ use crate::looper::NUM_LOOPERS;
 use crate::synth::LfoRateMode;
 use serde::{Deserialize, Serialize};
 
+/// Number of group buses loopers can be routed to instead of the master.
+pub const NUM_SUB_BUSES: usize = 2;
+
+/// Number of shared aux send returns, and which one is which.
+pub const NUM_SEND_BUSES: usize = 2;
+pub const REVERB_SEND: usize = 0;
+pub const DELAY_SEND: usize = 1;
+
+/// Converts a linear gain factor to decibels. Silence maps to negative infinity.
+pub fn linear_to_db(linear: f32) -> f32 {
+    if linear <= 1e-6 {
+        -f32::INFINITY
+    } else {
+        20.0 * linear.log10()
+    }
+}
+
+/// Converts a level in decibels back to a linear gain factor.
+pub fn db_to_linear(db: f32) -> f32 {
+    if db.is_infinite() && db < 0.0 {
+        0.0
+    } else {
+        10.0_f32.powf(db / 20.0)
+    }
+}
+
+/// Left/right gains for a pan position in -1..=1, using a constant-power (sin/cos) law
+/// scaled so the centre is unity on both sides and existing mono mixes keep their level.
+pub fn pan_gains(pan: f32) -> (f32, f32) {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+    (
+        angle.cos() * std::f32::consts::SQRT_2,
+        angle.sin() * std::f32::consts::SQRT_2,
+    )
+}
+
+/// Formats a pan position as "C", "L50" or "R100".
+pub fn format_pan(pan: f32) -> String {
+    let percent = (pan * 100.0).round() as i32;
+    match percent {
+        0 => "C".to_string(),
+        p if p < 0 => format!("L{}", -p),
+        p => format!("R{}", p),
+    }
+}
+
+/// Parses the output of [`format_pan`] back to a pan position. Plain numbers are read
+/// as percentages, negative meaning left.
+pub fn parse_pan(text: &str) -> Option<f32> {
+    let text = text.trim().to_ascii_uppercase();
+    let percent = if text == "C" {
+        0.0
+    } else if let Some(rest) = text.strip_prefix('L') {
+        -rest.trim().parse::<f32>().ok()?
+    } else if let Some(rest) = text.strip_prefix('R') {
+        rest.trim().parse::<f32>().ok()?
+    } else {
+        text.parse::<f32>().ok()?
+    };
+    Some((percent / 100.0).clamp(-1.0, 1.0))
+}
+
+/// Formats a linear gain as a short dB readout, e.g. "-6.0" or "-inf".
+pub fn format_db(linear: f32) -> String {
+    let db = linear_to_db(linear);
+    if db.is_infinite() {
+        "-inf".to_string()
+    } else {
+        format!("{:.1}", db)
+    }
+}
+
+/// Largest looper nudge either way, about 100 ms at 48 kHz.
+pub const MAX_NUDGE_SAMPLES: i32 = 4800;
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct MixerTrackState {
     pub volume: f32,
     pub is_muted: bool,
     pub is_soloed: bool,
+    /// Keeps the track playing while other tracks are soloed, e.g. for a drone.
+    #[serde(default)]
+    pub is_solo_safe: bool,
+    /// Sub-bus this track feeds; `None` sums straight into the master.
+    #[serde(default)]
+    pub bus: Option<usize>,
+    #[serde(default)]
+    pub meter_point: MeterPoint,
+    /// Stereo position from -1 (left) to 1 (right). Tracks routed to a sub-bus are summed
+    /// into it in mono, so pan only applies to tracks feeding the master directly.
+    #[serde(default)]
+    pub pan: f32,
+    /// Post-fader send levels (0..1) into the shared reverb and delay returns.
+    #[serde(default)]
+    pub reverb_send: f32,
+    #[serde(default)]
+    pub delay_send: f32,
+    /// Playback offset in samples for nudging a layer into the pocket. Positive values
+    /// play the loop later.
+    #[serde(default)]
+    pub nudge_samples: i32,
+    /// Plays the loop backwards without touching the recorded audio.
+    #[serde(default)]
+    pub is_reversed: bool,
+}
+
+impl MixerTrackState {
+    /// Whether the track is heard. While any track is soloed only soloed tracks and
+    /// unmuted solo-safe tracks play; a soloed track plays regardless of its mute.
+    pub fn is_audible(&self, is_any_soloed: bool) -> bool {
+        if is_any_soloed {
+            self.is_soloed || (self.is_solo_safe && !self.is_muted)
+        } else {
+            !self.is_muted
+        }
+    }
+
+    pub fn send_level(&self, send_index: usize) -> f32 {
+        match send_index {
+            REVERB_SEND => self.reverb_send,
+            DELAY_SEND => self.delay_send,
+            _ => 0.0,
+        }
+    }
+
+    pub fn set_send_level(&mut self, send_index: usize, level: f32) {
+        let level = level.clamp(0.0, 1.0);
+        match send_index {
+            REVERB_SEND => self.reverb_send = level,
+            DELAY_SEND => self.delay_send = level,
+            _ => {}
+        }
+    }
 }
 
 impl Default for MixerTrackState {
@@ -17,6 +145,49 @@ impl Default for MixerTrackState {
             volume: 1.0, // Represents 0 dB
             is_muted: false,
             is_soloed: false,
+            is_solo_safe: false,
+            bus: None,
+            meter_point: MeterPoint::default(),
+            pan: 0.0,
+            reverb_send: 0.0,
+            delay_send: 0.0,
+            nudge_samples: 0,
+            is_reversed: false,
+        }
+    }
+}
+
+/// Where a track's level meter taps the signal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MeterPoint {
+    /// The raw loop level after its FX, independent of the fader. Useful for gain staging.
+    PreFader,
+    /// The level after the track fader.
+    #[default]
+    PostFader,
+}
+
+impl MeterPoint {
+    /// The peak shown for `sample` on a track whose fader sits at `volume`.
+    pub fn meter_level(self, sample: f32, volume: f32) -> f32 {
+        match self {
+            MeterPoint::PreFader => sample.abs(),
+            MeterPoint::PostFader => (sample * volume).abs(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct MixerBusState {
+    pub volume: f32,
+    pub is_muted: bool,
+}
+
+impl Default for MixerBusState {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            is_muted: false,
         }
     }
 }
@@ -27,6 +198,13 @@ pub struct MetronomeTrackState {
     pub pitch_hz: f32,
     pub accent_pitch_hz: f32,
     pub is_muted: bool,
+    /// Plays the downbeat of each bar at `accent_pitch_hz`.
+    #[serde(default = "default_accent_first")]
+    pub accent_first: bool,
+}
+
+fn default_accent_first() -> bool {
+    true
 }
 
 impl Default for MetronomeTrackState {
@@ -36,20 +214,70 @@ impl Default for MetronomeTrackState {
             pitch_hz: 880.0,     // A5
             accent_pitch_hz: 1320.0, // E6 (a fifth above A5)
             is_muted: false,
+            accent_first: true,
+        }
+    }
+}
+
+/// Ducks target tracks by the envelope of a source track, e.g. pads pumping under a kick.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SidechainState {
+    /// Track whose post-fader level drives the ducking; `None` leaves the sidechain inert.
+    pub source: Option<usize>,
+    /// Bit `i` set means track `i` is ducked. The source is never ducked by itself.
+    pub targets: u32,
+    /// Gain reduction (0..1) when the source envelope is at full scale.
+    pub amount: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+}
+
+impl Default for SidechainState {
+    fn default() -> Self {
+        Self {
+            source: None,
+            targets: 0,
+            amount: 0.5,
+            attack_ms: 5.0,
+            release_ms: 150.0,
         }
     }
 }
 
+impl SidechainState {
+    pub fn is_target(&self, track_index: usize) -> bool {
+        self.source.is_some_and(|source| source != track_index)
+            && self.targets & (1 << track_index) != 0
+    }
+
+    pub fn toggle_target(&mut self, track_index: usize) {
+        self.targets ^= 1 << track_index;
+    }
+
+    /// Gain applied to a target track for a source envelope between 0 and 1.
+    pub fn duck_gain(&self, envelope: f32) -> f32 {
+        1.0 - self.amount.clamp(0.0, 1.0) * envelope.clamp(0.0, 1.0)
+    }
+}
+
+/// Fields missing from older sessions take their `Default` values.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MixerState {
     pub tracks: [MixerTrackState; NUM_LOOPERS],
     pub metronome: MetronomeTrackState,
+    pub buses: [MixerBusState; NUM_SUB_BUSES],
+    pub send_returns: [MixerBusState; NUM_SEND_BUSES],
+    pub sidechain: SidechainState,
     pub master_volume_m_u32: u32,
     pub limiter_is_active: bool,
     pub limiter_threshold_m_u32: u32,
     pub limiter_release_mode: LfoRateMode,
     pub limiter_release_ms_m_u32: u32,
     pub limiter_release_sync_rate_m_u32: u32,
+    pub limiter_true_peak: bool,
+    pub limiter_lookahead_ms: f32,
 }
 
 impl Default for MixerState {
@@ -57,12 +285,49 @@ impl Default for MixerState {
         Self {
             tracks: [MixerTrackState::default(); NUM_LOOPERS],
             metronome: MetronomeTrackState::default(),
+            buses: [MixerBusState::default(); NUM_SUB_BUSES],
+            send_returns: [MixerBusState::default(); NUM_SEND_BUSES],
+            sidechain: SidechainState::default(),
             master_volume_m_u32: 1_000_000,
             limiter_is_active: true,
             limiter_threshold_m_u32: 1_000_000,
             limiter_release_mode: LfoRateMode::Hz,
             limiter_release_ms_m_u32: 80_000,
             limiter_release_sync_rate_m_u32: 1_000_000,
+            limiter_true_peak: false,
+            limiter_lookahead_ms: 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unity_gain_is_zero_db() {
+        assert_eq!(linear_to_db(1.0), 0.0);
+        assert_eq!(db_to_linear(0.0), 1.0);
+    }
+
+    #[test]
+    fn half_gain_is_about_minus_six_db() {
+        assert!((linear_to_db(0.5) + 6.0206).abs() < 1e-3);
+        assert!((db_to_linear(-6.0206) - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn db_conversion_round_trips() {
+        for linear in [0.001, 0.1, 0.5, 1.0, 1.5] {
+            assert!((db_to_linear(linear_to_db(linear)) - linear).abs() < 1e-5);
         }
     }
+
+    #[test]
+    fn silence_is_minus_infinity() {
+        assert_eq!(linear_to_db(0.0), -f32::INFINITY);
+        assert_eq!(db_to_linear(-f32::INFINITY), 0.0);
+        assert_eq!(format_db(0.0), "-inf");
+        assert_eq!(format_db(0.5), "-6.0");
+    }
 }
\ No newline at end of file
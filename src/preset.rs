@@ -1,7 +1,8 @@
 // src/preset.rs
 use crate::sampler_engine;
-use crate::wavetable_engine;
+use crate::wavetable_engine::{self, WavetableSource};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum SynthEnginePreset {
@@ -16,8 +17,30 @@ impl Default for SynthEnginePreset {
     }
 }
 
-#[derive(Serialize, Deserialize, Default)]
+#[derive(Serialize, Deserialize, Default, Clone)]
 #[serde(default)]
 pub struct SynthPreset {
     pub engine_presets: [SynthEnginePreset; 2],
+}
+
+impl SynthPreset {
+    /// Every sample file the preset refers to, so bundling can repoint them.
+    pub fn sample_paths_mut(&mut self) -> Vec<&mut PathBuf> {
+        let mut paths = Vec::new();
+        for engine_preset in self.engine_presets.iter_mut() {
+            match engine_preset {
+                SynthEnginePreset::Wavetable(wt) => {
+                    paths.extend(wt.wavetable_sources.iter_mut().filter_map(|source| match source {
+                        WavetableSource::File(path) => Some(path),
+                        WavetableSource::Default(_) => None,
+                    }));
+                }
+                SynthEnginePreset::Sampler(sampler) => {
+                    paths.extend(sampler.sample_paths.iter_mut().flatten());
+                    paths.extend(sampler.round_robin_paths.iter_mut().flatten());
+                }
+            }
+        }
+        paths
+    }
 }
\ No newline at end of file
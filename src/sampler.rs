@@ -2,6 +2,9 @@ use crate::synth::AdsrSettings;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+/// Pads in a sampler kit.
+pub const NUM_PADS: usize = 16;
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 #[serde(default)]
 pub struct SamplerPadFxSettings {
@@ -14,6 +17,15 @@ pub struct SamplerPadFxSettings {
     pub reverb_decay: f32,      // 0.0 to 1.0
     pub is_reverb_gated: bool,
     pub gate_close_time_ms: f32, // e.g., 0 to 2000ms
+    /// Plays the sample backwards, from its last frame to its first.
+    pub is_reversed: bool,
+    /// Where playback starts, as a fraction (0-1) of the buffer in the playback direction.
+    pub start_offset: f32,
+    /// When true the pad ignores note-off and plays through to the end of the sample.
+    pub one_shot: bool,
+    pub note_repeat: NoteRepeatRate,
+    /// What happens to a still-sounding note when the pad is hit again.
+    pub retrigger_mode: PadRetriggerMode,
 }
 
 impl Default for SamplerPadFxSettings {
@@ -34,10 +46,99 @@ impl Default for SamplerPadFxSettings {
             reverb_decay: 0.8,
             is_reverb_gated: false,
             gate_close_time_ms: 0.0,
+            is_reversed: false,
+            start_offset: 0.0,
+            one_shot: false,
+            note_repeat: NoteRepeatRate::Off,
+            retrigger_mode: PadRetriggerMode::default(),
         }
     }
 }
 
+/// The subdivision at which a held pad retriggers itself, synced to the transport.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoteRepeatRate {
+    #[default]
+    Off,
+    Quarter,
+    Eighth,
+    EighthTriplet,
+    Sixteenth,
+    SixteenthTriplet,
+    ThirtySecond,
+}
+
+impl NoteRepeatRate {
+    pub const ALL: [NoteRepeatRate; 7] = [
+        NoteRepeatRate::Off,
+        NoteRepeatRate::Quarter,
+        NoteRepeatRate::Eighth,
+        NoteRepeatRate::EighthTriplet,
+        NoteRepeatRate::Sixteenth,
+        NoteRepeatRate::SixteenthTriplet,
+        NoteRepeatRate::ThirtySecond,
+    ];
+
+    /// How many retriggers fit in one 4/4 bar, or `None` when repeat is off.
+    pub fn divisions_per_bar(&self) -> Option<usize> {
+        match self {
+            NoteRepeatRate::Off => None,
+            NoteRepeatRate::Quarter => Some(4),
+            NoteRepeatRate::Eighth => Some(8),
+            NoteRepeatRate::EighthTriplet => Some(12),
+            NoteRepeatRate::Sixteenth => Some(16),
+            NoteRepeatRate::SixteenthTriplet => Some(24),
+            NoteRepeatRate::ThirtySecond => Some(32),
+        }
+    }
+}
+
+impl std::fmt::Display for NoteRepeatRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            NoteRepeatRate::Off => "Off",
+            NoteRepeatRate::Quarter => "1/4",
+            NoteRepeatRate::Eighth => "1/8",
+            NoteRepeatRate::EighthTriplet => "1/8T",
+            NoteRepeatRate::Sixteenth => "1/16",
+            NoteRepeatRate::SixteenthTriplet => "1/16T",
+            NoteRepeatRate::ThirtySecond => "1/32",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// How a pad handles being triggered while its previous note is still sounding.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PadRetriggerMode {
+    /// Cut the previous note and restart.
+    #[default]
+    Retrigger,
+    /// Let previous notes ring on underneath, up to a small voice cap.
+    Stack,
+    /// Fade the previous note out over a few milliseconds while the new one starts.
+    FadeRestart,
+}
+
+impl PadRetriggerMode {
+    pub const ALL: [PadRetriggerMode; 3] = [
+        PadRetriggerMode::Retrigger,
+        PadRetriggerMode::Stack,
+        PadRetriggerMode::FadeRestart,
+    ];
+}
+
+impl std::fmt::Display for PadRetriggerMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            PadRetriggerMode::Retrigger => "Retrigger",
+            PadRetriggerMode::Stack => "Stack",
+            PadRetriggerMode::FadeRestart => "Fade",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 #[serde(default)]
 pub struct SamplerPadSettings {
@@ -45,9 +146,217 @@ pub struct SamplerPadSettings {
     pub fx: SamplerPadFxSettings,
 }
 
+/// Kit-wide timing feel applied to pad triggers.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct PadGrooveSettings {
+    /// 0.0 to 1.0. Off-beat note-repeat steps are delayed by up to half a step.
+    pub swing: f32,
+    /// Maximum random delay added to note-repeat triggers, in milliseconds.
+    pub humanize_timing_ms: f32,
+    /// 0.0 to 1.0. Maximum random deviation applied to trigger velocity.
+    pub humanize_velocity: f32,
+    /// Seed for the humanize random generator, so a groove plays back the same way.
+    pub seed: u64,
+    /// Holds live pad hits until the next subdivision of the transport.
+    pub launch_quantize: LaunchQuantize,
+}
+
+impl Default for PadGrooveSettings {
+    fn default() -> Self {
+        Self {
+            swing: 0.0,
+            humanize_timing_ms: 0.0,
+            humanize_velocity: 0.0,
+            seed: 1,
+            launch_quantize: LaunchQuantize::Off,
+        }
+    }
+}
+
+/// The grid live pad hits are snapped forward to while the transport is playing.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LaunchQuantize {
+    #[default]
+    Off,
+    Quarter,
+    Eighth,
+    Sixteenth,
+}
+
+impl LaunchQuantize {
+    pub const ALL: [LaunchQuantize; 4] = [
+        LaunchQuantize::Off,
+        LaunchQuantize::Quarter,
+        LaunchQuantize::Eighth,
+        LaunchQuantize::Sixteenth,
+    ];
+
+    /// Grid steps in one 4/4 bar, or `None` when hits fire immediately.
+    pub fn divisions_per_bar(&self) -> Option<usize> {
+        match self {
+            LaunchQuantize::Off => None,
+            LaunchQuantize::Quarter => Some(4),
+            LaunchQuantize::Eighth => Some(8),
+            LaunchQuantize::Sixteenth => Some(16),
+        }
+    }
+}
+
+impl std::fmt::Display for LaunchQuantize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            LaunchQuantize::Off => "Off",
+            LaunchQuantize::Quarter => "1/4",
+            LaunchQuantize::Eighth => "1/8",
+            LaunchQuantize::Sixteenth => "1/16",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 #[serde(default)]
 pub struct SamplerKit {
     // An array of 16 pad settings, including path and fx.
     pub pads: [SamplerPadSettings; 16],
+    pub groove: PadGrooveSettings,
+}
+
+/// Length of the automatic fade at both ends of sample playback, independent of the ADSR.
+pub const DEFAULT_DECLICK_MS: f32 = 1.0;
+pub const MAX_DECLICK_MS: f32 = 10.0;
+
+/// Gain of the declick envelope for a playhead `from_start` samples past where playback began
+/// and `to_end` samples before the sample runs out. Zero length disables it.
+pub fn declick_gain(from_start: f32, to_end: f32, declick_samples: f32) -> f32 {
+    if declick_samples <= 0.0 {
+        return 1.0;
+    }
+    (from_start.min(to_end) / declick_samples).clamp(0.0, 1.0)
+}
+
+/// Silence inserted between pads in an exported kit sprite, in samples.
+pub const SPRITE_GAP_SAMPLES: usize = 256;
+
+/// Location of one pad's audio inside a kit sprite, in samples.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SpriteRegion {
+    pub offset: usize,
+    pub length: usize,
+}
+
+/// The JSON mapping written next to a kit sprite WAV. Together they form a
+/// single portable kit that does not depend on the original sample files.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct KitSpriteMapping {
+    pub sample_rate: u32,
+    pub regions: [Option<SpriteRegion>; 16],
+    pub fx: [SamplerPadFxSettings; 16],
+    pub groove: PadGrooveSettings,
+}
+
+/// Concatenates the pad buffers into one sprite, separated by short gaps of silence.
+/// Empty pads get no region.
+pub fn build_kit_sprite(pads: &[Option<Vec<f32>>; 16]) -> (Vec<f32>, [Option<SpriteRegion>; 16]) {
+    let mut sprite = Vec::new();
+    let mut regions = [None; 16];
+    for (region, pad) in regions.iter_mut().zip(pads.iter()) {
+        if let Some(buffer) = pad.as_ref().filter(|b| !b.is_empty()) {
+            if !sprite.is_empty() {
+                sprite.resize(sprite.len() + SPRITE_GAP_SAMPLES, 0.0);
+            }
+            *region = Some(SpriteRegion {
+                offset: sprite.len(),
+                length: buffer.len(),
+            });
+            sprite.extend_from_slice(buffer);
+        }
+    }
+    (sprite, regions)
+}
+
+/// Cuts a sprite back into per-pad buffers. Regions that fall outside the sprite
+/// are clamped to what is available.
+pub fn split_kit_sprite(
+    sprite: &[f32],
+    regions: &[Option<SpriteRegion>; 16],
+) -> [Option<Vec<f32>>; 16] {
+    std::array::from_fn(|i| {
+        regions[i].and_then(|region| {
+            let start = region.offset.min(sprite.len());
+            let end = (region.offset + region.length).min(sprite.len());
+            (end > start).then(|| sprite[start..end].to_vec())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_engine::write_wav_file;
+    use crate::settings::RecordingFormat;
+
+    fn test_pads() -> [Option<Vec<f32>>; 16] {
+        let mut pads: [Option<Vec<f32>>; 16] = Default::default();
+        pads[0] = Some((0..300).map(|i| (i as f32 * 0.05).sin() * 0.8).collect());
+        pads[3] = Some(vec![0.25; 17]);
+        pads[15] = Some((0..1000).map(|i| if i % 2 == 0 { 0.5 } else { -0.5 }).collect());
+        pads
+    }
+
+    #[test]
+    fn sprite_regions_are_separated_by_gaps() {
+        let (sprite, regions) = build_kit_sprite(&test_pads());
+        assert_eq!(regions[0], Some(SpriteRegion { offset: 0, length: 300 }));
+        assert_eq!(
+            regions[3],
+            Some(SpriteRegion { offset: 300 + SPRITE_GAP_SAMPLES, length: 17 })
+        );
+        assert!(regions[1].is_none());
+        assert_eq!(sprite.len(), 300 + 17 + 1000 + 2 * SPRITE_GAP_SAMPLES);
+    }
+
+    #[test]
+    fn sprite_export_and_import_round_trips_pad_buffers() {
+        let pads = test_pads();
+        let (sprite, regions) = build_kit_sprite(&pads);
+        let mapping = KitSpriteMapping {
+            sample_rate: 48_000,
+            regions,
+            ..Default::default()
+        };
+
+        let dir = std::env::temp_dir().join(format!("cypher-sprite-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let wav_path = dir.join("kit.wav");
+        write_wav_file(&wav_path, &sprite, mapping.sample_rate as f32, RecordingFormat::Int16)
+            .unwrap();
+        let mapping_json = serde_json::to_string(&mapping).unwrap();
+
+        let mut reader = hound::WavReader::open(&wav_path).unwrap();
+        let channels = reader.spec().channels as usize;
+        let decoded: Vec<f32> = reader
+            .samples::<i16>()
+            .step_by(channels)
+            .map(|s| s.unwrap() as f32 / i16::MAX as f32)
+            .collect();
+        let mapping: KitSpriteMapping = serde_json::from_str(&mapping_json).unwrap();
+        let imported = split_kit_sprite(&decoded, &mapping.regions);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        for (original, imported) in pads.iter().zip(imported.iter()) {
+            match (original, imported) {
+                (None, None) => {}
+                (Some(original), Some(imported)) => {
+                    assert_eq!(original.len(), imported.len());
+                    for (a, b) in original.iter().zip(imported) {
+                        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+                    }
+                }
+                _ => panic!("pad presence changed in the round trip"),
+            }
+        }
+    }
 }
\ No newline at end of file
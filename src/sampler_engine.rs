@@ -1,9 +1,11 @@
 // src/sampler_engine.rs
 use crate::synth::{
     Adsr, AdsrSettings, Engine, Filter, FilterSettings, Lfo, LfoRateMode, LfoSettings,
-    ModDestination, ModRouting, ModSource,
+    Glide, ModDestination, ModRouting, ModSource, note_to_freq, velocity_gain,
+    DEFAULT_TUNING_REFERENCE_HZ,
 };
 use crate::synth::{FastTanh, POW2_LUT};
+use crate::sampler::declick_gain;
 use crate::wavetable_engine::{SaturationSettings, WavetableSet};
 use egui::{epaint, lerp, Rect};
 use rayon::prelude::*; // Import Rayon for parallel processing
@@ -16,11 +18,93 @@ use std::sync::{Arc, RwLock};
 
 pub const NUM_SAMPLE_SLOTS: usize = 8;
 
+/// Loops shorter than this are ignored to avoid buzzing on tiny regions.
+const MIN_LOOP_LEN_SAMPLES: usize = 16;
+/// Maximum length of the crossfade that smooths the jump from loop end back to loop start.
+const LOOP_CROSSFADE_SAMPLES: f32 = 256.0;
+
+/// Sustain loop points for a sample slot, as indices into the loaded audio.
+/// While a note is held the playhead wraps from `end` back to `start`;
+/// after note-off it plays on through the tail.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct SampleLoopRegion {
+    pub enabled: bool,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl SampleLoopRegion {
+    /// Clamps the region to a sample of the given length. Returns `None` when looping is off
+    /// or the region is too short to loop.
+    fn bounds_for(&self, sample_len: usize) -> Option<(f32, f32)> {
+        if !self.enabled {
+            return None;
+        }
+        let end = self.end.min(sample_len.saturating_sub(1));
+        let start = self.start.min(end);
+        (end - start >= MIN_LOOP_LEN_SAMPLES).then_some((start as f32, end as f32))
+    }
+}
+
+/// Velocity response for a range of keys, e.g. so bass notes of a multisampled instrument
+/// respond more softly than the treble. Notes outside every zone use velocity as played.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct VelocityZone {
+    pub low_note: u8,
+    pub high_note: u8,
+    /// Exponent applied to the normalized velocity: above 1 is softer, below 1 is harder.
+    pub curve: f32,
+}
+
+impl Default for VelocityZone {
+    fn default() -> Self {
+        Self {
+            low_note: 0,
+            high_note: 127,
+            curve: 1.0,
+        }
+    }
+}
+
+impl VelocityZone {
+    pub const MIN_CURVE: f32 = 0.25;
+    pub const MAX_CURVE: f32 = 4.0;
+
+    fn contains(&self, note: u8) -> bool {
+        (self.low_note..=self.high_note).contains(&note)
+    }
+}
+
+/// Scales a normalized velocity by the curve of the first zone containing `note`.
+fn zoned_velocity(zones: &[VelocityZone], note: u8, velocity: f32) -> f32 {
+    zones.iter().find(|zone| zone.contains(note)).map_or(velocity, |zone| {
+        velocity.powf(zone.curve.clamp(VelocityZone::MIN_CURVE, VelocityZone::MAX_CURVE))
+    })
+}
+
 /// Holds the audio data and settings for a single multi-sample slot.
+/// A slot may hold several takes that are cycled round-robin on each note.
 #[derive(Clone, Default)]
 struct SampleSlot {
-    audio_data: Arc<Vec<f32>>,
+    samples: Vec<Arc<Vec<f32>>>,
+    next_round_robin: usize,
     root_note: u8,
+    loop_region: SampleLoopRegion,
+}
+
+impl SampleSlot {
+    fn has_audio(&self) -> bool {
+        self.samples.iter().any(|s| !s.is_empty())
+    }
+
+    /// Returns the next take in the rotation and advances it.
+    fn next_sample(&mut self) -> Arc<Vec<f32>> {
+        let sample = self.samples[self.next_round_robin % self.samples.len()].clone();
+        self.next_round_robin = (self.next_round_robin + 1) % self.samples.len();
+        sample
+    }
 }
 
 // A snapshot of all values that affect the sampler visualizer.
@@ -37,18 +121,25 @@ pub struct SamplerVisualizerSnapshot {
 pub struct SamplerEngineState {
     pub amp_adsr: AdsrSettings,
     pub filter_adsr: AdsrSettings,
+    pub velocity_sensitivity: f32,
     pub filter_settings: Arc<RwLock<FilterSettings>>,
     pub lfo_settings: Arc<RwLock<LfoSettings>>,
     pub lfo2_settings: Arc<RwLock<LfoSettings>>,
     pub mod_matrix: Arc<RwLock<Vec<ModRouting>>>,
     pub saturation_settings: Arc<RwLock<SaturationSettings>>,
     pub is_polyphonic: bool,
+    pub glide_time_ms: f32,
 
     // Sampler specific (Multi-sample)
     pub sample_names: [String; NUM_SAMPLE_SLOTS],
     pub sample_paths: [Option<PathBuf>; NUM_SAMPLE_SLOTS],
+    /// Additional takes per slot, played in rotation after the main sample.
+    pub round_robin_paths: Vec<Vec<PathBuf>>,
     pub sample_data_for_ui: [Arc<RwLock<Vec<f32>>>; NUM_SAMPLE_SLOTS],
     pub root_notes: [u8; NUM_SAMPLE_SLOTS],
+    /// Sustain loop points, indexed by slot.
+    pub loop_regions: Vec<SampleLoopRegion>,
+    pub velocity_zones: Vec<VelocityZone>,
     pub global_fine_tune_cents: f32,
     pub fade_out: f32,
 
@@ -77,16 +168,21 @@ impl SamplerEngineState {
         Self {
             amp_adsr: Default::default(),
             filter_adsr: Default::default(),
+            velocity_sensitivity: 1.0,
             filter_settings: Arc::new(RwLock::new(Default::default())),
             lfo_settings: Arc::new(RwLock::new(Default::default())),
             lfo2_settings: Arc::new(RwLock::new(Default::default())),
             mod_matrix: Arc::new(RwLock::new(Vec::new())),
             saturation_settings: Arc::new(RwLock::new(Default::default())),
             is_polyphonic: true,
+            glide_time_ms: 0.0,
             sample_names: std::array::from_fn(|_| "Empty".to_string()),
             sample_paths: Default::default(), // This correctly creates [None; 8]
+            round_robin_paths: vec![Vec::new(); NUM_SAMPLE_SLOTS],
             sample_data_for_ui: std::array::from_fn(|_| Arc::new(RwLock::new(Vec::new()))),
             root_notes: std::array::from_fn(|i| (24 + i * 12) as u8), // Default root notes C2, C3, ...
+            loop_regions: vec![SampleLoopRegion::default(); NUM_SAMPLE_SLOTS],
+            velocity_zones: Vec::new(),
             global_fine_tune_cents: 0.0,
             fade_out: 0.01,
             volume: Arc::new(AtomicU32::new(1_000_000)),
@@ -126,16 +222,25 @@ pub struct SamplerEnginePreset {
     pub volume: f32,
     pub amp_adsr: AdsrSettings,
     pub filter_adsr: AdsrSettings,
+    /// 0 plays every note at full level, 1 follows note velocity fully.
+    pub velocity_sensitivity: f32,
     pub filter: FilterSettings,
     pub lfo_settings: LfoSettings,
     pub lfo2_settings: LfoSettings,
     pub mod_matrix: Vec<ModRouting>,
     pub saturation_settings: SaturationSettings,
     pub is_polyphonic: bool,
+    pub glide_time_ms: f32,
 
     // Sampler specific (Multi-sample)
     pub sample_paths: [Option<PathBuf>; NUM_SAMPLE_SLOTS],
+    /// Additional round-robin takes, indexed by slot. Empty for single-sample slots.
+    pub round_robin_paths: Vec<Vec<PathBuf>>,
     pub root_notes: [u8; NUM_SAMPLE_SLOTS],
+    /// Sustain loop points, indexed by slot. Empty when no slot loops.
+    pub loop_regions: Vec<SampleLoopRegion>,
+    /// Per-key-range velocity curves, checked in order.
+    pub velocity_zones: Vec<VelocityZone>,
     pub global_fine_tune_cents: f32,
     pub fade_out: f32,
 }
@@ -146,14 +251,19 @@ impl Default for SamplerEnginePreset {
             volume: 1.0,
             amp_adsr: Default::default(),
             filter_adsr: Default::default(),
+            velocity_sensitivity: 1.0,
             filter: Default::default(),
             lfo_settings: Default::default(),
             lfo2_settings: Default::default(),
             mod_matrix: Vec::new(),
             saturation_settings: Default::default(),
             is_polyphonic: true,
+            glide_time_ms: 0.0,
             sample_paths: Default::default(),
+            round_robin_paths: Vec::new(),
             root_notes: std::array::from_fn(|i| (24 + i * 12) as u8), // Default root notes C2, C3, ...
+            loop_regions: Vec::new(),
+            velocity_zones: Vec::new(),
             global_fine_tune_cents: 0.0,
             fade_out: 0.01,
         }
@@ -171,6 +281,7 @@ struct ModulationValues {
     amp: f32,
     cutoff: f32,
     saturation: f32,
+    fx_mix: f32,
 }
 
 struct Voice {
@@ -178,12 +289,19 @@ struct Voice {
     sample_rate: f32,
     phase: f32,
     base_pitch_ratio: f32,
+    glide: Glide,
     velocity: f32,
+    /// `velocity` after the key zone's curve; drives the note's level.
+    gain_velocity: f32,
+    velocity_sensitivity: f32,
     amp_adsr: Adsr,
     filter_adsr: Adsr,
     filter: Filter,
     age: u32,
     sample_data: Arc<Vec<f32>>, // Each voice now holds its own sample data
+    loop_bounds: Option<(f32, f32)>,
+    /// Length of the anti-click fade at the start and end of the sample, in samples.
+    declick_samples: f32,
     // Buffer to hold the most recent processed modulation values for UI feedback
     last_mod_values: ModulationValues,
     last_env2_value: f32,
@@ -197,12 +315,17 @@ impl Voice {
             sample_rate,
             phase: 0.0,
             base_pitch_ratio: 1.0,
+            glide: Glide::default(),
             velocity: 0.0,
+            gain_velocity: 0.0,
+            velocity_sensitivity: 1.0,
             amp_adsr: Adsr::new(Default::default(), sample_rate),
             filter_adsr: Adsr::new(Default::default(), sample_rate),
             filter: Filter::new(),
             age: u32::MAX,
             sample_data: Arc::new(Vec::new()),
+            loop_bounds: None,
+            declick_samples: crate::sampler::DEFAULT_DECLICK_MS / 1000.0 * sample_rate,
             last_mod_values: ModulationValues::default(),
             last_env2_value: 0.0,
             last_drive_value: 0.0,
@@ -213,6 +336,12 @@ impl Voice {
         self.amp_adsr.state != crate::synth::AdsrState::Idle && !self.sample_data.is_empty()
     }
 
+    /// The loop bounds while the note is held; released notes play out the tail.
+    fn sustain_loop(&self) -> Option<(f32, f32)> {
+        self.loop_bounds
+            .filter(|_| self.amp_adsr.state != crate::synth::AdsrState::Release)
+    }
+
     fn process_sample(
         &mut self,
         cents_ratio: f32,
@@ -235,14 +364,16 @@ impl Voice {
             let source_val = match routing.source {
                 ModSource::Env2 => self.last_env2_value,
                 ModSource::Velocity => self.velocity,
+                ModSource::KeyTrack => crate::synth::key_track(self.note_id),
                 _ => continue,
             };
-            let mod_val = source_val * routing.amount;
+            let mod_val = routing.modulate(source_val);
             match routing.destination {
                 ModDestination::Pitch => final_mods.pitch += mod_val,
                 ModDestination::FilterCutoff => final_mods.cutoff += mod_val,
                 ModDestination::Amplitude => final_mods.amp += mod_val,
                 ModDestination::Saturation => final_mods.saturation += mod_val,
+                ModDestination::FxMix => final_mods.fx_mix += mod_val,
                 _ => {}
             }
         }
@@ -263,8 +394,25 @@ impl Voice {
                 fade_gain = fade_gain.clamp(0.0, 1.0);
             }
         }
-
-        let raw_sample = SamplerEngine::get_interpolated_sample(&self.sample_data, self.phase);
+        let last_index = (sample_len - 1) as f32;
+        fade_gain *= declick_gain(self.phase, last_index - self.phase, self.declick_samples);
+
+        let sustain_loop = self.sustain_loop();
+        let mut raw_sample = SamplerEngine::get_interpolated_sample(&self.sample_data, self.phase);
+        if let Some((loop_start, loop_end)) = sustain_loop {
+            // Blend towards the audio just before the loop start so the wrap is seamless.
+            let loop_len = loop_end - loop_start;
+            let crossfade = LOOP_CROSSFADE_SAMPLES.min(loop_len * 0.5).min(loop_start);
+            let crossfade_start = loop_end - crossfade;
+            if crossfade > 0.0 && self.phase > crossfade_start {
+                let t = ((self.phase - crossfade_start) / crossfade).min(1.0);
+                let wrapped = SamplerEngine::get_interpolated_sample(
+                    &self.sample_data,
+                    self.phase - loop_len,
+                );
+                raw_sample = lerp(raw_sample..=wrapped, t);
+            }
+        }
 
         // --- OPTIMIZED SATURATION LOGIC ---
         let final_saturation_mod = final_mods.saturation.clamp(-1.0, 1.0);
@@ -289,15 +437,21 @@ impl Voice {
         );
         let voice_output = filtered_sample
             * 0.8
-            * self.velocity
+            * velocity_gain(self.gain_velocity, self.velocity_sensitivity)
             * amp_env_val
             * (1.0 + final_mods.amp).max(0.0)
             * fade_gain;
 
         // --- OPTIMIZED PITCH CALCULATION ---
         let mod_pitch_ratio = POW2_LUT.get_interpolated(final_mods.pitch);
-        let phase_inc = self.base_pitch_ratio * cents_ratio * mod_pitch_ratio;
+        let phase_inc =
+            self.base_pitch_ratio * self.glide.process() * cents_ratio * mod_pitch_ratio;
         self.phase += phase_inc;
+        if let Some((loop_start, loop_end)) = sustain_loop {
+            if self.phase >= loop_end {
+                self.phase -= loop_end - loop_start;
+            }
+        }
 
         if self.phase >= (sample_len - 1) as f32 || self.phase < 0.0 {
             self.amp_adsr.reset();
@@ -314,13 +468,18 @@ impl Voice {
         &mut self,
         note: u8,
         velocity: u8,
+        gain_velocity: f32,
         pitch_ratio: f32,
         sample_data: Arc<Vec<f32>>,
+        loop_region: SampleLoopRegion,
     ) {
         self.note_id = note;
+        self.loop_bounds = loop_region.bounds_for(sample_data.len());
         self.phase = 0.0;
         self.base_pitch_ratio = pitch_ratio;
+        self.glide.stop();
         self.velocity = velocity as f32 / 127.0;
+        self.gain_velocity = gain_velocity;
         self.sample_data = sample_data;
         self.amp_adsr.note_on();
         self.filter_adsr.note_on();
@@ -331,21 +490,32 @@ impl Voice {
         self.amp_adsr.note_off();
         self.filter_adsr.note_off();
     }
+
+    fn reset(&mut self) {
+        self.amp_adsr.reset();
+        self.filter_adsr.reset();
+        self.filter = Filter::new();
+        self.age = u32::MAX;
+    }
 }
 
 pub struct SamplerEngine {
     voices: Vec<Voice>,
+    glide_time_ms: f32,
+    tuning_reference_hz: f32,
     is_polyphonic: bool,
     sample_rate: f32,
 
     // Sample data and settings
-    sample_slots: [SampleSlot; NUM_SAMPLE_SLOTS],
+    sample_slots: Box<[SampleSlot; NUM_SAMPLE_SLOTS]>,
     global_fine_tune_cents: f32,
     fade_out_norm: f32,
+    velocity_zones: Vec<VelocityZone>,
 
     // LFOs and Modulation
     lfo1: Lfo,
     lfo2: Lfo,
+    fx_mix_mod: f32,
     dummy_wavetable_set: WavetableSet, // For LFOs that might use wavetables
     pub filter_settings: Arc<RwLock<FilterSettings>>,
     pub lfo_settings: Arc<RwLock<LfoSettings>>,
@@ -388,13 +558,17 @@ impl SamplerEngine {
         let voices = (0..NUM_VOICES).map(|_| Voice::new(sample_rate)).collect();
         Self {
             voices,
+            glide_time_ms: 0.0,
+            tuning_reference_hz: DEFAULT_TUNING_REFERENCE_HZ,
             is_polyphonic: true,
             sample_rate,
             sample_slots: Default::default(),
             global_fine_tune_cents: 0.0,
             fade_out_norm: 0.01,
+            velocity_zones: Vec::new(),
             lfo1: Lfo::new(sample_rate),
             lfo2: Lfo::new(sample_rate),
+            fx_mix_mod: 0.0,
             dummy_wavetable_set: WavetableSet::new_basic(),
             filter_settings,
             lfo_settings,
@@ -447,13 +621,35 @@ impl SamplerEngine {
         val1 * (1.0 - frac) + val2 * frac
     }
 
-    fn note_to_freq(note: u8) -> f32 {
-        440.0 * 2.0_f32.powf((note as f32 - 69.0) / 12.0)
+    /// Replaces every take in the slot with a single sample. An empty buffer clears the slot.
+    /// Replaces the slot's audio for future notes. Sounding voices hold their own `Arc` to
+    /// the sample they started with, so they play out on the old audio without a click.
+    pub fn load_sample_for_slot(&mut self, slot_index: usize, audio_data: Arc<Vec<f32>>) {
+        if let Some(slot) = self.sample_slots.get_mut(slot_index) {
+            slot.samples.clear();
+            if !audio_data.is_empty() {
+                slot.samples.push(audio_data);
+            }
+            slot.next_round_robin = 0;
+        }
     }
 
-    pub fn load_sample_for_slot(&mut self, slot_index: usize, audio_data: Arc<Vec<f32>>) {
+    /// Adds another take to the slot's round-robin rotation.
+    pub fn append_sample_to_slot(&mut self, slot_index: usize, audio_data: Arc<Vec<f32>>) {
+        if let Some(slot) = self.sample_slots.get_mut(slot_index) {
+            if !audio_data.is_empty() {
+                slot.samples.push(audio_data);
+            }
+        }
+    }
+
+    pub fn set_velocity_zones(&mut self, zones: Vec<VelocityZone>) {
+        self.velocity_zones = zones;
+    }
+
+    pub fn set_loop_region(&mut self, slot_index: usize, region: SampleLoopRegion) {
         if let Some(slot) = self.sample_slots.get_mut(slot_index) {
-            slot.audio_data = audio_data;
+            slot.loop_region = region;
         }
     }
 
@@ -469,6 +665,13 @@ impl SamplerEngine {
         self.global_fine_tune_cents = global_fine_tune_cents;
         self.fade_out_norm = fade_out;
     }
+
+    pub fn set_declick_time(&mut self, declick_ms: f32) {
+        let declick_samples = declick_ms / 1000.0 * self.sample_rate;
+        for voice in &mut self.voices {
+            voice.declick_samples = declick_samples;
+        }
+    }
 }
 
 impl Engine for SamplerEngine {
@@ -494,13 +697,21 @@ impl Engine for SamplerEngine {
         let lfo1_freq = Self::get_lfo_freq(self.sample_rate, lfo1_settings, musical_bar_len);
         let lfo2_freq = Self::get_lfo_freq(self.sample_rate, lfo2_settings, musical_bar_len);
         for i in 0..block_size {
-            lfo1_output[i] =
-                self.lfo1
-                    .process(lfo1_freq, lfo1_settings.waveform, &self.dummy_wavetable_set);
-            lfo2_output[i] =
-                self.lfo2
-                    .process(lfo2_freq, lfo2_settings.waveform, &self.dummy_wavetable_set);
+            lfo1_output[i] = self.lfo1.process(
+                lfo1_freq,
+                lfo1_settings.waveform,
+                lfo1_settings.phase_offset,
+                &self.dummy_wavetable_set,
+            );
+            lfo2_output[i] = self.lfo2.process(
+                lfo2_freq,
+                lfo2_settings.waveform,
+                lfo2_settings.phase_offset,
+                &self.dummy_wavetable_set,
+            );
         }
+        let lfo1_depth = self.lfo1.advance_fade(lfo1_settings.fade_in_ms, block_size);
+        let lfo2_depth = self.lfo2.advance_fade(lfo2_settings.fade_in_ms, block_size);
 
         // --- Resize voice output buffers if necessary ---
         if self.voice_outputs[0].len() != block_size {
@@ -527,20 +738,21 @@ impl Engine for SamplerEngine {
                     let mut base_mods = ModulationValues::default();
                     for routing in mod_matrix.iter() {
                         let source_val = match routing.source {
-                            ModSource::Lfo1 => lfo1_output[i],
-                            ModSource::Lfo2 => lfo2_output[i],
+                            ModSource::Lfo1 => lfo1_output[i] * lfo1_depth,
+                            ModSource::Lfo2 => lfo2_output[i] * lfo2_depth,
                             ModSource::Static => 1.0,
                             ModSource::MidiCC(id) => {
                                 midi_cc_values[id.channel as usize][id.cc as usize].load(Ordering::Relaxed) as f32 / 1_000_000.0
                             }
                             _ => continue,
                         };
-                        let mod_val = source_val * routing.amount;
+                        let mod_val = routing.modulate(source_val);
                         match routing.destination {
                             ModDestination::Pitch => base_mods.pitch += mod_val,
                             ModDestination::FilterCutoff => base_mods.cutoff += mod_val,
                             ModDestination::Amplitude => base_mods.amp += mod_val,
                             ModDestination::Saturation => base_mods.saturation += mod_val,
+                            ModDestination::FxMix => base_mods.fx_mix += mod_val,
                             _ => {}
                         }
                     }
@@ -570,8 +782,8 @@ impl Engine for SamplerEngine {
             (voice.last_mod_values, voice.last_env2_value, voice.last_drive_value)
         } else {
             let mut idle_mods = ModulationValues::default();
-            let lfo1_val = *lfo1_output.last().unwrap_or(&0.0);
-            let lfo2_val = *lfo2_output.last().unwrap_or(&0.0);
+            let lfo1_val = *lfo1_output.last().unwrap_or(&0.0) * lfo1_depth;
+            let lfo2_val = *lfo2_output.last().unwrap_or(&0.0) * lfo2_depth;
             for routing in mod_matrix.iter() {
                 let source_val = match routing.source {
                     ModSource::Lfo1 => lfo1_val,
@@ -582,11 +794,12 @@ impl Engine for SamplerEngine {
                     }
                     _ => 0.0,
                 };
-                let mod_val = source_val * routing.amount;
+                let mod_val = routing.modulate(source_val);
                 match routing.destination {
                     ModDestination::Pitch => idle_mods.pitch += mod_val,
                     ModDestination::FilterCutoff => idle_mods.cutoff += mod_val,
                     ModDestination::Saturation => idle_mods.saturation += mod_val,
+                    ModDestination::FxMix => idle_mods.fx_mix += mod_val,
                     _ => {}
                 }
             }
@@ -607,44 +820,35 @@ impl Engine for SamplerEngine {
         let base_cutoff = filter_settings.cutoff;
         let final_cutoff = (base_cutoff + mods.cutoff).clamp(0.0, 1.0);
         self.final_cutoff_atomic.store((final_cutoff * 1_000_000.0) as u32, Ordering::Relaxed);
+        self.fx_mix_mod = mods.fx_mix;
     }
 
     fn note_on(&mut self, note: u8, velocity: u8) {
         if self.lfo_settings.read().unwrap().retrigger {
-            self.lfo1.reset_phase();
+            self.lfo1.retrigger();
         }
         if self.lfo2_settings.read().unwrap().retrigger {
-            self.lfo2.reset_phase();
+            self.lfo2.retrigger();
         }
 
         let octave = note / 12;
         let ideal_slot_index = (octave.saturating_sub(1)).min((NUM_SAMPLE_SLOTS - 1) as u8) as usize;
 
-        // Find the best available slot and its index
-        let chosen_slot_and_index = self.sample_slots[ideal_slot_index..]
-            .iter()
-            .enumerate()
-            .find(|(_, slot)| !slot.audio_data.is_empty())
-            .map(|(i, slot)| (slot, ideal_slot_index + i)) // Adjust index relative to the full array
-            .or_else(|| {
-                self.sample_slots[..ideal_slot_index]
-                    .iter()
-                    .enumerate()
-                    .rfind(|(_, slot)| !slot.audio_data.is_empty())
-                    .map(|(i, slot)| (slot, i)) // Map to swap tuple order
-            })
-            .or_else(|| {
-                self.sample_slots
-                    .iter()
-                    .enumerate()
-                    .find(|(_, slot)| !slot.audio_data.is_empty())
-                    .map(|(i, slot)| (slot, i)) // Map to swap tuple order
-            });
+        // Find the best available slot index
+        let chosen_index = (ideal_slot_index..NUM_SAMPLE_SLOTS)
+            .find(|&i| self.sample_slots[i].has_audio())
+            .or_else(|| (0..ideal_slot_index).rfind(|&i| self.sample_slots[i].has_audio()));
 
-        if let Some((slot, index)) = chosen_slot_and_index {
+        if let Some(index) = chosen_index {
             self.last_triggered_slot_index
                 .store(index, Ordering::Relaxed);
 
+            let slot = &mut self.sample_slots[index];
+            let root_note = slot.root_note;
+            let loop_region = slot.loop_region;
+            let audio_data = slot.next_sample();
+
+            let glide_samples = (self.glide_time_ms / 1000.0 * self.sample_rate) as u32;
             let target_voice = if self.is_polyphonic {
                 self.voices.iter_mut().max_by_key(|v| {
                     let priority = match v.amp_adsr.state {
@@ -661,10 +865,21 @@ impl Engine for SamplerEngine {
                 self.voices.get_mut(0)
             };
             if let Some(voice) = target_voice {
-                let note_freq = Self::note_to_freq(note);
-                let root_freq = Self::note_to_freq(slot.root_note);
+                // Samples are assumed to be recorded at standard pitch, so the root note is
+                // converted at 440 Hz and the played note at the tuning reference.
+                let note_freq = note_to_freq(note, self.tuning_reference_hz);
+                let root_freq = note_to_freq(root_note, DEFAULT_TUNING_REFERENCE_HZ);
                 let pitch_ratio = note_freq / root_freq;
-                voice.note_on(note, velocity, pitch_ratio, slot.audio_data.clone());
+                // In mono mode, glide from wherever the previous note's pitch currently is.
+                let previous_freq = (!self.is_polyphonic && voice.age != u32::MAX).then(|| {
+                    note_to_freq(voice.note_id, self.tuning_reference_hz) * voice.glide.ratio()
+                });
+                let gain_velocity =
+                    zoned_velocity(&self.velocity_zones, note, velocity as f32 / 127.0);
+                voice.note_on(note, velocity, gain_velocity, pitch_ratio, audio_data, loop_region);
+                if let Some(previous_freq) = previous_freq {
+                    voice.glide.start(previous_freq / note_freq, glide_samples);
+                }
             }
         }
     }
@@ -701,11 +916,77 @@ impl Engine for SamplerEngine {
         }
     }
 
+    fn set_velocity_sensitivity(&mut self, sensitivity: f32) {
+        for voice in &mut self.voices {
+            voice.velocity_sensitivity = sensitivity;
+        }
+    }
+
+    fn set_glide_time(&mut self, glide_time_ms: f32) {
+        self.glide_time_ms = glide_time_ms.max(0.0);
+    }
+
+    fn set_tuning_reference(&mut self, a4_hz: f32) {
+        self.tuning_reference_hz = a4_hz;
+    }
+
     fn reset_to_defaults(&mut self) {
-        self.sample_slots = Default::default();
+        *self.sample_slots = Default::default();
     }
 
     fn set_wavetable(&mut self, _slot_index: usize, _audio_data: Arc<Vec<f32>>, _name: String) {
         // This engine does not use wavetables, but this is required to conform to the Engine trait.
     }
+
+    fn reset_voices(&mut self) {
+        for voice in &mut self.voices {
+            voice.reset();
+        }
+        self.lfo1.reset_phase();
+        self.lfo2.reset_phase();
+    }
+
+    fn reset_synced_lfo_phases(&mut self) {
+        if self.lfo_settings.read().unwrap().mode == LfoRateMode::Sync {
+            self.lfo1.reset_phase();
+        }
+        if self.lfo2_settings.read().unwrap().mode == LfoRateMode::Sync {
+            self.lfo2.reset_phase();
+        }
+    }
+
+    fn fx_mix_modulation(&self) -> f32 {
+        self.fx_mix_mod
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn velocity_zones_scale_the_same_velocity_differently() {
+        let zones = [
+            VelocityZone {
+                low_note: 0,
+                high_note: 47,
+                curve: 2.0,
+            },
+            VelocityZone {
+                low_note: 72,
+                high_note: 127,
+                curve: 0.5,
+            },
+        ];
+        let velocity = 0.5;
+        let bass = zoned_velocity(&zones, 36, velocity);
+        let middle = zoned_velocity(&zones, 60, velocity);
+        let treble = zoned_velocity(&zones, 84, velocity);
+        assert!((bass - 0.25).abs() < 1e-6);
+        assert_eq!(middle, velocity);
+        assert!((treble - 0.5f32.sqrt()).abs() < 1e-6);
+        assert!(bass < middle && middle < treble);
+        // Full velocity is full velocity in every zone.
+        assert_eq!(zoned_velocity(&zones, 36, 1.0), 1.0);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,370 @@
+// src/session_bundle.rs
+
+//! Session bundles: a saved session plus the synth preset, sampler kit and samples it uses,
+//! packed into a single `.zip` so it can be opened on another machine. Every file is stored
+//! under its path relative to the config directory, which is how sessions, presets and kits
+//! already refer to each other. The session's own folder goes under `SESSION_ENTRY_DIR`.
+//! Files from outside the config directory are gathered into `EXTERNAL_ENTRY_DIR`, with a
+//! numbered name when two of them share a file name.
+//!
+//! Entries are written uncompressed ("stored") since audio barely compresses. Bundles that
+//! were re-zipped by another tool with deflate can still be read.
+
+use anyhow::Result;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Bundle folder holding `session.json` and the loop WAVs.
+pub const SESSION_ENTRY_DIR: &str = "Session";
+/// Where files from outside the config directory are placed in a bundle.
+const EXTERNAL_ENTRY_DIR: &str = "Samples/Bundled";
+
+/// Name for `path` inside a bundle: its path relative to `config_dir` with `/` separators,
+/// or `Samples/Bundled/<file name>` for anything outside it.
+pub fn entry_name(config_dir: &Path, path: &Path) -> String {
+    match path.strip_prefix(config_dir) {
+        Ok(relative) => relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => format!(
+            "{}/{}",
+            EXTERNAL_ENTRY_DIR,
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ),
+    }
+}
+
+/// `name` with ` (n)` added before its extension: `kick.wav` becomes `kick (2).wav`.
+pub fn numbered_name(name: &str, n: usize) -> String {
+    let (parent, file) = match name.rfind('/') {
+        Some(slash) => name.split_at(slash + 1),
+        None => ("", name),
+    };
+    match file.rfind('.').filter(|&dot| dot > 0) {
+        Some(dot) => format!("{}{} ({}){}", parent, &file[..dot], n, &file[dot..]),
+        None => format!("{}{} ({})", parent, file, n),
+    }
+}
+
+/// Where the entry `name` unpacks to under `root`, or `None` if it is absolute or climbs out
+/// of `root` with `..`.
+pub fn entry_destination(root: &Path, name: &str) -> Option<PathBuf> {
+    let relative = Path::new(name);
+    relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then(|| root.join(relative))
+}
+
+pub struct BundleWriter {
+    zip: ZipWriter<BufWriter<File>>,
+    entry_names: BTreeSet<String>,
+    /// Name each file or folder on disk was packed under, so one referenced twice is packed once.
+    packed: HashMap<PathBuf, String>,
+}
+
+impl BundleWriter {
+    pub fn create(path: &Path) -> Result<Self> {
+        Ok(Self {
+            zip: ZipWriter::new(BufWriter::new(File::create(path)?)),
+            entry_names: BTreeSet::new(),
+            packed: HashMap::new(),
+        })
+    }
+
+    /// True if `name` is an entry or a folder holding entries.
+    fn is_taken(&self, name: &str) -> bool {
+        self.entry_names.contains(name)
+            || self
+                .entry_names
+                .iter()
+                .any(|entry| entry.strip_prefix(name).is_some_and(|rest| rest.starts_with('/')))
+    }
+
+    /// Adds an entry. Adding a name that is already in the bundle does nothing.
+    pub fn add(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        if !self.entry_names.insert(name.to_string()) {
+            return Ok(());
+        }
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(data.len() as u64 >= u32::MAX as u64);
+        self.zip.start_file(name, options)?;
+        self.zip.write_all(data)?;
+        Ok(())
+    }
+
+    /// Adds an entry under `name`, or under a numbered variant of it if `name` is already
+    /// taken, and returns the name used.
+    pub fn add_unique(&mut self, name: &str, data: &[u8]) -> Result<String> {
+        let mut unique = name.to_string();
+        let mut suffix = 2;
+        while self.is_taken(&unique) {
+            unique = numbered_name(name, suffix);
+            suffix += 1;
+        }
+        self.add(&unique, data)?;
+        Ok(unique)
+    }
+
+    /// Adds the file at `path` under `entry_name(config_dir, path)`, numbered if another file
+    /// already has that name, and returns the name as a relative path, ready to replace the
+    /// reference to `path`.
+    pub fn add_file(&mut self, config_dir: &Path, path: &Path) -> Result<PathBuf> {
+        if let Some(name) = self.packed.get(path) {
+            return Ok(PathBuf::from(name));
+        }
+        let name = self.add_unique(&entry_name(config_dir, path), &fs::read(path)?)?;
+        self.packed.insert(path.to_path_buf(), name.clone());
+        Ok(PathBuf::from(name))
+    }
+
+    /// Adds every file under `folder`, keeping their layout, and returns the folder's name in
+    /// the bundle. Like `add_file`, the folder gets a numbered name if its name is taken.
+    pub fn add_folder(&mut self, config_dir: &Path, folder: &Path) -> Result<PathBuf> {
+        if let Some(name) = self.packed.get(folder) {
+            return Ok(PathBuf::from(name));
+        }
+        let base_name = entry_name(config_dir, folder);
+        let mut name = base_name.clone();
+        let mut suffix = 2;
+        while self.is_taken(&name) {
+            name = format!("{} ({})", base_name, suffix);
+            suffix += 1;
+        }
+        for entry in WalkDir::new(folder).into_iter().filter_map(|e| e.ok()) {
+            if let (true, Ok(relative)) = (entry.file_type().is_file(), entry.path().strip_prefix(folder)) {
+                let file_name = format!("{}/{}", name, entry_name(Path::new(""), relative));
+                self.add(&file_name, &fs::read(entry.path())?)?;
+            }
+        }
+        self.packed.insert(folder.to_path_buf(), name.clone());
+        Ok(PathBuf::from(name))
+    }
+
+    pub fn finish(self) -> Result<()> {
+        self.zip.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+/// Unpacks entries into a config dir without replacing what is already there. An entry that
+/// matches the local file at its path is not written again; one that differs is written under
+/// a numbered name, which the caller then points the references at.
+pub struct Unpacker<'a> {
+    root: &'a Path,
+    /// Name each entry or folder ended up under.
+    placed: HashMap<String, String>,
+    renamed: usize,
+}
+
+impl<'a> Unpacker<'a> {
+    pub fn new(root: &'a Path) -> Self {
+        Self { root, placed: HashMap::new(), renamed: 0 }
+    }
+
+    /// How many entries or folders clashed with different local files and were renamed.
+    pub fn renamed(&self) -> usize {
+        self.renamed
+    }
+
+    fn destination(&self, name: &str) -> Result<PathBuf> {
+        entry_destination(self.root, name).ok_or_else(|| anyhow::anyhow!("unsafe entry '{}'", name))
+    }
+
+    /// Unpacks the entry `name` and returns the name it is stored under.
+    pub fn place(&mut self, name: &str, data: &[u8]) -> Result<String> {
+        if let Some(placed) = self.placed.get(name) {
+            return Ok(placed.clone());
+        }
+        let mut placed = name.to_string();
+        let mut suffix = 2;
+        loop {
+            let destination = self.destination(&placed)?;
+            if !destination.exists() {
+                write_file(&destination, data)?;
+                break;
+            }
+            if destination.is_file() && fs::read(&destination)? == data {
+                break;
+            }
+            placed = numbered_name(name, suffix);
+            suffix += 1;
+        }
+        if placed != name {
+            self.renamed += 1;
+        }
+        self.placed.insert(name.to_string(), placed.clone());
+        Ok(placed)
+    }
+
+    /// Unpacks `files` (relative name, contents) as the folder `name` and returns the folder's
+    /// name. A local folder is only reused if it holds exactly these files, since everything in
+    /// it gets played.
+    pub fn place_folder(&mut self, name: &str, files: &[(String, &[u8])]) -> Result<String> {
+        if let Some(placed) = self.placed.get(name) {
+            return Ok(placed.clone());
+        }
+        let mut placed = name.to_string();
+        let mut suffix = 2;
+        loop {
+            let destination = self.destination(&placed)?;
+            if !destination.exists() {
+                for (relative, data) in files {
+                    write_file(&self.destination(&format!("{}/{}", placed, relative))?, data)?;
+                }
+                break;
+            }
+            if folder_holds_exactly(&destination, files) {
+                break;
+            }
+            placed = format!("{} ({})", name, suffix);
+            suffix += 1;
+        }
+        if placed != name {
+            self.renamed += 1;
+        }
+        self.placed.insert(name.to_string(), placed.clone());
+        Ok(placed)
+    }
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, data)?;
+    Ok(())
+}
+
+fn folder_holds_exactly(folder: &Path, files: &[(String, &[u8])]) -> bool {
+    let local_files = WalkDir::new(folder)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .count();
+    local_files == files.len()
+        && files.iter().all(|(relative, data)| {
+            entry_destination(folder, relative)
+                .and_then(|path| fs::read(path).ok())
+                .is_some_and(|local| local == *data)
+        })
+}
+
+/// Reads every entry of a bundle as (name, contents). Directory entries are skipped.
+pub fn read_bundle(path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        // Reading to the end also checks the entry's CRC.
+        entry.read_to_end(&mut data)?;
+        entries.push((entry.name().to_string(), data));
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_round_trips_its_entries_once_each() {
+        let path = std::env::temp_dir().join(format!("cypher-bundle-{}.zip", std::process::id()));
+        let mut writer = BundleWriter::create(&path).unwrap();
+        writer.add("Session/session.json", b"{}").unwrap();
+        writer.add("Samples/kick.wav", &[1, 2, 3]).unwrap();
+        writer.add("Samples/kick.wav", &[4, 5, 6]).unwrap();
+        writer.finish().unwrap();
+
+        let entries = read_bundle(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            entries,
+            [
+                ("Session/session.json".to_string(), b"{}".to_vec()),
+                ("Samples/kick.wav".to_string(), vec![1, 2, 3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn same_named_files_from_different_folders_get_their_own_entries() {
+        let dir = std::env::temp_dir().join(format!("cypher-bundle-names-{}", std::process::id()));
+        let config_dir = dir.join("config");
+        let first = dir.join("a/kick.wav");
+        let second = dir.join("b/kick.wav");
+        for (path, data) in [(&first, [1u8]), (&second, [2u8])] {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, data).unwrap();
+        }
+        let bundle_path = dir.join("bundle.zip");
+
+        let mut writer = BundleWriter::create(&bundle_path).unwrap();
+        let first_name = writer.add_file(&config_dir, &first).unwrap();
+        let second_name = writer.add_file(&config_dir, &second).unwrap();
+        assert_eq!(writer.add_file(&config_dir, &first).unwrap(), first_name);
+        writer.finish().unwrap();
+
+        let entries = read_bundle(&bundle_path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(first_name, PathBuf::from("Samples/Bundled/kick.wav"));
+        assert_eq!(second_name, PathBuf::from("Samples/Bundled/kick (2).wav"));
+        assert_eq!(
+            entries,
+            [
+                ("Samples/Bundled/kick.wav".to_string(), vec![1]),
+                ("Samples/Bundled/kick (2).wav".to_string(), vec![2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn unpacking_keeps_local_files_and_renames_entries_that_differ() {
+        let root = std::env::temp_dir().join(format!("cypher-unpack-{}", std::process::id()));
+        fs::create_dir_all(root.join("Samples/Drums")).unwrap();
+        fs::write(root.join("Samples/same.wav"), [1]).unwrap();
+        fs::write(root.join("Samples/kick.wav"), [1]).unwrap();
+        fs::write(root.join("Samples/Drums/a.wav"), [1]).unwrap();
+        fs::write(root.join("Samples/Drums/extra.wav"), [1]).unwrap();
+
+        let mut unpacker = Unpacker::new(&root);
+        let same = unpacker.place("Samples/same.wav", &[1]).unwrap();
+        let kick = unpacker.place("Samples/kick.wav", &[2]).unwrap();
+        let new = unpacker.place("Samples/new.wav", &[3]).unwrap();
+        let drums = unpacker.place_folder("Samples/Drums", &[("a.wav".to_string(), &[1])]).unwrap();
+        let renamed = unpacker.renamed();
+        let read = |name: &str| fs::read(root.join(name)).unwrap();
+        let (kick_data, kick_local, drums_data) =
+            (read("Samples/kick (2).wav"), read("Samples/kick.wav"), read("Samples/Drums (2)/a.wav"));
+        let drums_files = fs::read_dir(root.join("Samples/Drums (2)")).unwrap().count();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(same, "Samples/same.wav");
+        assert_eq!(kick, "Samples/kick (2).wav");
+        assert_eq!(new, "Samples/new.wav");
+        assert_eq!(drums, "Samples/Drums (2)");
+        assert_eq!(renamed, 2);
+        assert_eq!((kick_data, kick_local, drums_data), (vec![2], vec![1], vec![1]));
+        assert_eq!(drums_files, 1);
+    }
+
+    #[test]
+    fn entries_cannot_unpack_outside_the_root() {
+        let root = Path::new("config");
+        assert_eq!(entry_destination(root, "Samples/a.wav"), Some(root.join("Samples/a.wav")));
+        assert_eq!(entry_destination(root, "../a.wav"), None);
+        assert_eq!(entry_destination(root, "/a.wav"), None);
+    }
+}
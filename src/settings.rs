@@ -126,6 +126,284 @@ pub enum MidiControlMode {
     Relative,
 }
 
+/// Which part of the library incoming MIDI Program Change messages step through.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgramChangeTarget {
+    #[default]
+    SynthPresets,
+    SamplerKits,
+}
+
+/// Where the transport takes its loop length from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopSyncSource {
+    /// The first recorded loop sets the length.
+    #[default]
+    Internal,
+    /// The length follows the tempo of incoming MIDI clock, one bar of 4/4 per loop.
+    MidiClock,
+}
+
+/// How live recordings are trimmed of leading and trailing silence.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct TrimSilenceSettings {
+    pub enabled: bool,
+    /// Block RMS above which audio counts as sound.
+    pub rms_threshold: f32,
+    /// Consecutive 512-sample blocks of sound needed to mark the start or end.
+    pub required_blocks: usize,
+}
+
+impl Default for TrimSilenceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rms_threshold: 0.005,
+            required_blocks: 3,
+        }
+    }
+}
+
+/// Whether arming a looper for the first recording disarms the others.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArmPolicy {
+    /// Only one looper can be armed at a time.
+    #[default]
+    Exclusive,
+    /// Any number of loopers can be armed; they all start recording on the same input.
+    /// Before a transport exists, the first one closed sets the transport length and the
+    /// rest are lined up with it, ending on whole cycles of that length.
+    Multi,
+}
+
+/// Sample encoding for recordings and saved session loops.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordingFormat {
+    #[default]
+    Int16,
+    Int24,
+    Float32,
+}
+
+impl RecordingFormat {
+    pub const ALL: [RecordingFormat; 3] = [
+        RecordingFormat::Int16,
+        RecordingFormat::Int24,
+        RecordingFormat::Float32,
+    ];
+
+    pub fn wav_spec(self, channels: u16, sample_rate: u32) -> hound::WavSpec {
+        let (bits_per_sample, sample_format) = match self {
+            RecordingFormat::Int16 => (16, hound::SampleFormat::Int),
+            RecordingFormat::Int24 => (24, hound::SampleFormat::Int),
+            RecordingFormat::Float32 => (32, hound::SampleFormat::Float),
+        };
+        hound::WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample,
+            sample_format,
+        }
+    }
+}
+
+impl fmt::Display for RecordingFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordingFormat::Int16 => write!(f, "16-bit"),
+            RecordingFormat::Int24 => write!(f, "24-bit"),
+            RecordingFormat::Float32 => write!(f, "32-bit Float"),
+        }
+    }
+}
+
+/// Level at which `OutputClipMode::SoftClip` starts rounding off peaks (about -0.9 dBFS).
+const SOFT_CLIP_KNEE: f32 = 0.9;
+
+/// How the master output is kept within full scale after the user limiter, which may be off.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputClipMode {
+    HardClamp,
+    /// Leaves the signal untouched below the knee and bends it smoothly towards full scale.
+    #[default]
+    SoftClip,
+}
+
+impl OutputClipMode {
+    pub const ALL: [OutputClipMode; 2] = [OutputClipMode::HardClamp, OutputClipMode::SoftClip];
+
+    pub fn apply(self, sample: f32) -> f32 {
+        match self {
+            OutputClipMode::HardClamp => sample.clamp(-1.0, 1.0),
+            OutputClipMode::SoftClip => {
+                let magnitude = sample.abs();
+                if magnitude <= SOFT_CLIP_KNEE {
+                    return sample;
+                }
+                let headroom = 1.0 - SOFT_CLIP_KNEE;
+                let overshoot = (magnitude - SOFT_CLIP_KNEE) / headroom;
+                (SOFT_CLIP_KNEE + headroom * overshoot.tanh()).copysign(sample)
+            }
+        }
+    }
+}
+
+impl fmt::Display for OutputClipMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputClipMode::HardClamp => write!(f, "Hard Clamp"),
+            OutputClipMode::SoftClip => write!(f, "Soft Clip"),
+        }
+    }
+}
+
+/// How the UI meters follow the engine's peak readings.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MeterBallistics {
+    /// Uses `MeterSettings::attack_ms` and `release_ms`.
+    #[default]
+    Peak,
+    /// Broadcast PPM: 10 ms rise and a slow fall, so short peaks still register.
+    Ppm,
+    /// VU: a 300 ms rise and fall that shows average loudness rather than peaks.
+    Vu,
+}
+
+impl MeterBallistics {
+    pub const ALL: [MeterBallistics; 3] =
+        [MeterBallistics::Peak, MeterBallistics::Ppm, MeterBallistics::Vu];
+}
+
+impl fmt::Display for MeterBallistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeterBallistics::Peak => write!(f, "Peak"),
+            MeterBallistics::Ppm => write!(f, "PPM"),
+            MeterBallistics::Vu => write!(f, "VU"),
+        }
+    }
+}
+
+/// How the mixer and level meters smooth what the audio thread reports.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct MeterSettings {
+    pub ballistics: MeterBallistics,
+    /// Time constant of a rising `Peak` meter, in ms; 0 jumps straight to the reading.
+    pub attack_ms: f32,
+    /// Time constant of a falling `Peak` meter, in ms.
+    pub release_ms: f32,
+    /// How long the peak-hold marker stays at the highest level before falling; 0 hides it.
+    pub peak_hold_ms: f32,
+}
+
+impl Default for MeterSettings {
+    fn default() -> Self {
+        Self {
+            ballistics: MeterBallistics::Peak,
+            attack_ms: 0.0,
+            release_ms: 300.0,
+            peak_hold_ms: 1500.0,
+        }
+    }
+}
+
+impl MeterSettings {
+    /// Rise and fall time constants in seconds.
+    fn time_constants(&self) -> (f32, f32) {
+        match self.ballistics {
+            MeterBallistics::Peak => (self.attack_ms / 1000.0, self.release_ms / 1000.0),
+            // 20 dB of fall in about 2.8 s.
+            MeterBallistics::Ppm => (0.01, 1.2),
+            // Reaches 99% of a step in 300 ms.
+            MeterBallistics::Vu => (0.065, 0.065),
+        }
+    }
+
+    /// Moves a displayed level towards a new reading over `dt` seconds.
+    pub fn follow(&self, displayed: f32, reading: f32, dt: f32) -> f32 {
+        let (attack, release) = self.time_constants();
+        let time_constant = if reading > displayed { attack } else { release };
+        if time_constant <= 0.0 {
+            return reading;
+        }
+        reading + (displayed - reading) * (-dt / time_constant).exp()
+    }
+}
+
+/// Severity bands for the top bar CPU indicator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuLoadLevel {
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl CpuLoadLevel {
+    /// Classifies a load percentage against the configured warning and critical thresholds.
+    pub fn from_load(load_percent: f32, warning_threshold: f32, critical_threshold: f32) -> Self {
+        if load_percent >= critical_threshold {
+            CpuLoadLevel::Critical
+        } else if load_percent >= warning_threshold {
+            CpuLoadLevel::Warning
+        } else {
+            CpuLoadLevel::Normal
+        }
+    }
+}
+
+/// Input peak at or above which the input counts as clipping (about -0.1 dBFS).
+const INPUT_CLIP_LEVEL: f32 = 0.989;
+
+/// How long a headroom warning stays up after the input drops back below it.
+const INPUT_HEADROOM_HOLD_SECS: f32 = 1.5;
+
+/// How much room the live input has before it clips, shown next to the input ARM button.
+/// Ordered from most to least room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum InputHeadroom {
+    #[default]
+    Ok,
+    /// Above the configured warning level but not yet clipping.
+    Low,
+    Clipping,
+}
+
+impl InputHeadroom {
+    /// Classifies a linear input peak against the warning level in dBFS.
+    pub fn from_peak(peak: f32, warning_db: f32) -> Self {
+        if peak >= INPUT_CLIP_LEVEL {
+            InputHeadroom::Clipping
+        } else if peak >= 10.0_f32.powf(warning_db / 20.0) {
+            InputHeadroom::Low
+        } else {
+            InputHeadroom::Ok
+        }
+    }
+}
+
+/// Classifies raw input peaks, holding the worst reading for `INPUT_HEADROOM_HOLD_SECS` so
+/// a single clipped buffer stays visible long enough to be noticed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InputHeadroomHold {
+    headroom: InputHeadroom,
+    held_secs: f32,
+}
+
+impl InputHeadroomHold {
+    pub fn update(&mut self, peak: f32, warning_db: f32, dt: f32) -> InputHeadroom {
+        let current = InputHeadroom::from_peak(peak, warning_db);
+        if current >= self.headroom || self.held_secs >= INPUT_HEADROOM_HOLD_SECS {
+            self.headroom = current;
+            self.held_secs = 0.0;
+        } else {
+            self.held_secs += dt;
+        }
+        self.headroom
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum ControllableParameter {
     // Looper
@@ -174,6 +452,10 @@ pub enum ControllableParameter {
     MetronomeVolume,
     MetronomePitch,
     MetronomeToggleMute,
+
+    // 88 Keys
+    /// Plays a chord suggestion group from the 88-keys view while the control is held.
+    ChordSuggestion(usize),
 }
 
 impl ControllableParameter {
@@ -226,6 +508,8 @@ pub enum FxParamName {
     Decay,
     RateHz,
     DepthMs,
+    InputGainDb,
+    OutputGainDb,
 }
 
 impl FxParamName {
@@ -250,6 +534,8 @@ impl FxParamName {
             FxParamName::Decay => "decay",
             FxParamName::RateHz => "rate_hz",
             FxParamName::DepthMs => "depth_ms",
+            FxParamName::InputGainDb => "input_gain_db",
+            FxParamName::OutputGainDb => "output_gain_db",
         }
     }
 }
@@ -302,6 +588,7 @@ impl std::fmt::Display for ControllableParameter {
             ControllableParameter::MetronomeVolume => write!(f, "Metronome Volume"),
             ControllableParameter::MetronomePitch => write!(f, "Metronome Pitch"),
             ControllableParameter::MetronomeToggleMute => write!(f, "Metronome Mute Toggle"),
+            ControllableParameter::ChordSuggestion(i) => write!(f, "Chord Suggestion {} Play", i + 1),
         }
     }
 }
@@ -312,17 +599,66 @@ pub struct AppSettings {
     pub host_name: Option<String>,
     pub midi_port_names: Vec<String>,
     pub audio_note_channel: u8,
+    /// Snap incoming note pitches to the 88-keys view's selected scale in `scale_quantize_key`.
+    pub scale_quantize_midi: bool,
+    /// Pitch class (0 = C) of the key used by `scale_quantize_midi`.
+    pub scale_quantize_key: u8,
     pub midi_device_control_channels: BTreeMap<String, u8>,
     pub input_device: Option<String>,
     pub output_device: Option<String>,
     pub sample_rate: Option<u32>,
     pub buffer_size: Option<u32>,
     pub input_latency_compensation_ms: f32,
+    /// Frequency of A4 for the synth and sampler, e.g. 432 or 442 Hz.
+    pub tuning_reference_hz: f32,
     pub last_sampler_kit: Option<PathBuf>,
     pub last_synth_preset: Option<PathBuf>,
     pub last_theme: Option<PathBuf>,
+    /// Most recently saved or loaded session folders, newest first.
+    pub recent_sessions: Vec<PathBuf>,
     pub bpm_rounding: bool,
+    /// Crossfade at the wrap point of recorded and overdubbed loops, in ms; 0 is off.
+    pub loop_crossfade_ms: f32,
+    /// Anti-click fade at the start and end of pad and sampler playback, in ms; 0 is off.
+    pub sample_declick_ms: f32,
+    /// Crossfade between the old and new engine when an engine slot changes type, in ms.
+    pub engine_swap_fade_ms: f32,
+    /// Final safety stage on the master output, applied whether or not the limiter is on.
+    pub output_clip_mode: OutputClipMode,
+    /// Keep the synth, sampler and input audible while the transport is paused, for practice.
+    pub monitor_while_stopped: bool,
+    /// Restart all tempo-synced LFOs on the transport downbeat so modulation lines up.
+    pub lfo_transport_sync: bool,
     pub relative_encoder_multiplier: f32,
+    pub faders_in_db: bool,
+    pub program_change_target: ProgramChangeTarget,
+    pub loop_sync_source: LoopSyncSource,
+    /// Encoding for live recordings, stems and saved session loops.
+    pub recording_format: RecordingFormat,
+    pub arm_policy: ArmPolicy,
+    pub trim_silence: TrimSilenceSettings,
+    /// Peak-normalize live recordings to `normalize_target_db` once they are trimmed.
+    pub normalize_recordings: bool,
+    pub normalize_target_db: f32,
+    /// Dither 16-bit recordings instead of truncating.
+    pub dither_recordings: bool,
+    pub loudness_meter_enabled: bool,
+    /// Target integrated loudness; the master readout shows how far the mix sits from it.
+    pub loudness_reference_lufs: f32,
+    /// CPU load percentages at which the top bar indicator turns yellow and red.
+    pub cpu_warning_threshold: f32,
+    pub cpu_critical_threshold: f32,
+    pub meters: MeterSettings,
+    /// Seconds between session autosaves while loopers hold audio; 0 turns autosave off.
+    pub autosave_interval_secs: u32,
+    /// Input peak (dBFS) above which the input is flagged as running out of headroom.
+    pub input_headroom_warning_db: f32,
+    /// Refuse to arm the input while it is clipping.
+    pub block_arm_when_clipping: bool,
+    /// User-saved starting parameters for newly added FX components, keyed by type.
+    pub fx_component_defaults: BTreeMap<fx::FxComponentType, serde_json::Value>,
+    /// Saved FX preset (by name) loaded into each insertion point on a new session.
+    pub default_fx_presets: BTreeMap<fx::InsertionPoint, String>,
     pub midi_mappings: BTreeMap<FullMidiIdentifier, ControllableParameter>,
     pub midi_mapping_modes: BTreeMap<FullMidiIdentifier, MidiControlMode>,
     pub midi_mapping_inversions: BTreeMap<FullMidiIdentifier, bool>,
@@ -334,17 +670,46 @@ impl Default for AppSettings {
             host_name: None,
             midi_port_names: Vec::new(),
             audio_note_channel: 0,
+            scale_quantize_midi: false,
+            scale_quantize_key: 0,
             midi_device_control_channels: BTreeMap::new(),
             input_device: None,
             output_device: None,
             sample_rate: None,
             buffer_size: None,
             input_latency_compensation_ms: 5.0,
+            tuning_reference_hz: crate::synth::DEFAULT_TUNING_REFERENCE_HZ,
             last_sampler_kit: None,
             last_synth_preset: None,
             last_theme: None,
+            recent_sessions: Vec::new(),
             bpm_rounding: false,
+            loop_crossfade_ms: 0.0,
+            sample_declick_ms: crate::sampler::DEFAULT_DECLICK_MS,
+            engine_swap_fade_ms: crate::audio_engine::DEFAULT_ENGINE_SWAP_FADE_MS,
+            output_clip_mode: OutputClipMode::default(),
+            monitor_while_stopped: true,
+            lfo_transport_sync: false,
             relative_encoder_multiplier: 1.0,
+            faders_in_db: false,
+            program_change_target: ProgramChangeTarget::default(),
+            loop_sync_source: LoopSyncSource::default(),
+            recording_format: RecordingFormat::default(),
+            arm_policy: ArmPolicy::default(),
+            trim_silence: TrimSilenceSettings::default(),
+            normalize_recordings: false,
+            normalize_target_db: -1.0,
+            dither_recordings: false,
+            loudness_meter_enabled: false,
+            loudness_reference_lufs: -14.0,
+            cpu_warning_threshold: 60.0,
+            cpu_critical_threshold: 85.0,
+            meters: MeterSettings::default(),
+            autosave_interval_secs: 60,
+            input_headroom_warning_db: -3.0,
+            block_arm_when_clipping: false,
+            fx_component_defaults: BTreeMap::new(),
+            default_fx_presets: BTreeMap::new(),
             midi_mappings: BTreeMap::new(),
             midi_mapping_modes: BTreeMap::new(),
             midi_mapping_inversions: BTreeMap::new(),
@@ -352,6 +717,9 @@ impl Default for AppSettings {
     }
 }
 
+/// How many entries `AppSettings::recent_sessions` keeps.
+pub const MAX_RECENT_SESSIONS: usize = 10;
+
 pub fn get_config_dir() -> Option<PathBuf> {
     if let Ok(exe_path) = env::current_exe() {
         if let Some(exe_dir) = exe_path.parent() {
@@ -436,4 +804,88 @@ pub fn load_settings() -> AppSettings {
         }
     }
     AppSettings::default()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_load_levels_switch_at_the_configured_thresholds() {
+        let settings = AppSettings::default();
+        let level = |load| {
+            CpuLoadLevel::from_load(
+                load,
+                settings.cpu_warning_threshold,
+                settings.cpu_critical_threshold,
+            )
+        };
+        assert_eq!(level(0.0), CpuLoadLevel::Normal);
+        assert_eq!(level(59.9), CpuLoadLevel::Normal);
+        assert_eq!(level(60.0), CpuLoadLevel::Warning);
+        assert_eq!(level(84.9), CpuLoadLevel::Warning);
+        assert_eq!(level(85.0), CpuLoadLevel::Critical);
+        assert_eq!(level(150.0), CpuLoadLevel::Critical);
+    }
+
+    #[test]
+    fn cpu_load_levels_follow_custom_thresholds() {
+        assert_eq!(CpuLoadLevel::from_load(30.0, 25.0, 50.0), CpuLoadLevel::Warning);
+        assert_eq!(CpuLoadLevel::from_load(30.0, 40.0, 50.0), CpuLoadLevel::Normal);
+        assert_eq!(CpuLoadLevel::from_load(30.0, 10.0, 20.0), CpuLoadLevel::Critical);
+    }
+
+    #[test]
+    fn input_headroom_warns_from_the_configured_level() {
+        let warning_db = AppSettings::default().input_headroom_warning_db;
+        let headroom = |peak| InputHeadroom::from_peak(peak, warning_db);
+        // -3 dBFS is a linear peak of about 0.708.
+        assert_eq!(headroom(0.0), InputHeadroom::Ok);
+        assert_eq!(headroom(0.70), InputHeadroom::Ok);
+        assert_eq!(headroom(0.71), InputHeadroom::Low);
+        assert_eq!(headroom(0.98), InputHeadroom::Low);
+        assert_eq!(headroom(INPUT_CLIP_LEVEL), InputHeadroom::Clipping);
+        assert_eq!(headroom(1.5), InputHeadroom::Clipping);
+        assert_eq!(InputHeadroom::from_peak(0.4, -12.0), InputHeadroom::Low);
+    }
+
+    #[test]
+    fn input_headroom_warning_is_held_for_a_fixed_time() {
+        let mut hold = InputHeadroomHold::default();
+        let dt = 0.1;
+        assert_eq!(hold.update(1.0, -3.0, dt), InputHeadroom::Clipping);
+        // One clipped reading keeps the warning up for the hold time, however quiet it gets.
+        let held_frames = (0..100)
+            .take_while(|_| hold.update(0.0, -3.0, dt) == InputHeadroom::Clipping)
+            .count();
+        assert!((held_frames as f32 * dt - INPUT_HEADROOM_HOLD_SECS).abs() <= dt);
+        assert_eq!(hold.update(0.0, -3.0, dt), InputHeadroom::Ok);
+        // A worse reading replaces a held one at once.
+        assert_eq!(hold.update(0.8, -3.0, dt), InputHeadroom::Low);
+        assert_eq!(hold.update(1.0, -3.0, dt), InputHeadroom::Clipping);
+    }
+
+    #[test]
+    fn soft_clip_rounds_off_peaks_instead_of_clamping() {
+        let soft = |s| OutputClipMode::SoftClip.apply(s);
+        let hard = |s| OutputClipMode::HardClamp.apply(s);
+        // Below the knee both leave the signal alone.
+        assert_eq!(soft(0.5), 0.5);
+        assert_eq!(soft(-SOFT_CLIP_KNEE), -SOFT_CLIP_KNEE);
+
+        // Hard clamping flattens everything over full scale to the same value...
+        assert_eq!(hard(1.2), 1.0);
+        assert_eq!(hard(2.0), 1.0);
+        // ...while the soft clip bends towards full scale and keeps rising with the input.
+        let peaks = [0.95, 1.0, 1.1, 1.2].map(soft);
+        assert!(peaks.iter().all(|&p| p > SOFT_CLIP_KNEE && p < 1.0));
+        assert!(peaks.windows(2).all(|w| w[1] > w[0]));
+        assert!(soft(10.0) <= 1.0);
+        assert!(soft(0.95) < 0.95);
+        assert_eq!(soft(-1.2), -soft(1.2));
+        // No corner at the knee: the slope just above it is still about 1.
+        let slope = (soft(SOFT_CLIP_KNEE + 1e-3) - SOFT_CLIP_KNEE) / 1e-3;
+        assert!((slope - 1.0).abs() < 0.02, "got {slope}");
+    }
 }
\ No newline at end of file
@@ -98,4 +98,179 @@ pub fn find_slices_from_visual_peaks(
     }
 
     refined_regions
-}
\ No newline at end of file
+}
+
+const ONSET_FRAME_SIZE: usize = 1024;
+const ONSET_HOP_SIZE: usize = 256;
+/// Frames on either side averaged into the adaptive onset threshold.
+const ONSET_THRESHOLD_RADIUS: usize = 8;
+/// Two onsets closer than this are treated as one hit.
+const MIN_ONSET_GAP_MS: f32 = 40.0;
+/// Frames quieter than this (in dBFS) never start a slice, so noise floors don't chatter.
+const ONSET_FLOOR_DB: f32 = -60.0;
+
+/// Slices audio at its transients, for drum loops and other material without silent gaps.
+///
+/// Builds an onset function from the positive frame-to-frame rise in log energy, then
+/// places a boundary at each local peak that clears an adaptive threshold (the local mean
+/// plus a margin). Higher `sensitivity` (0..1) shrinks the margin and finds softer hits.
+///
+/// # Returns
+/// Start and end sample of each slice; each slice runs up to the next onset.
+pub fn find_slices_from_transients(
+    audio_data: &[f32],
+    sample_rate: u32,
+    sensitivity: f32,
+) -> Vec<(usize, usize)> {
+    if audio_data.len() < ONSET_FRAME_SIZE {
+        return if audio_data.is_empty() { vec![] } else { vec![(0, audio_data.len())] };
+    }
+
+    let num_frames = (audio_data.len() - ONSET_FRAME_SIZE) / ONSET_HOP_SIZE + 1;
+    let energy_db: Vec<f32> = (0..num_frames)
+        .map(|frame| {
+            let start = frame * ONSET_HOP_SIZE;
+            let chunk = &audio_data[start..start + ONSET_FRAME_SIZE];
+            let mean_square = chunk.iter().map(|s| s * s).sum::<f32>() / ONSET_FRAME_SIZE as f32;
+            10.0 * mean_square.max(1e-12).log10()
+        })
+        .collect();
+
+    let onset_strength: Vec<f32> = (0..num_frames)
+        .map(|frame| {
+            if frame == 0 || energy_db[frame] < ONSET_FLOOR_DB {
+                0.0
+            } else {
+                (energy_db[frame] - energy_db[frame - 1]).max(0.0)
+            }
+        })
+        .collect();
+
+    // A rise of a few dB is a hit at full sensitivity; at zero it takes a sharp attack.
+    let margin_db = 1.0 + (1.0 - sensitivity.clamp(0.0, 1.0)) * 11.0;
+    let min_gap_frames =
+        ((MIN_ONSET_GAP_MS / 1000.0 * sample_rate as f32) / ONSET_HOP_SIZE as f32).ceil() as usize;
+
+    let mut onsets: Vec<usize> = Vec::new();
+    for frame in 1..num_frames {
+        let strength = onset_strength[frame];
+        let lo = frame.saturating_sub(ONSET_THRESHOLD_RADIUS);
+        let hi = (frame + ONSET_THRESHOLD_RADIUS + 1).min(num_frames);
+        let local_mean = onset_strength[lo..hi].iter().sum::<f32>() / (hi - lo) as f32;
+        let is_peak = onset_strength[frame.saturating_sub(2)..(frame + 3).min(num_frames)]
+            .iter()
+            .all(|&s| s <= strength);
+        if !is_peak || strength < local_mean + margin_db {
+            continue;
+        }
+        if onsets.last().is_some_and(|&last| frame - last < min_gap_frames) {
+            continue;
+        }
+        onsets.push(frame);
+    }
+
+    // Only the last hop of the rising frame is new audio, so the attack lies there. Start
+    // one hop earlier so it isn't clipped; audio before the first hit becomes its own slice.
+    let mut starts: Vec<usize> = onsets
+        .iter()
+        .map(|&frame| frame * ONSET_HOP_SIZE + ONSET_FRAME_SIZE - 2 * ONSET_HOP_SIZE)
+        .collect();
+    if starts.first().is_none_or(|&first| first > 0) && energy_db[0] >= ONSET_FLOOR_DB {
+        starts.insert(0, 0);
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| (start, starts.get(i + 1).copied().unwrap_or(audio_data.len())))
+        .filter(|(start, end)| end > start)
+        .collect()
+}
+
+/// The zero crossing closest to `sample`, looking at most `search_radius` samples either
+/// way. Cutting there keeps exported slices from starting or ending on a click. Returns
+/// `sample` unchanged if no crossing is in range.
+pub fn nearest_zero_crossing(audio_data: &[f32], sample: usize, search_radius: usize) -> usize {
+    let is_crossing = |i: usize| {
+        i > 0 && i < audio_data.len() && (audio_data[i - 1] <= 0.0) != (audio_data[i] <= 0.0)
+    };
+    (0..=search_radius)
+        .flat_map(|distance| [sample.checked_sub(distance), sample.checked_add(distance)])
+        .flatten()
+        .find(|&i| is_crossing(i))
+        .unwrap_or(sample)
+}
+
+/// Moves the start (or end) of `regions[index]` to `sample`, keeping the region at least one
+/// sample long and clear of its neighbours. A boundary shared with the neighbouring region,
+/// as transient slices have, moves for both.
+pub fn move_slice_boundary(
+    regions: &mut [(usize, usize)],
+    index: usize,
+    is_end: bool,
+    sample: usize,
+    total_samples: usize,
+) {
+    let Some(&(start, end)) = regions.get(index) else {
+        return;
+    };
+    if is_end {
+        let next = regions.get(index + 1).copied();
+        let linked = next.is_some_and(|(next_start, _)| next_start == end);
+        let max = match next {
+            Some((_, next_end)) if linked => next_end - 1,
+            Some((next_start, _)) => next_start,
+            None => total_samples,
+        };
+        let new_end = sample.clamp(start + 1, max.max(start + 1));
+        regions[index].1 = new_end;
+        if linked {
+            regions[index + 1].0 = new_end;
+        }
+    } else {
+        let previous = index.checked_sub(1).map(|i| regions[i]);
+        let linked = previous.is_some_and(|(_, previous_end)| previous_end == start);
+        let min = match previous {
+            Some((previous_start, _)) if linked => previous_start + 1,
+            Some((_, previous_end)) => previous_end,
+            None => 0,
+        };
+        let new_start = sample.clamp(min.min(end - 1), end - 1);
+        regions[index].0 = new_start;
+        if linked {
+            regions[index - 1].1 = new_start;
+        }
+    }
+}
+
+/// Splits the region containing `sample` in two at that point. Returns false if `sample`
+/// isn't strictly inside a region.
+pub fn split_slice_at(regions: &mut Vec<(usize, usize)>, sample: usize) -> bool {
+    let Some(index) = regions.iter().position(|&(start, end)| start < sample && sample < end) else {
+        return false;
+    };
+    let end = regions[index].1;
+    regions[index].1 = sample;
+    regions.insert(index + 1, (sample, end));
+    true
+}
+
+/// Joins `regions[index]` and the region after it into one, swallowing any gap between them.
+pub fn merge_slice_with_next(regions: &mut Vec<(usize, usize)>, index: usize) {
+    if index + 1 < regions.len() {
+        let (_, next_end) = regions.remove(index + 1);
+        regions[index].1 = next_end;
+    }
+}
+
+/// Samples summarised by each entry of `block_peaks`.
+pub const PEAK_BLOCK_SIZE: usize = 256;
+
+/// Absolute peak of each `PEAK_BLOCK_SIZE` block of `audio_data`, so a zoomed-out waveform can
+/// be drawn without touching every sample each frame.
+pub fn block_peaks(audio_data: &[f32]) -> Vec<f32> {
+    audio_data
+        .chunks(PEAK_BLOCK_SIZE)
+        .map(|block| block.iter().fold(0.0f32, |max, &v| max.max(v.abs())))
+        .collect()
+}
@@ -1,8 +1,10 @@
 // src/synth.rs
+use crate::fx_components::lfo::SteppedRandom;
 use crate::sampler_engine;
 use crate::settings::MidiControlId;
 use crate::wavetable_engine::{
-    self, SaturationSettings, WavetableEngine, WavetableMixerSettings, WavetableSet,
+    self, SaturationSettings, UnisonSettings, WavetableEngine, WavetableMixerSettings,
+    WavetableSet,
 };
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -93,6 +95,30 @@ pub static EXP_LUT: Lazy<Lut> = Lazy::new(|| Lut::new(0.0, 10.0, |x| (-x).exp())
 
 pub const WAVETABLE_SIZE: usize = 2048;
 
+/// Standard concert pitch for A4 (MIDI note 69).
+pub const DEFAULT_TUNING_REFERENCE_HZ: f32 = 440.0;
+pub const MIN_TUNING_REFERENCE_HZ: f32 = 400.0;
+pub const MAX_TUNING_REFERENCE_HZ: f32 = 480.0;
+
+/// Equal-temperament frequency of a MIDI note, with A4 tuned to `a4_hz`.
+#[inline]
+pub fn note_to_freq(note: u8, a4_hz: f32) -> f32 {
+    a4_hz * 2.0_f32.powf((note as f32 - 69.0) / 12.0)
+}
+
+/// Key-tracking value of a MIDI note: 0 at middle C (60), +/-1 per octave.
+#[inline]
+pub fn key_track(note: u8) -> f32 {
+    (note as f32 - 60.0) / 12.0
+}
+
+/// Maps a normalized note velocity to an amplitude gain. At a sensitivity of 0 every
+/// note plays at full level; at 1 the gain follows the velocity directly.
+#[inline(always)]
+pub fn velocity_gain(velocity: f32, sensitivity: f32) -> f32 {
+    1.0 - sensitivity.clamp(0.0, 1.0) * (1.0 - velocity)
+}
+
 // --- Generic Engine Trait ---
 pub trait Engine {
     /// Processes a block of audio samples, writing the output into `output_buffer`.
@@ -107,8 +133,19 @@ pub trait Engine {
     fn set_polyphonic(&mut self, poly: bool);
     fn set_amp_adsr(&mut self, settings: AdsrSettings);
     fn set_filter_adsr(&mut self, settings: AdsrSettings);
+    fn set_velocity_sensitivity(&mut self, sensitivity: f32);
+    /// Portamento time used in mono mode. Zero disables glide.
+    fn set_glide_time(&mut self, glide_time_ms: f32);
+    /// Frequency of A4 used for note-to-frequency conversion. Applies to new notes.
+    fn set_tuning_reference(&mut self, a4_hz: f32);
     fn reset_to_defaults(&mut self);
     fn set_wavetable(&mut self, slot_index: usize, audio_data: Arc<Vec<f32>>, name: String);
+    /// Hard-stops every voice and clears envelope and filter state. Loaded audio is kept.
+    fn reset_voices(&mut self);
+    /// Restarts the LFOs that run in tempo-synced mode, e.g. on the transport downbeat.
+    fn reset_synced_lfo_phases(&mut self);
+    /// Amount the mod matrix adds to the engine's FX rack mix, as of the last processed block.
+    fn fx_mix_modulation(&self) -> f32;
 }
 
 // --- Synth Engine Enum ---
@@ -165,6 +202,27 @@ impl Engine for SynthEngine {
         }
     }
 
+    fn set_velocity_sensitivity(&mut self, sensitivity: f32) {
+        match self {
+            SynthEngine::Wavetable(e) => e.set_velocity_sensitivity(sensitivity),
+            SynthEngine::Sampler(e) => e.set_velocity_sensitivity(sensitivity),
+        }
+    }
+
+    fn set_glide_time(&mut self, glide_time_ms: f32) {
+        match self {
+            SynthEngine::Wavetable(e) => e.set_glide_time(glide_time_ms),
+            SynthEngine::Sampler(e) => e.set_glide_time(glide_time_ms),
+        }
+    }
+
+    fn set_tuning_reference(&mut self, a4_hz: f32) {
+        match self {
+            SynthEngine::Wavetable(e) => e.set_tuning_reference(a4_hz),
+            SynthEngine::Sampler(e) => e.set_tuning_reference(a4_hz),
+        }
+    }
+
     fn reset_to_defaults(&mut self) {
         match self {
             SynthEngine::Wavetable(e) => e.reset_to_defaults(),
@@ -178,11 +236,34 @@ impl Engine for SynthEngine {
             SynthEngine::Sampler(e) => e.set_wavetable(slot_index, audio_data, name), // (no-op)
         }
     }
+
+    fn reset_voices(&mut self) {
+        match self {
+            SynthEngine::Wavetable(e) => e.reset_voices(),
+            SynthEngine::Sampler(e) => e.reset_voices(),
+        }
+    }
+
+    fn reset_synced_lfo_phases(&mut self) {
+        match self {
+            SynthEngine::Wavetable(e) => e.reset_synced_lfo_phases(),
+            SynthEngine::Sampler(e) => e.reset_synced_lfo_phases(),
+        }
+    }
+
+    fn fx_mix_modulation(&self) -> f32 {
+        match self {
+            SynthEngine::Wavetable(e) => e.fx_mix_modulation(),
+            SynthEngine::Sampler(e) => e.fx_mix_modulation(),
+        }
+    }
 }
 
 // --- Main Synth Struct (unchanged logic, but now holds the enum) ---
 pub struct Synth {
     pub engines: [SynthEngine; 2],
+    tuning_reference_hz: f32,
+    declick_ms: f32,
 }
 
 impl Synth {
@@ -193,7 +274,38 @@ impl Synth {
             Self::create_engine(sample_rate, params0),
             Self::create_engine(sample_rate, params1),
         ];
-        Self { engines }
+        Self {
+            engines,
+            tuning_reference_hz: DEFAULT_TUNING_REFERENCE_HZ,
+            declick_ms: crate::sampler::DEFAULT_DECLICK_MS,
+        }
+    }
+
+    /// Retunes both engines. Engines created later via [`Synth::replace_engine`] inherit it.
+    pub fn set_tuning_reference(&mut self, a4_hz: f32) {
+        self.tuning_reference_hz = a4_hz.clamp(MIN_TUNING_REFERENCE_HZ, MAX_TUNING_REFERENCE_HZ);
+        for engine in self.engines.iter_mut() {
+            engine.set_tuning_reference(self.tuning_reference_hz);
+        }
+    }
+
+    /// Sets the sampler engines' anti-click fade. Engines created later inherit it.
+    pub fn set_declick_time(&mut self, declick_ms: f32) {
+        self.declick_ms = declick_ms.max(0.0);
+        for engine in self.engines.iter_mut() {
+            if let SynthEngine::Sampler(s) = engine {
+                s.set_declick_time(self.declick_ms);
+            }
+        }
+    }
+
+    /// Swaps in a new engine at `index`, tuned to the current reference, and returns the old one.
+    pub fn replace_engine(&mut self, index: usize, mut engine: SynthEngine) -> SynthEngine {
+        engine.set_tuning_reference(self.tuning_reference_hz);
+        if let SynthEngine::Sampler(s) = &mut engine {
+            s.set_declick_time(self.declick_ms);
+        }
+        std::mem::replace(&mut self.engines[index], engine)
     }
 
     pub fn create_engine(sample_rate: f32, params: EngineParamsUnion) -> SynthEngine {
@@ -201,7 +313,7 @@ impl Synth {
             EngineParamsUnion::Wavetable(p) => {
                 SynthEngine::Wavetable(wavetable_engine::WavetableEngine::new(
                     sample_rate, p.0, p.1, p.2, p.3, p.4, p.5, p.6, p.7, p.8, p.9, p.10, p.11, p.12,
-                    p.13, p.14, p.15, p.16, p.17,
+                    p.13, p.14, p.15, p.16, p.17, p.18,
                 ))
             }
             EngineParamsUnion::Sampler(p) => {
@@ -233,6 +345,16 @@ impl Synth {
         self.engines[0].note_off(note);
         self.engines[1].note_off(note);
     }
+
+    pub fn reset_voices(&mut self) {
+        self.engines[0].reset_voices();
+        self.engines[1].reset_voices();
+    }
+
+    pub fn reset_synced_lfo_phases(&mut self) {
+        self.engines[0].reset_synced_lfo_phases();
+        self.engines[1].reset_synced_lfo_phases();
+    }
 }
 
 // --- Shared Helper Structs and Enums (still live here) ---
@@ -354,6 +476,57 @@ impl Adsr {
     }
 }
 
+/// Exponential pitch ramp used for monophonic portamento. The ramp is expressed as a
+/// ratio against the target pitch, starting at `previous / target` and reaching 1.0.
+#[derive(Clone, Copy)]
+pub struct Glide {
+    ratio: f32,
+    step: f32,
+    samples_left: u32,
+}
+
+impl Default for Glide {
+    fn default() -> Self {
+        Self {
+            ratio: 1.0,
+            step: 1.0,
+            samples_left: 0,
+        }
+    }
+}
+
+impl Glide {
+    pub fn start(&mut self, from_ratio: f32, num_samples: u32) {
+        if num_samples == 0 || from_ratio <= 0.0 || (from_ratio - 1.0).abs() < 1e-6 {
+            self.stop();
+            return;
+        }
+        self.ratio = from_ratio;
+        self.step = from_ratio.powf(-1.0 / num_samples as f32);
+        self.samples_left = num_samples;
+    }
+
+    pub fn stop(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    #[inline(always)]
+    pub fn process(&mut self) -> f32 {
+        if self.samples_left > 0 {
+            self.ratio *= self.step;
+            self.samples_left -= 1;
+            if self.samples_left == 0 {
+                self.ratio = 1.0;
+            }
+        }
+        self.ratio
+    }
+}
+
 pub struct Filter {
     z1: f32,
     z2: f32,
@@ -391,6 +564,9 @@ pub struct Lfo {
     phase: f32,
     last_output: f32,
     sample_rate: f32,
+    random: SteppedRandom,
+    /// Samples since the last retrigger, for the mod matrix fade-in.
+    fade_elapsed: u32,
 }
 
 impl Lfo {
@@ -399,6 +575,8 @@ impl Lfo {
             phase: 0.0,
             last_output: 0.0,
             sample_rate,
+            random: SteppedRandom::new(rand::random()),
+            fade_elapsed: u32::MAX,
         }
     }
 
@@ -406,27 +584,41 @@ impl Lfo {
         &mut self,
         frequency: f32,
         waveform: LfoWaveform,
+        phase_offset: f32,
         wavetable_set: &crate::wavetable_engine::WavetableSet,
     ) -> f32 {
         let phase_inc = frequency / self.sample_rate;
         self.phase = (self.phase + phase_inc) % 1.0;
+        let phase = (self.phase + phase_offset.clamp(0.0, 1.0)) % 1.0;
 
         let output = match waveform {
-            LfoWaveform::Sine => (self.phase * TAU).sin(),
-            LfoWaveform::Triangle => 1.0 - 4.0 * (self.phase - 0.5).abs(),
-            LfoWaveform::Saw => 2.0 * self.phase - 1.0,
-            LfoWaveform::InvSaw => 1.0 - 2.0 * self.phase,
-            LfoWaveform::Square => if self.phase < 0.5 { 1.0 } else { -1.0 },
+            LfoWaveform::Sine => (phase * TAU).sin(),
+            LfoWaveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            LfoWaveform::Saw => 2.0 * phase - 1.0,
+            LfoWaveform::InvSaw => 1.0 - 2.0 * phase,
+            LfoWaveform::Square => if phase < 0.5 { 1.0 } else { -1.0 },
             LfoWaveform::Random => {
-                if self.phase < phase_inc {
+                if phase < phase_inc {
                     self.last_output = rand::random::<f32>() * 2.0 - 1.0;
                 }
                 self.last_output
             }
-            LfoWaveform::Wavetable1 => Self::process_wavetable(self.phase, 0, wavetable_set),
-            LfoWaveform::Wavetable2 => Self::process_wavetable(self.phase, 1, wavetable_set),
-            LfoWaveform::Wavetable3 => Self::process_wavetable(self.phase, 2, wavetable_set),
-            LfoWaveform::Wavetable4 => Self::process_wavetable(self.phase, 3, wavetable_set),
+            LfoWaveform::SampleHold => {
+                if phase < phase_inc {
+                    self.random.advance();
+                }
+                self.random.held()
+            }
+            LfoWaveform::SmoothRandom => {
+                if phase < phase_inc {
+                    self.random.advance();
+                }
+                self.random.smooth(phase)
+            }
+            LfoWaveform::Wavetable1 => Self::process_wavetable(phase, 0, wavetable_set),
+            LfoWaveform::Wavetable2 => Self::process_wavetable(phase, 1, wavetable_set),
+            LfoWaveform::Wavetable3 => Self::process_wavetable(phase, 2, wavetable_set),
+            LfoWaveform::Wavetable4 => Self::process_wavetable(phase, 3, wavetable_set),
         };
         output
     }
@@ -452,6 +644,26 @@ impl Lfo {
 
     pub fn reset_phase(&mut self) {
         self.phase = 0.0;
+        self.random.reseed();
+    }
+
+    /// Restarts the cycle at the phase offset and the mod matrix fade-in from zero.
+    pub fn retrigger(&mut self) {
+        self.reset_phase();
+        self.fade_elapsed = 0;
+    }
+
+    /// Depth of this LFO's mod matrix contribution for the next `block_size` samples. Ramps
+    /// from 0 to 1 over `fade_in_ms` after a retrigger.
+    pub fn advance_fade(&mut self, fade_in_ms: f32, block_size: usize) -> f32 {
+        let fade_samples = fade_in_ms * 0.001 * self.sample_rate;
+        let depth = if fade_samples < 1.0 {
+            1.0
+        } else {
+            (self.fade_elapsed as f32 / fade_samples).min(1.0)
+        };
+        self.fade_elapsed = self.fade_elapsed.saturating_add(block_size as u32);
+        depth
     }
 }
 
@@ -499,15 +711,24 @@ pub enum ModSource {
     Velocity,
     Static,
     MidiCC(MidiControlId),
+    /// The voice's note relative to middle C, in octaves (see `key_track`). Each voice tracks
+    /// its own note; in mono mode the single voice follows the most recent note.
+    KeyTrack,
 }
 impl ModSource {
-    pub const ALL: [ModSource; 5] = [
+    pub const ALL: [ModSource; 6] = [
         ModSource::Lfo1,
         ModSource::Lfo2,
         ModSource::Env2,
         ModSource::Velocity,
+        ModSource::KeyTrack,
         ModSource::Static,
     ];
+
+    /// Whether the source swings -1..1. Envelopes, velocity and MIDI CCs are already 0..1.
+    pub fn is_bipolar(&self) -> bool {
+        matches!(self, ModSource::Lfo1 | ModSource::Lfo2)
+    }
 }
 impl std::fmt::Display for ModSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -517,6 +738,7 @@ impl std::fmt::Display for ModSource {
             ModSource::Env2 => write!(f, "Env2"),
             ModSource::Velocity => write!(f, "Velocity"),
             ModSource::Static => write!(f, "Static"),
+            ModSource::KeyTrack => write!(f, "Key Track"),
             ModSource::MidiCC(id) => write!(f, "MIDI CC {} (Ch {})", id.cc, id.channel + 1),
         }
     }
@@ -530,19 +752,23 @@ pub enum LfoWaveform {
     InvSaw,
     Square,
     Random,
+    SampleHold,
+    SmoothRandom,
     Wavetable1,
     Wavetable2,
     Wavetable3,
     Wavetable4,
 }
 impl LfoWaveform {
-    pub const ALL: [LfoWaveform; 10] = [
+    pub const ALL: [LfoWaveform; 12] = [
         LfoWaveform::Sine,
         LfoWaveform::Triangle,
         LfoWaveform::Saw,
         LfoWaveform::InvSaw,
         LfoWaveform::Square,
         LfoWaveform::Random,
+        LfoWaveform::SampleHold,
+        LfoWaveform::SmoothRandom,
         LfoWaveform::Wavetable1,
         LfoWaveform::Wavetable2,
         LfoWaveform::Wavetable3,
@@ -558,6 +784,8 @@ impl std::fmt::Display for LfoWaveform {
             LfoWaveform::InvSaw => write!(f, "InvSaw"),
             LfoWaveform::Square => write!(f, "Square"),
             LfoWaveform::Random => write!(f, "Random"),
+            LfoWaveform::SampleHold => write!(f, "S&H"),
+            LfoWaveform::SmoothRandom => write!(f, "Smooth Random"),
             LfoWaveform::Wavetable1 => write!(f, "Wavetable 1"),
             LfoWaveform::Wavetable2 => write!(f, "Wavetable 2"),
             LfoWaveform::Wavetable3 => write!(f, "Wavetable 3"),
@@ -582,9 +810,12 @@ pub enum ModDestination {
     BellAmount,
     BellWidth,
     Saturation,
+    /// Offsets the engine's FX rack wet/dry mix. Engine-wide, so per-voice sources follow the
+    /// oldest voice.
+    FxMix,
 }
 impl ModDestination {
-    pub const ALL: [ModDestination; 8] = [
+    pub const ALL: [ModDestination; 9] = [
         ModDestination::WavetablePosition,
         ModDestination::Pitch,
         ModDestination::Amplitude,
@@ -593,6 +824,7 @@ impl ModDestination {
         ModDestination::BellAmount,
         ModDestination::BellWidth,
         ModDestination::Saturation,
+        ModDestination::FxMix,
     ];
 }
 impl std::fmt::Display for ModDestination {
@@ -606,6 +838,37 @@ impl std::fmt::Display for ModDestination {
             ModDestination::BellAmount => write!(f, "Bell Amount"),
             ModDestination::BellWidth => write!(f, "Bell Width"),
             ModDestination::Saturation => write!(f, "Saturation"),
+            ModDestination::FxMix => write!(f, "FX Mix"),
+        }
+    }
+}
+
+/// Response applied to a mod source before it is scaled by the routing's amount.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum ModCurve {
+    #[default]
+    Linear,
+    Exponential,
+    Logarithmic,
+}
+impl ModCurve {
+    pub const ALL: [ModCurve; 3] = [ModCurve::Linear, ModCurve::Exponential, ModCurve::Logarithmic];
+
+    /// Bends the magnitude of a -1..1 value, keeping its sign.
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            ModCurve::Linear => value,
+            ModCurve::Exponential => value * value.abs(),
+            ModCurve::Logarithmic => value.abs().sqrt().copysign(value),
+        }
+    }
+}
+impl std::fmt::Display for ModCurve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModCurve::Linear => write!(f, "Lin"),
+            ModCurve::Exponential => write!(f, "Exp"),
+            ModCurve::Logarithmic => write!(f, "Log"),
         }
     }
 }
@@ -615,6 +878,14 @@ pub struct ModRouting {
     pub source: ModSource,
     pub destination: ModDestination,
     pub amount: f32, // -1.0 to 1.0
+    /// Added to the destination regardless of the source, e.g. to hold a filter open.
+    #[serde(default)]
+    pub offset: f32, // -1.0 to 1.0
+    #[serde(default)]
+    pub curve: ModCurve,
+    /// Rescales a bipolar source (the LFOs) to 0..1 before the curve and amount.
+    #[serde(default)]
+    pub unipolar: bool,
 }
 impl Default for ModRouting {
     fn default() -> Self {
@@ -622,9 +893,24 @@ impl Default for ModRouting {
             source: ModSource::Lfo1,
             destination: ModDestination::WavetablePosition,
             amount: 0.0,
+            offset: 0.0,
+            curve: ModCurve::Linear,
+            unipolar: false,
         }
     }
 }
+impl ModRouting {
+    /// The routing's contribution to its destination for a given source value.
+    #[inline]
+    pub fn modulate(&self, source_val: f32) -> f32 {
+        let source_val = if self.unipolar && self.source.is_bipolar() {
+            source_val * 0.5 + 0.5
+        } else {
+            source_val
+        };
+        self.curve.apply(source_val) * self.amount + self.offset
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct LfoSettings {
@@ -633,6 +919,12 @@ pub struct LfoSettings {
     pub sync_rate: f32,
     pub mode: LfoRateMode,
     pub retrigger: bool,
+    /// Where in the cycle (0..1) the LFO starts on retrigger.
+    #[serde(default)]
+    pub phase_offset: f32,
+    /// Fades the LFO's mod matrix routings in over this long after a retrigger.
+    #[serde(default)]
+    pub fade_in_ms: f32,
 }
 impl Default for LfoSettings {
     fn default() -> Self {
@@ -642,6 +934,8 @@ impl Default for LfoSettings {
             sync_rate: 1.0, // 1/4 note default
             mode: LfoRateMode::Hz,
             retrigger: false,
+            phase_offset: 0.0,
+            fade_in_ms: 0.0,
         }
     }
 }
@@ -668,6 +962,7 @@ pub struct WavetableParams(
     pub Arc<AtomicU32>, // Saturation Mod Value
     pub Arc<AtomicU32>, // Final WT Pos (Feedback)
     pub Arc<AtomicU32>, // Final Cutoff (Feedback)
+    pub Arc<RwLock<UnisonSettings>>,
 );
 
 #[derive(Clone, Debug)]
@@ -697,4 +992,18 @@ pub type EngineWithVolumeAndPeak = (
     Arc<AtomicU32>, // Volume
     Arc<AtomicU32>, // Peak Meter
     EngineParamsUnion,
-);
\ No newline at end of file
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tuning_reference_scales_note_frequencies() {
+        assert!((note_to_freq(69, 432.0) - 432.0).abs() < 1e-3);
+        assert!((note_to_freq(57, 432.0) - 216.0).abs() < 1e-3);
+        assert!((note_to_freq(69, DEFAULT_TUNING_REFERENCE_HZ) - 440.0).abs() < 1e-3);
+        let ratio = note_to_freq(60, 432.0) / note_to_freq(60, DEFAULT_TUNING_REFERENCE_HZ);
+        assert!((ratio - 432.0 / 440.0).abs() < 1e-6);
+    }
+}
\ No newline at end of file
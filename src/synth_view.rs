@@ -1,16 +1,21 @@
 use crate::app::{CypherApp, EngineState, SynthUISection};
-use crate::asset::Asset;
+use crate::asset::{self, Asset};
 use crate::audio_engine::AudioCommand;
-use crate::sampler_engine::NUM_SAMPLE_SLOTS;
+use crate::mixer::format_db;
+use crate::sampler_engine::{SampleLoopRegion, VelocityZone, NUM_SAMPLE_SLOTS};
 use crate::synth::{
-    AdsrSettings, FilterMode, LfoRateMode, LfoWaveform, ModDestination, ModRouting, ModSource,
+    AdsrSettings, FilterMode, LfoRateMode, LfoWaveform, ModCurve, ModDestination, ModRouting,
+    ModSource,
 };
 use crate::theme::SynthEditorTheme;
-use crate::wavetable_engine::{WavetableSet, WavetableSource};
+use crate::wavetable_engine::{
+    WavetableSet, WavetableSource, MAX_UNISON_VOICES, NUM_WAVETABLE_SLOTS,
+};
 use egui::{
     epaint::{self, PathShape, RectShape, StrokeKind},
-    lerp, pos2, Align, Align2, Button, Color32, ComboBox, CornerRadius, DragAndDrop, Frame, Layout,
-    ProgressBar, Rect, RichText, ScrollArea, Sense, Shape, Slider, Stroke, Ui, Vec2, Window,
+    lerp, pos2, Align, Align2, Button, Color32, ComboBox, CornerRadius, DragAndDrop, DragValue,
+    Frame, Layout, ProgressBar, Rect, RichText, ScrollArea, Sense, Shape, Slider, Stroke, Ui, Vec2,
+    Window,
 };
 use std::path::PathBuf;
 use std::sync::atomic::Ordering;
@@ -102,12 +107,41 @@ pub fn draw_synth_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
                 if custom_button(ui, "New Preset", &theme).clicked() {
                     app.initialize_new_preset();
                 }
+                if custom_button(ui, "Randomize", &theme).clicked() {
+                    let modulation_only = app.randomize_modulation_only;
+                    app.randomize_engine(0, modulation_only);
+                    app.randomize_engine(1, modulation_only);
+                }
+                ui.toggle_value(
+                    &mut app.randomize_modulation_only,
+                    RichText::new("Mod Only").color(theme.label_color),
+                );
                 if custom_button(ui, "Save Preset", &theme).clicked() {
                     app.save_preset();
                 }
                 if custom_button(ui, "Load Preset", &theme).clicked() {
                     app.load_preset();
                 }
+                ui.separator();
+                for (slot, label) in ["A", "B"].into_iter().enumerate() {
+                    let is_active = app.synth_ab_active == slot;
+                    if ui.add(egui::Button::new(label).selected(is_active)).clicked() {
+                        app.select_synth_ab_slot(slot);
+                    }
+                }
+                if custom_button(ui, "Copy A→B", &theme).clicked() {
+                    app.copy_synth_a_to_b();
+                }
+                if custom_button(ui, "Swap", &theme).clicked() {
+                    app.swap_synth_ab_slots();
+                }
+                ui.separator();
+                if custom_button(ui, "Copy Engine 1 → 2", &theme).clicked() {
+                    app.copy_engine_settings(0, 1);
+                }
+                if custom_button(ui, "Copy Engine 2 → 1", &theme).clicked() {
+                    app.copy_engine_settings(1, 0);
+                }
             });
             ui.separator();
 
@@ -199,6 +233,7 @@ fn draw_engine_panel(app: &mut CypherApp, ui: &mut Ui, engine_index: usize) {
                         let mut vol = state.volume.load(Ordering::Relaxed) as f32 / 1_000_000.0;
                         if ui.add(
                             Slider::new(&mut vol, 0.0..=1.5)
+                                .custom_formatter(|v, _| format!("{} dB", format_db(v as f32)))
                                 .text(RichText::new(format!("Vol E{}", engine_index)).color(theme.label_color)),
                         )
                             .changed()
@@ -207,6 +242,22 @@ fn draw_engine_panel(app: &mut CypherApp, ui: &mut Ui, engine_index: usize) {
                                 .volume
                                 .store((vol * 1_000_000.0) as u32, Ordering::Relaxed);
                         }
+                        if ui
+                            .add_enabled(
+                                !state.is_polyphonic,
+                                Slider::new(&mut state.glide_time_ms, 0.0..=2000.0)
+                                    .logarithmic(true)
+                                    .suffix(" ms")
+                                    .text(RichText::new("Glide").color(theme.label_color)),
+                            )
+                            .on_disabled_hover_text("Glide is only available in mono mode")
+                            .changed()
+                        {
+                            command_to_send = Some(AudioCommand::SetGlideTime(
+                                engine_index,
+                                state.glide_time_ms,
+                            ));
+                        }
                     });
                 }
                 EngineState::Sampler(state) => {
@@ -228,6 +279,7 @@ fn draw_engine_panel(app: &mut CypherApp, ui: &mut Ui, engine_index: usize) {
                         let mut vol = state.volume.load(Ordering::Relaxed) as f32 / 1_000_000.0;
                         if ui.add(
                             Slider::new(&mut vol, 0.0..=1.5)
+                                .custom_formatter(|v, _| format!("{} dB", format_db(v as f32)))
                                 .text(RichText::new(format!("Vol E{}", engine_index)).color(theme.label_color)),
                         )
                             .changed()
@@ -236,6 +288,22 @@ fn draw_engine_panel(app: &mut CypherApp, ui: &mut Ui, engine_index: usize) {
                                 .volume
                                 .store((vol * 1_000_000.0) as u32, Ordering::Relaxed);
                         }
+                        if ui
+                            .add_enabled(
+                                !state.is_polyphonic,
+                                Slider::new(&mut state.glide_time_ms, 0.0..=2000.0)
+                                    .logarithmic(true)
+                                    .suffix(" ms")
+                                    .text(RichText::new("Glide").color(theme.label_color)),
+                            )
+                            .on_disabled_hover_text("Glide is only available in mono mode")
+                            .changed()
+                        {
+                            command_to_send = Some(AudioCommand::SetGlideTime(
+                                engine_index,
+                                state.glide_time_ms,
+                            ));
+                        }
                     });
                 }
             }
@@ -253,7 +321,7 @@ fn draw_engine_panel(app: &mut CypherApp, ui: &mut Ui, engine_index: usize) {
         ui.add(
             ProgressBar::new(peak)
                 .desired_height(4.0)
-                .fill(app.theme.top_bar.transport_bar_fill),
+                .fill(app.theme.synth_editor_window.peak_meter_color),
         );
 
         // --- Visualizer ---
@@ -271,6 +339,7 @@ fn draw_engine_panel(app: &mut CypherApp, ui: &mut Ui, engine_index: usize) {
                     draw_wavetable_preview(app, ui, rect, engine_index);
                 } else {
                     draw_sampler_waveform_preview(app, ui, rect, engine_index);
+                    draw_sampler_loop_overlay(app, ui, rect, engine_index);
                 }
             }
         });
@@ -335,7 +404,7 @@ fn draw_engine_panel(app: &mut CypherApp, ui: &mut Ui, engine_index: usize) {
 }
 
 fn draw_wavetable_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize) {
-    let mut sample_to_load: Option<(usize, PathBuf)> = None;
+    let mut samples_to_load: Vec<(usize, PathBuf)> = Vec::new();
     let theme = app.theme.synth_editor_window.clone();
     let mut changed = false;
     let mut reset_to_default = false; // New flag
@@ -382,7 +451,7 @@ fn draw_wavetable_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize
             ui.add_space(4.0);
         });
 
-        for i in 0..4 {
+        for i in 0..NUM_WAVETABLE_SLOTS {
             let frame = Frame::new().fill(frame_fill);
             let group_response = frame.show(ui, |ui| {
                 ui.set_min_width(ui.available_width() - 10.0);
@@ -430,7 +499,9 @@ fn draw_wavetable_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize
             });
             let drop_target_rect = group_response.response.rect;
             let is_hovered = ui.rect_contains_pointer(drop_target_rect);
-            if is_hovered && DragAndDrop::has_any_payload(ui.ctx()) {
+            if is_hovered
+                && (DragAndDrop::has_any_payload(ui.ctx()) || asset::is_os_file_hovering(ui.ctx()))
+            {
                 ui.painter().rect_stroke(
                     drop_target_rect,
                     CornerRadius::ZERO,
@@ -441,10 +512,20 @@ fn draw_wavetable_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize
             if is_hovered && ui.input(|i| i.pointer.any_released()) {
                 if let Some(payload) = DragAndDrop::take_payload::<Asset>(ui.ctx()) {
                     if let Asset::Sample(sample_ref) = (*payload).clone() {
-                        sample_to_load = Some((i, sample_ref.path));
+                        samples_to_load.push((i, sample_ref.path));
                     }
                 }
             }
+            if is_hovered {
+                // Several files dropped at once fill this slot and the ones after it.
+                for (offset, path) in asset::take_dropped_sample_files(ui.ctx())
+                    .into_iter()
+                    .take(NUM_WAVETABLE_SLOTS - i)
+                    .enumerate()
+                {
+                    samples_to_load.push((i + offset, path));
+                }
+            }
             ui.add_space(4.0);
         }
 
@@ -472,6 +553,22 @@ fn draw_wavetable_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize
                 ui.separator();
                 changed |= ui.add(Slider::new(&mut mixer.layer_volumes[4], 0.0..=1.0).text(RichText::new("Blend").color(theme.label_color))).changed();
             }
+            ui.separator();
+            if let Ok(mut unison) = state.unison_settings.write() {
+                let poly = state.is_polyphonic;
+                ui.add_enabled(
+                    poly,
+                    Slider::new(&mut unison.voices, 1..=MAX_UNISON_VOICES)
+                        .text(RichText::new("Unison").color(theme.label_color)),
+                )
+                .on_disabled_hover_text("Unison is only available in poly mode");
+                ui.add_enabled(
+                    poly && unison.voices > 1,
+                    Slider::new(&mut unison.detune_cents, 0.0..=100.0)
+                        .suffix(" ct")
+                        .text(RichText::new("Detune").color(theme.label_color)),
+                );
+            }
         });
         ui.add_space(8.0);
 
@@ -505,7 +602,7 @@ fn draw_wavetable_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize
         }
     }
 
-    if let Some((slot_index, path)) = sample_to_load {
+    for (slot_index, path) in samples_to_load {
         app.load_wav_for_synth_slot(engine_index, slot_index, path);
         if let EngineState::Wavetable(state) = &mut app.engine_states[engine_index] {
             state.force_redraw_generation += 1;
@@ -516,8 +613,11 @@ fn draw_wavetable_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize
 fn draw_sampler_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize) {
     let mut command_to_send: Option<AudioCommand> = None;
     let theme = app.theme.synth_editor_window.clone();
-    let mut sample_to_load: Option<(usize, PathBuf)> = None;
+    let mut samples_to_load: Vec<(usize, PathBuf)> = Vec::new();
     let mut slot_to_clear: Option<usize> = None;
+    let mut round_robin_to_add: Option<(usize, PathBuf)> = None;
+    let mut loop_region_changed: Option<(usize, SampleLoopRegion)> = None;
+    let mut velocity_zones_changed: Option<Vec<VelocityZone>> = None;
 
     // Helper function to convert MIDI note number to a name (e.g., 60 -> "C4")
     fn midi_to_note_name(note: u8) -> String {
@@ -548,11 +648,31 @@ fn draw_sampler_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize)
                             .monospace()
                             .color(app.theme.synth_editor_window.wt_slot_name_color),
                     );
+                    let extra_takes = state.round_robin_paths[i].len();
+                    if extra_takes > 0 {
+                        ui.label(
+                            RichText::new(format!("RR x{}", extra_takes + 1))
+                                .monospace()
+                                .color(theme.label_color),
+                        );
+                    }
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                         if state.sample_paths[i].is_some() {
                             if ui.add(Button::new("Clear").small().fill(theme.button_bg)).clicked() {
                                 slot_to_clear = Some(i);
                             }
+                            if ui
+                                .add(Button::new("+ Take").small().fill(theme.button_bg))
+                                .on_hover_text("Add a round-robin take to this slot")
+                                .clicked()
+                            {
+                                if let Some(path) = rfd::FileDialog::new()
+                                    .add_filter("Audio", &crate::asset::SAMPLE_FILE_EXTENSIONS)
+                                    .pick_file()
+                                {
+                                    round_robin_to_add = Some((i, path));
+                                }
+                            }
                         }
                     });
                 });
@@ -568,6 +688,19 @@ fn draw_sampler_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize)
                         settings_changed = true;
                     }
                     ui.monospace(midi_to_note_name(state.root_notes[i]));
+                    let region = &mut state.loop_regions[i];
+                    if ui
+                        .checkbox(&mut region.enabled, RichText::new("Loop").color(theme.label_color))
+                        .on_hover_text("Loop between the loop points while the note is held")
+                        .changed()
+                    {
+                        // Start with the whole sample; the points are dragged on the waveform.
+                        if region.enabled && region.end <= region.start {
+                            region.start = 0;
+                            region.end = state.sample_data_for_ui[i].read().unwrap().len();
+                        }
+                        loop_region_changed = Some((i, *region));
+                    }
                 });
             });
 
@@ -575,7 +708,7 @@ fn draw_sampler_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize)
             let is_hovered = ui.rect_contains_pointer(drop_target_rect);
 
             if is_hovered {
-                if DragAndDrop::has_any_payload(ui.ctx()) {
+                if DragAndDrop::has_any_payload(ui.ctx()) || asset::is_os_file_hovering(ui.ctx()) {
                     ui.painter().rect_stroke(
                         drop_target_rect,
                         CornerRadius::ZERO,
@@ -586,10 +719,17 @@ fn draw_sampler_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize)
                 if ui.input(|inp| inp.pointer.any_released()) {
                     if let Some(payload) = DragAndDrop::take_payload::<Asset>(ui.ctx()) {
                         if let Asset::Sample(sample_ref) = (*payload).clone() {
-                            sample_to_load = Some((i, sample_ref.path));
+                            samples_to_load.push((i, sample_ref.path));
                         }
                     }
                 }
+                for (offset, path) in asset::take_dropped_sample_files(ui.ctx())
+                    .into_iter()
+                    .take(NUM_SAMPLE_SLOTS - i)
+                    .enumerate()
+                {
+                    samples_to_load.push((i + offset, path));
+                }
             }
             ui.add_space(4.0);
         }
@@ -627,6 +767,58 @@ fn draw_sampler_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize)
             }
         });
 
+        ui.separator();
+        ui.add_space(4.0);
+        ui.label(RichText::new("Velocity Zones").color(theme.label_color)).on_hover_text(
+            "Shape the velocity response per key range. The first matching zone wins.",
+        );
+        let mut zones_changed = false;
+        let mut zone_to_remove = None;
+        for (z, zone) in state.velocity_zones.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                let note_formatter = |v: f64, _| midi_to_note_name(v as u8);
+                zones_changed |= ui
+                    .add(
+                        DragValue::new(&mut zone.low_note)
+                            .range(0..=127)
+                            .custom_formatter(note_formatter),
+                    )
+                    .changed();
+                ui.label(RichText::new("to").color(theme.label_color));
+                zones_changed |= ui
+                    .add(
+                        DragValue::new(&mut zone.high_note)
+                            .range(0..=127)
+                            .custom_formatter(note_formatter),
+                    )
+                    .changed();
+                zone.high_note = zone.high_note.max(zone.low_note);
+                zones_changed |= ui
+                    .add(
+                        DragValue::new(&mut zone.curve)
+                            .range(VelocityZone::MIN_CURVE..=VelocityZone::MAX_CURVE)
+                            .speed(0.01)
+                            .prefix("Curve "),
+                    )
+                    .on_hover_text("Above 1 responds softer, below 1 harder")
+                    .changed();
+                if ui.add(Button::new("x").small().fill(theme.button_bg)).clicked() {
+                    zone_to_remove = Some(z);
+                }
+            });
+        }
+        if let Some(z) = zone_to_remove {
+            state.velocity_zones.remove(z);
+            zones_changed = true;
+        }
+        if ui.add(Button::new("+ Zone").small().fill(theme.button_bg)).clicked() {
+            state.velocity_zones.push(VelocityZone::default());
+            zones_changed = true;
+        }
+        if zones_changed {
+            velocity_zones_changed = Some(state.velocity_zones.clone());
+        }
+
         if settings_changed {
             command_to_send = Some(AudioCommand::SetSamplerSettings {
                 engine_index,
@@ -637,15 +829,31 @@ fn draw_sampler_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize)
         }
     }
 
+    if let Some((slot_index, path)) = round_robin_to_add {
+        app.append_round_robin_sample(engine_index, slot_index, path);
+    }
     if let Some(slot_idx) = slot_to_clear {
         app.clear_sample_for_sampler_slot(engine_index, slot_idx);
     }
-    if let Some((slot_index, path)) = sample_to_load {
+    for (slot_index, path) in samples_to_load {
         app.load_sample_for_sampler_slot(engine_index, slot_index, path);
     }
     if let Some(cmd) = command_to_send {
         app.send_command(cmd);
     }
+    if let Some((slot_index, region)) = loop_region_changed {
+        app.send_command(AudioCommand::SetSamplerLoopRegion {
+            engine_index,
+            slot_index,
+            region,
+        });
+    }
+    if let Some(zones) = velocity_zones_changed {
+        app.send_command(AudioCommand::SetSamplerVelocityZones {
+            engine_index,
+            zones,
+        });
+    }
 }
 
 fn draw_saturation_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize) {
@@ -903,6 +1111,30 @@ fn draw_amp_env_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize)
     if changed {
         app.send_command(AudioCommand::SetAmpAdsr(engine_index, amp_adsr));
     }
+
+    let sensitivity = match &mut app.engine_states[engine_index] {
+        EngineState::Wavetable(state) => &mut state.velocity_sensitivity,
+        EngineState::Sampler(state) => &mut state.velocity_sensitivity,
+    };
+    let velocity_changed = ui
+        .scope(|ui| {
+            let visuals = &mut ui.style_mut().visuals.widgets;
+            visuals.inactive.bg_fill = theme.slider_track_color;
+            visuals.hovered.bg_fill = theme.slider_grab_hover_color;
+            visuals.active.bg_fill = theme.slider_grab_color;
+            ui.style_mut().visuals.slider_trailing_fill = true;
+            ui.add(
+                Slider::new(sensitivity, 0.0..=1.0)
+                    .text(RichText::new("Velocity").color(theme.label_color)),
+            )
+            .on_hover_text("How strongly note velocity affects loudness")
+            .changed()
+        })
+        .inner;
+    if velocity_changed {
+        let value = *sensitivity;
+        app.send_command(AudioCommand::SetVelocitySensitivity(engine_index, value));
+    }
 }
 
 fn draw_filter_env_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize) {
@@ -1086,6 +1318,29 @@ fn draw_lfo_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usize, lfo_
                 }
             });
         });
+        ui.horizontal(|ui| {
+            ui.scope(|ui| {
+                let visuals = &mut ui.style_mut().visuals.widgets;
+                visuals.inactive.bg_fill = theme.control_bg;
+                visuals.hovered.bg_fill = theme.control_hover_bg;
+                visuals.active.bg_fill = theme.control_hover_bg;
+
+                ui.label(RichText::new("Phase:").color(theme.label_color));
+                changed |= ui
+                    .add(DragValue::new(&mut lfo.phase_offset).speed(0.005).range(0.0..=1.0))
+                    .changed();
+                ui.label(RichText::new("Fade In:").color(theme.label_color));
+                changed |= ui
+                    .add(
+                        DragValue::new(&mut lfo.fade_in_ms)
+                            .speed(10.0)
+                            .range(0.0..=10_000.0)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text("Fades this LFO's routings in after a retrigger")
+                    .changed();
+            });
+        });
     };
     match &mut app.engine_states[engine_index] {
         EngineState::Wavetable(s) => {
@@ -1218,8 +1473,28 @@ fn draw_mod_matrix_controls(app: &mut CypherApp, ui: &mut Ui, engine_index: usiz
                     visuals.active.bg_fill = theme.slider_grab_color;
 
                     ui.add(Slider::new(&mut routing.amount, -1.0..=1.0).text(RichText::new("Amount").color(theme.label_color)));
+                    ui.add(Slider::new(&mut routing.offset, -1.0..=1.0).text(RichText::new("Offset").color(theme.label_color)));
                 });
 
+                ComboBox::new(format!("curve_{}_{}", engine_index, i), "")
+                    .selected_text(routing.curve.to_string())
+                    .show_ui(ui, |ui| {
+                        let style = ui.style_mut();
+                        style.visuals.panel_fill = theme.combo_popup_bg;
+                        style.visuals.selection.bg_fill = theme.combo_selection_bg;
+                        for curve in ModCurve::ALL {
+                            ui.selectable_value(&mut routing.curve, curve, curve.to_string());
+                        }
+                    });
+
+                if routing.source.is_bipolar() {
+                    ui.toggle_value(
+                        &mut routing.unipolar,
+                        RichText::new("Uni").color(theme.label_color),
+                    )
+                    .on_hover_text("Use the LFO as 0..1 instead of -1..1");
+                }
+
                 if custom_button(ui, "x", &theme).clicked() {
                     to_remove = Some(i);
                 }
@@ -1617,6 +1892,77 @@ fn draw_sampler_waveform_preview(app: &mut CypherApp, ui: &mut Ui, rect: Rect, e
     }
 }
 
+/// Draws the loop region of the displayed sample slot over the waveform preview, with
+/// handles that can be dragged to move the loop points.
+fn draw_sampler_loop_overlay(app: &mut CypherApp, ui: &mut Ui, rect: Rect, engine_index: usize) {
+    let EngineState::Sampler(engine_state) = &mut app.engine_states[engine_index] else {
+        return;
+    };
+    // Same slot choice as the waveform preview: the last triggered slot, else the first loaded.
+    let sample_len = |i: usize| engine_state.sample_data_for_ui[i].read().unwrap().len();
+    let last_triggered = engine_state.last_triggered_slot_index.load(Ordering::Relaxed);
+    let slot_index = if last_triggered < NUM_SAMPLE_SLOTS && sample_len(last_triggered) > 0 {
+        Some(last_triggered)
+    } else {
+        (0..NUM_SAMPLE_SLOTS).find(|&i| sample_len(i) > 0)
+    };
+    let Some(slot_index) = slot_index else {
+        return;
+    };
+    let len = sample_len(slot_index);
+    let mut region = engine_state.loop_regions[slot_index];
+    if !region.enabled || len < 2 {
+        return;
+    }
+
+    let theme = &app.theme.synth_editor_window;
+    let to_x = |idx: usize| rect.left() + (idx.min(len) as f32 / len as f32) * rect.width();
+    let to_idx = |x: f32| (((x - rect.left()) / rect.width()).clamp(0.0, 1.0) * len as f32) as usize;
+
+    let painter = ui.painter_at(rect);
+    let loop_color = theme.mod_pitch_color;
+    let (start_x, end_x) = (to_x(region.start), to_x(region.end));
+    painter.rect_filled(
+        Rect::from_x_y_ranges(start_x..=end_x, rect.y_range()),
+        CornerRadius::ZERO,
+        Color32::from_rgba_unmultiplied(loop_color.r(), loop_color.g(), loop_color.b(), 30),
+    );
+
+    let mut changed = false;
+    for (is_start, x) in [(true, start_x), (false, end_x)] {
+        painter.line_segment(
+            [pos2(x, rect.top()), pos2(x, rect.bottom())],
+            Stroke::new(2.0, loop_color),
+        );
+        let handle_rect = Rect::from_center_size(pos2(x, rect.center().y), Vec2::new(8.0, rect.height()));
+        let id = ui.id().with(("sampler_loop_handle", engine_index, slot_index, is_start));
+        let response = ui.interact(handle_rect, id, Sense::drag());
+        if response.hovered() || response.dragged() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+        }
+        if response.dragged() {
+            if let Some(pointer) = response.interact_pointer_pos() {
+                let idx = to_idx(pointer.x);
+                if is_start {
+                    region.start = idx.min(region.end.saturating_sub(1));
+                } else {
+                    region.end = idx.max(region.start + 1).min(len);
+                }
+                changed = true;
+            }
+        }
+    }
+
+    if changed {
+        engine_state.loop_regions[slot_index] = region;
+        app.send_command(AudioCommand::SetSamplerLoopRegion {
+            engine_index,
+            slot_index,
+            region,
+        });
+    }
+}
+
 fn get_waveform_sample(
     wavetable_set: &Arc<RwLock<WavetableSet>>,
     table_idx: usize,
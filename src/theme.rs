@@ -21,6 +21,9 @@ fn default_top_bar_transport_background() -> Color32 { Color32::from_rgba_unmult
 fn default_top_bar_xrun_text() -> Color32 { Color32::from_rgba_unmultiplied(255, 0, 0, 255) }
 fn default_top_bar_session_button_bg() -> Color32 { Color32::from_rgba_unmultiplied(98, 0, 83, 255) }
 fn default_top_bar_session_save_as_bg() -> Color32 { Color32::from_rgba_unmultiplied(51, 0, 111, 255) }
+fn default_top_bar_cpu_ok() -> Color32 { Color32::from_rgba_unmultiplied(0, 200, 80, 255) }
+fn default_top_bar_cpu_warning() -> Color32 { Color32::from_rgba_unmultiplied(240, 200, 0, 255) }
+fn default_top_bar_cpu_critical() -> Color32 { Color32::from_rgba_unmultiplied(255, 0, 0, 255) }
 
 // Instrument Panel
 fn default_instrument_panel_bg() -> Color32 { Color32::from_rgba_unmultiplied(0, 5, 23, 255) }
@@ -50,6 +53,7 @@ fn default_looper_overdubbing_bg() -> Color32 { Color32::from_rgba_unmultiplied(
 fn default_looper_progress_bar_bg() -> Color32 { Color32::from_rgba_unmultiplied(20, 0, 78, 255) }
 fn default_looper_clear_button_bg() -> Color32 { Color32::from_rgba_unmultiplied(13, 0, 25, 255) }
 fn default_looper_text_color() -> Color32 { Color32::from_rgba_unmultiplied(255, 255, 255, 255) }
+fn default_looper_playhead_color() -> Color32 { Color32::from_rgba_unmultiplied(230, 230, 230, 255) }
 fn default_looper_track_colors() -> [Color32; NUM_LOOPERS] {
     [
         Color32::from_rgba_unmultiplied(255, 0, 79, 255),
@@ -143,6 +147,7 @@ fn default_synth_editor_mod_pitch_color() -> Color32 { Color32::from_rgba_unmult
 fn default_synth_editor_mod_filter_color() -> Color32 { Color32::from_rgba_unmultiplied(255, 163, 0, 255) }
 fn default_synth_editor_mod_amp_cold_color() -> Color32 { Color32::from_rgba_unmultiplied(0, 180, 255, 255) }
 fn default_synth_editor_mod_amp_hot_color() -> Color32 { Color32::from_rgba_unmultiplied(255, 140, 0, 255) }
+fn default_synth_editor_peak_meter_color() -> Color32 { Color32::from_rgba_unmultiplied(0, 25, 25, 255) }
 
 // Piano Keys
 fn default_piano_white_key_color() -> Color32 { Color32::from_rgba_unmultiplied(240, 240, 240, 255) }
@@ -201,8 +206,11 @@ pub struct TopBarTheme {
     #[serde(default = "default_top_bar_xrun_text")] pub xrun_text_color: Color32,
     #[serde(default = "default_top_bar_session_button_bg")] pub session_button_bg: Color32,
     #[serde(default = "default_top_bar_session_save_as_bg")] pub session_save_as_button_bg: Color32,
+    #[serde(default = "default_top_bar_cpu_ok")] pub cpu_ok_color: Color32,
+    #[serde(default = "default_top_bar_cpu_warning")] pub cpu_warning_color: Color32,
+    #[serde(default = "default_top_bar_cpu_critical")] pub cpu_critical_color: Color32,
 }
-impl Default for TopBarTheme { fn default() -> Self { Self { background: default_top_bar_background(), button_bg: default_top_bar_button_bg(), text_color: default_top_bar_text_color(), separator_color: default_top_bar_separator_color(), transport_bar_fill: default_top_bar_transport_fill(), transport_bar_background: default_top_bar_transport_background(), xrun_text_color: default_top_bar_xrun_text(), session_button_bg: default_top_bar_session_button_bg(), session_save_as_button_bg: default_top_bar_session_save_as_bg() } } }
+impl Default for TopBarTheme { fn default() -> Self { Self { background: default_top_bar_background(), button_bg: default_top_bar_button_bg(), text_color: default_top_bar_text_color(), separator_color: default_top_bar_separator_color(), transport_bar_fill: default_top_bar_transport_fill(), transport_bar_background: default_top_bar_transport_background(), xrun_text_color: default_top_bar_xrun_text(), session_button_bg: default_top_bar_session_button_bg(), session_save_as_button_bg: default_top_bar_session_save_as_bg(), cpu_ok_color: default_top_bar_cpu_ok(), cpu_warning_color: default_top_bar_cpu_warning(), cpu_critical_color: default_top_bar_cpu_critical() } } }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(default)]
@@ -242,9 +250,10 @@ pub struct LooperTheme {
     #[serde(default = "default_looper_progress_bar_bg")] pub progress_bar_bg: Color32,
     #[serde(default = "default_looper_clear_button_bg")] pub clear_button_bg: Color32,
     #[serde(default = "default_looper_text_color")] pub text_color: Color32,
+    #[serde(default = "default_looper_playhead_color")] pub playhead_color: Color32,
     #[serde(default = "default_looper_track_colors")] pub track_colors: [Color32; NUM_LOOPERS],
 }
-impl Default for LooperTheme { fn default() -> Self { Self { empty_bg: default_looper_empty_bg(), armed_bg: default_looper_armed_bg(), recording_bg: default_looper_recording_bg(), overdubbing_bg: default_looper_overdubbing_bg(), progress_bar_bg: default_looper_progress_bar_bg(), clear_button_bg: default_looper_clear_button_bg(), text_color: default_looper_text_color(), track_colors: default_looper_track_colors() } } }
+impl Default for LooperTheme { fn default() -> Self { Self { empty_bg: default_looper_empty_bg(), armed_bg: default_looper_armed_bg(), recording_bg: default_looper_recording_bg(), overdubbing_bg: default_looper_overdubbing_bg(), progress_bar_bg: default_looper_progress_bar_bg(), clear_button_bg: default_looper_clear_button_bg(), text_color: default_looper_text_color(), playhead_color: default_looper_playhead_color(), track_colors: default_looper_track_colors() } } }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(default)]
@@ -340,6 +349,7 @@ pub struct SynthEditorTheme {
     #[serde(default = "default_synth_editor_mod_filter_color")] pub mod_filter_color: Color32,
     #[serde(default = "default_synth_editor_mod_amp_cold_color")] pub mod_amp_cold_color: Color32,
     #[serde(default = "default_synth_editor_mod_amp_hot_color")] pub mod_amp_hot_color: Color32,
+    #[serde(default = "default_synth_editor_peak_meter_color")] pub peak_meter_color: Color32,
 }
 impl Default for SynthEditorTheme { fn default() -> Self { Self {
     background: default_synth_editor_bg(),
@@ -370,6 +380,7 @@ impl Default for SynthEditorTheme { fn default() -> Self { Self {
     mod_filter_color: default_synth_editor_mod_filter_color(),
     mod_amp_cold_color: default_synth_editor_mod_amp_cold_color(),
     mod_amp_hot_color: default_synth_editor_mod_amp_hot_color(),
+    peak_meter_color: default_synth_editor_peak_meter_color(),
 } } }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -497,4 +508,32 @@ impl From<&Theme> for Visuals {
 
         visuals
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_meter_and_playhead_colors_round_trip_and_default_when_missing() {
+        let mut theme = Theme::default();
+        theme.loopers.playhead_color = Color32::from_rgb(1, 2, 3);
+        theme.synth_editor_window.peak_meter_color = Color32::from_rgb(4, 5, 6);
+
+        let json = serde_json::to_string(&theme).unwrap();
+        let loaded: Theme = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.loopers.playhead_color, Color32::from_rgb(1, 2, 3));
+        assert_eq!(loaded.synth_editor_window.peak_meter_color, Color32::from_rgb(4, 5, 6));
+
+        // A theme saved before these colors existed.
+        let mut old: serde_json::Value = serde_json::from_str(&json).unwrap();
+        old["loopers"].as_object_mut().unwrap().remove("playhead_color");
+        old["synth_editor_window"].as_object_mut().unwrap().remove("peak_meter_color");
+        let loaded: Theme = serde_json::from_value(old).unwrap();
+        assert_eq!(loaded.loopers.playhead_color, default_looper_playhead_color());
+        assert_eq!(
+            loaded.synth_editor_window.peak_meter_color,
+            default_synth_editor_peak_meter_color()
+        );
+    }
 }
\ No newline at end of file
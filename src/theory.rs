@@ -84,12 +84,92 @@ impl std::fmt::Display for Scale {
     }
 }
 
+/// A scale loaded from a JSON file in the `Scales` config folder.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct UserScale {
+    pub name: String,
+    /// Semitones above the root. Normalized on load by `UserScale::normalized`.
+    pub intervals: Vec<u8>,
+}
+
+impl UserScale {
+    /// Folds the intervals into one octave, sorts them and makes sure the root is included,
+    /// so a hand-written file with e.g. `[12, 3, 7]` still makes a usable scale.
+    pub fn normalized(mut self) -> Self {
+        self.intervals.push(0);
+        for interval in self.intervals.iter_mut() {
+            *interval %= 12;
+        }
+        self.intervals.sort_unstable();
+        self.intervals.dedup();
+        self
+    }
+}
+
+/// Names of the twelve pitch classes, starting from C.
+pub const PITCH_CLASS_NAMES: [&str; 12] =
+    ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// Every (root pitch class, scale) that contains all of the held notes' pitch classes,
+/// best match first. Scales rooted on the lowest held note come first, then scales in
+/// `Scale::ALL` order, so common diatonic modes beat pentatonics and the chromatic
+/// scale only appears as a last resort.
+pub fn scales_containing(notes: &BTreeSet<u8>) -> Vec<(u8, Scale)> {
+    let Some(&lowest) = notes.iter().next() else {
+        return Vec::new();
+    };
+    let held_mask = notes.iter().fold(0u16, |mask, &n| mask | 1 << (n % 12));
+    let mut matches: Vec<(u8, Scale)> = (0..12u8)
+        .flat_map(|root| Scale::ALL.iter().map(move |&scale| (root, scale)))
+        .filter(|&(root, scale)| {
+            let scale_mask = scale
+                .get_intervals()
+                .iter()
+                .fold(0u16, |mask, &i| mask | 1 << ((root + i) % 12));
+            held_mask & !scale_mask == 0
+        })
+        .collect();
+    matches.sort_by_key(|&(root, scale)| {
+        let scale_rank = Scale::ALL.iter().position(|&s| s == scale).unwrap_or(usize::MAX);
+        (root != lowest % 12, scale_rank, root)
+    });
+    matches
+}
+
+/// Bitmask of the pitch classes (bit 0 = C) of a scale built on `key_root`.
+pub fn scale_pitch_class_mask(key_root: u8, intervals: &[u8]) -> u16 {
+    notes_from_intervals(key_root % 12, intervals)
+        .iter()
+        .fold(0u16, |mask, &note| mask | 1 << (note % 12))
+}
+
+/// The note nearest to `note` whose pitch class is in `mask`; ties snap down. An empty
+/// mask leaves the note alone.
+pub fn quantize_to_scale(note: u8, mask: u16) -> u8 {
+    if mask == 0 {
+        return note;
+    }
+    (0..12i16)
+        .flat_map(|distance| [note as i16 - distance, note as i16 + distance])
+        .filter(|candidate| (0..=127).contains(candidate))
+        .find(|&candidate| mask & 1 << (candidate % 12) != 0)
+        .map_or(note, |candidate| candidate as u8)
+}
+
 /// Generates a vector of MIDI note numbers for a given scale and root note.
 pub fn get_scale_notes(root_note: u8, scale: Scale) -> Vec<u8> {
-    let intervals = scale.get_intervals();
+    notes_from_intervals(root_note, scale.get_intervals())
+}
+
+/// MIDI notes for any interval list (built-in or user scale) above `root_note`, dropping
+/// anything past the top of the MIDI range. Scales can have any number of notes.
+pub fn notes_from_intervals(root_note: u8, intervals: &[u8]) -> Vec<u8> {
     intervals
         .iter()
-        .map(|&interval| root_note + interval)
+        .map(|&interval| root_note as u16 + interval as u16)
+        .filter(|&note| note <= 127)
+        .map(|note| note as u8)
         .collect()
 }
 
@@ -103,18 +183,41 @@ pub enum ChordQuality {
     DominantSeventh,
     MajorSeventh,
     MinorSeventh,
+    HalfDiminishedSeventh,
+    DiminishedSeventh,
+    Sus2,
+    Sus4,
 }
 
 impl ChordQuality {
+    /// Every chord quality, in the order the chord style editor lists them.
+    pub const ALL: [ChordQuality; 11] = [
+        ChordQuality::MajorTriad,
+        ChordQuality::MinorTriad,
+        ChordQuality::DiminishedTriad,
+        ChordQuality::AugmentedTriad,
+        ChordQuality::Sus2,
+        ChordQuality::Sus4,
+        ChordQuality::DominantSeventh,
+        ChordQuality::MajorSeventh,
+        ChordQuality::MinorSeventh,
+        ChordQuality::HalfDiminishedSeventh,
+        ChordQuality::DiminishedSeventh,
+    ];
+
     /// An array of all chord qualities we can currently recognize.
-    const RECOGNIZABLE: [ChordQuality; 7] = [
+    const RECOGNIZABLE: [ChordQuality; 11] = [
         ChordQuality::MajorTriad,
         ChordQuality::MinorTriad,
         ChordQuality::DominantSeventh,
         ChordQuality::MajorSeventh,
         ChordQuality::MinorSeventh,
+        ChordQuality::HalfDiminishedSeventh,
+        ChordQuality::DiminishedSeventh,
         ChordQuality::DiminishedTriad,
         ChordQuality::AugmentedTriad,
+        ChordQuality::Sus2,
+        ChordQuality::Sus4,
     ];
 
     /// Returns the interval pattern (in semitones from the root) for the chord quality.
@@ -127,10 +230,33 @@ impl ChordQuality {
             ChordQuality::DominantSeventh => &[0, 4, 7, 10],
             ChordQuality::MajorSeventh => &[0, 4, 7, 11],
             ChordQuality::MinorSeventh => &[0, 3, 7, 10],
+            ChordQuality::HalfDiminishedSeventh => &[0, 3, 6, 10],
+            ChordQuality::DiminishedSeventh => &[0, 3, 6, 9],
+            ChordQuality::Sus2 => &[0, 2, 7],
+            ChordQuality::Sus4 => &[0, 5, 7],
         }
     }
 }
 
+impl std::fmt::Display for ChordQuality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ChordQuality::MajorTriad => "Major",
+            ChordQuality::MinorTriad => "Minor",
+            ChordQuality::DiminishedTriad => "Diminished",
+            ChordQuality::AugmentedTriad => "Augmented",
+            ChordQuality::DominantSeventh => "Dominant 7th",
+            ChordQuality::MajorSeventh => "Major 7th",
+            ChordQuality::MinorSeventh => "Minor 7th",
+            ChordQuality::HalfDiminishedSeventh => "Minor 7th b5",
+            ChordQuality::DiminishedSeventh => "Diminished 7th",
+            ChordQuality::Sus2 => "Sus2",
+            ChordQuality::Sus4 => "Sus4",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 /// Represents a recognized chord, with its root and quality.
 #[derive(Debug, Clone)]
 pub struct Chord {
@@ -140,11 +266,17 @@ pub struct Chord {
 
 /// Attempts to recognize a chord from a set of played MIDI notes.
 /// Handles inversions by checking all possible rotations of the notes.
+///
+/// A quality fits when all of its notes are held, with at most one extra pitch class
+/// (a 9th over a seventh chord, say). The richest fit wins: more chord tones first, then
+/// a root on the lowest held note, then an exact fit over one with an extra note.
 pub fn recognize_chord(notes: &BTreeSet<u8>) -> Option<Chord> {
     if notes.len() < 3 {
         return None;
     }
+    let lowest = *notes.iter().next()?;
 
+    let mut best: Option<(Chord, (usize, bool, bool))> = None;
     // Iterate through each note in the set as a potential root.
     for &potential_root in notes {
         let intervals: BTreeSet<u8> = notes
@@ -152,25 +284,33 @@ pub fn recognize_chord(notes: &BTreeSet<u8>) -> Option<Chord> {
             .map(|&note| (note as i16 - potential_root as i16).rem_euclid(12) as u8)
             .collect();
 
-        // The root note itself should have an interval of 0.
-        if !intervals.contains(&0) {
-            continue;
-        }
-
-        // Check if the derived intervals match any known chord quality.
+        // Check which known chord qualities the derived intervals contain.
         for quality in ChordQuality::RECOGNIZABLE {
             let quality_intervals: BTreeSet<u8> =
                 quality.get_intervals().iter().copied().collect();
-            if intervals == quality_intervals {
-                return Some(Chord {
-                    root: potential_root,
-                    quality,
-                });
+            if !quality_intervals.is_subset(&intervals)
+                || intervals.len() > quality_intervals.len() + 1
+            {
+                continue;
+            }
+            let score = (
+                quality_intervals.len(),
+                potential_root % 12 == lowest % 12,
+                intervals.len() == quality_intervals.len(),
+            );
+            if best.as_ref().is_none_or(|(_, best_score)| score > *best_score) {
+                best = Some((
+                    Chord {
+                        root: potential_root,
+                        quality,
+                    },
+                    score,
+                ));
             }
         }
     }
 
-    None
+    best.map(|(chord, _)| chord)
 }
 
 /// A structure deserialized from JSON that defines the "flavor" of chord suggestions.
@@ -182,10 +322,38 @@ pub struct ChordStyle {
     pub suggestions: BTreeMap<String, ChordQuality>,
 }
 
+/// The harmonic functions `get_chord_suggestions` looks up in a `ChordStyle`, with the
+/// label the chord style editor shows for each.
+pub const CHORD_FUNCTIONS: [(&str, &str); 5] = [
+    ("dominant", "Dominant (V)"),
+    ("subdominant", "Subdominant (IV)"),
+    ("relative_minor", "Relative Minor (vi)"),
+    ("relative_major", "Relative Major (bIII)"),
+    ("dominant_of_dominant", "Secondary Dominant (V/V)"),
+];
+
+impl ChordStyle {
+    /// Keys from `CHORD_FUNCTIONS` this style has no chord quality for.
+    pub fn missing_functions(&self) -> Vec<&'static str> {
+        CHORD_FUNCTIONS
+            .iter()
+            .map(|&(key, _)| key)
+            .filter(|key| !self.suggestions.contains_key(*key))
+            .collect()
+    }
+}
+
 
 /// Builds a vector of MIDI notes for a given root and chord quality, starting in a specific octave.
+/// `inversion` moves that many of the lowest chord tones up an octave (1 = first inversion);
+/// it is capped at the chord's last tone, so a triad has no third inversion.
 /// Includes logic to shift the chord by an octave if it would go off the ends of an 88-key piano.
-pub fn build_chord_notes(root: u8, quality: ChordQuality, start_octave: u8) -> Vec<u8> {
+pub fn build_chord_notes(
+    root: u8,
+    quality: ChordQuality,
+    start_octave: u8,
+    inversion: usize,
+) -> Vec<u8> {
     const FIRST_KEY: u8 = 21; // A0
     const LAST_KEY: u8 = 108;  // C8
 
@@ -196,9 +364,11 @@ pub fn build_chord_notes(root: u8, quality: ChordQuality, start_octave: u8) -> V
     // Our octave numbers are 1-8, so we use them directly.
     let target_root = (root % 12) + (start_octave * 12);
 
+    let inversion = inversion.min(intervals.len() - 1);
     let mut notes: Vec<u8> = intervals
         .iter()
-        .map(|&interval| target_root + interval)
+        .enumerate()
+        .map(|(i, &interval)| target_root + interval + if i < inversion { 12 } else { 0 })
         .collect();
 
     // Check boundaries and shift the entire chord if necessary.
@@ -223,6 +393,40 @@ pub fn build_chord_notes(root: u8, quality: ChordQuality, start_octave: u8) -> V
     notes
 }
 
+/// The inversion of a chord that moves the least from `reference` (e.g. the held chord):
+/// the one whose notes sit closest, in total, to their nearest reference note. The
+/// reference is first moved by octaves so its lowest note lands in `start_octave`.
+pub fn closest_inversion(
+    root: u8,
+    quality: ChordQuality,
+    start_octave: u8,
+    reference: &BTreeSet<u8>,
+) -> usize {
+    let Some(&reference_lowest) = reference.iter().next() else {
+        return 0;
+    };
+    let shift = (start_octave as i32 * 12 + (reference_lowest % 12) as i32) - reference_lowest as i32;
+    let reference: Vec<i32> = reference.iter().map(|&n| n as i32 + shift).collect();
+
+    (0..quality.get_intervals().len())
+        .min_by_key(|&inversion| {
+            build_chord_notes(root, quality, start_octave, inversion)
+                .iter()
+                .map(|&note| {
+                    reference
+                        .iter()
+                        .map(|&r| (note as i32 - r).abs())
+                        .min()
+                        .unwrap_or(0)
+                })
+                .sum::<i32>()
+        })
+        .unwrap_or(0)
+}
+
+/// Most suggestions `get_chord_suggestions` returns; one per harmonic function it covers.
+pub const MAX_CHORD_SUGGESTIONS: usize = 4;
+
 /// Gets four harmonically related chord suggestions based on the Circle of Fifths.
 ///
 /// # Arguments
@@ -254,7 +458,11 @@ pub fn get_chord_suggestions(
     // 3. Relative Minor/Major
     let is_major = matches!(
         played_chord.quality,
-        ChordQuality::MajorTriad | ChordQuality::MajorSeventh | ChordQuality::DominantSeventh
+        ChordQuality::MajorTriad
+            | ChordQuality::MajorSeventh
+            | ChordQuality::DominantSeventh
+            | ChordQuality::Sus2
+            | ChordQuality::Sus4
     );
     if is_major {
         // Suggest the relative minor (vi)
@@ -277,4 +485,107 @@ pub fn get_chord_suggestions(
     }
 
     suggestions
+}
+
+/// Like `get_chord_suggestions`, but keeps every suggestion inside the scale with
+/// `scale_intervals` rooted on `key_root` (a pitch class). Suggestions whose root is in the scale but whose quality
+/// isn't are revoiced to the closest diatonic quality; the rest are dropped. If the played
+/// chord itself isn't diatonic, the unconstrained suggestions are returned instead.
+pub fn get_chord_suggestions_in_scale(
+    played_chord: &Chord,
+    style: &ChordStyle,
+    key_root: u8,
+    scale_intervals: &[u8],
+) -> Vec<(ChordQuality, u8)> {
+    let unconstrained = get_chord_suggestions(played_chord, style);
+    let scale_mask = scale_intervals
+        .iter()
+        .fold(0u16, |mask, &i| mask | 1 << ((key_root + i) % 12));
+    let fits = |quality: ChordQuality, root: u8| {
+        quality
+            .get_intervals()
+            .iter()
+            .all(|&i| scale_mask & 1 << ((root + i) % 12) != 0)
+    };
+
+    if !fits(played_chord.quality, played_chord.root) {
+        return unconstrained;
+    }
+
+    let constrained: Vec<(ChordQuality, u8)> = unconstrained
+        .iter()
+        .filter_map(|&(quality, root)| {
+            if fits(quality, root) {
+                return Some((quality, root));
+            }
+            // Prefer a chord of the same size (triad vs. seventh) before any that fits.
+            let size = quality.get_intervals().len();
+            ChordQuality::RECOGNIZABLE
+                .iter()
+                .filter(|q| q.get_intervals().len() == size)
+                .chain(ChordQuality::RECOGNIZABLE.iter())
+                .find(|&&q| fits(q, root))
+                .map(|&q| (q, root))
+        })
+        .collect();
+
+    if constrained.is_empty() {
+        unconstrained
+    } else {
+        constrained
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn c_major_triad_suggests_c_major_first() {
+        let held = BTreeSet::from([60, 64, 67]);
+        let scales = scales_containing(&held);
+        assert_eq!(scales[0], (0, Scale::Ionian));
+        for related in [(0, Scale::Lydian), (0, Scale::Mixolydian), (0, Scale::MajorPentatonic)] {
+            assert!(scales.contains(&related), "missing {related:?}");
+        }
+        // Scales on other roots that still hold C, E and G rank after every C-rooted one.
+        let first_other_root = scales.iter().position(|&(root, _)| root != 0).unwrap();
+        assert!(scales[first_other_root..].iter().all(|&(root, _)| root != 0));
+        assert!(scales.contains(&(7, Scale::Ionian)));
+        assert!(scales.contains(&(5, Scale::Ionian)));
+        assert!(!scales.contains(&(0, Scale::NaturalMinor)));
+        assert!(!scales.contains(&(2, Scale::Ionian)));
+    }
+
+    fn recognized(notes: &[u8]) -> Option<(u8, ChordQuality)> {
+        recognize_chord(&notes.iter().copied().collect())
+            .map(|chord| (chord.root % 12, chord.quality))
+    }
+
+    #[test]
+    fn new_chord_qualities_are_recognized_from_their_intervals() {
+        for quality in [
+            ChordQuality::HalfDiminishedSeventh,
+            ChordQuality::DiminishedSeventh,
+            ChordQuality::Sus2,
+            ChordQuality::Sus4,
+        ] {
+            let notes: Vec<u8> = quality.get_intervals().iter().map(|i| 62 + i).collect();
+            assert_eq!(recognized(&notes), Some((2, quality)), "{quality}");
+        }
+        // D half-diminished in first inversion, and with an added 9th.
+        assert_eq!(recognized(&[65, 68, 72, 74]), Some((2, ChordQuality::HalfDiminishedSeventh)));
+        assert_eq!(
+            recognized(&[62, 64, 65, 68, 72]),
+            Some((2, ChordQuality::HalfDiminishedSeventh))
+        );
+        // Symmetric shapes take their root from the lowest note.
+        assert_eq!(recognized(&[65, 68, 71, 74]), Some((5, ChordQuality::DiminishedSeventh)));
+        assert_eq!(recognized(&[67, 72, 74]), Some((7, ChordQuality::Sus4)));
+    }
+
+    #[test]
+    fn no_held_notes_suggest_no_scale() {
+        assert!(scales_containing(&BTreeSet::new()).is_empty());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,79 @@
+// src/ui/chord_style_editor_view.rs
+
+use crate::app::CypherApp;
+use crate::theory::{ChordQuality, CHORD_FUNCTIONS};
+use egui::{Color32, ComboBox, Grid, RichText, Window};
+
+pub fn draw_chord_style_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
+    let mut is_open = app.chord_style_editor_window_open;
+    let mut style_to_load = None;
+
+    Window::new("Chord Style Editor")
+        .open(&mut is_open)
+        .default_size([360.0, 260.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Start From:");
+                ComboBox::from_id_salt("chord_style_editor_source")
+                    .selected_text(app.selected_chord_style.name.clone())
+                    .show_ui(ui, |ui| {
+                        for (name, path) in &app.available_chord_styles {
+                            if ui.selectable_label(&app.selected_chord_style.name == name, name).clicked() {
+                                style_to_load = Some(path.clone());
+                            }
+                        }
+                    });
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("Name:");
+                ui.text_edit_singleline(&mut app.chord_style_draft.name);
+            });
+
+            Grid::new("chord_style_functions").num_columns(2).show(ui, |ui| {
+                for (key, label) in CHORD_FUNCTIONS {
+                    ui.label(label);
+                    let current = app.chord_style_draft.suggestions.get(key).copied();
+                    ComboBox::from_id_salt(("chord_style_function", key))
+                        .selected_text(current.map_or("None".to_string(), |q| q.to_string()))
+                        .show_ui(ui, |ui| {
+                            for quality in ChordQuality::ALL {
+                                if ui
+                                    .selectable_label(current == Some(quality), quality.to_string())
+                                    .clicked()
+                                {
+                                    app.chord_style_draft
+                                        .suggestions
+                                        .insert(key.to_string(), quality);
+                                }
+                            }
+                        });
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+
+            let missing = app.chord_style_draft.missing_functions();
+            let can_save = missing.is_empty() && !app.chord_style_draft.name.trim().is_empty();
+            ui.horizontal(|ui| {
+                if ui.add_enabled(can_save, egui::Button::new("Save Style")).clicked() {
+                    if let Err(e) = app.save_chord_style_draft() {
+                        eprintln!("Failed to save chord style: {}", e);
+                    }
+                }
+                if !missing.is_empty() {
+                    ui.label(
+                        RichText::new(format!("Missing: {}", missing.join(", ")))
+                            .color(Color32::LIGHT_RED),
+                    );
+                }
+            });
+        });
+
+    if let Some(path) = style_to_load {
+        app.load_chord_style(&path);
+        app.chord_style_draft = app.selected_chord_style.clone();
+    }
+    app.chord_style_editor_window_open = is_open;
+}
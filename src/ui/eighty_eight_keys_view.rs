@@ -1,8 +1,8 @@
 // src/ui/eighty_eight_keys_view.rs
-use crate::app::{ChordDisplayMode, CypherApp, TheoryMode};
-use crate::theory::Scale;
+use crate::app::{ChordDisplayMode, ChordInversion, CypherApp, ScaleDetection, TheoryMode};
+use crate::theory::{Scale, PITCH_CLASS_NAMES};
 use egui::{
-    epaint, vec2, ComboBox, CornerRadius, Frame, Pos2, Rect, RichText, Stroke,
+    epaint, vec2, ComboBox, CornerRadius, Frame, Pos2, Rect, RichText, Sense, Stroke,
     Ui,
 };
 use std::collections::BTreeMap;
@@ -59,17 +59,45 @@ pub fn draw_88_keys_panel(app: &mut CypherApp, ui: &mut Ui) {
             match app.theory_mode {
                 TheoryMode::Scales => {
                     ui.label(RichText::new("Scale:").color(app.theme.library.text_color));
-                    ComboBox::from_id_salt("scale_selector")
-                        .selected_text(app.selected_scale.to_string())
-                        .show_ui(ui, |ui| {
-                            for scale in Scale::ALL {
-                                ui.selectable_value(
-                                    &mut app.selected_scale,
-                                    scale,
-                                    scale.to_string(),
-                                );
-                            }
-                        });
+                    draw_scale_picker(app, ui, "scale_selector");
+
+                    ui.toggle_value(&mut app.settings.scale_quantize_midi, "Snap MIDI")
+                        .on_hover_text("Snap incoming notes to the nearest note of this scale.");
+                    if app.settings.scale_quantize_midi {
+                        let key = &mut app.settings.scale_quantize_key;
+                        ComboBox::from_id_salt("scale_quantize_key")
+                            .selected_text(PITCH_CLASS_NAMES[*key as usize % 12])
+                            .width(50.0)
+                            .show_ui(ui, |ui| {
+                                for (root, name) in PITCH_CLASS_NAMES.iter().enumerate() {
+                                    ui.selectable_value(key, root as u8, *name);
+                                }
+                            })
+                            .response
+                            .on_hover_text("Key the scale is built on.");
+                    }
+
+                    ui.separator();
+
+                    ui.label(RichText::new("Chords:").color(app.theme.library.text_color));
+                    ui.selectable_value(
+                        &mut app.scale_detection,
+                        ScaleDetection::SingleNote,
+                        "Ignore",
+                    )
+                        .on_hover_text("Only a single held note changes the displayed scale.");
+                    ui.selectable_value(
+                        &mut app.scale_detection,
+                        ScaleDetection::BestMatch,
+                        "Detect Scale",
+                    )
+                        .on_hover_text("Holding several notes shows the scale that best fits them.");
+                    if let Some((root, scale)) = app.detected_scale {
+                        ui.label(
+                            RichText::new(format!("{} {}", PITCH_CLASS_NAMES[root as usize], scale))
+                                .color(app.theme.library.text_color),
+                        );
+                    }
                 }
                 TheoryMode::Chords => {
                     ui.label(RichText::new("Display:").color(app.theme.library.text_color));
@@ -84,6 +112,28 @@ pub fn draw_88_keys_panel(app: &mut CypherApp, ui: &mut Ui) {
                         "Stacked",
                     );
 
+                    let inversion_before = app.chord_inversion;
+                    ComboBox::from_id_salt("chord_inversion_selector")
+                        .selected_text(app.chord_inversion.to_string())
+                        .width(70.0)
+                        .show_ui(ui, |ui| {
+                            for inversion in ChordInversion::ALL {
+                                ui.selectable_value(
+                                    &mut app.chord_inversion,
+                                    inversion,
+                                    inversion.to_string(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text("Auto picks the inversion closest to the held chord.");
+                    if app.chord_inversion != inversion_before {
+                        app.last_recognized_chord_notes.clear();
+                    }
+
+                    ui.toggle_value(&mut app.chord_pad_sends_midi, "Chord Pad")
+                        .on_hover_text("Play clicked suggestions as MIDI notes on the audio note channel.");
+
                     ui.separator();
 
                     ui.label(RichText::new("Style:").color(app.theme.library.text_color));
@@ -103,6 +153,36 @@ pub fn draw_88_keys_panel(app: &mut CypherApp, ui: &mut Ui) {
                     if let Some(path) = style_to_load {
                         app.load_chord_style(&path);
                     }
+                    if ui.button("Edit...").clicked() {
+                        app.open_chord_style_editor();
+                    }
+
+                    ui.separator();
+
+                    // Changing the key re-runs the suggestions for the chord still held.
+                    let key_before =
+                        (app.chord_key_root, app.selected_scale, app.selected_user_scale);
+                    ui.label(RichText::new("Key:").color(app.theme.library.text_color));
+                    let key_text = app
+                        .chord_key_root
+                        .map_or("Any", |root| PITCH_CLASS_NAMES[root as usize]);
+                    ComboBox::from_id_salt("chord_key_selector")
+                        .selected_text(key_text)
+                        .width(50.0)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut app.chord_key_root, None, "Any");
+                            for (root, name) in PITCH_CLASS_NAMES.iter().enumerate() {
+                                ui.selectable_value(&mut app.chord_key_root, Some(root as u8), *name);
+                            }
+                        })
+                        .response
+                        .on_hover_text("Keep suggestions inside this key's scale.");
+                    ui.add_enabled_ui(app.chord_key_root.is_some(), |ui| {
+                        draw_scale_picker(app, ui, "chord_scale_selector");
+                    });
+                    if (app.chord_key_root, app.selected_scale, app.selected_user_scale) != key_before {
+                        app.last_recognized_chord_notes.clear();
+                    }
                 }
             }
         });
@@ -116,8 +196,36 @@ pub fn draw_88_keys_panel(app: &mut CypherApp, ui: &mut Ui) {
     });
 }
 
+/// Scale combo box listing the built-in scales, then any user scales from the `Scales` folder.
+fn draw_scale_picker(app: &mut CypherApp, ui: &mut Ui, id_salt: &str) {
+    ComboBox::from_id_salt(id_salt)
+        .selected_text(app.selected_scale_name())
+        .show_ui(ui, |ui| {
+            for scale in Scale::ALL {
+                let selected = app.selected_user_scale.is_none() && app.selected_scale == scale;
+                if ui.selectable_label(selected, scale.to_string()).clicked() {
+                    app.selected_scale = scale;
+                    app.selected_user_scale = None;
+                }
+            }
+            if !app.available_user_scales.is_empty() {
+                ui.separator();
+            }
+            for (i, scale) in app.available_user_scales.iter().enumerate() {
+                if ui
+                    .selectable_label(app.selected_user_scale == Some(i), &scale.name)
+                    .clicked()
+                {
+                    app.selected_user_scale = Some(i);
+                }
+            }
+        });
+}
+
 fn draw_piano_keyboard(app: &mut CypherApp, ui: &mut Ui) {
     let available_rect = ui.available_rect_before_wrap();
+    // Clicking a suggestion auditions it, so the keyboard needs to sense the pointer.
+    let response = ui.allocate_rect(available_rect, Sense::click_and_drag());
     let painter = ui.painter_at(available_rect);
     let theme = &app.theme.piano_keys;
 
@@ -135,6 +243,9 @@ fn draw_piano_keyboard(app: &mut CypherApp, ui: &mut Ui) {
     let live_notes = app.live_midi_notes.read().unwrap();
     let is_stacked_mode =
         app.theory_mode == TheoryMode::Chords && app.chord_display_mode == ChordDisplayMode::Stacked;
+    // Key rects in hit-test order (black keys sit on top) and the stacked suggestion circles.
+    let mut key_hits: Vec<(Rect, u8)> = Vec::new();
+    let mut circle_hits: Vec<(Pos2, f32, usize)> = Vec::new();
 
     // --- Draw White Keys ---
     for (i,&note) in white_keys.iter().enumerate() {
@@ -153,6 +264,7 @@ fn draw_piano_keyboard(app: &mut CypherApp, ui: &mut Ui) {
         }
 
         painter.rect(key_rect, CornerRadius::ZERO, fill_color, Stroke::new(1.0, theme.outline_color), epaint::StrokeKind::Inside);
+        key_hits.push((key_rect, note));
     }
 
     // --- Draw Black Keys ---
@@ -176,6 +288,7 @@ fn draw_piano_keyboard(app: &mut CypherApp, ui: &mut Ui) {
             }
 
             painter.rect(key_rect, CornerRadius::ZERO, fill_color, Stroke::new(1.0, theme.outline_color), epaint::StrokeKind::Inside);
+            key_hits.insert(0, (key_rect, note));
         }
     }
 
@@ -209,7 +322,74 @@ fn draw_piano_keyboard(app: &mut CypherApp, ui: &mut Ui) {
                 let center = Pos2::new(key_center_x, y_pos);
                 let color = app.theme.loopers.track_colors[color_index % app.theme.loopers.track_colors.len()];
                 painter.circle(center, radius, color, Stroke::new(1.5, theme.outline_color));
+                circle_hits.push((center, radius, color_index));
             }
         }
     }
+    drop(live_notes);
+
+    // --- Audition the suggestion under the pointer while it is held ---
+    if app.theory_mode != TheoryMode::Chords || !response.is_pointer_button_down_on() {
+        let held_from_midi = app.chord_held_from_midi && app.theory_mode == TheoryMode::Chords;
+        if !app.auditioned_chord_notes.is_empty() && !held_from_midi {
+            app.stop_chord_audition();
+        }
+        return;
+    }
+    if !app.auditioned_chord_notes.is_empty() {
+        return; // Already sounding; the chord plays until the button is released.
+    }
+    let Some(pointer) = response.interact_pointer_pos() else {
+        return;
+    };
+    let group = if is_stacked_mode {
+        circle_hits
+            .iter()
+            .find(|(center, radius, _)| center.distance(pointer) <= *radius)
+            .map(|(_, _, group)| *group)
+    } else {
+        suggestion_group_at_key(pointer, &key_hits, &app.displayed_theory_notes)
+    };
+    if let Some(group) = group {
+        app.audition_chord_group(group);
+    }
+}
+
+/// The suggestion group of the displayed note whose key is under `pointer`, if any.
+/// `key_hits` is in hit-test order, black keys first.
+fn suggestion_group_at_key(
+    pointer: Pos2,
+    key_hits: &[(Rect, u8)],
+    displayed_theory_notes: &[(u8, usize)],
+) -> Option<usize> {
+    key_hits
+        .iter()
+        .find(|(rect, _)| rect.contains(pointer))
+        .and_then(|(_, note)| displayed_theory_notes.iter().find(|(n, _)| n == note))
+        .map(|(_, group)| *group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::chord_group_notes;
+
+    #[test]
+    fn clicking_a_suggestion_key_auditions_its_whole_group() {
+        // C major (group 0) and D minor (group 1), drawn on three white keys and one black key.
+        let displayed = [(60, 0), (64, 0), (67, 0), (62, 1), (65, 1), (69, 1)];
+        let key = |x: f32| Rect::from_min_size(Pos2::new(x, 0.0), vec2(10.0, 100.0));
+        let key_hits = [(key(15.0), 61), (key(0.0), 60), (key(10.0), 62), (key(20.0), 64)];
+
+        let group = suggestion_group_at_key(Pos2::new(12.0, 90.0), &key_hits, &displayed);
+        assert_eq!(group, Some(1));
+        assert_eq!(chord_group_notes(&displayed, 1), vec![62, 65, 69]);
+
+        let group = suggestion_group_at_key(Pos2::new(5.0, 90.0), &key_hits, &displayed);
+        assert_eq!(group, Some(0));
+        assert_eq!(chord_group_notes(&displayed, 0), vec![60, 64, 67]);
+
+        // The black key on top is not part of any suggestion.
+        assert_eq!(suggestion_group_at_key(Pos2::new(17.0, 50.0), &key_hits, &displayed), None);
+    }
 }
\ No newline at end of file
@@ -5,7 +5,9 @@
 
 use crate::app::CypherApp;
 use crate::audio_engine::AudioCommand;
-use crate::fx::{FxChainLink, FxComponentType, FxPreset, ModulationRoutingData};
+use crate::fx::{
+    component_params_to_json, FxChainLink, FxComponentType, FxPreset, ModulationRoutingData,
+};
 use crate::fx_components::*;
 use crate::settings;
 use egui::{
@@ -48,8 +50,11 @@ pub fn draw_fx_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
             let mut clear_chain_clicked = false;
             let mut preset_to_load_path: Option<PathBuf> = None;
             let mut save_preset_as = false;
+            let mut toggle_default_preset = false;
+            let mut default_to_save: Option<(FxComponentType, serde_json::Value)> = None;
 
             let mut any_mod_ui_changed = false;
+            let mut kill_dry_changed = false;
 
             // --- Top Bar ---
             ui.horizontal(|ui| {
@@ -67,6 +72,22 @@ pub fn draw_fx_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
                 if ui.button("Save Preset As...").clicked() {
                     save_preset_as = true;
                 }
+
+                // Only a chain saved to the FX folder can be the default, as it is stored by name.
+                let is_saved_preset =
+                    app.available_fx_presets.iter().any(|(name, _)| name == preset_name);
+                let is_default =
+                    app.settings.default_fx_presets.get(&target).map(String::as_str)
+                        == Some(preset_name);
+                let response = ui
+                    .add_enabled(
+                        is_saved_preset || is_default,
+                        egui::Button::selectable(is_default, "Default"),
+                    )
+                    .on_hover_text(format!("Load this preset into {} on a new session", target));
+                if response.clicked() {
+                    toggle_default_preset = true;
+                }
                 if ui.button("Clear Chain").clicked() {
                     clear_chain_clicked = true;
                 }
@@ -84,6 +105,15 @@ pub fn draw_fx_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
                 } else {
                     ui.add_enabled(false, Slider::new(&mut 0.0, 0.0..=1.0).text("Dry/Wet"));
                 }
+                if let Some(preset) = app.fx_presets.get_mut(&target) {
+                    if ui
+                        .checkbox(&mut preset.kill_dry, "Kill Dry")
+                        .on_hover_text("Output only the processed signal, e.g. for a reverb used as a send")
+                        .changed()
+                    {
+                        kill_dry_changed = true;
+                    }
+                }
                 ui.separator();
 
                 ComboBox::from_id_salt("add_component_combo")
@@ -133,6 +163,16 @@ pub fn draw_fx_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
                                     if ui.toggle_value(&mut bypassed, "Bypass").changed() {
                                         link.params.bypassed().store(bypassed, Ordering::Relaxed);
                                     }
+                                    if ui
+                                        .button("Set Default")
+                                        .on_hover_text("New components of this type will start with these settings")
+                                        .clicked()
+                                    {
+                                        default_to_save = Some((
+                                            link.component_type,
+                                            component_params_to_json(&link.params),
+                                        ));
+                                    }
                                 });
                             });
                             ui.separator();
@@ -140,6 +180,11 @@ pub fn draw_fx_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
                             if draw_component_ui(ui, link, i, &chain_clone_for_mods) {
                                 any_mod_ui_changed = true;
                             }
+
+                            // Modulators pass audio straight through, so trims only apply to processors.
+                            if !matches!(link.component_type, FxComponentType::Lfo | FxComponentType::EnvelopeFollower) {
+                                draw_trim_ui(ui, link.params.trim());
+                            }
                         });
                     }
                 }
@@ -156,8 +201,16 @@ pub fn draw_fx_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
                 }
             }
 
+            if let Some((comp_type, values)) = default_to_save {
+                app.settings.fx_component_defaults.insert(comp_type, values);
+                app.save_settings();
+            }
+
             if let Some(comp_type) = new_component_type {
-                app.fx_presets.entry(target).or_default().chain.push(FxChainLink::new(comp_type));
+                app.fx_presets.entry(target).or_default().chain.push(FxChainLink::with_user_defaults(
+                    comp_type,
+                    &app.settings.fx_component_defaults,
+                ));
                 structure_changed = true;
             }
 
@@ -211,12 +264,23 @@ pub fn draw_fx_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
                     }
                 }
             }
+            if toggle_default_preset {
+                if let Some(preset) = app.fx_presets.get(&target) {
+                    let name = preset.name.clone();
+                    if app.settings.default_fx_presets.get(&target) == Some(&name) {
+                        app.settings.default_fx_presets.remove(&target);
+                    } else {
+                        app.settings.default_fx_presets.insert(target, name);
+                    }
+                    app.save_settings();
+                }
+            }
             if clear_chain_clicked {
                 app.fx_presets.remove(&target);
                 app.send_command(AudioCommand::ClearFxRack(target));
             }
 
-            if any_mod_ui_changed {
+            if any_mod_ui_changed || kill_dry_changed {
                 structure_changed = true;
             }
 
@@ -234,6 +298,22 @@ pub fn draw_fx_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
     app.fx_editor_window_open = is_open;
 }
 
+/// Draws the input/output trim sliders shown under every audio-processing component.
+fn draw_trim_ui(ui: &mut Ui, trim: &GainTrim) {
+    ui.horizontal(|ui| {
+        ui.label("In (dB)");
+        let mut input_db = trim.input_db();
+        if ui.add(Slider::new(&mut input_db, -24.0..=24.0)).changed() {
+            trim.input_gain_db.store(GainTrim::db_to_stored(input_db), Ordering::Relaxed);
+        }
+        ui.label("Out (dB)");
+        let mut output_db = trim.output_db();
+        if ui.add(Slider::new(&mut output_db, -24.0..=24.0)).changed() {
+            trim.output_gain_db.store(GainTrim::db_to_stored(output_db), Ordering::Relaxed);
+        }
+    });
+}
+
 /// Dynamically draws the UI for a single FxChainLink.
 fn draw_component_ui(ui: &mut Ui, link: &mut FxChainLink, index: usize, chain: &[FxChainLink]) -> bool {
     let mut modulation_was_changed = false;
@@ -301,6 +381,38 @@ fn draw_component_ui(ui: &mut Ui, link: &mut FxChainLink, index: usize, chain: &
             ui.end_row();
         }
         ComponentParams::Waveshaper(p) => {
+            ui.label("Curve");
+            let mut mode = waveshaper::WaveshaperMode::from(p.mode.load(Ordering::Relaxed));
+            let initial_mode = mode;
+            ComboBox::from_id_salt(format!("waveshaper_mode_combo_{}", index))
+                .selected_text(mode.name())
+                .show_ui(ui, |ui| {
+                    for option in waveshaper::WaveshaperMode::ALL {
+                        ui.selectable_value(&mut mode, option, option.name());
+                    }
+                });
+            if initial_mode != mode {
+                p.mode.store(mode as u32, Ordering::Relaxed);
+            }
+            ui.end_row();
+
+            ui.label("Oversampling");
+            let mut oversample = p.oversample.load(Ordering::Relaxed);
+            let initial_oversample = oversample;
+            ComboBox::from_id_salt(format!("waveshaper_oversample_combo_{}", index))
+                .selected_text(format!("{}x", oversample))
+                .show_ui(ui, |ui| {
+                    for factor in waveshaper::OVERSAMPLE_FACTORS {
+                        ui.selectable_value(&mut oversample, factor, format!("{}x", factor));
+                    }
+                })
+                .response
+                .on_hover_text("Runs the curve at a higher rate to reduce aliasing. Costs CPU.");
+            if initial_oversample != oversample {
+                p.oversample.store(oversample, Ordering::Relaxed);
+            }
+            ui.end_row();
+
             ui.label("Drive (Pre-Gain dB)");
             let mut drive = p.drive_db.load(Ordering::Relaxed) as f32 / waveshaper::DB_SCALER;
             if ui.add(Slider::new(&mut drive, 0.0..=48.0)).changed() {
@@ -331,6 +443,21 @@ fn draw_component_ui(ui: &mut Ui, link: &mut FxChainLink, index: usize, chain: &
             ui.end_row();
         }
         ComponentParams::Lfo(p) => {
+            ui.label("Waveform");
+            let mut waveform = lfo::LfoWaveform::from(p.waveform.load(Ordering::Relaxed));
+            let initial_waveform = waveform;
+            ComboBox::from_id_salt(format!("lfo_waveform_combo_{}", index))
+                .selected_text(waveform.name())
+                .show_ui(ui, |ui| {
+                    for option in lfo::LfoWaveform::ALL {
+                        ui.selectable_value(&mut waveform, option, option.name());
+                    }
+                });
+            if initial_waveform != waveform {
+                p.waveform.store(waveform as u32, Ordering::Relaxed);
+            }
+            ui.end_row();
+
             ui.label("Rate (Hz)");
             let mut freq = p.frequency_hz.load(Ordering::Relaxed) as f32 / lfo::PARAM_SCALER;
             if ui.add(Slider::new(&mut freq, 0.01..=20.0).logarithmic(true)).changed() {
@@ -397,6 +524,20 @@ fn draw_component_ui(ui: &mut Ui, link: &mut FxChainLink, index: usize, chain: &
                 p.downsample.store((downsample * quantizer::PARAM_SCALER) as u32, Ordering::Relaxed);
             }
             ui.end_row();
+
+            ui.label("Slew (ms)");
+            let mut slew = p.slew_time.load(Ordering::Relaxed) as f32 / quantizer::PARAM_SCALER;
+            if ui.add(Slider::new(&mut slew, 0.0..=1000.0).logarithmic(true)).changed() {
+                p.slew_time.store((slew * quantizer::PARAM_SCALER) as u32, Ordering::Relaxed);
+            }
+            ui.end_row();
+
+            ui.label("Probability");
+            let mut probability = p.probability.load(Ordering::Relaxed) as f32 / quantizer::PARAM_SCALER;
+            if ui.add(Slider::new(&mut probability, 0.0..=1.0)).changed() {
+                p.probability.store((probability * quantizer::PARAM_SCALER) as u32, Ordering::Relaxed);
+            }
+            ui.end_row();
         }
         ComponentParams::Formant(p) => {
             ui.label("Character");
@@ -412,10 +553,27 @@ fn draw_component_ui(ui: &mut Ui, link: &mut FxChainLink, index: usize, chain: &
                 p.resonance.store((resonance * formant::PARAM_SCALER) as u32, Ordering::Relaxed);
             }
             ui.end_row();
+
+            ui.label("Vowel Morph");
+            let mut vowel_morph = p.vowel_morph.load(Ordering::Relaxed);
+            if ui.checkbox(&mut vowel_morph, "A-E-I-O-U").changed() {
+                p.vowel_morph.store(vowel_morph, Ordering::Relaxed);
+            }
+            ui.end_row();
+
+            ui.label("Vowel");
+            let mut vowel = p.vowel.load(Ordering::Relaxed) as f32 / formant::PARAM_SCALER;
+            if ui.add_enabled(vowel_morph, Slider::new(&mut vowel, 0.0..=1.0)).changed() {
+                p.vowel.store((vowel * formant::PARAM_SCALER) as u32, Ordering::Relaxed);
+            }
+            ui.end_row();
         }
     });
 
-    let is_modulator = matches!(link.component_type, FxComponentType::Lfo | FxComponentType::EnvelopeFollower);
+    let is_modulator = matches!(
+        link.component_type,
+        FxComponentType::Lfo | FxComponentType::EnvelopeFollower | FxComponentType::Quantizer
+    );
     if is_modulator {
         egui::collapsing_header::CollapsingHeader::new("Modulations")
             .id_salt(format!("mod_header_{}", index))
@@ -503,11 +661,11 @@ fn get_available_params(comp_type: Option<FxComponentType>) -> Vec<&'static str>
         Some(FxComponentType::Delay) => vec!["time_ms", "feedback", "damping"],
         Some(FxComponentType::Filter) => vec!["frequency_hz", "resonance"],
         Some(FxComponentType::Waveshaper) => vec!["drive_db"],
-        Some(FxComponentType::Quantizer) => vec!["bit_depth", "downsample"],
+        Some(FxComponentType::Quantizer) => vec!["bit_depth", "downsample", "slew_time", "probability"],
         Some(FxComponentType::Reverb) => vec!["size", "decay", "damping"],
         Some(FxComponentType::Flanger) => vec!["rate_hz", "depth_ms", "feedback"],
         Some(FxComponentType::EnvelopeFollower) => vec!["attack_ms", "release_ms"],
-        Some(FxComponentType::Formant) => vec!["character", "resonance"],
+        Some(FxComponentType::Formant) => vec!["character", "resonance", "vowel"],
         _ => vec![],
     }
 }
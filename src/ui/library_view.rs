@@ -1,19 +1,25 @@
 // src/ui/library_view.rs
 
 use crate::app::{CypherApp, LibraryView};
-use crate::asset::{Asset, AssetRef, FolderRef, SampleRef};
+use crate::asset::{self, Asset, AssetRef, FolderRef, SampleRef};
 use crate::audio_engine::AudioCommand;
-use crate::sampler::{SamplerKit, SamplerPadFxSettings, SamplerPadSettings};
+use crate::mixer::format_db;
+use crate::sampler::{
+    LaunchQuantize, NoteRepeatRate, PadRetriggerMode, SamplerKit, SamplerPadFxSettings, SamplerPadSettings,
+    NUM_PADS,
+};
 use crate::settings;
 use crate::synth::AdsrSettings;
 use crate::ui;
+use egui::text::LayoutJob;
 use egui::{
     epaint, vec2, Align2, Button, CornerRadius, DragAndDrop, Frame, Id, Margin, Response,
-    RichText, ScrollArea, Sense, Slider, Stroke, Ui, Window,
+    RichText, ScrollArea, Sense, Slider, Stroke, TextEdit, TextFormat, Ui, Window,
 };
 use rfd::FileDialog;
 use std::cmp::max;
 use std::fs;
+use std::ops::Range;
 use std::path::PathBuf;
 use std::sync::atomic::Ordering;
 use std::time::{Duration, Instant};
@@ -72,6 +78,11 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
             {
                 app.rescan_asset_library();
                 app.rescan_chord_styles();
+                app.rescan_user_scales();
+            }
+            if app.is_scanning_asset_library() {
+                ui.spinner();
+                ui.label(RichText::new("Scanning...").color(app.theme.library.text_color));
             }
             ui.separator();
 
@@ -184,7 +195,17 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
             {
                 ui.separator();
 
-                if !app.library_path.is_empty() {
+                ui.add(
+                    TextEdit::singleline(&mut app.library_search)
+                        .hint_text("Search...")
+                        .desired_width(140.0),
+                );
+                if !app.library_search.is_empty() && ui.small_button("✖").on_hover_text("Clear search").clicked() {
+                    app.library_search.clear();
+                }
+                ui.separator();
+
+                if !app.library_path.is_empty() && app.library_search.is_empty() {
                     let back_button = Button::new("⬅ Back")
                         .min_size(button_min_size)
                         .fill(app.theme.library.button_bg)
@@ -197,22 +218,21 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
                         app.library_path.pop();
                     }
                 }
-                let path_str = if app.library_path.is_empty() {
-                    match app.library_view {
-                        LibraryView::Samples => "Samples",
-                        LibraryView::Synths => "Synths",
-                        LibraryView::Kits => "Kits",
-                        LibraryView::Sessions => "Sessions",
-                        _ => "", // Should not happen
-                    }
-                        .to_string()
+                let category_name = match app.library_view {
+                    LibraryView::Samples => "Samples",
+                    LibraryView::Synths => "Synths",
+                    LibraryView::Kits => "Kits",
+                    LibraryView::Sessions => "Sessions",
+                    _ => "", // Should not happen
+                };
+                let status = if !app.library_search.is_empty() {
+                    format!("Searching: all {}", category_name)
+                } else if app.library_path.is_empty() {
+                    format!("Browsing: {}", category_name)
                 } else {
-                    app.library_path.join(" / ")
+                    format!("Browsing: {}", app.library_path.join(" / "))
                 };
-                ui.label(
-                    RichText::new(format!("Browsing: {}", path_str))
-                        .color(app.theme.library.text_color),
-                );
+                ui.label(RichText::new(status).color(app.theme.library.text_color));
             }
         });
         ui.separator();
@@ -243,9 +263,21 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
                 }
             }
 
+            // Searching lists matches from every folder of the category instead of one folder.
+            let search = app.library_search.trim().to_string();
+            let is_searching = !search.is_empty() && app.library_view != LibraryView::Soundscapes;
+            let highlight = if is_searching { search.as_str() } else { "" };
+            let assets: Vec<&Asset> = if is_searching {
+                category_root.search(&search)
+            } else {
+                current_folder.assets.iter().collect()
+            };
+            let folder_count = if is_searching { 0 } else { current_folder.subfolders.len() };
+
             let mut preset_to_load: Option<PathBuf> = None;
             let mut kit_to_load: Option<PathBuf> = None;
             let mut session_to_load: Option<PathBuf> = None;
+            let mut sample_to_audition: Option<PathBuf> = None;
             let theme = app.theme.clone();
 
             ScrollArea::vertical()
@@ -255,7 +287,7 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
                     const SPACING: f32 = 20.0;
                     const SCROLL_RESERVATION_WIDTH: f32 = CARD_WIDTH * 2.0 + SPACING;
 
-                    let total_items = current_folder.subfolders.len() + current_folder.assets.len();
+                    let total_items = folder_count + assets.len();
                     if total_items == 0 {
                         return;
                     }
@@ -273,7 +305,7 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
                         .spacing([SPACING, SPACING])
                         .show(ui, |ui| {
                             // Only show clickable sub-folders if we are NOT in Soundscapes view
-                            if app.library_view != LibraryView::Soundscapes {
+                            if app.library_view != LibraryView::Soundscapes && !is_searching {
                                 for folder_name in current_folder.subfolders.keys() {
                                     let response = draw_folder_card(ui, folder_name, &theme);
 
@@ -291,7 +323,7 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
                                 }
                             }
 
-                            for asset in &current_folder.assets {
+                            for &asset in &assets {
                                 // --- Main filtering logic is here ---
                                 let should_draw = match (app.library_view, asset) {
                                     (LibraryView::Soundscapes, Asset::Folder(_)) => true,
@@ -312,7 +344,8 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
                                         sample_ref,
                                         "🎵",
                                         asset.clone(),
-                                        Sense::drag(),
+                                        Sense::click_and_drag(),
+                                        highlight,
                                         &theme,
                                     ),
                                     Asset::SynthPreset(preset_ref) => draw_asset_card(
@@ -321,6 +354,7 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
                                         "🎹",
                                         asset.clone(),
                                         Sense::click_and_drag(),
+                                        highlight,
                                         &theme,
                                     ),
                                     Asset::SamplerKit(kit_ref) => draw_asset_card(
@@ -329,6 +363,7 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
                                         "🥁",
                                         asset.clone(),
                                         Sense::click_and_drag(),
+                                        highlight,
                                         &theme,
                                     ),
                                     Asset::Session(session_ref) => draw_asset_card(
@@ -337,6 +372,7 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
                                         "💾",
                                         asset.clone(),
                                         Sense::click_and_drag(),
+                                        highlight,
                                         &theme,
                                     ),
                                     Asset::Folder(folder_ref) => draw_folder_asset_card(
@@ -353,6 +389,9 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
 
                                 if is_clicked {
                                     match asset {
+                                        Asset::Sample(sample_ref) => {
+                                            sample_to_audition = Some(sample_ref.path().clone())
+                                        }
                                         Asset::SynthPreset(preset_ref) => {
                                             preset_to_load = Some(preset_ref.path().clone())
                                         }
@@ -387,6 +426,9 @@ pub fn draw_library_panel(app: &mut CypherApp, ui: &mut Ui) {
             if let Some(path) = session_to_load {
                 app.load_session(&path);
             }
+            if let Some(path) = sample_to_audition {
+                app.toggle_sample_audition(&path);
+            }
         }
     });
 }
@@ -486,6 +528,7 @@ fn draw_asset_card(
     icon: &str,
     asset_payload: Asset,
     sense: Sense,
+    highlight: &str,
     theme: &crate::theme::Theme,
 ) -> Response {
     let size = vec2(100.0, 80.0);
@@ -512,12 +555,20 @@ fn draw_asset_card(
             egui::FontId::proportional(32.0),
             theme.library.text_color,
         );
-        let name_galley = ui.painter().layout(
-            asset_ref.name().to_string(),
-            egui::FontId::monospace(12.0),
-            theme.library.text_color,
-            rect.width() - 8.0,
-        );
+        let name_format = TextFormat::simple(egui::FontId::monospace(12.0), theme.library.text_color);
+        let mut name_job = LayoutJob::default();
+        let name = asset_ref.name();
+        match highlight_range(name, highlight) {
+            Some(range) => {
+                name_job.append(&name[..range.start], 0.0, name_format.clone());
+                let highlighted = TextFormat { background: theme.library.tab_active_bg, ..name_format.clone() };
+                name_job.append(&name[range.clone()], 0.0, highlighted);
+                name_job.append(&name[range.end..], 0.0, name_format);
+            }
+            None => name_job.append(name, 0.0, name_format),
+        }
+        name_job.wrap.max_width = rect.width() - 8.0;
+        let name_galley = ui.painter().layout_job(name_job);
         let icon_pos = egui::pos2(rect.center().x - icon_galley.size().x / 2.0, rect.top() + 12.0);
         let name_pos = egui::pos2(
             rect.center().x - name_galley.size().x / 2.0,
@@ -531,6 +582,19 @@ fn draw_asset_card(
     response
 }
 
+/// Byte range of the first case-insensitive occurrence of `query` in `name`, for
+/// highlighting search matches. `None` if there's no match or lowercasing changes the
+/// name's length, which would throw the byte offsets off.
+fn highlight_range(name: &str, query: &str) -> Option<Range<usize>> {
+    let lowercase_name = name.to_lowercase();
+    if query.is_empty() || lowercase_name.len() != name.len() {
+        return None;
+    }
+    let start = lowercase_name.find(&query.to_lowercase())?;
+    let range = start..start + query.len();
+    (name.is_char_boundary(range.start) && name.is_char_boundary(range.end)).then_some(range)
+}
+
 pub fn draw_sample_pad_window(app: &mut CypherApp, ctx: &egui::Context) {
     let mut is_open = app.sample_pad_window_open;
     Window::new("Sample Pads")
@@ -596,7 +660,10 @@ pub fn draw_sample_pad_window(app: &mut CypherApp, ctx: &egui::Context) {
                                 }
                             });
 
-                            let kit = SamplerKit { pads };
+                            let kit = SamplerKit {
+                                pads,
+                                groove: app.pad_groove,
+                            };
 
                             if let Ok(json) = serde_json::to_string_pretty(&kit) {
                                 if let Err(e) = fs::write(&path, json) {
@@ -624,20 +691,87 @@ pub fn draw_sample_pad_window(app: &mut CypherApp, ctx: &egui::Context) {
                         }
                     }
                 }
+                ui.separator();
+
+                let export_button =
+                    Button::new("Export Sprite").fill(app.theme.sampler_pad_window.kit_button_bg);
+                if ui
+                    .add(export_button)
+                    .on_hover_text("Export the kit as one WAV plus a JSON pad mapping")
+                    .clicked()
+                {
+                    if let Some(path) = FileDialog::new()
+                        .add_filter("wav", &["wav"])
+                        .save_file()
+                    {
+                        if let Err(e) = app.export_kit_sprite(&path) {
+                            eprintln!("Failed to export kit sprite: {}", e);
+                        }
+                    }
+                }
+
+                let import_button =
+                    Button::new("Import Sprite").fill(app.theme.sampler_pad_window.kit_button_bg);
+                if ui.add(import_button).clicked() {
+                    if let Some(path) = FileDialog::new()
+                        .add_filter("wav", &["wav"])
+                        .pick_file()
+                    {
+                        if let Err(e) = app.import_kit_sprite(&path) {
+                            eprintln!("Failed to import kit sprite: {}", e);
+                        }
+                    }
+                }
             });
             ui.memory_mut(|m| m.data.insert_temp(trash_mode_id, trash_mode));
+
+            let mut groove_changed = false;
+            ui.horizontal(|ui| {
+                let groove = &mut app.pad_groove;
+                groove_changed |= ui
+                    .add(Slider::new(&mut groove.swing, 0.0..=1.0).text("Swing"))
+                    .changed();
+                groove_changed |= ui
+                    .add(
+                        Slider::new(&mut groove.humanize_timing_ms, 0.0..=30.0)
+                            .suffix(" ms")
+                            .text("Humanize"),
+                    )
+                    .changed();
+                groove_changed |= ui
+                    .add(Slider::new(&mut groove.humanize_velocity, 0.0..=1.0).text("Vel Var"))
+                    .changed();
+                egui::ComboBox::from_label("Launch")
+                    .selected_text(groove.launch_quantize.to_string())
+                    .show_ui(ui, |ui| {
+                        for quantize in LaunchQuantize::ALL {
+                            groove_changed |= ui
+                                .selectable_value(
+                                    &mut groove.launch_quantize,
+                                    quantize,
+                                    quantize.to_string(),
+                                )
+                                .changed();
+                        }
+                    })
+                    .response
+                    .on_hover_text("Holds live pad hits until the next beat subdivision while the transport plays");
+            });
+            if groove_changed {
+                app.send_command(AudioCommand::SetPadGroove(app.pad_groove));
+            }
             ui.separator();
 
             let current_playing_mask = app.playing_pads.load(Ordering::Relaxed);
             let spacing = 10.0;
             let pad_size = (ui.available_width() - spacing * 3.0) / 4.0;
             let size_vec = vec2(pad_size, pad_size);
-            let mut sample_to_load: Option<(usize, SampleRef)> = None;
+            let mut samples_to_load: Vec<(usize, SampleRef)> = Vec::new();
 
             egui::Grid::new("sample_pad_grid")
                 .spacing([spacing, spacing])
                 .show(ui, |ui| {
-                    for i in 0..16 {
+                    for i in 0..NUM_PADS {
                         let visual_row = i / 4;
                         let visual_col = i % 4;
                         let logical_pad_index = (3 - visual_row) * 4 + visual_col;
@@ -676,7 +810,16 @@ pub fn draw_sample_pad_window(app: &mut CypherApp, ctx: &egui::Context) {
                             if is_hovered && ui.input(|i| i.pointer.any_released()) {
                                 if let Some(asset) = DragAndDrop::take_payload::<Asset>(ui.ctx()) {
                                     if let Asset::Sample(sample_ref) = (*asset).clone() {
-                                        sample_to_load = Some((logical_pad_index, sample_ref));
+                                        samples_to_load.push((logical_pad_index, sample_ref));
+                                    }
+                                }
+                            }
+                            if is_hovered {
+                                // Several files dropped at once fill this pad and the ones after it.
+                                let dropped = asset::take_dropped_sample_files(ui.ctx());
+                                for (offset, path) in dropped.into_iter().take(NUM_PADS - logical_pad_index).enumerate() {
+                                    if let Some(sample_ref) = SampleRef::new(path) {
+                                        samples_to_load.push((logical_pad_index + offset, sample_ref));
                                     }
                                 }
                             }
@@ -688,10 +831,12 @@ pub fn draw_sample_pad_window(app: &mut CypherApp, ctx: &egui::Context) {
                     }
                 });
 
-            if let Some((pad_index, sample_ref)) = sample_to_load {
-                app.load_sample_for_pad(pad_index, sample_ref);
+            if let Some(&(pad_index, _)) = samples_to_load.first() {
                 active_pad_editor = Some(pad_index);
             }
+            for (pad_index, sample_ref) in samples_to_load {
+                app.load_sample_for_pad(pad_index, sample_ref);
+            }
 
             ui.add_space(10.0);
 
@@ -718,8 +863,8 @@ fn draw_pad(
 ) -> Response {
     let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
 
-    let is_being_dragged_over =
-        ui.rect_contains_pointer(rect) && DragAndDrop::has_any_payload(ui.ctx());
+    let is_being_dragged_over = ui.rect_contains_pointer(rect)
+        && (DragAndDrop::has_any_payload(ui.ctx()) || asset::is_os_file_hovering(ui.ctx()));
 
     let mut is_flashing = false;
     if let Some(flash_start) = flash_timers[pad_index] {
@@ -847,7 +992,11 @@ fn draw_pad_fx_editor(app: &mut CypherApp, ui: &mut Ui, pad_index: usize) {
                     let fx = &mut app.sampler_pad_fx_settings[pad_index];
 
                     if ui
-                        .add(Slider::new(&mut fx.volume, 0.0..=1.5).text("Volume"))
+                        .add(
+                            Slider::new(&mut fx.volume, 0.0..=1.5)
+                                .custom_formatter(|v, _| format!("{} dB", format_db(v as f32)))
+                                .text("Volume"),
+                        )
                         .changed()
                     {
                         fx_changed = true;
@@ -858,6 +1007,13 @@ fn draw_pad_fx_editor(app: &mut CypherApp, ui: &mut Ui, pad_index: usize) {
                     {
                         fx_changed = true;
                     }
+                    if ui
+                        .add(Slider::new(&mut fx.start_offset, 0.0..=0.99).text("Start"))
+                        .on_hover_text("Skip into the sample, e.g. to trim silence before the hit")
+                        .changed()
+                    {
+                        fx_changed = true;
+                    }
                     if ui
                         .add(Slider::new(&mut fx.distortion_amount, 0.0..=1.0).text("Distortion"))
                         .changed()
@@ -902,6 +1058,55 @@ fn draw_pad_fx_editor(app: &mut CypherApp, ui: &mut Ui, pad_index: usize) {
                         fx.is_reverb_gated = !fx.is_reverb_gated;
                         fx_changed = true;
                     }
+                    let reverse_button = Button::new("Reverse").fill(if fx.is_reversed {
+                        theme.trash_mode_active_bg
+                    } else {
+                        theme.kit_button_bg
+                    });
+                    if ui.add(reverse_button).clicked() {
+                        fx.is_reversed = !fx.is_reversed;
+                        fx_changed = true;
+                    }
+                    let one_shot_button = Button::new("One Shot").fill(if fx.one_shot {
+                        theme.trash_mode_active_bg
+                    } else {
+                        theme.kit_button_bg
+                    });
+                    if ui
+                        .add(one_shot_button)
+                        .on_hover_text("Play the whole sample regardless of how long the note is held")
+                        .clicked()
+                    {
+                        fx.one_shot = !fx.one_shot;
+                        fx_changed = true;
+                    }
+                    egui::ComboBox::from_label("Retrigger")
+                        .selected_text(fx.retrigger_mode.to_string())
+                        .show_ui(ui, |ui| {
+                            for mode in PadRetriggerMode::ALL {
+                                if ui
+                                    .selectable_value(&mut fx.retrigger_mode, mode, mode.to_string())
+                                    .changed()
+                                {
+                                    fx_changed = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Retrigger cuts the previous hit, Stack lets it ring on, Fade ramps it out quickly to avoid clicks");
+                    ui.separator();
+                    egui::ComboBox::from_label("Note Repeat")
+                        .selected_text(fx.note_repeat.to_string())
+                        .show_ui(ui, |ui| {
+                            for rate in NoteRepeatRate::ALL {
+                                if ui
+                                    .selectable_value(&mut fx.note_repeat, rate, rate.to_string())
+                                    .changed()
+                                {
+                                    fx_changed = true;
+                                }
+                            }
+                        });
                 });
             });
         });
@@ -4,11 +4,13 @@ use crate::app::CypherApp;
 use crate::audio_engine::AudioCommand;
 use crate::fx;
 use crate::looper::{LooperState, NUM_LOOPERS};
-use crate::settings;
+use crate::mixer::format_db;
+use crate::settings::{self, CpuLoadLevel, InputHeadroom};
 use crate::synth_view;
 use crate::ui;
 use crate::ui::about_view::draw_about_window;
 use crate::ui::atmo_view::draw_atmo_window;
+use crate::ui::chord_style_editor_view::draw_chord_style_editor_window;
 use crate::ui::fx_editor_view::draw_fx_editor_window;
 use crate::ui::midi_mapping_view::draw_midi_mapping_window;
 use crate::ui::mixer_view::horizontal_volume_fader;
@@ -26,6 +28,30 @@ use std::time::Instant;
 
 const CLICK_DRAG_THRESHOLD: f32 = 5.0;
 
+/// Offers to bring back an autosave left by a session that was never saved, e.g. after a crash.
+fn draw_autosave_restore_prompt(app: &mut CypherApp, ctx: &egui::Context) {
+    egui::Window::new("Recover Session")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+        .frame(Frame::window(&ctx.style()).fill(app.theme.options_window.background))
+        .show(ctx, |ui| {
+            ui.label(
+                RichText::new("An autosaved session newer than your last save was found.")
+                    .color(app.theme.options_window.label_color),
+            );
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("Restore").clicked() {
+                    app.restore_autosave();
+                }
+                if ui.button("Discard").clicked() {
+                    app.discard_autosave();
+                }
+            });
+        });
+}
+
 pub fn draw_main_view(app: &mut CypherApp, ctx: &egui::Context) {
     if app.options_window_open {
         ui::draw_options_window(app, ctx);
@@ -54,6 +80,12 @@ pub fn draw_main_view(app: &mut CypherApp, ctx: &egui::Context) {
     if app.atmo_window_open {
         draw_atmo_window(app, ctx);
     }
+    if app.chord_style_editor_window_open {
+        draw_chord_style_editor_window(app, ctx);
+    }
+    if app.recoverable_autosave.is_some() {
+        draw_autosave_restore_prompt(app, ctx);
+    }
 
     // --- Draw Notification Overlay ---
     if let Some((msg, _)) = &app.recording_notification {
@@ -130,6 +162,32 @@ pub fn draw_main_view(app: &mut CypherApp, ctx: &egui::Context) {
                     app.save_session(None);
                 }
 
+                ui.menu_button("Recent", |ui| {
+                    let recent_sessions = app.recent_sessions();
+                    if recent_sessions.is_empty() {
+                        ui.label("No recent sessions");
+                    }
+                    for path in recent_sessions {
+                        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                        if ui.button(name).on_hover_text(path.display().to_string()).clicked() {
+                            app.load_session(&path);
+                        }
+                    }
+                });
+
+                ui.menu_button("Bundle", |ui| {
+                    if ui
+                        .button("Export Session Bundle...")
+                        .on_hover_text("Packs the saved session with its preset, kit and samples into one zip.")
+                        .clicked()
+                    {
+                        app.export_session_bundle();
+                    }
+                    if ui.button("Import Bundle...").clicked() {
+                        app.import_session_bundle();
+                    }
+                });
+
                 ui.separator();
 
                 let len = app.transport_len_samples.load(Ordering::Relaxed);
@@ -149,6 +207,18 @@ pub fn draw_main_view(app: &mut CypherApp, ctx: &egui::Context) {
                         .color(app.theme.top_bar.text_color),
                 );
 
+                let tempo_locked = app.tempo_locked.load(Ordering::Relaxed);
+                let lock_button = Button::new("Lock")
+                    .selected(tempo_locked)
+                    .fill(app.theme.top_bar.button_bg);
+                if ui
+                    .add(lock_button)
+                    .on_hover_text("Tempo lock: keep the current loop length when loopers are cleared.")
+                    .clicked()
+                {
+                    app.tempo_locked.store(!tempo_locked, Ordering::Relaxed);
+                }
+
                 ui.separator();
 
                 ui.label(
@@ -174,11 +244,30 @@ pub fn draw_main_view(app: &mut CypherApp, ctx: &egui::Context) {
 
                 let cpu_load_val = app.cpu_load.load(Ordering::Relaxed);
                 let cpu_load_percent = cpu_load_val as f32 / 10.0;
+                let cpu_color = match CpuLoadLevel::from_load(
+                    cpu_load_percent,
+                    app.settings.cpu_warning_threshold,
+                    app.settings.cpu_critical_threshold,
+                ) {
+                    CpuLoadLevel::Normal => app.theme.top_bar.cpu_ok_color,
+                    CpuLoadLevel::Warning => app.theme.top_bar.cpu_warning_color,
+                    CpuLoadLevel::Critical => app.theme.top_bar.cpu_critical_color,
+                };
+                ui.label(RichText::new("●").color(cpu_color));
                 let cpu_text = RichText::new(format!("CPU: {:>5.1}%", cpu_load_percent))
                     .monospace()
                     .color(app.theme.top_bar.text_color);
                 ui.label(cpu_text);
 
+                if app.safe_mode.load(Ordering::Relaxed) {
+                    ui.label(
+                        RichText::new("SAFE MODE")
+                            .monospace()
+                            .color(app.theme.top_bar.xrun_text_color),
+                    )
+                    .on_hover_text("FX, synth and sampler are bypassed. Turn off in Options.");
+                }
+
                 let xruns = app.xrun_count.load(Ordering::Relaxed);
                 let mut xrun_text = RichText::new(format!("Xruns: {}", xruns)).monospace();
                 if xruns > 0 {
@@ -417,6 +506,14 @@ fn draw_looper_button(
                     fill: Color32::TRANSPARENT,
                     stroke: Stroke::new(4.0, progress_color).into(),
                 }));
+
+                if !waveform.is_empty() {
+                    let direction = vec2(end_angle.cos(), end_angle.sin());
+                    ui.painter().line_segment(
+                        [center + direction * base_radius * 0.2, center + direction * base_radius],
+                        Stroke::new(1.5, theme.loopers.playhead_color),
+                    );
+                }
             }
 
             let button_size = vec2(80.0, 30.0);
@@ -603,7 +700,9 @@ fn draw_synth_panel(app: &mut CypherApp, ui: &mut Ui) {
                     app.displayed_synth_master_peak_level,
                     app.theme.instrument_panel.fader_track_bg,
                     &app.theme,
+                    app.settings.faders_in_db,
                 )
+                    .on_hover_text(format!("{} dB", format_db(vol_f32)))
                     .dragged()
                 {
                     app.synth_master_volume
@@ -691,7 +790,9 @@ fn draw_sampler_panel(app: &mut CypherApp, ui: &mut Ui) {
                     app.displayed_sampler_peak_level,
                     app.theme.instrument_panel.fader_track_bg,
                     &app.theme,
+                    app.settings.faders_in_db,
                 )
+                    .on_hover_text(format!("{} dB", format_db(vol_f32)))
                     .dragged()
                 {
                     app.sampler_volume
@@ -783,10 +884,19 @@ fn draw_audio_input_panel(app: &mut CypherApp, ui: &mut Ui) {
                         app.theme.instrument_panel.button_bg
                     })
                     .sense(Sense::click_and_drag());
+                let arm_blocked = !is_armed
+                    && app.settings.block_arm_when_clipping
+                    && app.input_headroom == InputHeadroom::Clipping;
                 let response = ui.add_sized(button_size, arm_button);
-                if response.clicked()
-                    || (response.drag_stopped()
-                    && response.drag_delta().length() < CLICK_DRAG_THRESHOLD)
+                let response = if arm_blocked {
+                    response.on_hover_text("The input is clipping. Lower its gain before arming.")
+                } else {
+                    response
+                };
+                if !arm_blocked
+                    && (response.clicked()
+                        || (response.drag_stopped()
+                            && response.drag_delta().length() < CLICK_DRAG_THRESHOLD))
                 {
                     app.send_command(AudioCommand::ToggleAudioInputArm);
                 }
@@ -828,6 +938,15 @@ fn draw_audio_input_panel(app: &mut CypherApp, ui: &mut Ui) {
                 .show_percentage()
                 .desired_width(ui.available_width() - 20.0);
             ui.add(bar);
+
+            let headroom_warning = match app.input_headroom {
+                InputHeadroom::Ok => None,
+                InputHeadroom::Low => Some(("LOW HEADROOM", app.theme.top_bar.cpu_warning_color)),
+                InputHeadroom::Clipping => Some(("CLIPPING", app.theme.top_bar.cpu_critical_color)),
+            };
+            if let Some((text, color)) = headroom_warning {
+                ui.label(RichText::new(text).monospace().color(color));
+            }
         });
     });
 }
@@ -906,6 +1025,7 @@ fn draw_transport_panel(app: &mut CypherApp, ui: &mut Ui) {
                 {
                     app.clear_all_fx_racks();
                     app.send_command(AudioCommand::ClearAllAndPlay);
+                    app.load_default_fx_chains();
                 }
 
                 // --- Record Button ---
@@ -937,14 +1057,36 @@ fn draw_transport_panel(app: &mut CypherApp, ui: &mut Ui) {
                             let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
                             let filename = format!("LiveRec_{}.wav", timestamp);
                             let path = rec_dir.join(filename);
-                            app.send_command(AudioCommand::StopOutputRecording {
-                                output_path: path.clone(),
-                            });
+                            app.send_command(app.stop_output_recording_command(path.clone()));
                             app.recording_notification =
                                 Some((format!("Saved to {}", path.display()), Instant::now()));
                         }
                     }
                 }
+
+                // --- Stem Record Button ---
+                let stems_text = if app.is_recording_stems {
+                    "■ STEMS"
+                } else {
+                    "● STEMS"
+                };
+                let stems_color = if app.is_recording_stems {
+                    app.theme.transport_controls.record_active_bg
+                } else {
+                    app.theme.transport_controls.record_button_bg
+                };
+                let stems_button = Button::new(RichText::new(stems_text).monospace())
+                    .fill(stems_color)
+                    .sense(Sense::click_and_drag());
+                let response = ui
+                    .add_sized(button_size, stems_button)
+                    .on_hover_text("Records every looper, the synth and the sampler to separate WAV files.");
+                if response.clicked()
+                    || (response.drag_stopped()
+                    && response.drag_delta().length() < CLICK_DRAG_THRESHOLD)
+                {
+                    app.toggle_stem_recording();
+                }
             });
         });
     });
@@ -3,6 +3,8 @@
 use crate::app::CypherApp;
 use crate::fx;
 use crate::looper::NUM_LOOPERS;
+use crate::theory;
+use crate::mixer::{NUM_SEND_BUSES, NUM_SUB_BUSES};
 use crate::settings::{
     ControllableParameter, FullMidiIdentifier, FxParamIdentifier, FxParamName, MidiControlMode,
 };
@@ -237,6 +239,17 @@ pub fn draw_midi_mapping_window(app: &mut CypherApp, ctx: &egui::Context) {
                         }
                     });
 
+                    // --- 88 Keys Section ---
+                    ui.collapsing(RichText::new("88 Keys").strong().color(theme.label_color), |ui| {
+                        for i in 0..theory::MAX_CHORD_SUGGESTIONS {
+                            let param = ControllableParameter::ChordSuggestion(i);
+                            let row_color = if i % 2 == 0 { theme.row_even_bg } else { theme.row_odd_bg };
+                            Frame::new().fill(row_color).show(ui, |ui| {
+                                draw_mapping_row(ui, param, &reverse_lookup, app);
+                            });
+                        }
+                    });
+
                     // --- Looper Triggers Section ---
                     ui.collapsing(RichText::new("Loopers").strong().color(theme.label_color), |ui| {
                         for i in 0..NUM_LOOPERS {
@@ -278,6 +291,8 @@ pub fn draw_midi_mapping_window(app: &mut CypherApp, ctx: &egui::Context) {
                         let all_insertion_points = [
                             (0..NUM_LOOPERS).map(fx::InsertionPoint::Looper).collect::<Vec<_>>(),
                             (0..2).map(fx::InsertionPoint::Synth).collect::<Vec<_>>(),
+                            (0..NUM_SUB_BUSES).map(fx::InsertionPoint::Bus).collect::<Vec<_>>(),
+                            (0..NUM_SEND_BUSES).map(fx::InsertionPoint::Send).collect::<Vec<_>>(),
                             vec![
                                 fx::InsertionPoint::Sampler,
                                 fx::InsertionPoint::Input,
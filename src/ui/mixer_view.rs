@@ -1,9 +1,15 @@
 // src/ui/mixer_view.rs
 
 use crate::app::CypherApp;
-use crate::audio_engine::AudioCommand;
+use crate::audio_engine::{
+    stored_to_lufs, AudioCommand, MAX_LIMITER_LOOKAHEAD_MS, TRUE_PEAK_LATENCY_SAMPLES,
+};
 use crate::fx;
 use crate::looper::NUM_LOOPERS;
+use crate::mixer::{
+    db_to_linear, format_db, format_pan, linear_to_db, parse_pan, MeterPoint, MAX_NUDGE_SAMPLES,
+    NUM_SEND_BUSES, NUM_SUB_BUSES,
+};
 use crate::synth::LfoRateMode;
 use egui::{
     epaint, vec2, Align, Color32, ComboBox, CornerRadius, DragValue, Frame, Layout, Pos2, Rect,
@@ -13,13 +19,35 @@ use std::sync::atomic::Ordering;
 
 const CLICK_DRAG_THRESHOLD: f32 = 5.0;
 
-// --- Helper Functions ---
-fn linear_to_db(linear: f32) -> f32 {
-    if linear <= 1e-6 {
-        -f32::INFINITY
-    } else {
-        20.0 * linear.log10()
+// --- Fader Mapping Helper Functions ---
+const FADER_MAX_LINEAR: f32 = 1.5;
+const FADER_MIN_DB: f32 = -60.0;
+
+/// Maps a normalized fader position (0.0 to 1.0) to a linear gain value.
+/// In dB mode the travel is spread evenly between `FADER_MIN_DB` and the 1.5 ceiling.
+fn fader_pos_to_value(pos: f32, db_scale: bool) -> f32 {
+    let pos = pos.clamp(0.0, 1.0);
+    if !db_scale {
+        return pos * FADER_MAX_LINEAR;
+    }
+    if pos <= 0.0 {
+        return 0.0;
+    }
+    let max_db = linear_to_db(FADER_MAX_LINEAR);
+    db_to_linear(FADER_MIN_DB + pos * (max_db - FADER_MIN_DB)).min(FADER_MAX_LINEAR)
+}
+
+/// The inverse of `fader_pos_to_value`.
+fn value_to_fader_pos(value: f32, db_scale: bool) -> f32 {
+    if !db_scale {
+        return (value / FADER_MAX_LINEAR).clamp(0.0, 1.0);
     }
+    let db = linear_to_db(value);
+    if db.is_infinite() {
+        return 0.0;
+    }
+    let max_db = linear_to_db(FADER_MAX_LINEAR);
+    ((db - FADER_MIN_DB) / (max_db - FADER_MIN_DB)).clamp(0.0, 1.0)
 }
 
 // --- Pitch Mapping Helper Functions ---
@@ -38,14 +66,16 @@ fn fader_value_to_pitch(fader_value: f32) -> f32 {
 }
 
 
-// Custom volume fader widget (vertical)
+// Custom volume fader widget (vertical). `meter_level` is drawn as given, so callers
+// decide whether it is pre- or post-fader.
 fn volume_fader(
     ui: &mut Ui,
     value: &mut f32,
-    peak_level: f32,
+    meter_level: f32,
     theme: &crate::theme::Theme,
     track_color: Color32,
     meter_color: Color32,
+    db_scale: bool,
 ) -> Response {
     let desired_height = ui.available_height().max(0.0);
     let desired_size = vec2(20.0, desired_height);
@@ -55,7 +85,7 @@ fn volume_fader(
     if response.dragged() {
         if let Some(pos) = response.interact_pointer_pos() {
             let relative_y = 1.0 - (pos.y - rect.top()) / rect.height();
-            *value = (relative_y.clamp(0.0, 1.0) * 1.5).clamp(0.0, 1.5);
+            *value = fader_pos_to_value(relative_y, db_scale);
         }
     }
 
@@ -78,14 +108,13 @@ fn volume_fader(
         );
 
         // 2. Draw the peak meter inside the track
-        if peak_level > 0.0 {
-            let post_fader_peak = peak_level * *value;
-            let bar_height = rect.height() * (post_fader_peak / 1.5).clamp(0.0, 1.0);
+        if meter_level > 0.0 {
+            let bar_height = rect.height() * value_to_fader_pos(meter_level, db_scale);
             let bar_rect = Rect::from_min_size(
                 rect.left_bottom() - vec2(0.0, bar_height),
                 vec2(rect.width(), bar_height),
             );
-            let color = if post_fader_peak > 1.0 {
+            let color = if meter_level > 1.0 {
                 theme.mixer.meter_clip_color
             } else {
                 meter_color // Use the passed-in meter color
@@ -95,7 +124,7 @@ fn volume_fader(
 
         // 3. Draw the fader thumb
         let thumb_height = 8.0;
-        let thumb_y = rect.top() + rect.height() * (1.0 - (*value / 1.5).clamp(0.0, 1.0));
+        let thumb_y = rect.top() + rect.height() * (1.0 - value_to_fader_pos(*value, db_scale));
         let thumb_center = Pos2::new(rect.center().x, thumb_y);
         let thumb_rect =
             Rect::from_center_size(thumb_center, vec2(rect.width() + 4.0, thumb_height));
@@ -111,6 +140,21 @@ fn volume_fader(
     response
 }
 
+/// Draws a meter's peak-hold line across a fader drawn by `volume_fader`.
+fn draw_peak_hold(ui: &Ui, fader_rect: Rect, hold_level: f32, theme: &crate::theme::Theme, db_scale: bool) {
+    if hold_level <= 0.0 {
+        return;
+    }
+    let y = fader_rect.bottom() - fader_rect.height() * value_to_fader_pos(hold_level, db_scale);
+    let color = if hold_level > 1.0 {
+        theme.mixer.meter_clip_color
+    } else {
+        theme.global_text_color
+    };
+    ui.painter_at(fader_rect)
+        .hline(fader_rect.x_range(), y, Stroke::new(2.0, color));
+}
+
 fn gain_reduction_meter(
     ui: &mut Ui,
     reduction_normalized: f32,
@@ -149,6 +193,7 @@ pub fn horizontal_volume_fader(
     peak_level: f32,
     track_bg: Color32,
     theme: &crate::theme::Theme,
+    db_scale: bool,
 ) -> Response {
     let desired_size = vec2(ui.available_width() * 0.8, 20.0);
     let (rect, response) = ui.allocate_exact_size(desired_size, Sense::drag());
@@ -156,7 +201,7 @@ pub fn horizontal_volume_fader(
     if response.dragged() {
         if let Some(pos) = response.interact_pointer_pos() {
             let relative_x = (pos.x - rect.left()) / rect.width();
-            *value = (relative_x.clamp(0.0, 1.0) * 1.5).clamp(0.0, 1.5);
+            *value = fader_pos_to_value(relative_x, db_scale);
         }
     }
 
@@ -173,7 +218,7 @@ pub fn horizontal_volume_fader(
 
         if peak_level > 0.0 {
             let post_fader_peak = peak_level * *value;
-            let bar_width = rect.width() * (post_fader_peak / 1.5).clamp(0.0, 1.0);
+            let bar_width = rect.width() * value_to_fader_pos(post_fader_peak, db_scale);
             let bar_rect = Rect::from_min_size(rect.left_top(), vec2(bar_width, rect.height()));
             let color = if post_fader_peak > 1.0 {
                 theme.mixer.meter_clip_color
@@ -184,7 +229,7 @@ pub fn horizontal_volume_fader(
         }
 
         let thumb_width = 8.0;
-        let thumb_x = rect.left() + rect.width() * (*value / 1.5).clamp(0.0, 1.0);
+        let thumb_x = rect.left() + rect.width() * value_to_fader_pos(*value, db_scale);
         let thumb_center = Pos2::new(thumb_x, rect.center().y);
         let thumb_rect =
             Rect::from_center_size(thumb_center, vec2(thumb_width, rect.height() + 4.0));
@@ -205,13 +250,44 @@ fn draw_track_strip(ui: &mut Ui, app: &mut CypherApp, track_id: usize) {
     let mut fx_button_clicked = false;
     let mut mute_button_clicked = false;
     let mut solo_button_clicked = false;
+    let mut solo_safe_clicked = false;
+    let mut meter_point_clicked = false;
 
     // Isolate the lock and copy the data we need for drawing.
-    let (is_muted, is_soloed, mut volume) = {
+    let (
+        is_muted,
+        is_soloed,
+        is_solo_safe,
+        mut volume,
+        mut bus,
+        meter_point,
+        mut pan,
+        mut send_levels,
+        mut nudge_samples,
+        is_reversed,
+    ) = {
         let mixer_state = app.track_mixer_state.read().unwrap();
         let track = &mixer_state.tracks[track_id];
-        (track.is_muted, track.is_soloed, track.volume)
+        let send_levels: [f32; NUM_SEND_BUSES] =
+            std::array::from_fn(|send| track.send_level(send));
+        (
+            track.is_muted,
+            track.is_soloed,
+            track.is_solo_safe,
+            track.volume,
+            track.bus,
+            track.meter_point,
+            track.pan,
+            send_levels,
+            track.nudge_samples,
+            track.is_reversed,
+        )
     };
+    let mut reverse_clicked = false;
+    let original_pan = pan;
+    let original_nudge_samples = nudge_samples;
+    let original_send_levels = send_levels;
+    let original_bus = bus;
 
     ui.with_layout(Layout::bottom_up(Align::Center), |ui| {
         // --- Track Label ---
@@ -226,6 +302,89 @@ fn draw_track_strip(ui: &mut Ui, app: &mut CypherApp, track_id: usize) {
         let half_width = available_width * 0.5;
         let fx_button_size = vec2(half_width, 20.0);
 
+        // --- Output Routing ---
+        let bus_label = |bus: Option<usize>| match bus {
+            Some(b) => format!("Bus {}", b + 1),
+            None => "Master".to_string(),
+        };
+        ComboBox::from_id_salt(("track_output_bus", track_id))
+            .width(available_width)
+            .selected_text(RichText::new(bus_label(bus)).monospace().size(10.0))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut bus, None, bus_label(None));
+                for b in 0..NUM_SUB_BUSES {
+                    ui.selectable_value(&mut bus, Some(b), bus_label(Some(b)));
+                }
+            });
+        ui.add_space(2.0);
+
+        // --- Aux Sends ---
+        ui.horizontal(|ui| {
+            let spacing = ui.style().spacing.item_spacing.x;
+            let send_width = ((available_width - spacing) / NUM_SEND_BUSES as f32).max(0.0);
+            for (send, level) in send_levels.iter_mut().enumerate() {
+                let name = fx::send_name(send);
+                let prefix = name.chars().next().unwrap_or('A');
+                let send_value = DragValue::new(level)
+                    .range(0.0..=1.0)
+                    .speed(0.01)
+                    .custom_formatter(move |v, _| format!("{}{:.0}", prefix, v * 100.0))
+                    .custom_parser(|text| {
+                        let digits = text.trim_start_matches(|c: char| c.is_alphabetic());
+                        digits.trim().parse::<f64>().ok().map(|p| p / 100.0)
+                    });
+                ui.add_sized(vec2(send_width, 18.0), send_value)
+                    .on_hover_text(format!("{} send level (post-fader)", name));
+            }
+        });
+        ui.add_space(2.0);
+
+        // --- Pan ---
+        let pan_value = DragValue::new(&mut pan)
+            .range(-1.0..=1.0)
+            .speed(0.01)
+            .custom_formatter(|value, _| format_pan(value as f32))
+            .custom_parser(|text| parse_pan(text).map(f64::from));
+        let pan_hint = if bus.is_some() {
+            "Pan has no effect while the track feeds a sub-bus"
+        } else {
+            "Stereo position. Drag to pan, double-click to type (e.g. L30, C, R100)"
+        };
+        ui.add_enabled_ui(bus.is_none(), |ui| {
+            ui.add_sized(vec2(available_width, 18.0), pan_value)
+                .on_hover_text(pan_hint)
+                .on_disabled_hover_text(pan_hint);
+        });
+        ui.add_space(2.0);
+
+        // --- Nudge ---
+        let nudge_ms = nudge_samples as f32 / app.active_sample_rate.max(1) as f32 * 1000.0;
+        let nudge_value = DragValue::new(&mut nudge_samples)
+            .range(-MAX_NUDGE_SAMPLES..=MAX_NUDGE_SAMPLES)
+            .speed(1.0)
+            .suffix(" smp");
+        ui.add_sized(vec2(available_width, 18.0), nudge_value).on_hover_text(format!(
+            "Nudge ({:+.1} ms). Positive values play the loop later, negative earlier",
+            nudge_ms
+        ));
+        ui.add_space(2.0);
+
+        // --- Playback Reverse ---
+        let reverse_button = egui::Button::new(RichText::new("REV").monospace().size(10.0))
+            .fill(if is_reversed {
+                app.theme.mixer.solo_on_bg
+            } else {
+                app.theme.mixer.solo_off_bg
+            });
+        if ui
+            .add_sized(vec2(available_width, 18.0), reverse_button)
+            .on_hover_text("Play the loop backwards. The recording itself is not changed")
+            .clicked()
+        {
+            reverse_clicked = true;
+        }
+        ui.add_space(2.0);
+
         // --- FX Button (centered on its own row) ---
         ui.horizontal(|ui| {
             ui.add_space(half_width / 2.0); // Add spacer to center the button
@@ -265,33 +424,55 @@ fn draw_track_strip(ui: &mut Ui, app: &mut CypherApp, track_id: usize) {
                 mute_button_clicked = true;
             }
 
+            let solo_text = RichText::new("S").monospace().size(12.0);
             let solo_button =
-                egui::Button::new(RichText::new("S").monospace().size(12.0))
+                egui::Button::new(if is_solo_safe { solo_text.underline() } else { solo_text })
                     .fill(if is_soloed {
                         app.theme.mixer.solo_on_bg
                     } else {
                         app.theme.mixer.solo_off_bg
                     })
                     .sense(Sense::click_and_drag());
-            let response = ui.add_sized(button_size, solo_button);
+            let response = ui
+                .add_sized(button_size, solo_button)
+                .on_hover_text(if is_solo_safe {
+                    "Solo-safe: keeps playing when other tracks are soloed (right-click to clear)"
+                } else {
+                    "Right-click to make solo-safe"
+                });
             if response.clicked()
                 || (response.drag_stopped()
                 && response.drag_delta().length() < CLICK_DRAG_THRESHOLD)
             {
                 solo_button_clicked = true;
             }
+            if response.secondary_clicked() {
+                solo_safe_clicked = true;
+            }
         });
+        ui.add_space(2.0);
+
+        // --- Meter Point ---
+        let (meter_text, meter_hint) = match meter_point {
+            MeterPoint::PreFader => ("PRE", "Meter shows the loop level before the fader"),
+            MeterPoint::PostFader => ("POST", "Meter shows the level after the fader"),
+        };
+        let meter_button = egui::Button::new(RichText::new(meter_text).monospace().size(10.0))
+            .fill(app.theme.mixer.mute_off_bg)
+            .sense(Sense::click_and_drag());
+        let response = ui
+            .add_sized(vec2(available_width, 16.0), meter_button)
+            .on_hover_text(meter_hint);
+        if response.clicked()
+            || (response.drag_stopped()
+            && response.drag_delta().length() < CLICK_DRAG_THRESHOLD)
+        {
+            meter_point_clicked = true;
+        }
         ui.add_space(4.0);
 
         // --- Volume Readout ---
-        let db_text = {
-            let db = linear_to_db(volume);
-            if db.is_infinite() {
-                "-inf".to_string()
-            } else {
-                format!("{:.1}", db)
-            }
-        };
+        let db_text = format_db(volume);
         ui.label(
             RichText::new(db_text)
                 .monospace()
@@ -309,7 +490,17 @@ fn draw_track_strip(ui: &mut Ui, app: &mut CypherApp, track_id: usize) {
             &app.theme,
             track_color,
             track_color, // Pass track_color for the meter as well
+            app.settings.faders_in_db,
         );
+        if app.settings.meters.peak_hold_ms > 0.0 {
+            draw_peak_hold(
+                ui,
+                fader_response.rect,
+                app.looper_peak_holds[track_id].level,
+                &app.theme,
+                app.settings.faders_in_db,
+            );
+        }
 
         // --- Apply Changes After Drawing ---
         if fader_response.dragged() {
@@ -328,6 +519,154 @@ fn draw_track_strip(ui: &mut Ui, app: &mut CypherApp, track_id: usize) {
     if solo_button_clicked {
         app.send_command(AudioCommand::ToggleMixerSolo(track_id));
     }
+    if solo_safe_clicked {
+        app.send_command(AudioCommand::ToggleMixerSoloSafe(track_id));
+    }
+    if bus != original_bus {
+        app.send_command(AudioCommand::SetMixerTrackBus { track_index: track_id, bus });
+    }
+    for (send_index, &level) in send_levels.iter().enumerate() {
+        if level != original_send_levels[send_index] {
+            app.send_command(AudioCommand::SetMixerTrackSend {
+                track_index: track_id,
+                send_index,
+                level,
+            });
+        }
+    }
+    if reverse_clicked {
+        app.send_command(AudioCommand::ToggleLooperReverse(track_id));
+    }
+    if nudge_samples != original_nudge_samples {
+        app.send_command(AudioCommand::SetLooperNudge {
+            looper_index: track_id,
+            samples: nudge_samples,
+        });
+    }
+    if pan != original_pan {
+        app.send_command(AudioCommand::SetMixerTrackPan { track_index: track_id, pan });
+    }
+    if meter_point_clicked {
+        let meter_point = match meter_point {
+            MeterPoint::PreFader => MeterPoint::PostFader,
+            MeterPoint::PostFader => MeterPoint::PreFader,
+        };
+        app.send_command(AudioCommand::SetMixerTrackMeterPoint {
+            track_index: track_id,
+            meter_point,
+        });
+    }
+}
+
+/// Strip for a sub-bus (`InsertionPoint::Bus`) or a send return (`InsertionPoint::Send`).
+fn draw_bus_strip(ui: &mut Ui, app: &mut CypherApp, point: fx::InsertionPoint) {
+    let bus_fader_bg = app.theme.mixer.fader_track_bg.gamma_multiply(2.0);
+    let mut fx_button_clicked = false;
+    let mut mute_button_clicked = false;
+
+    let (is_muted, mut volume) = {
+        let mixer_state = app.track_mixer_state.read().unwrap();
+        let bus = match point {
+            fx::InsertionPoint::Send(i) => &mixer_state.send_returns[i],
+            fx::InsertionPoint::Bus(i) => &mixer_state.buses[i],
+            _ => return,
+        };
+        (bus.is_muted, bus.volume)
+    };
+    let label = match point {
+        fx::InsertionPoint::Send(i) => fx::send_name(i),
+        _ => point.to_string(),
+    };
+
+    ui.with_layout(Layout::bottom_up(Align::Center), |ui| {
+        ui.label(
+            RichText::new(label)
+                .monospace()
+                .color(app.theme.mixer.label_color),
+        );
+        ui.add_space(4.0);
+
+        let available_width = ui.available_width();
+        let button_size = vec2(available_width * 0.5, 20.0);
+
+        ui.horizontal(|ui| {
+            ui.add_space((available_width - button_size.x) / 2.0);
+            let fx_button = egui::Button::new(RichText::new("FX").monospace().size(12.0))
+                .fill(app.theme.mixer.mute_off_bg)
+                .sense(Sense::click_and_drag());
+            let response = ui.add_sized(button_size, fx_button);
+            if response.clicked()
+                || (response.drag_stopped()
+                && response.drag_delta().length() < CLICK_DRAG_THRESHOLD)
+            {
+                fx_button_clicked = true;
+            }
+        });
+        ui.add_space(2.0);
+
+        ui.horizontal(|ui| {
+            ui.add_space((available_width - button_size.x) / 2.0);
+            let mute_button = egui::Button::new(RichText::new("M").monospace().size(12.0))
+                .fill(if is_muted {
+                    app.theme.mixer.mute_on_bg
+                } else {
+                    app.theme.mixer.mute_off_bg
+                })
+                .sense(Sense::click_and_drag());
+            let response = ui.add_sized(button_size, mute_button);
+            if response.clicked()
+                || (response.drag_stopped()
+                && response.drag_delta().length() < CLICK_DRAG_THRESHOLD)
+            {
+                mute_button_clicked = true;
+            }
+        });
+        ui.add_space(4.0);
+
+        ui.label(
+            RichText::new(format_db(volume))
+                .monospace()
+                .size(10.0)
+                .background_color(app.theme.mixer.fader_track_bg)
+                .color(app.theme.global_text_color),
+        );
+        ui.add_space(5.0);
+
+        if volume_fader(
+            ui,
+            &mut volume,
+            0.0,
+            &app.theme,
+            bus_fader_bg,
+            Color32::TRANSPARENT,
+            app.settings.faders_in_db,
+        )
+            .dragged()
+        {
+            match point {
+                fx::InsertionPoint::Send(send_index) => {
+                    app.send_command(AudioCommand::SetMixerSendReturnVolume { send_index, volume })
+                }
+                fx::InsertionPoint::Bus(bus_index) => {
+                    app.send_command(AudioCommand::SetMixerBusVolume { bus_index, volume })
+                }
+                _ => {}
+            }
+        }
+    });
+
+    if fx_button_clicked {
+        app.handle_fx_button_click(point);
+    }
+    if mute_button_clicked {
+        match point {
+            fx::InsertionPoint::Send(i) => {
+                app.send_command(AudioCommand::ToggleMixerSendReturnMute(i))
+            }
+            fx::InsertionPoint::Bus(i) => app.send_command(AudioCommand::ToggleMixerBusMute(i)),
+            _ => {}
+        }
+    }
 }
 
 fn draw_master_strip(ui: &mut Ui, app: &mut CypherApp) {
@@ -458,15 +797,61 @@ fn draw_master_strip(ui: &mut Ui, app: &mut CypherApp) {
             app.send_command(AudioCommand::ToggleLimiter);
         }
 
-        ui.add_space(4.0);
-        let db_text = {
-            let db = linear_to_db(vol);
-            if db.is_infinite() {
-                "-inf".to_string()
+        let true_peak_button = egui::Button::new(RichText::new("TP").monospace().size(10.0))
+            .fill(if app.limiter_true_peak {
+                app.theme.mixer.limiter_active_bg
             } else {
-                format!("{:.1}", db)
+                app.theme.mixer.mute_off_bg
+            });
+        if ui
+            .add(true_peak_button)
+            .on_hover_text(format!(
+                "True-peak detection: also limits peaks between samples ({} samples of latency)",
+                TRUE_PEAK_LATENCY_SAMPLES
+            ))
+            .clicked()
+        {
+            app.limiter_true_peak = !app.limiter_true_peak;
+            app.send_command(AudioCommand::SetLimiterTruePeak(app.limiter_true_peak));
+        }
+
+        ui.scope(|ui| {
+            let visuals = &mut ui.style_mut().visuals.widgets;
+            visuals.inactive.bg_fill = app.theme.mixer.fader_track_bg;
+            visuals.hovered.bg_fill = app.theme.mixer.fader_track_bg.linear_multiply(1.2);
+            visuals.active.bg_fill = app.theme.mixer.fader_thumb_color;
+
+            let true_peak_ms = if app.limiter_true_peak && app.active_sample_rate > 0 {
+                TRUE_PEAK_LATENCY_SAMPLES as f32 * 1000.0 / app.active_sample_rate as f32
+            } else {
+                0.0
+            };
+            let latency_ms = app.limiter_lookahead_ms + true_peak_ms;
+            let response = ui
+                .add(
+                    DragValue::new(&mut app.limiter_lookahead_ms)
+                        .speed(0.05)
+                        .range(0.0..=MAX_LIMITER_LOOKAHEAD_MS)
+                        .fixed_decimals(1)
+                        .update_while_editing(false)
+                        .prefix("LA ")
+                        .suffix("ms"),
+                )
+                .on_hover_text(format!(
+                    "Lookahead: starts gain reduction before peaks arrive. \
+                     The limiter currently delays the output by {:.1} ms; \
+                     recordings are shifted back to match.",
+                    latency_ms
+                ));
+            // Changing the lookahead resets the limiter's delay line, so a drag is only
+            // applied once it ends rather than at every step.
+            if response.drag_stopped() || (response.changed() && !response.dragged()) {
+                app.send_command(AudioCommand::SetLimiterLookahead(app.limiter_lookahead_ms));
             }
-        };
+        });
+
+        ui.add_space(4.0);
+        let db_text = format_db(vol);
         ui.label(
             RichText::new(db_text)
                 .monospace()
@@ -474,6 +859,9 @@ fn draw_master_strip(ui: &mut Ui, app: &mut CypherApp) {
                 .background_color(app.theme.mixer.fader_track_bg)
                 .color(app.theme.global_text_color),
         );
+        if app.settings.loudness_meter_enabled {
+            draw_loudness_readout(ui, app);
+        }
         ui.add_space(5.0);
 
         let layout = Layout::left_to_right(Align::Min).with_cross_align(Align::Min);
@@ -496,6 +884,7 @@ fn draw_master_strip(ui: &mut Ui, app: &mut CypherApp) {
                 &app.theme,
                 master_fader_bg,
                 app.theme.mixer.meter_normal_color, // Use global theme color
+                app.settings.faders_in_db,
             )
                 .dragged()
             {
@@ -503,16 +892,26 @@ fn draw_master_strip(ui: &mut Ui, app: &mut CypherApp) {
             }
             ui.add_space(2.0);
 
-            if volume_fader(
+            let master_meter_level = app.displayed_master_peak_level * vol;
+            let master_fader = volume_fader(
                 ui,
                 &mut vol,
-                app.displayed_master_peak_level,
+                master_meter_level,
                 &app.theme,
                 master_fader_bg,
                 app.theme.mixer.meter_normal_color, // Use global theme color
-            )
-                .dragged()
-            {
+                app.settings.faders_in_db,
+            );
+            if app.settings.meters.peak_hold_ms > 0.0 {
+                draw_peak_hold(
+                    ui,
+                    master_fader.rect,
+                    app.master_peak_hold.level * vol,
+                    &app.theme,
+                    app.settings.faders_in_db,
+                );
+            }
+            if master_fader.dragged() {
                 app.send_command(AudioCommand::SetMasterVolume(vol));
             }
         });
@@ -523,6 +922,55 @@ fn draw_master_strip(ui: &mut Ui, app: &mut CypherApp) {
     }
 }
 
+/// Momentary, short-term and integrated loudness of the master output relative to the
+/// reference level. Click to restart the measurement.
+fn draw_loudness_readout(ui: &mut Ui, app: &mut CypherApp) {
+    let reference = app.settings.loudness_reference_lufs;
+    // The strip is laid out bottom-up, so this reads M, S, I from the top.
+    let readings = [
+        ("I", "Integrated", &app.loudness.integrated),
+        ("S", "Short-term (3 s)", &app.loudness.short_term),
+        ("M", "Momentary (400 ms)", &app.loudness.momentary),
+    ];
+    let mut reset_clicked = false;
+    for (tag, name, stored) in readings {
+        let lufs = stored_to_lufs(stored.load(Ordering::Relaxed));
+        let (text, color) = match lufs {
+            Some(lufs) => {
+                let color = if lufs > reference {
+                    app.theme.mixer.meter_clip_color
+                } else {
+                    app.theme.global_text_color
+                };
+                (format!("{} {:.1} LUFS", tag, lufs), color)
+            }
+            None => (format!("{} -- LUFS", tag), app.theme.global_text_color),
+        };
+        let hover_text = match lufs {
+            Some(lufs) => format!(
+                "{} loudness: {:.1} LUFS ({:+.1} LU from the {:.1} LUFS reference).\nClick to reset.",
+                name,
+                lufs,
+                lufs - reference,
+                reference
+            ),
+            None => format!("No reading yet. Reference: {:.1} LUFS.", reference),
+        };
+        let label = egui::Label::new(
+            RichText::new(text)
+                .monospace()
+                .size(10.0)
+                .background_color(app.theme.mixer.fader_track_bg)
+                .color(color),
+        )
+        .sense(Sense::click());
+        reset_clicked |= ui.add(label).on_hover_text(hover_text).clicked();
+    }
+    if reset_clicked {
+        app.send_command(AudioCommand::ResetLoudnessMeter);
+    }
+}
+
 fn draw_atmo_strip(ui: &mut Ui, app: &mut CypherApp) {
     let mut vol = app.atmo_master_volume.load(Ordering::Relaxed) as f32 / 1_000_000.0;
     let atmo_fader_bg = app.theme.mixer.fader_track_bg.gamma_multiply(3.0);
@@ -555,14 +1003,7 @@ fn draw_atmo_strip(ui: &mut Ui, app: &mut CypherApp) {
 
         ui.add_space(4.0);
 
-        let db_text = {
-            let db = linear_to_db(vol);
-            if db.is_infinite() {
-                "-inf".to_string()
-            } else {
-                format!("{:.1}", db)
-            }
-        };
+        let db_text = format_db(vol);
         ui.label(
             RichText::new(db_text)
                 .monospace()
@@ -572,13 +1013,15 @@ fn draw_atmo_strip(ui: &mut Ui, app: &mut CypherApp) {
         );
         ui.add_space(5.0);
 
+        let atmo_meter_level = app.displayed_atmo_peak_level * vol;
         if volume_fader(
             ui,
             &mut vol,
-            app.displayed_atmo_peak_level,
+            atmo_meter_level,
             &app.theme,
             atmo_fader_bg,
             app.theme.mixer.meter_normal_color,
+            app.settings.faders_in_db,
         )
             .dragged()
         {
@@ -593,14 +1036,15 @@ fn draw_atmo_strip(ui: &mut Ui, app: &mut CypherApp) {
 fn draw_metronome_strip(ui: &mut Ui, app: &mut CypherApp) {
     let metro_fader_bg = app.theme.mixer.fader_track_bg.gamma_multiply(3.0);
     let mut mute_button_clicked = false;
+    let mut accent_button_clicked = false;
     let mut pitch_changed = false;
     let mut accent_pitch_changed = false;
     let mut volume_changed = false;
 
-    let (is_muted, mut volume, pitch_hz, accent_pitch_hz) = {
+    let (is_muted, mut volume, pitch_hz, accent_pitch_hz, accent_first) = {
         let mixer_state = app.track_mixer_state.read().unwrap();
         let metro = &mixer_state.metronome;
-        (metro.is_muted, metro.volume, metro.pitch_hz, metro.accent_pitch_hz)
+        (metro.is_muted, metro.volume, metro.pitch_hz, metro.accent_pitch_hz, metro.accent_first)
     };
 
     let mut pitch_fader_val = pitch_to_fader_value(pitch_hz);
@@ -612,9 +1056,10 @@ fn draw_metronome_strip(ui: &mut Ui, app: &mut CypherApp) {
         ui.add_space(4.0);
 
         let available_width = ui.available_width();
-        let button_size = vec2(available_width * 0.5, 20.0);
         ui.horizontal(|ui| {
-            ui.add_space((available_width - button_size.x) / 2.0);
+            let spacing = ui.style().spacing.item_spacing.x;
+            let button_width = ((available_width - spacing) / 2.0).max(0.0);
+            let button_size = vec2(button_width, 20.0);
             let mute_button = egui::Button::new(RichText::new("M").monospace().size(12.0))
                 .fill(if is_muted {
                     app.theme.mixer.mute_on_bg
@@ -629,6 +1074,23 @@ fn draw_metronome_strip(ui: &mut Ui, app: &mut CypherApp) {
             {
                 mute_button_clicked = true;
             }
+
+            let accent_button = egui::Button::new(RichText::new("A").monospace().size(12.0))
+                .fill(if accent_first {
+                    app.theme.mixer.solo_on_bg
+                } else {
+                    app.theme.mixer.solo_off_bg
+                })
+                .sense(Sense::click_and_drag());
+            let response = ui
+                .add_sized(button_size, accent_button)
+                .on_hover_text("Accent the first beat of each bar");
+            if response.clicked()
+                || (response.drag_stopped()
+                && response.drag_delta().length() < CLICK_DRAG_THRESHOLD)
+            {
+                accent_button_clicked = true;
+            }
         });
         ui.add_space(4.0); // Space between Mute button and labels.
 
@@ -681,19 +1143,26 @@ fn draw_metronome_strip(ui: &mut Ui, app: &mut CypherApp) {
             let side_margin = (ui.available_width() - total_fader_group_width).max(0.0) / 2.0;
             ui.add_space(side_margin);
 
-            if volume_fader(ui, &mut volume, 0.0, &app.theme, metro_fader_bg, Color32::TRANSPARENT).dragged() {
+            if volume_fader(ui, &mut volume, 0.0, &app.theme, metro_fader_bg, Color32::TRANSPARENT, app.settings.faders_in_db).dragged() {
                 volume_changed = true;
             }
-            if volume_fader(ui, &mut pitch_fader_val, 0.0, &app.theme, metro_fader_bg, Color32::TRANSPARENT).dragged() {
+            if volume_fader(ui, &mut pitch_fader_val, 0.0, &app.theme, metro_fader_bg, Color32::TRANSPARENT, false).dragged() {
                 pitch_changed = true;
             }
-            if volume_fader(ui, &mut accent_pitch_fader_val, 0.0, &app.theme, metro_fader_bg, Color32::TRANSPARENT).dragged() {
+            if volume_fader(ui, &mut accent_pitch_fader_val, 0.0, &app.theme, metro_fader_bg, Color32::TRANSPARENT, false).dragged() {
                 accent_pitch_changed = true;
             }
         });
     });
 
     if mute_button_clicked { app.send_command(AudioCommand::ToggleMetronomeMute); }
+    if accent_button_clicked {
+        app.send_command(AudioCommand::SetMetronome {
+            enabled: !is_muted,
+            volume,
+            accent_first: !accent_first,
+        });
+    }
     if volume_changed { app.send_command(AudioCommand::SetMetronomeVolume(volume)); }
     if pitch_changed {
         let new_pitch_hz = fader_value_to_pitch(pitch_fader_val);
@@ -705,6 +1174,133 @@ fn draw_metronome_strip(ui: &mut Ui, app: &mut CypherApp) {
     }
 }
 
+fn draw_sidechain_controls(ui: &mut Ui, app: &mut CypherApp) {
+    let original = app.track_mixer_state.read().unwrap().sidechain;
+    let mut sidechain = original;
+    let track_label = |track: Option<usize>| match track {
+        Some(t) => format!("Track {}", t + 1),
+        None => "Off".to_string(),
+    };
+
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Sidechain").monospace().color(app.theme.mixer.label_color));
+        ComboBox::from_id_salt("sidechain_source")
+            .selected_text(track_label(sidechain.source))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut sidechain.source, None, track_label(None));
+                for t in 0..NUM_LOOPERS {
+                    ui.selectable_value(&mut sidechain.source, Some(t), track_label(Some(t)));
+                }
+            })
+            .response
+            .on_hover_text("Track whose level ducks the targets");
+
+        ui.add_enabled_ui(sidechain.source.is_some(), |ui| {
+            ui.label(RichText::new("Ducks").color(app.theme.mixer.label_color));
+            for t in 0..NUM_LOOPERS {
+                if sidechain.source == Some(t) {
+                    continue;
+                }
+                let is_target = sidechain.is_target(t);
+                let button = egui::Button::new(RichText::new(format!("{}", t + 1)).monospace())
+                    .fill(if is_target {
+                        app.theme.mixer.solo_on_bg
+                    } else {
+                        app.theme.mixer.solo_off_bg
+                    });
+                if ui.add(button).on_hover_text(track_label(Some(t))).clicked() {
+                    sidechain.toggle_target(t);
+                }
+            }
+            ui.separator();
+            ui.add(
+                DragValue::new(&mut sidechain.amount)
+                    .range(0.0..=1.0)
+                    .speed(0.01)
+                    .custom_formatter(|v, _| format!("Amt {:.0}%", v * 100.0)),
+            )
+            .on_hover_text("Gain reduction when the source is at full level");
+            ui.add(
+                DragValue::new(&mut sidechain.attack_ms)
+                    .range(0.1..=100.0)
+                    .speed(0.1)
+                    .prefix("Atk ")
+                    .suffix(" ms"),
+            );
+            ui.add(
+                DragValue::new(&mut sidechain.release_ms)
+                    .range(10.0..=1000.0)
+                    .speed(1.0)
+                    .prefix("Rel ")
+                    .suffix(" ms"),
+            );
+        });
+    });
+
+    if sidechain != original {
+        app.send_command(AudioCommand::SetMixerSidechain(sidechain));
+    }
+}
+
+fn draw_spectrum(ui: &mut Ui, app: &CypherApp) {
+    let desired_size = vec2(ui.available_width(), 60.0);
+    let (rect, _) = ui.allocate_exact_size(desired_size, Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, app.theme.mixer.fader_track_bg);
+
+    let bands = &app.displayed_spectrum;
+    if bands.is_empty() {
+        return;
+    }
+    let band_width = rect.width() / bands.len() as f32;
+    for (i, &level) in bands.iter().enumerate() {
+        if level <= 0.0 {
+            continue;
+        }
+        let left = rect.left() + i as f32 * band_width;
+        let bar = Rect::from_min_max(
+            Pos2::new(left + 1.0, rect.bottom() - rect.height() * level),
+            Pos2::new(left + band_width - 1.0, rect.bottom()),
+        );
+        painter.rect_filled(bar, 0.0, app.theme.mixer.meter_normal_color);
+    }
+}
+
+fn draw_correlation_meter(ui: &mut Ui, app: &CypherApp) {
+    let correlation = app.displayed_correlation.clamp(-1.0, 1.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Phase").monospace().color(app.theme.mixer.label_color));
+        let desired_size = vec2(ui.available_width() - 48.0, 12.0);
+        let (rect, response) = ui.allocate_exact_size(desired_size, Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, app.theme.mixer.fader_track_bg);
+
+        let center_x = rect.center().x;
+        let value_x = center_x + correlation * rect.width() * 0.5;
+        let bar = Rect::from_x_y_ranges(
+            center_x.min(value_x)..=center_x.max(value_x),
+            rect.y_range(),
+        );
+        // Negative correlation means the channels cancel when summed to mono.
+        let color = if correlation < 0.0 {
+            app.theme.mixer.meter_clip_color
+        } else {
+            app.theme.mixer.meter_normal_color
+        };
+        painter.rect_filled(bar, 0.0, color);
+        painter.vline(center_x, rect.y_range(), Stroke::new(1.0, app.theme.mixer.label_color));
+        response.on_hover_text(
+            "L/R correlation of the master: +1 is mono, 0 is wide, below 0 cancels in mono",
+        );
+
+        ui.label(
+            RichText::new(format!("{:+.2}", correlation))
+                .monospace()
+                .color(app.theme.mixer.label_color),
+        );
+    });
+}
+
 pub fn draw_mixer_panel(app: &mut CypherApp, ui: &mut Ui) {
     let frame_style = Frame::new().fill(app.theme.mixer.panel_background);
     ui.group(|ui| {
@@ -719,7 +1315,8 @@ pub fn draw_mixer_panel(app: &mut CypherApp, ui: &mut Ui) {
 
             let stroke = Stroke::new(1.0, ui.style().visuals.window_stroke.color);
 
-            ui.columns(NUM_LOOPERS + 3, |columns| {
+            const FIXED_STRIPS_START: usize = NUM_LOOPERS + NUM_SUB_BUSES + NUM_SEND_BUSES;
+            ui.columns(FIXED_STRIPS_START + 3, |columns| {
                 // Draw Looper Tracks with separators
                 for i in 0..NUM_LOOPERS {
                     let column_ui = &mut columns[i];
@@ -736,8 +1333,27 @@ pub fn draw_mixer_panel(app: &mut CypherApp, ui: &mut Ui) {
                     );
                 }
 
+                // Draw Sub-Bus and Send Return Strips with separators
+                let bus_points = (0..NUM_SUB_BUSES)
+                    .map(fx::InsertionPoint::Bus)
+                    .chain((0..NUM_SEND_BUSES).map(fx::InsertionPoint::Send));
+                for (b, point) in bus_points.enumerate() {
+                    let column_ui = &mut columns[NUM_LOOPERS + b];
+                    let vline_y_range = column_ui.clip_rect().y_range();
+
+                    draw_bus_strip(column_ui, app, point);
+
+                    let painter = column_ui.painter();
+                    let rect = column_ui.min_rect();
+                    painter.vline(
+                        rect.right() + column_ui.style().spacing.item_spacing.x / 2.0,
+                        vline_y_range,
+                        stroke,
+                    );
+                }
+
                 // Draw Atmo Strip and its separator
-                let atmo_column_ui = &mut columns[NUM_LOOPERS];
+                let atmo_column_ui = &mut columns[FIXED_STRIPS_START];
                 let vline_y_range = atmo_column_ui.clip_rect().y_range();
                 draw_atmo_strip(atmo_column_ui, app);
 
@@ -752,10 +1368,10 @@ pub fn draw_mixer_panel(app: &mut CypherApp, ui: &mut Ui) {
                 // --- END OF ADDED CODE ---
 
                 // Draw Metronome Strip (no separator needed after it, as Master draws one before)
-                draw_metronome_strip(&mut columns[NUM_LOOPERS + 1], app);
+                draw_metronome_strip(&mut columns[FIXED_STRIPS_START + 1], app);
 
                 // Draw Master Strip with its separator
-                let master_column_ui = &mut columns[NUM_LOOPERS + 2];
+                let master_column_ui = &mut columns[FIXED_STRIPS_START + 2];
                 let vline_y_range = master_column_ui.clip_rect().y_range();
                 let painter = master_column_ui.painter();
                 let rect = master_column_ui.min_rect();
@@ -766,6 +1382,11 @@ pub fn draw_mixer_panel(app: &mut CypherApp, ui: &mut Ui) {
                 );
                 draw_master_strip(master_column_ui, app);
             });
+            ui.separator();
+            draw_sidechain_controls(ui, app);
+            ui.separator();
+            draw_spectrum(ui, app);
+            draw_correlation_meter(ui, app);
         });
     });
 }
\ No newline at end of file
@@ -9,6 +9,7 @@ mod midi_mapping_view;
 mod about_view;
 mod fx_editor_view;
 mod atmo_view;
+mod chord_style_editor_view;
 // Added
 
 pub use main_view::draw_main_view;
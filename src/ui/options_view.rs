@@ -1,8 +1,19 @@
 // src/ui/options_view.rs
 
-use crate::app::CypherApp;
+use crate::app::{CypherApp, PerformanceHistory, CPU_HISTORY_LEN};
+use crate::audio_engine::{
+    AudioCommand, MAX_ENGINE_SWAP_FADE_MS, MAX_INPUT_LATENCY_MS, MAX_LOOP_CROSSFADE_MS,
+};
+use crate::sampler::MAX_DECLICK_MS;
+use crate::settings::{
+    AppSettings, ArmPolicy, MeterBallistics, LoopSyncSource, OutputClipMode, ProgramChangeTarget, RecordingFormat,
+};
+use crate::synth::{MAX_TUNING_REFERENCE_HZ, MIN_TUNING_REFERENCE_HZ};
+use crate::theme::Theme;
 use cpal::traits::DeviceTrait;
-use egui::{Button, Checkbox, DragValue, Frame, Grid, RichText, ScrollArea, Slider, Window};
+use egui::{
+    pos2, vec2, Button, Checkbox, CornerRadius, DragValue, Frame, Grid, RichText, ScrollArea, Sense, Slider, Stroke, Ui, Window,
+};
 use std::sync::atomic::Ordering;
 
 pub fn draw_options_window(app: &mut CypherApp, ctx: &egui::Context) {
@@ -12,6 +23,14 @@ pub fn draw_options_window(app: &mut CypherApp, ctx: &egui::Context) {
     let mut host_changed = false;
     let mut close_options_and_open_about = false;
     let mut export_codebase_clicked = false; // <-- 1. FLAG DECLARED HERE
+    let mut soft_reset_clicked = false;
+    let mut tuning_changed = false;
+    let mut loop_crossfade_changed = false;
+    let mut monitor_while_stopped_changed = false;
+    let mut lfo_transport_sync_changed = false;
+    let mut sample_declick_changed = false;
+    let mut engine_swap_fade_changed = false;
+    let mut output_clip_mode_changed = false;
 
     Window::new("Options")
         .open(&mut app.options_window_open)
@@ -141,6 +160,114 @@ pub fn draw_options_window(app: &mut CypherApp, ctx: &egui::Context) {
 
             ui.add_space(8.0);
 
+            // 4. Program Change Target
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Program Change Loads").color(app.theme.options_window.label_color));
+                let current = app.settings.program_change_target;
+                let label = |target: ProgramChangeTarget| match target {
+                    ProgramChangeTarget::SynthPresets => "Synth Presets",
+                    ProgramChangeTarget::SamplerKits => "Sampler Kits",
+                };
+                egui::ComboBox::new("program_change_target_combo", "")
+                    .selected_text(label(current))
+                    .show_ui(ui, |ui| {
+                        for target in [ProgramChangeTarget::SynthPresets, ProgramChangeTarget::SamplerKits] {
+                            ui.selectable_value(&mut app.settings.program_change_target, target, label(target));
+                        }
+                    });
+            });
+
+            // 5. Loop Sync Source
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Loop Sync Source").color(app.theme.options_window.label_color));
+                let current = app.settings.loop_sync_source;
+                let label = |source: LoopSyncSource| match source {
+                    LoopSyncSource::Internal => "Internal",
+                    LoopSyncSource::MidiClock => "MIDI Clock",
+                };
+                egui::ComboBox::new("loop_sync_source_combo", "")
+                    .selected_text(label(current))
+                    .show_ui(ui, |ui| {
+                        for source in [LoopSyncSource::Internal, LoopSyncSource::MidiClock] {
+                            ui.selectable_value(&mut app.settings.loop_sync_source, source, label(source));
+                        }
+                    })
+                    .response
+                    .on_hover_text("MIDI Clock sets the loop length to one bar at the external tempo and follows Start/Stop/Continue.");
+                app.follow_midi_clock.store(
+                    app.settings.loop_sync_source == LoopSyncSource::MidiClock,
+                    Ordering::Relaxed,
+                );
+            });
+
+            // 6. Arm Policy
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Looper Arming").color(app.theme.options_window.label_color));
+                let label = |policy: ArmPolicy| match policy {
+                    ArmPolicy::Exclusive => "Exclusive",
+                    ArmPolicy::Multi => "Multi-Arm",
+                };
+                egui::ComboBox::new("arm_policy_combo", "")
+                    .selected_text(label(app.settings.arm_policy))
+                    .show_ui(ui, |ui| {
+                        for policy in [ArmPolicy::Exclusive, ArmPolicy::Multi] {
+                            ui.selectable_value(&mut app.settings.arm_policy, policy, label(policy));
+                        }
+                    })
+                    .response
+                    .on_hover_text("Exclusive disarms other loopers when one is armed. Multi-Arm lets several loopers start recording on the same input.");
+                app.exclusive_arm.store(
+                    app.settings.arm_policy == ArmPolicy::Exclusive,
+                    Ordering::Relaxed,
+                );
+            });
+
+            // 7. Recording Format
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Recording Format").color(app.theme.options_window.label_color));
+                egui::ComboBox::new("recording_format_combo", "")
+                    .selected_text(app.settings.recording_format.to_string())
+                    .show_ui(ui, |ui| {
+                        for format in RecordingFormat::ALL {
+                            ui.selectable_value(&mut app.settings.recording_format, format, format.to_string());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Bit depth for live recordings, stems and saved session loops. Sessions in any format load back.");
+            });
+
+            ui.horizontal(|ui| {
+                let trim = &mut app.settings.trim_silence;
+                ui.checkbox(&mut trim.enabled, "Trim Silence")
+                    .on_hover_text("Cuts silence from the start and end of live recordings. Turn off to keep fade-ins intact.");
+                ui.add_enabled(
+                    trim.enabled,
+                    DragValue::new(&mut trim.rms_threshold).range(0.0001..=0.1).speed(0.0005).max_decimals(4),
+                )
+                    .on_hover_text("RMS level above which audio counts as sound.");
+                ui.add_enabled(
+                    trim.enabled,
+                    DragValue::new(&mut trim.required_blocks).range(1..=32).suffix(" blocks"),
+                )
+                    .on_hover_text("Consecutive 512-sample blocks of sound needed before the recording starts or ends.");
+            });
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut app.settings.normalize_recordings, "Normalize Recordings")
+                    .on_hover_text("Scales each live recording so its peak hits the target level after silence is trimmed.");
+                ui.add_enabled(
+                    app.settings.normalize_recordings,
+                    DragValue::new(&mut app.settings.normalize_target_db).range(-24.0..=0.0).speed(0.1).suffix(" dBFS"),
+                );
+                ui.add_enabled(
+                    app.settings.recording_format == RecordingFormat::Int16,
+                    Checkbox::new(&mut app.settings.dither_recordings, "Dither"),
+                )
+                    .on_hover_text("Adds TPDF dither before 16-bit quantization.");
+            });
+
+            ui.add_space(8.0);
+
             if ui.add(Button::new("MIDI Control Setup").fill(app.theme.options_window.widget_bg)).clicked() {
                 app.midi_mapping_window_open = true;
             }
@@ -247,13 +374,189 @@ pub fn draw_options_window(app: &mut CypherApp, ctx: &egui::Context) {
                     ui.end_row();
 
                     let mut comp_f32 = app.input_latency_compensation_ms.load(Ordering::Relaxed) as f32 / 100.0;
-                    let slider = Slider::new(&mut comp_f32, 0.0..=50.0).suffix(" ms");
-                    if ui.add(slider).on_hover_text("Adds a small safety buffer to the audio input to prevent crackling. Higher values are more stable but increase latency.").changed() {
+                    let slider = Slider::new(&mut comp_f32, 0.0..=MAX_INPUT_LATENCY_MS).suffix(" ms");
+                    if ui.add(slider).on_hover_text("Adds a small safety buffer to the audio input to prevent crackling. Higher values are more stable but increase latency. Recordings and overdubs are shifted back by the same amount so they stay in time.").changed() {
                         app.input_latency_compensation_ms.store((comp_f32 * 100.0).round() as u32, Ordering::Relaxed);
                     }
                     ui.label(RichText::new("Input Safety Buffer").color(app.theme.options_window.label_color));
                     ui.end_row();
 
+                    let tuning = DragValue::new(&mut app.settings.tuning_reference_hz)
+                        .range(MIN_TUNING_REFERENCE_HZ..=MAX_TUNING_REFERENCE_HZ)
+                        .speed(0.1)
+                        .suffix(" Hz");
+                    if ui.add(tuning).on_hover_text("Frequency of A4 for the synth and sampler. Standard pitch is 440 Hz.").changed() {
+                        tuning_changed = true;
+                    }
+                    ui.label(RichText::new("Tuning Reference (A4)").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    let crossfade = DragValue::new(&mut app.settings.loop_crossfade_ms)
+                        .range(0.0..=MAX_LOOP_CROSSFADE_MS)
+                        .speed(0.1)
+                        .suffix(" ms");
+                    if ui.add(crossfade).on_hover_text("Crossfades the wrap point of recorded and overdubbed loops to remove clicks. 0 turns it off.").changed() {
+                        loop_crossfade_changed = true;
+                    }
+                    ui.label(RichText::new("Loop Seam Crossfade").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    let declick = DragValue::new(&mut app.settings.sample_declick_ms)
+                        .range(0.0..=MAX_DECLICK_MS)
+                        .speed(0.05)
+                        .suffix(" ms");
+                    if ui.add(declick).on_hover_text("A tiny fade at the start and end of pad and sampler playback, so samples that don't start or end at zero don't click. 0 turns it off.").changed() {
+                        sample_declick_changed = true;
+                    }
+                    ui.label(RichText::new("Sample Declick").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    let swap_fade = DragValue::new(&mut app.settings.engine_swap_fade_ms)
+                        .range(0.0..=MAX_ENGINE_SWAP_FADE_MS)
+                        .speed(0.5)
+                        .suffix(" ms");
+                    if ui.add(swap_fade).on_hover_text("Crossfades the old and new engine when a synth engine slot switches between wavetable and sampler, so sounding notes fade out instead of being cut.").changed() {
+                        engine_swap_fade_changed = true;
+                    }
+                    ui.label(RichText::new("Engine Swap Fade").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    let is_monitoring = app.settings.monitor_while_stopped;
+                    let button_color = if is_monitoring { app.theme.options_window.bpm_rounding_on_bg } else { app.theme.options_window.widget_bg };
+                    if ui.add(Button::new("Monitor While Stopped").fill(button_color))
+                        .on_hover_text("Keeps the synth, sampler and monitored input audible while the transport is paused. Loopers are always silent when paused.")
+                        .clicked()
+                    {
+                        app.settings.monitor_while_stopped = !is_monitoring;
+                        monitor_while_stopped_changed = true;
+                    }
+                    ui.label(RichText::new("Live Sources When Paused").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    let is_lfo_synced = app.settings.lfo_transport_sync;
+                    let button_color = if is_lfo_synced { app.theme.options_window.bpm_rounding_on_bg } else { app.theme.options_window.widget_bg };
+                    if ui.add(Button::new("Retrigger on Downbeat").fill(button_color))
+                        .on_hover_text("Restarts every tempo-synced synth LFO at the start of each loop so modulation stays in phase with the transport.")
+                        .clicked()
+                    {
+                        app.settings.lfo_transport_sync = !is_lfo_synced;
+                        lfo_transport_sync_changed = true;
+                    }
+                    ui.label(RichText::new("Synced LFOs").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    let is_db = app.settings.faders_in_db;
+                    let button_color = if is_db { app.theme.options_window.bpm_rounding_on_bg } else { app.theme.options_window.widget_bg };
+                    if ui.add(Button::new("dB Faders").fill(button_color))
+                        .on_hover_text("Volume faders travel in decibels instead of linear gain.")
+                        .clicked()
+                    {
+                        app.settings.faders_in_db = !is_db;
+                    }
+                    ui.label(RichText::new("Fader Scale").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut app.settings.cpu_warning_threshold).range(0.0..=100.0).suffix("%"))
+                            .on_hover_text("CPU load at which the top bar indicator turns yellow.");
+                        let min_critical = app.settings.cpu_warning_threshold;
+                        ui.add(egui::DragValue::new(&mut app.settings.cpu_critical_threshold).range(min_critical..=100.0).suffix("%"))
+                            .on_hover_text("CPU load at which the top bar indicator turns red.");
+                    });
+                    ui.label(RichText::new("CPU Warn / Critical").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    ui.horizontal(|ui| {
+                        let meters = &mut app.settings.meters;
+                        egui::ComboBox::new("meter_ballistics_combo", "")
+                            .selected_text(meters.ballistics.to_string())
+                            .show_ui(ui, |ui| {
+                                for ballistics in MeterBallistics::ALL {
+                                    ui.selectable_value(&mut meters.ballistics, ballistics, ballistics.to_string());
+                                }
+                            })
+                            .response
+                            .on_hover_text("How the level meters move. Peak uses the rise and fall times set here; PPM and VU follow their broadcast standards.");
+                        ui.add_enabled_ui(meters.ballistics == MeterBallistics::Peak, |ui| {
+                            ui.add(DragValue::new(&mut meters.attack_ms).range(0.0..=500.0).speed(1.0).suffix(" ms"))
+                                .on_hover_text("Rise time. 0 jumps straight to each new peak.");
+                            ui.add(DragValue::new(&mut meters.release_ms).range(10.0..=5000.0).speed(5.0).suffix(" ms"))
+                                .on_hover_text("Fall time.");
+                        });
+                        ui.add(DragValue::new(&mut meters.peak_hold_ms).range(0.0..=10000.0).speed(10.0).suffix(" ms"))
+                            .on_hover_text("How long the mixer's peak-hold line stays at the highest level. 0 hides it.");
+                    });
+                    ui.label(RichText::new("Meters (Rise / Fall / Hold)").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    ui.add(DragValue::new(&mut app.settings.autosave_interval_secs).range(0..=3600).suffix(" s"))
+                        .on_hover_text("How often the session is autosaved while any looper holds audio. A crash can be recovered at the next launch. 0 turns it off.");
+                    ui.label(RichText::new("Autosave Interval").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut app.settings.input_headroom_warning_db).range(-24.0..=0.0).speed(0.1).suffix(" dB"))
+                            .on_hover_text("Input peak at which the Audio Input panel warns of low headroom.");
+                        let is_blocking = app.settings.block_arm_when_clipping;
+                        let button_color = if is_blocking { app.theme.options_window.bpm_rounding_on_bg } else { app.theme.options_window.widget_bg };
+                        if ui.add(Button::new("Block Arm").fill(button_color))
+                            .on_hover_text("Refuses to arm the input while it is clipping, so a take isn't ruined.")
+                            .clicked()
+                        {
+                            app.settings.block_arm_when_clipping = !is_blocking;
+                        }
+                    });
+                    ui.label(RichText::new("Input Headroom Warning").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    ui.horizontal(|ui| {
+                        let is_enabled = app.settings.loudness_meter_enabled;
+                        let button_color = if is_enabled { app.theme.options_window.bpm_rounding_on_bg } else { app.theme.options_window.widget_bg };
+                        if ui.add(Button::new("LUFS").fill(button_color))
+                            .on_hover_text("Shows the integrated loudness (ITU-R BS.1770) of the master output in the mixer.")
+                            .clicked()
+                        {
+                            app.settings.loudness_meter_enabled = !is_enabled;
+                            app.loudness_meter_enabled.store(!is_enabled, Ordering::Relaxed);
+                        }
+                        ui.add(egui::DragValue::new(&mut app.settings.loudness_reference_lufs).range(-36.0..=0.0).speed(0.1).suffix(" LUFS"))
+                            .on_hover_text("Reference level. The master readout shows the difference from this target.");
+                    });
+                    ui.label(RichText::new("Loudness Meter / Reference").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    egui::ComboBox::new("output_clip_mode_combo", "")
+                        .selected_text(app.settings.output_clip_mode.to_string())
+                        .show_ui(ui, |ui| {
+                            for mode in OutputClipMode::ALL {
+                                if ui.selectable_value(&mut app.settings.output_clip_mode, mode, mode.to_string()).changed() {
+                                    output_clip_mode_changed = true;
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("The last stage of the master output, applied even with the limiter off. Soft Clip rounds off peaks near full scale instead of cutting them flat.");
+                    ui.label(RichText::new("Output Safety").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    let safe_mode = app.safe_mode.load(Ordering::Relaxed);
+                    let button_color = if safe_mode { app.theme.options_window.bpm_rounding_on_bg } else { app.theme.options_window.widget_bg };
+                    if ui.add(Button::new("Safe Mode").fill(button_color))
+                        .on_hover_text("Bypasses all FX, the synth, sampler, atmosphere, metronome and limiter so only the raw loopers and input play. Use it to tell DSP problems from device problems.")
+                        .clicked()
+                    {
+                        app.safe_mode.store(!safe_mode, Ordering::Relaxed);
+                    }
+                    ui.label(RichText::new("Troubleshooting").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
+                    let reset_button = Button::new("Reset Engine").fill(app.theme.options_window.widget_bg);
+                    if ui.add(reset_button).on_hover_text("Stops all voices and clears FX tails and limiter state without reloading the session.").clicked() {
+                        soft_reset_clicked = true;
+                    }
+                    ui.label(RichText::new("Panic").color(app.theme.options_window.label_color));
+                    ui.end_row();
+
                     let is_active = app.settings.bpm_rounding;
                     let button_color = if is_active { app.theme.options_window.bpm_rounding_on_bg } else { app.theme.options_window.widget_bg };
                     let button = Button::new("BPM Rounding").fill(button_color);
@@ -285,6 +588,11 @@ pub fn draw_options_window(app: &mut CypherApp, ctx: &egui::Context) {
                     ui.end_row();
                 });
 
+            ui.add_space(10.0);
+            ui.collapsing(RichText::new("Performance").color(app.theme.options_window.label_color), |ui| {
+                draw_performance_history(ui, &app.performance_history, &app.settings, &app.theme);
+            });
+
             ui.add_space(20.0);
             ui.separator();
             ui.add_space(10.0);
@@ -327,6 +635,30 @@ pub fn draw_options_window(app: &mut CypherApp, ctx: &egui::Context) {
     if apply_was_clicked {
         app.apply_audio_settings();
     }
+    if soft_reset_clicked {
+        app.send_command(AudioCommand::SoftReset);
+    }
+    if tuning_changed {
+        app.send_command(AudioCommand::SetTuningReference(app.settings.tuning_reference_hz));
+    }
+    if output_clip_mode_changed {
+        app.send_command(AudioCommand::SetOutputClipMode(app.settings.output_clip_mode));
+    }
+    if sample_declick_changed {
+        app.send_command(AudioCommand::SetSampleDeclick(app.settings.sample_declick_ms));
+    }
+    if engine_swap_fade_changed {
+        app.send_command(AudioCommand::SetEngineSwapFade(app.settings.engine_swap_fade_ms));
+    }
+    if lfo_transport_sync_changed {
+        app.send_command(AudioCommand::SetLfoTransportSync(app.settings.lfo_transport_sync));
+    }
+    if monitor_while_stopped_changed {
+        app.send_command(AudioCommand::SetMonitorWhileStopped(app.settings.monitor_while_stopped));
+    }
+    if loop_crossfade_changed {
+        app.send_command(AudioCommand::SetLoopCrossfade(app.settings.loop_crossfade_ms));
+    }
     if midi_ports_changed {
         if let Err(e) = app.reconnect_midi() {
             eprintln!("Failed to reconnect MIDI: {}", e);
@@ -336,4 +668,54 @@ pub fn draw_options_window(app: &mut CypherApp, ctx: &egui::Context) {
         app.save_settings();
         app.options_window_open = false;
     }
+}
+
+/// Graph of recent CPU load against the warning and critical thresholds, plus the xrun log.
+fn draw_performance_history(ui: &mut Ui, history: &PerformanceHistory, settings: &AppSettings, theme: &Theme) {
+    let (rect, _) = ui.allocate_exact_size(vec2(ui.available_width(), 80.0), Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, CornerRadius::same(2), theme.options_window.widget_bg);
+    let y_for = |percent: f32| rect.bottom() - (percent.clamp(0.0, 100.0) / 100.0) * rect.height();
+    for (threshold, color) in [
+        (settings.cpu_warning_threshold, theme.top_bar.cpu_warning_color),
+        (settings.cpu_critical_threshold, theme.top_bar.cpu_critical_color),
+    ] {
+        let y = y_for(threshold);
+        painter.line_segment([pos2(rect.left(), y), pos2(rect.right(), y)], Stroke::new(1.0, color.linear_multiply(0.5)));
+    }
+    let x_step = rect.width() / (CPU_HISTORY_LEN - 1) as f32;
+    let x_offset = (CPU_HISTORY_LEN - history.cpu_load.len()) as f32 * x_step;
+    let points: Vec<_> = history
+        .cpu_load
+        .iter()
+        .enumerate()
+        .map(|(i, &load)| pos2(rect.left() + x_offset + i as f32 * x_step, y_for(load)))
+        .collect();
+    painter.line(points, Stroke::new(1.5, theme.options_window.slider_grab_color));
+
+    let peak = history.cpu_load.iter().copied().fold(0.0f32, f32::max);
+    let average = if history.cpu_load.is_empty() {
+        0.0
+    } else {
+        history.cpu_load.iter().sum::<f32>() / history.cpu_load.len() as f32
+    };
+    ui.label(
+        RichText::new(format!("Recent CPU: {:.1}% average, {:.1}% peak", average, peak))
+            .color(theme.options_window.label_color),
+    );
+
+    ui.add_space(6.0);
+    ui.label(RichText::new("Xrun Log").color(theme.options_window.label_color));
+    if history.xrun_log.is_empty() {
+        ui.label(RichText::new("No xruns since launch.").color(theme.options_window.label_color));
+    } else {
+        ScrollArea::vertical()
+            .id_salt("xrun_log_scroll")
+            .max_height(100.0)
+            .show(ui, |ui| {
+                for entry in history.xrun_log.iter().rev() {
+                    ui.label(RichText::new(entry).monospace().color(theme.options_window.label_color));
+                }
+            });
+    }
 }
\ No newline at end of file
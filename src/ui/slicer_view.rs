@@ -1,15 +1,25 @@
-use crate::app::{CypherApp, SlicerState};
+use crate::app::{CypherApp, SliceMode, SlicerState};
+use crate::asset::{self, SAMPLE_FILE_EXTENSIONS};
+use crate::sampler::{SamplerKit, SamplerPadSettings};
+use crate::sampler_engine::NUM_SAMPLE_SLOTS;
 use crate::settings;
 use crate::slicer;
 use crate::theme::SlicerWindowTheme;
 use egui::{
-    epaint, CentralPanel, Frame, Grid, Pos2, Rect, RichText, Sense, Slider, Stroke,
-    TextEdit, TopBottomPanel, Ui, Window,
+    epaint, Button, CentralPanel, ComboBox, CursorIcon, Frame, Grid, Pos2, Rect, RichText, Sense,
+    Shape, Slider, Stroke, TextEdit, TopBottomPanel, Ui, Window,
 };
 use rfd::FileDialog;
 use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How close (in pixels) the pointer must be to a slice boundary to grab it.
+const BOUNDARY_GRAB_DISTANCE: f32 = 5.0;
+/// How far (in ms) a dragged or split boundary may move to land on a zero crossing.
+const ZERO_CROSSING_SEARCH_MS: f32 = 5.0;
 
 fn recalculate_slices(state: &mut SlicerState) {
+    state.selected_slice = None;
     let source_audio = if let Some(sa) = &state.source_audio {
         sa
     } else {
@@ -20,6 +30,15 @@ fn recalculate_slices(state: &mut SlicerState) {
         return;
     }
 
+    if state.mode == SliceMode::Transient {
+        state.slice_regions = slicer::find_slices_from_transients(
+            &source_audio.data,
+            source_audio.sample_rate,
+            state.transient_sensitivity,
+        );
+        return;
+    }
+
     let num_points = 4096.min(total_samples);
     let samples_per_point = total_samples as f32 / num_points as f32;
     let mut visual_peaks = Vec::with_capacity(num_points);
@@ -42,38 +61,44 @@ fn recalculate_slices(state: &mut SlicerState) {
 }
 
 fn load_slicer_sample(app: &mut CypherApp) {
-    if let Some(path) = FileDialog::new().add_filter("wav", &["wav"]).pick_file() {
-        match crate::app::load_source_audio_file_with_sr(&path) {
-            Ok(source_audio) => {
-                let total_samples = source_audio.data.len();
-                let slicer_state = &mut app.slicer_state;
-                if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
-                    slicer_state.base_export_name = name.to_string();
-                }
-                slicer_state.source_audio = Some(source_audio);
-                slicer_state.view_start_sample = 0;
-                slicer_state.view_end_sample = total_samples;
-                recalculate_slices(slicer_state);
-            }
-            Err(e) => {
-                eprintln!("Failed to load sample for slicer: {}", e);
-                app.slicer_state.source_audio = None;
+    if let Some(path) = FileDialog::new()
+        .add_filter("Audio", &SAMPLE_FILE_EXTENSIONS)
+        .pick_file()
+    {
+        load_slicer_file(app, &path);
+    }
+}
+
+fn load_slicer_file(app: &mut CypherApp, path: &Path) {
+    match crate::app::load_source_audio_file_with_sr(path) {
+        Ok(source_audio) => {
+            let slicer_state = &mut app.slicer_state;
+            if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                slicer_state.base_export_name = name.to_string();
             }
+            slicer_state.set_source_audio(source_audio);
+            recalculate_slices(slicer_state);
+        }
+        Err(e) => {
+            eprintln!("Failed to load sample for slicer: {}", e);
+            app.slicer_state.source_audio = None;
         }
     }
 }
 
-fn export_slices(app: &mut CypherApp) {
+/// Writes each slice as a WAV file and returns the paths written, in slice order.
+fn export_slices(app: &mut CypherApp) -> Vec<PathBuf> {
+    let mut written = Vec::new();
     let state = &app.slicer_state;
     let source_audio = if let Some(sa) = &state.source_audio {
         sa
     } else {
-        return;
+        return written;
     };
 
     if state.base_export_name.is_empty() {
         eprintln!("Export failed: Base filename cannot be empty.");
-        return;
+        return written;
     }
 
     if let Some(config_dir) = settings::get_config_dir() {
@@ -90,11 +115,11 @@ fn export_slices(app: &mut CypherApp) {
                 export_dir.display(),
                 e
             );
-            return;
+            return written;
         }
 
         let total_samples = source_audio.data.len();
-        let tail_samples = (state.tail_ms / 1000.0 * source_audio.sample_rate as f32).round() as usize;
+        let tail_samples = state.tail_samples(source_audio.sample_rate);
 
         const FADE_MS: f32 = 5.0;
         let fade_samples = (FADE_MS / 1000.0 * source_audio.sample_rate as f32) as usize;
@@ -136,7 +161,9 @@ fn export_slices(app: &mut CypherApp) {
                         let amplitude = i16::MAX as f32;
                         writer.write_sample((sample * amplitude) as i16).ok();
                     }
-                    writer.finalize().ok();
+                    if writer.finalize().is_ok() {
+                        written.push(path);
+                    }
                 }
                 Err(e) => {
                     eprintln!("Failed to create wav file at {}: {}", path.display(), e);
@@ -145,6 +172,60 @@ fn export_slices(app: &mut CypherApp) {
         }
         app.rescan_asset_library();
     }
+    written
+}
+
+/// Exports the slices, then saves a kit under `Kits/` with the first 16 on pads 1-16,
+/// optionally loading it right away.
+fn export_slices_to_kit(app: &mut CypherApp) {
+    let slice_paths = export_slices(app);
+    if slice_paths.is_empty() {
+        return;
+    }
+    let Some(config_dir) = settings::get_config_dir() else {
+        return;
+    };
+
+    let mut pads: [SamplerPadSettings; 16] = Default::default();
+    for (pad, path) in pads.iter_mut().zip(&slice_paths) {
+        // Kits store paths relative to the config dir so they survive moving it.
+        pad.path = Some(path.strip_prefix(&config_dir).unwrap_or(path).to_path_buf());
+    }
+    let kit = SamplerKit {
+        pads,
+        groove: app.pad_groove,
+    };
+
+    let kits_dir = config_dir.join("Kits");
+    if let Err(e) = fs::create_dir_all(&kits_dir) {
+        eprintln!("Failed to create kits directory {}: {}", kits_dir.display(), e);
+        return;
+    }
+    let base_name = &app.slicer_state.base_export_name;
+    let mut kit_path = kits_dir.join(format!("{}.json", base_name));
+    let mut copy = 2;
+    while kit_path.exists() {
+        kit_path = kits_dir.join(format!("{} {}.json", base_name, copy));
+        copy += 1;
+    }
+
+    match serde_json::to_string_pretty(&kit) {
+        Ok(json) => {
+            if let Err(e) = fs::write(&kit_path, json) {
+                eprintln!("Failed to write kit {}: {}", kit_path.display(), e);
+                return;
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to serialize kit: {}", e);
+            return;
+        }
+    }
+
+    app.rescan_asset_library();
+    if app.slicer_state.load_exported_kit {
+        app.load_kit(&kit_path);
+    }
 }
 
 pub fn draw_slicer_window(app: &mut CypherApp, ctx: &egui::Context) {
@@ -158,7 +239,7 @@ pub fn draw_slicer_window(app: &mut CypherApp, ctx: &egui::Context) {
         screen_rect.center().y - window_size.y / 2.0,
     );
 
-    Window::new("Sample Slicer")
+    let window = Window::new("Sample Slicer")
         .open(&mut is_open)
         .frame(Frame::window(&ctx.style()).fill(theme.background))
         .default_size(window_size)
@@ -185,6 +266,17 @@ pub fn draw_slicer_window(app: &mut CypherApp, ctx: &egui::Context) {
                                 ))
                                     .color(theme.label_color),
                             );
+                            ui.separator();
+                            if ui.add(egui::Button::new("Zoom to Fit").fill(theme.button_bg)).clicked() {
+                                app.slicer_state.zoom_to_fit();
+                            }
+                            let has_selection = app.slicer_state.selected_slice.is_some();
+                            if ui.add_enabled(has_selection, egui::Button::new("Zoom to Selection").fill(theme.button_bg))
+                                .on_disabled_hover_text("Click a slice in the waveform to select it.")
+                                .clicked()
+                            {
+                                app.slicer_state.zoom_to_selection();
+                            }
                         });
                         ui.separator();
 
@@ -200,20 +292,46 @@ pub fn draw_slicer_window(app: &mut CypherApp, ctx: &egui::Context) {
                             visuals.widgets.active.bg_stroke = Stroke::NONE;
 
                             Grid::new("slicer_params_grid").show(ui, |ui| {
-                                ui.label(RichText::new("Silence Threshold").color(theme.label_color));
-                                if ui.add(Slider::new(&mut app.slicer_state.threshold, 0.0..=0.2).logarithmic(true)).changed() {
-                                    params_changed = true;
-                                }
+                                ui.label(RichText::new("Mode").color(theme.label_color));
+                                ui.horizontal(|ui| {
+                                    let silence = ui.selectable_value(&mut app.slicer_state.mode, SliceMode::Silence, "Silence");
+                                    let transient = ui.selectable_value(&mut app.slicer_state.mode, SliceMode::Transient, "Transient");
+                                    if silence.changed() || transient.changed() {
+                                        params_changed = true;
+                                    }
+                                });
                                 ui.end_row();
 
-                                ui.label(RichText::new("Min Silence (ms)").color(theme.label_color));
-                                if ui.add(Slider::new(&mut app.slicer_state.min_silence_ms, 1.0..=1000.0)).changed() {
-                                    params_changed = true;
+                                match app.slicer_state.mode {
+                                    SliceMode::Silence => {
+                                        ui.label(RichText::new("Silence Threshold").color(theme.label_color));
+                                        if ui.add(Slider::new(&mut app.slicer_state.threshold, 0.0..=0.2).logarithmic(true)).changed() {
+                                            params_changed = true;
+                                        }
+                                        ui.end_row();
+
+                                        ui.label(RichText::new("Min Silence (ms)").color(theme.label_color));
+                                        if ui.add(Slider::new(&mut app.slicer_state.min_silence_ms, 1.0..=1000.0)).changed() {
+                                            params_changed = true;
+                                        }
+                                        ui.end_row();
+
+                                        ui.label(RichText::new("Tail (ms)").color(theme.label_color));
+                                        ui.add(Slider::new(&mut app.slicer_state.tail_ms, 0.0..=10000.0));
+                                        ui.end_row();
+                                    }
+                                    SliceMode::Transient => {
+                                        ui.label(RichText::new("Sensitivity").color(theme.label_color));
+                                        if ui.add(Slider::new(&mut app.slicer_state.transient_sensitivity, 0.0..=1.0)).changed() {
+                                            params_changed = true;
+                                        }
+                                        ui.end_row();
+                                    }
                                 }
-                                ui.end_row();
 
-                                ui.label(RichText::new("Tail (ms)").color(theme.label_color));
-                                ui.add(Slider::new(&mut app.slicer_state.tail_ms, 0.0..=10000.0));
+                                ui.label(RichText::new("Snap to Zero").color(theme.label_color));
+                                ui.checkbox(&mut app.slicer_state.snap_to_zero_crossing, "")
+                                    .on_hover_text("Drag a slice edge to move it; right-click the waveform to split or merge slices.");
                                 ui.end_row();
                             });
                         });
@@ -278,9 +396,37 @@ pub fn draw_slicer_window(app: &mut CypherApp, ctx: &egui::Context) {
                             });
                         });
 
-                        if ui.add(egui::Button::new("Export Slices").fill(theme.button_bg)).clicked() {
-                            export_slices(app);
-                        }
+                        ui.horizontal(|ui| {
+                            if ui.add(egui::Button::new("Export Slices").fill(theme.button_bg)).clicked() {
+                                export_slices(app);
+                            }
+                            if ui.add(egui::Button::new("Export to Kit").fill(theme.button_bg)).clicked() {
+                                export_slices_to_kit(app);
+                            }
+                            ui.checkbox(&mut app.slicer_state.load_exported_kit, RichText::new("Load Kit").color(theme.label_color))
+                                .on_hover_text("Load the new kit onto the pads after exporting. Only the first 16 slices fit.");
+                            ui.separator();
+                            if ui.add(egui::Button::new("Export to Sampler").fill(theme.button_bg))
+                                .on_hover_text(format!(
+                                    "Load the first {} slices into the sampler engine's slots, one octave each.",
+                                    NUM_SAMPLE_SLOTS
+                                ))
+                                .clicked()
+                            {
+                                let slice_paths = export_slices(app);
+                                if !slice_paths.is_empty() {
+                                    let engine_index = app.slicer_state.target_engine_index;
+                                    app.load_slices_into_sampler_engine(engine_index, &slice_paths);
+                                }
+                            }
+                            ComboBox::from_id_salt("slicer_target_engine")
+                                .selected_text(format!("Engine {}", app.slicer_state.target_engine_index + 1))
+                                .show_ui(ui, |ui| {
+                                    for i in 0..2 {
+                                        ui.selectable_value(&mut app.slicer_state.target_engine_index, i, format!("Engine {}", i + 1));
+                                    }
+                                });
+                        });
                     }
                 });
 
@@ -297,11 +443,23 @@ pub fn draw_slicer_window(app: &mut CypherApp, ctx: &egui::Context) {
                             if ui.button("Load Sample...").clicked() {
                                 load_slicer_sample(app);
                             }
+                            ui.label(RichText::new("or drop a file here").color(theme.label_color));
                         });
                     }
                 });
         });
 
+    // A file dropped from the OS onto the window replaces the source; only the first is used.
+    let is_drop_target = window.is_some_and(|w| {
+        ctx.pointer_latest_pos()
+            .is_some_and(|pos| w.response.rect.contains(pos))
+    });
+    if is_drop_target {
+        if let Some(path) = asset::take_dropped_sample_files(ctx).into_iter().next() {
+            load_slicer_file(app, &path);
+        }
+    }
+
     app.slicer_window_open = is_open;
 }
 
@@ -311,44 +469,84 @@ fn draw_interactive_waveform(ui: &mut Ui, state: &mut crate::app::SlicerState, t
         ui.allocate_painter(desired_rect.size(), Sense::click_and_drag());
     let rect = response.rect;
 
-    let source_audio = if let Some(sa) = &state.source_audio {
-        sa
-    } else {
-        return;
-    };
-
-    let total_samples = source_audio.data.len();
+    let total_samples = state.source_audio.as_ref().map_or(0, |sa| sa.data.len());
     if total_samples == 0 {
         return;
     }
 
+    // Keep the view valid even if the slices or audio changed underneath it.
+    state.set_view(
+        state.view_start_sample as f64,
+        state.view_end_sample.saturating_sub(state.view_start_sample) as f64,
+    );
+
     if response.hovered() {
-        let scroll = ui.ctx().input(|i| i.raw_scroll_delta);
-        if scroll.y != 0.0 {
+        let (scroll, shift) = ui.ctx().input(|i| (i.raw_scroll_delta, i.modifiers.shift));
+        let view_span = (state.view_end_sample - state.view_start_sample) as f64;
+        // Wheel zooms around the pointer; shift+wheel or a horizontal scroll pans.
+        if scroll.y != 0.0 && !shift {
             let zoom_factor = if scroll.y > 0.0 { 0.8 } else { 1.25 };
-            let pointer_pos = ui.ctx().input(|i| i.pointer.hover_pos()).unwrap_or(rect.center());
-            let hover_ratio = ((pointer_pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
-            let view_span = (state.view_end_sample - state.view_start_sample) as f32;
-            let sample_at_hover = state.view_start_sample as f32 + view_span * hover_ratio;
-            let new_view_span = (view_span * zoom_factor).max(50.0);
-            let new_start = sample_at_hover - new_view_span * hover_ratio;
-            let new_end = new_start + new_view_span;
-            state.view_start_sample = new_start.round() as usize;
-            state.view_end_sample = new_end.round() as usize;
+            let pointer_pos = response.hover_pos().unwrap_or(rect.center());
+            let hover_ratio = ((pointer_pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0) as f64;
+            let sample_at_hover = state.view_start_sample as f64 + view_span * hover_ratio;
+            let new_view_span = view_span * zoom_factor;
+            state.set_view(sample_at_hover - new_view_span * hover_ratio, new_view_span);
+        }
+        let pan_pixels = scroll.x + if shift { scroll.y } else { 0.0 };
+        if pan_pixels != 0.0 {
+            let sample_delta = pan_pixels as f64 / rect.width() as f64 * view_span;
+            state.set_view(state.view_start_sample as f64 - sample_delta, view_span);
+        }
+    }
+
+    if let Some(pos) = response.hover_pos() {
+        if state.dragging_boundary.is_some() || boundary_near(state, rect, pos.x).is_some() {
+            ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
         }
     }
-    if response.dragged() {
+    if response.drag_started() {
+        state.dragging_boundary = response
+            .interact_pointer_pos()
+            .and_then(|pos| boundary_near(state, rect, pos.x));
+    }
+    if state.dragging_boundary.is_none() && response.dragged() {
+        let view_span = (state.view_end_sample - state.view_start_sample) as f64;
+        let sample_delta = response.drag_delta().x as f64 / rect.width() as f64 * view_span;
+        state.set_view(state.view_start_sample as f64 - sample_delta, view_span);
+    }
+
+    let pointer_sample = response.interact_pointer_pos().or(response.hover_pos()).map(|pos| {
+        let ratio = ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
         let view_span = (state.view_end_sample - state.view_start_sample) as f32;
-        let pixel_delta = response.drag_delta().x;
-        let sample_delta = (pixel_delta / rect.width() * view_span).round() as isize;
-        state.view_start_sample = (state.view_start_sample as isize - sample_delta).max(0) as usize;
-        state.view_end_sample = (state.view_end_sample as isize - sample_delta).max(0) as usize;
+        ((state.view_start_sample as f32 + view_span * ratio).round() as usize).min(total_samples)
+    });
+    let source_audio = if let Some(sa) = &state.source_audio {
+        sa
+    } else {
+        return;
+    };
+    let snap_radius = (ZERO_CROSSING_SEARCH_MS / 1000.0 * source_audio.sample_rate as f32) as usize;
+    let snap = |sample: usize| {
+        if state.snap_to_zero_crossing {
+            slicer::nearest_zero_crossing(&source_audio.data, sample, snap_radius)
+        } else {
+            sample
+        }
+    };
+
+    if let Some((index, is_end)) = state.dragging_boundary {
+        if let Some(sample) = pointer_sample {
+            let sample = snap(sample);
+            slicer::move_slice_boundary(&mut state.slice_regions, index, is_end, sample, total_samples);
+        }
+        if response.drag_stopped() {
+            state.dragging_boundary = None;
+        }
     }
-    if state.view_end_sample <= state.view_start_sample {
-        state.view_end_sample = state.view_start_sample + 1;
+    if response.clicked() {
+        state.selected_slice = pointer_sample
+            .and_then(|sample| state.slice_regions.iter().position(|&(start, end)| start <= sample && sample < end));
     }
-    state.view_end_sample = state.view_end_sample.min(total_samples);
-    state.view_start_sample = state.view_start_sample.min(state.view_end_sample.saturating_sub(1));
 
     let view_start = state.view_start_sample;
     let view_end = state.view_end_sample;
@@ -360,36 +558,111 @@ fn draw_interactive_waveform(ui: &mut Ui, state: &mut crate::app::SlicerState, t
         rect.min.x + (sample_idx.saturating_sub(view_start)) as f32 / view_span as f32 * rect.width()
     };
 
-    let num_pixels = rect.width().ceil() as usize;
-    for pixel_x_offset in 0..num_pixels {
-        let sample_start_f = view_start as f32 + pixel_x_offset as f32 * samples_per_pixel;
-        let sample_end_f = sample_start_f + samples_per_pixel;
-        let sample_start_idx = (sample_start_f.floor() as usize).min(total_samples);
-        let sample_end_idx = (sample_end_f.ceil() as usize).min(total_samples);
-        if sample_start_idx >= sample_end_idx { continue; }
-        let chunk = &source_audio.data[sample_start_idx..sample_end_idx];
-        let peak = chunk.iter().fold(0.0f32, |max, &v| max.max(v.abs()));
-        let x = rect.min.x + pixel_x_offset as f32;
-        let y_center = rect.center().y;
-        let y_offset = peak * rect.height() / 2.0;
-        painter.line_segment([Pos2::new(x, y_center - y_offset), Pos2::new(x, y_center + y_offset)], Stroke::new(1.0, theme.waveform_color));
+    let y_center = rect.center().y;
+    if samples_per_pixel <= 1.0 {
+        // Zoomed in past one sample per pixel: draw the samples themselves.
+        let points = (view_start..(view_end + 1).min(total_samples))
+            .map(|i| Pos2::new(sample_to_x(i), y_center - source_audio.data[i] * rect.height() / 2.0))
+            .collect();
+        painter.add(Shape::line(points, Stroke::new(1.0, theme.waveform_color)));
+    } else {
+        // Read whole-block peaks when a pixel spans several blocks, so long files stay cheap.
+        let use_block_peaks = samples_per_pixel >= slicer::PEAK_BLOCK_SIZE as f32;
+        let num_pixels = rect.width().ceil() as usize;
+        for pixel_x_offset in 0..num_pixels {
+            let sample_start_f = view_start as f32 + pixel_x_offset as f32 * samples_per_pixel;
+            let sample_end_f = sample_start_f + samples_per_pixel;
+            let sample_start_idx = (sample_start_f.floor() as usize).min(total_samples);
+            let sample_end_idx = (sample_end_f.ceil() as usize).min(total_samples);
+            if sample_start_idx >= sample_end_idx { continue; }
+            let chunk = if use_block_peaks {
+                let first_block = sample_start_idx / slicer::PEAK_BLOCK_SIZE;
+                let last_block = sample_end_idx.div_ceil(slicer::PEAK_BLOCK_SIZE).min(state.waveform_peaks.len());
+                &state.waveform_peaks[first_block.min(last_block)..last_block]
+            } else {
+                &source_audio.data[sample_start_idx..sample_end_idx]
+            };
+            let peak = chunk.iter().fold(0.0f32, |max, &v| max.max(v.abs()));
+            let x = rect.min.x + pixel_x_offset as f32;
+            let y_offset = peak * rect.height() / 2.0;
+            painter.line_segment([Pos2::new(x, y_center - y_offset), Pos2::new(x, y_center + y_offset)], Stroke::new(1.0, theme.waveform_color));
+        }
     }
 
-    let tail_samples = (state.tail_ms / 1000.0 * source_audio.sample_rate as f32).round() as usize;
+    let tail_samples = state.tail_samples(source_audio.sample_rate);
     let overlay_color = theme.slice_marker_color.gamma_multiply(0.35);
+    let selected_overlay_color = theme.slice_marker_color.gamma_multiply(0.6);
 
-    for (start_sample, end_sample) in &state.slice_regions {
+    for (index, (start_sample, end_sample)) in state.slice_regions.iter().enumerate() {
         let extended_end_sample = (*end_sample + tail_samples).min(total_samples);
         if extended_end_sample < view_start || *start_sample > view_end { continue; }
         let x1 = sample_to_x(*start_sample);
         let x2 = sample_to_x(extended_end_sample);
         let overlay_rect = Rect::from_x_y_ranges(x1..=x2, rect.y_range());
-        painter.rect_filled(overlay_rect, epaint::CornerRadius::ZERO, overlay_color);
+        let fill = if state.selected_slice == Some(index) { selected_overlay_color } else { overlay_color };
+        painter.rect_filled(overlay_rect, epaint::CornerRadius::ZERO, fill);
+
+        // Draggable edges; the tail isn't part of the slice, so the end handle sits before it.
+        for (is_end, sample) in [(false, *start_sample), (true, *end_sample)] {
+            if sample < view_start || sample > view_end { continue; }
+            let width = if state.dragging_boundary == Some((index, is_end)) { 2.0 } else { 1.0 };
+            painter.vline(sample_to_x(sample), rect.y_range(), Stroke::new(width, theme.slice_marker_color));
+        }
     }
 
-    let y_center = rect.center().y;
-    let y_offset = state.threshold * rect.height() / 2.0;
-    let line_stroke = Stroke::new(1.0, theme.slice_marker_color.gamma_multiply(0.5));
-    painter.hline(rect.x_range(), y_center - y_offset, line_stroke);
-    painter.hline(rect.x_range(), y_center + y_offset, line_stroke);
-}
\ No newline at end of file
+    if state.mode == SliceMode::Silence {
+        let y_center = rect.center().y;
+        let y_offset = state.threshold * rect.height() / 2.0;
+        let line_stroke = Stroke::new(1.0, theme.slice_marker_color.gamma_multiply(0.5));
+        painter.hline(rect.x_range(), y_center - y_offset, line_stroke);
+        painter.hline(rect.x_range(), y_center + y_offset, line_stroke);
+    }
+
+    if response.secondary_clicked() {
+        state.context_menu_sample = pointer_sample;
+    }
+    response.context_menu(|ui| {
+        let Some(sample) = state.context_menu_sample else {
+            ui.close();
+            return;
+        };
+        let region_count = state.slice_regions.len();
+        let region_index = state.slice_regions.iter().position(|&(start, end)| start <= sample && sample < end);
+
+        if ui.add_enabled(region_index.is_some(), Button::new("Split Here")).clicked() {
+            slicer::split_slice_at(&mut state.slice_regions, snap(sample));
+            state.selected_slice = None;
+            ui.close();
+        }
+        if ui.add_enabled(region_index.is_some_and(|i| i + 1 < region_count), Button::new("Merge With Next")).clicked() {
+            if let Some(index) = region_index {
+                slicer::merge_slice_with_next(&mut state.slice_regions, index);
+                state.selected_slice = None;
+            }
+            ui.close();
+        }
+        if ui.add_enabled(region_index.is_some_and(|i| i > 0), Button::new("Merge With Previous")).clicked() {
+            if let Some(index) = region_index {
+                slicer::merge_slice_with_next(&mut state.slice_regions, index - 1);
+                state.selected_slice = None;
+            }
+            ui.close();
+        }
+    });
+}
+/// The slice edge closest to screen position `x`, if one is within grabbing distance. Returns
+/// the region index and whether it's the region's end.
+fn boundary_near(state: &SlicerState, rect: Rect, x: f32) -> Option<(usize, bool)> {
+    let view_span = state.view_end_sample.saturating_sub(state.view_start_sample).max(1) as f32;
+    let sample_to_x =
+        |sample: usize| rect.min.x + (sample as f32 - state.view_start_sample as f32) / view_span * rect.width();
+    state
+        .slice_regions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, &(start, end))| [(index, false, start), (index, true, end)])
+        .map(|(index, is_end, sample)| ((sample_to_x(sample) - x).abs(), index, is_end))
+        .filter(|(distance, _, _)| *distance <= BOUNDARY_GRAB_DISTANCE)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, index, is_end)| (index, is_end))
+}
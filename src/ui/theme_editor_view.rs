@@ -99,6 +99,15 @@ pub fn draw_theme_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
                                     ui.label("XRun Text");
                                     ui.color_edit_button_srgba(&mut app.theme.top_bar.xrun_text_color);
                                     ui.end_row();
+                                    ui.label("CPU OK");
+                                    ui.color_edit_button_srgba(&mut app.theme.top_bar.cpu_ok_color);
+                                    ui.end_row();
+                                    ui.label("CPU Warning");
+                                    ui.color_edit_button_srgba(&mut app.theme.top_bar.cpu_warning_color);
+                                    ui.end_row();
+                                    ui.label("CPU Critical");
+                                    ui.color_edit_button_srgba(&mut app.theme.top_bar.cpu_critical_color);
+                                    ui.end_row();
                                 });
                             });
 
@@ -174,10 +183,10 @@ pub fn draw_theme_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
                                     ui.label("Armed BG");
                                     ui.color_edit_button_srgba(&mut app.theme.loopers.armed_bg);
                                     ui.end_row();
-                                    ui.label("Recording BG");
+                                    ui.label("Recording Waveform");
                                     ui.color_edit_button_srgba(&mut app.theme.loopers.recording_bg);
                                     ui.end_row();
-                                    ui.label("Overdub BG");
+                                    ui.label("Overdub Waveform");
                                     ui.color_edit_button_srgba(&mut app.theme.loopers.overdubbing_bg);
                                     ui.end_row();
                                     ui.label("Progress Bar BG");
@@ -189,6 +198,9 @@ pub fn draw_theme_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
                                     ui.label("Text");
                                     ui.color_edit_button_srgba(&mut app.theme.loopers.text_color);
                                     ui.end_row();
+                                    ui.label("Playhead");
+                                    ui.color_edit_button_srgba(&mut app.theme.loopers.playhead_color);
+                                    ui.end_row();
                                 });
                                 ui.collapsing("Track Colors", |ui| {
                                     Grid::new("looper_track_colors").show(ui, |ui| {
@@ -458,6 +470,9 @@ pub fn draw_theme_editor_window(app: &mut CypherApp, ctx: &egui::Context) {
                                         ui.label("Mod: Amp (Hot)");
                                         ui.color_edit_button_srgba(&mut theme.mod_amp_hot_color);
                                         ui.end_row();
+                                        ui.label("Engine Peak Meter");
+                                        ui.color_edit_button_srgba(&mut theme.peak_meter_color);
+                                        ui.end_row();
                                     });
                                 });
                             });
@@ -1,7 +1,7 @@
 // src/wavetable_engine.rs
 use crate::synth::{
     Adsr, AdsrSettings, Engine, Filter, FilterSettings, Lfo, LfoRateMode, LfoSettings,
-    ModDestination, ModRouting, ModSource, WAVETABLE_SIZE,
+    Glide, ModDestination, ModRouting, ModSource, velocity_gain, WAVETABLE_SIZE,
 };
 use crate::synth::{FastTanh, EXP_LUT, POW2_LUT}; // Use our performance utilities
 use egui::{epaint, lerp, Rect}; // Added `Rect` for the cache
@@ -29,6 +29,30 @@ impl Default for SaturationSettings {
     }
 }
 
+/// Upper bound on stacked unison copies per voice, to keep CPU use predictable.
+pub const MAX_UNISON_VOICES: usize = 7;
+/// Wavetable slots an engine morphs between.
+pub const NUM_WAVETABLE_SLOTS: usize = 4;
+
+/// Stacks detuned copies of each played note for thicker sounds. Only used in poly mode.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct UnisonSettings {
+    /// 1 to `MAX_UNISON_VOICES`. 1 disables unison.
+    pub voices: usize,
+    /// Total detune spread across the stack, in cents.
+    pub detune_cents: f32,
+}
+
+impl Default for UnisonSettings {
+    fn default() -> Self {
+        Self {
+            voices: 1,
+            detune_cents: 15.0,
+        }
+    }
+}
+
 /// A snapshot of all values that affect the wavetable visualizer.
 /// Used to detect when the visualizer needs to be redrawn and cached.
 #[derive(PartialEq, Clone, Copy, Debug, Default)]
@@ -48,13 +72,16 @@ pub struct VisualizerSnapshot {
 pub struct WavetableEngineState {
     pub amp_adsr: AdsrSettings,
     pub filter_adsr: AdsrSettings,
+    pub velocity_sensitivity: f32,
     pub filter_settings: Arc<RwLock<FilterSettings>>,
     pub wavetable_mixer_settings: Arc<RwLock<WavetableMixerSettings>>,
     pub lfo_settings: Arc<RwLock<LfoSettings>>,
     pub lfo2_settings: Arc<RwLock<LfoSettings>>,
     pub mod_matrix: Arc<RwLock<Vec<ModRouting>>>,
     pub saturation_settings: Arc<RwLock<SaturationSettings>>,
+    pub unison_settings: Arc<RwLock<UnisonSettings>>,
     pub is_polyphonic: bool,
+    pub glide_time_ms: f32,
     pub wavetable_names: [String; 4],
     pub wavetable_sources: [WavetableSource; 4],
     pub window_positions: [f32; 4],
@@ -91,13 +118,16 @@ impl WavetableEngineState {
         Self {
             amp_adsr: AdsrSettings::default(),
             filter_adsr: AdsrSettings::default(),
+            velocity_sensitivity: 1.0,
             filter_settings: Arc::new(RwLock::new(FilterSettings::default())),
             wavetable_mixer_settings: Arc::new(RwLock::new(WavetableMixerSettings::default())),
             lfo_settings: Arc::new(RwLock::new(LfoSettings::default())),
             lfo2_settings: Arc::new(RwLock::new(LfoSettings::default())),
             mod_matrix: Arc::new(RwLock::new(Vec::new())),
             saturation_settings: Arc::new(RwLock::new(Default::default())),
+            unison_settings: Arc::new(RwLock::new(Default::default())),
             is_polyphonic: true,
+            glide_time_ms: 0.0,
             wavetable_names: [
                 default_tables.tables[0].name.clone(),
                 default_tables.tables[1].name.clone(),
@@ -157,13 +187,17 @@ pub struct WavetableEnginePreset {
     pub volume: f32,
     pub amp_adsr: AdsrSettings,
     pub filter_adsr: AdsrSettings,
+    /// 0 plays every note at full level, 1 follows note velocity fully.
+    pub velocity_sensitivity: f32,
     pub filter: FilterSettings,
     pub lfo_settings: LfoSettings,
     pub lfo2_settings: LfoSettings,
     pub mod_matrix: Vec<ModRouting>,
     pub saturation_settings: SaturationSettings,
+    pub unison: UnisonSettings,
     pub wavetable_position_m_u32: u32,
     pub is_polyphonic: bool,
+    pub glide_time_ms: f32,
     pub wavetable_sources: [WavetableSource; 4],
     pub window_positions: [f32; 4],
     pub wavetable_mixer: WavetableMixerSettings,
@@ -175,13 +209,16 @@ impl Default for WavetableEnginePreset {
             volume: 1.0,
             amp_adsr: Default::default(),
             filter_adsr: Default::default(),
+            velocity_sensitivity: 1.0,
             filter: Default::default(),
             lfo_settings: Default::default(),
             lfo2_settings: Default::default(),
             mod_matrix: Vec::new(),
             saturation_settings: Default::default(),
+            unison: Default::default(),
             wavetable_position_m_u32: 0,
             is_polyphonic: true,
+            glide_time_ms: 0.0,
             wavetable_sources: [
                 WavetableSource::Default("Sine".to_string()),
                 WavetableSource::Default("Saw".to_string()),
@@ -332,14 +369,20 @@ struct ModulationValues {
     bell_amount: f32,
     bell_width: f32,
     saturation: f32,
+    fx_mix: f32,
 }
 
 struct Voice {
     note_id: u8,
     sample_rate: f32,
     phase: f32,
+    unison_phases: [f32; MAX_UNISON_VOICES],
+    unison_voices: usize,
+    unison_detune_cents: f32,
     base_frequency: f32,
+    glide: Glide,
     velocity: f32,
+    velocity_sensitivity: f32,
     amp_adsr: Adsr,
     filter_adsr: Adsr,
     filter: Filter,
@@ -356,8 +399,13 @@ impl Voice {
             note_id: 0,
             sample_rate,
             phase: 0.0,
+            unison_phases: [0.0; MAX_UNISON_VOICES],
+            unison_voices: 1,
+            unison_detune_cents: 0.0,
             base_frequency: 440.0,
+            glide: Glide::default(),
             velocity: 0.0,
+            velocity_sensitivity: 1.0,
             amp_adsr: Adsr::new(AdsrSettings::default(), sample_rate),
             filter_adsr: Adsr::new(AdsrSettings::default(), sample_rate),
             filter: Filter::new(),
@@ -372,6 +420,45 @@ impl Voice {
         self.amp_adsr.state != crate::synth::AdsrState::Idle
     }
 
+    /// Reads the layered and blended oscillator output at a single table phase.
+    fn oscillator_sample(
+        wavetable_set: &WavetableSet,
+        wavetable_mixer_settings: WavetableMixerSettings,
+        final_morph_pos: f32,
+        final_mods: &ModulationValues,
+        phase: f32,
+    ) -> f32 {
+        let mut layer_output = 0.0;
+        for i in 0..4 {
+            if wavetable_mixer_settings.layer_volumes[i] > 1e-6 {
+                if let Some(table) = wavetable_set.tables.get(i) {
+                    let layer_sample =
+                        WavetableSet::get_interpolated_sample(&table.table, phase);
+                    layer_output += layer_sample * wavetable_mixer_settings.layer_volumes[i];
+                }
+            }
+        }
+
+        let mut blended_output = 0.0;
+        if wavetable_mixer_settings.layer_volumes[4] > 1e-6 {
+            let blended_sample = wavetable_set.get_sample(final_morph_pos, phase);
+            let phase_norm = phase / WAVETABLE_SIZE as f32;
+            let bell_pos = (final_mods.bell_pos * 0.5 + 0.5).clamp(0.0, 1.0);
+            let sigma =
+                (0.15_f32 * POW2_LUT.get_interpolated(-2.0 * final_mods.bell_width)).clamp(0.02, 1.0);
+
+            // --- OPTIMIZED BELL FILTER CALCULATION ---
+            let exp_input = (phase_norm - bell_pos).powi(2) / (2.0 * sigma.powi(2));
+            let bell_shape = EXP_LUT.get_interpolated(exp_input);
+            let bell_effect = bell_shape * final_mods.bell_amount;
+
+            let bell_filtered_sample = blended_sample * (1.0 + bell_effect);
+            blended_output = bell_filtered_sample * wavetable_mixer_settings.layer_volumes[4];
+        }
+
+        layer_output + blended_output
+    }
+
     /// This is the performance-critical "hot loop" function.
     /// It now processes a single sample using pre-calculated base modulation values.
     fn process_sample(
@@ -398,8 +485,9 @@ impl Voice {
                 // Voice-specific sources
                 ModSource::Env2 => self.last_env2_value,
                 ModSource::Velocity => self.velocity,
+                ModSource::KeyTrack => crate::synth::key_track(self.note_id),
             };
-            let mod_val = source_val * routing.amount;
+            let mod_val = routing.modulate(source_val);
             match routing.destination {
                 ModDestination::WavetablePosition => final_mods.wt_pos += mod_val,
                 ModDestination::Pitch => final_mods.pitch += mod_val,
@@ -409,6 +497,7 @@ impl Voice {
                 ModDestination::BellAmount => final_mods.bell_amount += mod_val,
                 ModDestination::BellWidth => final_mods.bell_width += mod_val,
                 ModDestination::Saturation => final_mods.saturation += mod_val,
+                ModDestination::FxMix => final_mods.fx_mix += mod_val,
             }
         }
 
@@ -417,40 +506,41 @@ impl Voice {
         let final_morph_pos = base_morph_pos + (final_mods.wt_pos * wt_pos_scaler);
 
         // --- OPTIMIZED PITCH CALCULATION ---
-        let final_frequency = self.base_frequency * POW2_LUT.get_interpolated(final_mods.pitch);
+        let final_frequency = self.base_frequency
+            * self.glide.process()
+            * POW2_LUT.get_interpolated(final_mods.pitch);
 
         let phase_inc = final_frequency / self.sample_rate * WAVETABLE_SIZE as f32;
-        self.phase = (self.phase + phase_inc) % WAVETABLE_SIZE as f32;
-
-        let mut layer_output = 0.0;
-        for i in 0..4 {
-            if wavetable_mixer_settings.layer_volumes[i] > 1e-6 {
-                if let Some(table) = wavetable_set.tables.get(i) {
-                    let layer_sample =
-                        WavetableSet::get_interpolated_sample(&table.table, self.phase);
-                    layer_output += layer_sample * wavetable_mixer_settings.layer_volumes[i];
-                }
+        let final_osc_sample = if self.unison_voices <= 1 {
+            self.phase = (self.phase + phase_inc) % WAVETABLE_SIZE as f32;
+            Self::oscillator_sample(
+                wavetable_set,
+                wavetable_mixer_settings,
+                final_morph_pos,
+                &final_mods,
+                self.phase,
+            )
+        } else {
+            // Spread the copies evenly across the detune range, centred on the played pitch.
+            let spread_step = 2.0 / (self.unison_voices - 1) as f32;
+            let detune_semitones = self.unison_detune_cents / 100.0 * 0.5;
+            let mut unison_sum = 0.0;
+            let unison_voices = self.unison_voices;
+            for (k, phase) in self.unison_phases.iter_mut().take(unison_voices).enumerate() {
+                let offset = (k as f32 * spread_step - 1.0) * detune_semitones;
+                let detuned_inc = phase_inc * POW2_LUT.get_interpolated(offset);
+                *phase = (*phase + detuned_inc) % WAVETABLE_SIZE as f32;
+                unison_sum += Self::oscillator_sample(
+                    wavetable_set,
+                    wavetable_mixer_settings,
+                    final_morph_pos,
+                    &final_mods,
+                    *phase,
+                );
             }
-        }
-
-        let mut blended_output = 0.0;
-        if wavetable_mixer_settings.layer_volumes[4] > 1e-6 {
-            let blended_sample = wavetable_set.get_sample(final_morph_pos, self.phase);
-            let phase_norm = self.phase / WAVETABLE_SIZE as f32;
-            let bell_pos = (final_mods.bell_pos * 0.5 + 0.5).clamp(0.0, 1.0);
-            let sigma =
-                (0.15_f32 * POW2_LUT.get_interpolated(-2.0 * final_mods.bell_width)).clamp(0.02, 1.0);
-
-            // --- OPTIMIZED BELL FILTER CALCULATION ---
-            let exp_input = (phase_norm - bell_pos).powi(2) / (2.0 * sigma.powi(2));
-            let bell_shape = EXP_LUT.get_interpolated(exp_input);
-            let bell_effect = bell_shape * final_mods.bell_amount;
-
-            let bell_filtered_sample = blended_sample * (1.0 + bell_effect);
-            blended_output = bell_filtered_sample * wavetable_mixer_settings.layer_volumes[4];
-        }
-
-        let final_osc_sample = layer_output + blended_output;
+            // Equal-power scaling keeps the stack close to the level of a single voice.
+            unison_sum / (self.unison_voices as f32).sqrt()
+        };
 
         // --- OPTIMIZED SATURATION LOGIC ---
         let final_saturation_mod = final_mods.saturation.clamp(-1.0, 1.0);
@@ -472,7 +562,11 @@ impl Voice {
             .filter
             .process(compensated_sample, final_filter_settings, self.sample_rate);
 
-        let output = filtered_sample * 0.5 * self.velocity * amp_env_val * (1.0 + final_mods.amp);
+        let output = filtered_sample
+            * 0.5
+            * velocity_gain(self.velocity, self.velocity_sensitivity)
+            * amp_env_val
+            * (1.0 + final_mods.amp);
 
         // Store the final modulation values for this sample for UI feedback
         self.last_mod_values = final_mods;
@@ -481,9 +575,14 @@ impl Voice {
         output
     }
 
-    fn note_on(&mut self, note: u8, velocity: u8) {
+    fn note_on(&mut self, note: u8, velocity: u8, a4_hz: f32) {
         self.note_id = note;
-        self.base_frequency = 440.0 * 2.0_f32.powf((note as f32 - 69.0) / 12.0);
+        self.base_frequency = crate::synth::note_to_freq(note, a4_hz);
+        self.glide.stop();
+        // Start unison copies at staggered phases so they don't sum in lockstep.
+        for (k, phase) in self.unison_phases.iter_mut().enumerate() {
+            *phase = (k as f32 * 0.618_034 * WAVETABLE_SIZE as f32) % WAVETABLE_SIZE as f32;
+        }
         self.velocity = velocity as f32 / 127.0;
         self.amp_adsr.note_on();
         self.filter_adsr.note_on();
@@ -494,15 +593,43 @@ impl Voice {
         self.amp_adsr.note_off();
         self.filter_adsr.note_off();
     }
+
+    fn reset(&mut self) {
+        self.amp_adsr.reset();
+        self.filter_adsr.reset();
+        self.filter = Filter::new();
+        self.age = u32::MAX;
+    }
+}
+
+/// How long a replaced table takes to morph into its successor while voices are sounding.
+const TABLE_SWAP_FADE_MS: f32 = 30.0;
+/// While a table swap runs, blocks are processed in pieces this long so the blend moves in
+/// small steps instead of jumping once per block.
+const TABLE_SWAP_FADE_STEP: usize = 16;
+
+/// A table replacement in progress. The slot's contents are blended from the old to the new
+/// table a few samples at a time; since the oscillators read tables linearly this is the
+/// same as crossfading the old and new outputs.
+struct TableSwapFade {
+    slot_index: usize,
+    from: Vec<f32>,
+    to: Vec<f32>,
+    position: usize,
+    len: usize,
 }
 
 pub struct WavetableEngine {
     voices: Vec<Voice>,
+    table_swap_fade: Option<TableSwapFade>,
+    glide_time_ms: f32,
+    tuning_reference_hz: f32,
     pub wavetable_set: Arc<RwLock<WavetableSet>>,
     is_polyphonic: bool,
     sample_rate: f32,
     lfo1: Lfo,
     lfo2: Lfo,
+    fx_mix_mod: f32,
     pub wavetable_position_atomic: Arc<AtomicU32>,
     pub filter_settings: Arc<RwLock<FilterSettings>>,
     pub wavetable_mixer_settings: Arc<RwLock<WavetableMixerSettings>>,
@@ -510,6 +637,7 @@ pub struct WavetableEngine {
     pub lfo2_settings: Arc<RwLock<LfoSettings>>,
     pub mod_matrix: Arc<RwLock<Vec<ModRouting>>>,
     pub saturation_settings: Arc<RwLock<SaturationSettings>>,
+    pub unison_settings: Arc<RwLock<UnisonSettings>>,
     pub lfo_value_atomic: Arc<AtomicU32>,
     pub lfo2_value_atomic: Arc<AtomicU32>,
     pub env2_value_atomic: Arc<AtomicU32>,
@@ -546,16 +674,21 @@ impl WavetableEngine {
         saturation_mod_atomic: Arc<AtomicU32>,
         final_wt_pos_atomic: Arc<AtomicU32>,
         final_cutoff_atomic: Arc<AtomicU32>,
+        unison_settings: Arc<RwLock<UnisonSettings>>,
     ) -> Self {
         let voices = (0..NUM_VOICES).map(|_| Voice::new(sample_rate)).collect();
 
         Self {
             voices,
+            table_swap_fade: None,
+            glide_time_ms: 0.0,
+            tuning_reference_hz: crate::synth::DEFAULT_TUNING_REFERENCE_HZ,
             wavetable_set,
             is_polyphonic: true,
             sample_rate,
             lfo1: Lfo::new(sample_rate),
             lfo2: Lfo::new(sample_rate),
+            fx_mix_mod: 0.0,
             wavetable_position_atomic,
             filter_settings,
             wavetable_mixer_settings,
@@ -563,6 +696,7 @@ impl WavetableEngine {
             lfo2_settings,
             mod_matrix,
             saturation_settings,
+            unison_settings,
             lfo_value_atomic,
             lfo2_value_atomic,
             env2_value_atomic,
@@ -577,6 +711,26 @@ impl WavetableEngine {
         }
     }
 
+    /// Moves any running table swap forward by one block and writes the blended table.
+    fn advance_table_swap_fade(&mut self, block_size: usize) {
+        let Some(fade) = &mut self.table_swap_fade else {
+            return;
+        };
+        fade.position += block_size;
+        let t = (fade.position as f32 / fade.len as f32).min(1.0);
+        if let Ok(mut guard) = self.wavetable_set.write() {
+            if let Some(wavetable) = guard.tables.get_mut(fade.slot_index) {
+                let blend = wavetable.table.iter_mut().zip(&fade.from).zip(&fade.to);
+                for ((out, &from), &to) in blend {
+                    *out = from + (to - from) * t;
+                }
+            }
+        }
+        if t >= 1.0 {
+            self.table_swap_fade = None;
+        }
+    }
+
     fn get_lfo_freq(sample_rate: f32, settings: LfoSettings, musical_bar_len: usize) -> f32 {
         match settings.mode {
             LfoRateMode::Hz => settings.hz_rate,
@@ -599,7 +753,14 @@ impl Engine for WavetableEngine {
         midi_cc_values: &Arc<[[AtomicU32; 128]; 16]>,
     ) {
         let block_size = output_buffer.len();
+        if self.table_swap_fade.is_some() && block_size > TABLE_SWAP_FADE_STEP {
+            for chunk in output_buffer.chunks_mut(TABLE_SWAP_FADE_STEP) {
+                self.process(chunk, musical_bar_len, midi_cc_values);
+            }
+            return;
+        }
         output_buffer.fill(0.0); // Clear the output buffer initially
+        self.advance_table_swap_fade(block_size);
 
         // --- Read all shared data once per block ---
         let lfo1_settings = *self.lfo_settings.read().unwrap();
@@ -607,6 +768,7 @@ impl Engine for WavetableEngine {
         let filter_settings = *self.filter_settings.read().unwrap();
         let wavetable_mixer_settings = *self.wavetable_mixer_settings.read().unwrap();
         let saturation_settings = *self.saturation_settings.read().unwrap();
+        let unison_settings = *self.unison_settings.read().unwrap();
         let mod_matrix = self.mod_matrix.read().unwrap();
         let wavetable_set_guard = match self.wavetable_set.read() {
             Ok(guard) => guard,
@@ -619,13 +781,21 @@ impl Engine for WavetableEngine {
         let lfo1_freq = Self::get_lfo_freq(self.sample_rate, lfo1_settings, musical_bar_len);
         let lfo2_freq = Self::get_lfo_freq(self.sample_rate, lfo2_settings, musical_bar_len);
         for i in 0..block_size {
-            lfo1_output[i] =
-                self.lfo1
-                    .process(lfo1_freq, lfo1_settings.waveform, &wavetable_set_guard);
-            lfo2_output[i] =
-                self.lfo2
-                    .process(lfo2_freq, lfo2_settings.waveform, &wavetable_set_guard);
+            lfo1_output[i] = self.lfo1.process(
+                lfo1_freq,
+                lfo1_settings.waveform,
+                lfo1_settings.phase_offset,
+                &wavetable_set_guard,
+            );
+            lfo2_output[i] = self.lfo2.process(
+                lfo2_freq,
+                lfo2_settings.waveform,
+                lfo2_settings.phase_offset,
+                &wavetable_set_guard,
+            );
         }
+        let lfo1_depth = self.lfo1.advance_fade(lfo1_settings.fade_in_ms, block_size);
+        let lfo2_depth = self.lfo2.advance_fade(lfo2_settings.fade_in_ms, block_size);
 
         // --- Resize voice output buffers if necessary ---
         if self.voice_outputs[0].len() != block_size {
@@ -634,6 +804,13 @@ impl Engine for WavetableEngine {
             }
         }
 
+        // Unison multiplies the per-voice cost, so it is capped and only used in poly mode.
+        let unison_voices = if self.is_polyphonic {
+            unison_settings.voices.clamp(1, MAX_UNISON_VOICES)
+        } else {
+            1
+        };
+
         // --- Parallel Processing of Voices ---
         self.voices
             .par_iter_mut()
@@ -647,6 +824,8 @@ impl Engine for WavetableEngine {
 
                 let base_morph_pos =
                     self.wavetable_position_atomic.load(Ordering::Relaxed) as f32 / 1_000_000.0;
+                voice.unison_voices = unison_voices;
+                voice.unison_detune_cents = unison_settings.detune_cents;
 
                 // Process this voice for the entire block
                 for i in 0..block_size {
@@ -654,15 +833,15 @@ impl Engine for WavetableEngine {
                     let mut base_mods = ModulationValues::default();
                     for routing in mod_matrix.iter() {
                         let source_val = match routing.source {
-                            ModSource::Lfo1 => lfo1_output[i],
-                            ModSource::Lfo2 => lfo2_output[i],
+                            ModSource::Lfo1 => lfo1_output[i] * lfo1_depth,
+                            ModSource::Lfo2 => lfo2_output[i] * lfo2_depth,
                             ModSource::Static => 1.0,
                             ModSource::MidiCC(id) => {
                                 midi_cc_values[id.channel as usize][id.cc as usize].load(Ordering::Relaxed) as f32 / 1_000_000.0
                             }
-                            ModSource::Env2 | ModSource::Velocity => continue,
+                            ModSource::Env2 | ModSource::Velocity | ModSource::KeyTrack => continue,
                         };
-                        let mod_val = source_val * routing.amount;
+                        let mod_val = routing.modulate(source_val);
                         match routing.destination {
                             ModDestination::WavetablePosition => base_mods.wt_pos += mod_val,
                             ModDestination::Pitch => base_mods.pitch += mod_val,
@@ -672,6 +851,7 @@ impl Engine for WavetableEngine {
                             ModDestination::BellAmount => base_mods.bell_amount += mod_val,
                             ModDestination::BellWidth => base_mods.bell_width += mod_val,
                             ModDestination::Saturation => base_mods.saturation += mod_val,
+                            ModDestination::FxMix => base_mods.fx_mix += mod_val,
                         }
                     }
 
@@ -705,8 +885,8 @@ impl Engine for WavetableEngine {
         } else {
             // When idle, we simulate modulation from LFOs and static sources for the visualizer.
             let mut idle_mods = ModulationValues::default();
-            let lfo1_val = *lfo1_output.last().unwrap_or(&0.0);
-            let lfo2_val = *lfo2_output.last().unwrap_or(&0.0);
+            let lfo1_val = *lfo1_output.last().unwrap_or(&0.0) * lfo1_depth;
+            let lfo2_val = *lfo2_output.last().unwrap_or(&0.0) * lfo2_depth;
             for routing in mod_matrix.iter() {
                 let source_val = match routing.source {
                     ModSource::Lfo1 => lfo1_val,
@@ -715,9 +895,9 @@ impl Engine for WavetableEngine {
                     ModSource::MidiCC(id) => {
                         midi_cc_values[id.channel as usize][id.cc as usize].load(Ordering::Relaxed) as f32 / 1_000_000.0
                     }
-                    _ => 0.0, // Env2 and Velocity are 0 when idle
+                    _ => 0.0, // Env2, Velocity and KeyTrack are 0 when idle
                 };
-                let mod_val = source_val * routing.amount;
+                let mod_val = routing.modulate(source_val);
                 match routing.destination {
                     ModDestination::WavetablePosition => idle_mods.wt_pos += mod_val,
                     ModDestination::Pitch => idle_mods.pitch += mod_val,
@@ -726,6 +906,7 @@ impl Engine for WavetableEngine {
                     ModDestination::BellAmount => idle_mods.bell_amount += mod_val,
                     ModDestination::BellWidth => idle_mods.bell_width += mod_val,
                     ModDestination::Saturation => idle_mods.saturation += mod_val,
+                    ModDestination::FxMix => idle_mods.fx_mix += mod_val,
                     _ => {}
                 }
             }
@@ -757,14 +938,15 @@ impl Engine for WavetableEngine {
         let base_cutoff = filter_settings.cutoff;
         let final_cutoff = (base_cutoff + mods.cutoff).clamp(0.0, 1.0);
         self.final_cutoff_atomic.store((final_cutoff * 1_000_000.0) as u32, Ordering::Relaxed);
+        self.fx_mix_mod = mods.fx_mix;
     }
 
     fn note_on(&mut self, note: u8, velocity: u8) {
         if self.lfo_settings.read().unwrap().retrigger {
-            self.lfo1.reset_phase();
+            self.lfo1.retrigger();
         }
         if self.lfo2_settings.read().unwrap().retrigger {
-            self.lfo2.reset_phase();
+            self.lfo2.retrigger();
         }
 
         if self.is_polyphonic {
@@ -778,7 +960,7 @@ impl Engine for WavetableEngine {
                 };
                 (priority, v.age)
             }) {
-                voice.note_on(note, velocity);
+                voice.note_on(note, velocity, self.tuning_reference_hz);
             }
         } else {
             // For monophonic mode, we always retrigger the main voice.
@@ -790,8 +972,17 @@ impl Engine for WavetableEngine {
             }
 
             // Now that the first borrow is finished, we can create a new one.
+            let glide_samples = (self.glide_time_ms / 1000.0 * self.sample_rate) as u32;
             if let Some(voice) = self.voices.get_mut(0) {
-                voice.note_on(note, velocity);
+                // Glide from wherever the previous note's pitch currently is.
+                let previous_frequency = (voice.age != u32::MAX)
+                    .then(|| voice.base_frequency * voice.glide.ratio());
+                voice.note_on(note, velocity, self.tuning_reference_hz);
+                if let Some(previous_frequency) = previous_frequency {
+                    voice
+                        .glide
+                        .start(previous_frequency / voice.base_frequency, glide_samples);
+                }
             }
         }
     }
@@ -813,6 +1004,20 @@ impl Engine for WavetableEngine {
             voice.filter_adsr.set_settings(settings);
         }
     }
+    fn set_velocity_sensitivity(&mut self, sensitivity: f32) {
+        for voice in &mut self.voices {
+            voice.velocity_sensitivity = sensitivity;
+        }
+    }
+
+    fn set_glide_time(&mut self, glide_time_ms: f32) {
+        self.glide_time_ms = glide_time_ms.max(0.0);
+    }
+
+    fn set_tuning_reference(&mut self, a4_hz: f32) {
+        self.tuning_reference_hz = a4_hz;
+    }
+
 
     fn set_polyphonic(&mut self, poly: bool) {
         self.is_polyphonic = poly;
@@ -829,19 +1034,61 @@ impl Engine for WavetableEngine {
     }
 
     fn set_wavetable(&mut self, slot_index: usize, audio_data: Arc<Vec<f32>>, name: String) {
+        // Land any swap still in flight so its slot isn't left half-blended.
+        if let Some(fade) = self.table_swap_fade.take() {
+            if let Ok(mut guard) = self.wavetable_set.write() {
+                if let Some(wavetable) = guard.tables.get_mut(fade.slot_index) {
+                    wavetable.table = fade.to;
+                }
+            }
+        }
+        let voices_sounding = self.voices.iter().any(|v| v.is_active());
+        let fade_len = (TABLE_SWAP_FADE_MS * 0.001 * self.sample_rate) as usize;
         if let Ok(mut guard) = self.wavetable_set.write() {
             if let Some(wavetable) = guard.tables.get_mut(slot_index) {
                 if !name.is_empty() {
                     wavetable.name = name;
                 }
-                wavetable.table = (*audio_data).clone();
+                if voices_sounding && fade_len > 0 && wavetable.table.len() == audio_data.len() {
+                    self.table_swap_fade = Some(TableSwapFade {
+                        slot_index,
+                        from: wavetable.table.clone(),
+                        to: (*audio_data).clone(),
+                        position: 0,
+                        len: fade_len,
+                    });
+                } else {
+                    wavetable.table = (*audio_data).clone();
+                }
             }
         }
     }
 
     fn reset_to_defaults(&mut self) {
+        self.table_swap_fade = None;
         if let Ok(mut guard) = self.wavetable_set.write() {
             *guard = WavetableSet::new_basic();
         }
     }
+
+    fn reset_voices(&mut self) {
+        for voice in &mut self.voices {
+            voice.reset();
+        }
+        self.lfo1.reset_phase();
+        self.lfo2.reset_phase();
+    }
+
+    fn reset_synced_lfo_phases(&mut self) {
+        if self.lfo_settings.read().unwrap().mode == LfoRateMode::Sync {
+            self.lfo1.reset_phase();
+        }
+        if self.lfo2_settings.read().unwrap().mode == LfoRateMode::Sync {
+            self.lfo2.reset_phase();
+        }
+    }
+
+    fn fx_mix_modulation(&self) -> f32 {
+        self.fx_mix_mod
+    }
 }
\ No newline at end of file